@@ -0,0 +1,275 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Moderation primitives for public chat.
+//!
+//! Messages are checked in the order of validation, rate limiting and word filtering, so that
+//! filtered messages still count towards the rate limit while invalid ones do not.
+//!
+//! Words are matched as whole words after normalization, which folds case, common Latin
+//! diacritics and leet speak, and drops punctuation within words. Punctuation around words is
+//! stripped before folding leet speak, so that `bad!` is not read as `badi`. Runs of three or more
+//! of a letter are matched as both one and two of it, so that `baaad` matches `bad` while `as`
+//! does not match `ass`. Runs of single letters separated by spaces are joined as well, so that
+//! `b a d` matches `bad`.
+
+use crate::FerriciaResult;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Verdict of a chat message, as returned to Java by its discriminant.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(i32)]
+pub(crate) enum ChatVerdict {
+	Allowed = 0,
+	Empty,
+	TooLong,
+	/// Invalid UTF-8 or containing control characters
+	Malformed,
+	RateLimited,
+	/// Containing a word in any of the word lists
+	Filtered,
+}
+
+/// A token bucket of messages.
+struct RateBucket {
+	tokens: f32,
+	updated: Instant,
+}
+
+pub(crate) struct ChatFilter {
+	/// In characters
+	max_length: usize,
+	/// Number of messages that may be sent at once
+	burst: u32,
+	/// Time to regain a message
+	interval: Duration,
+	buckets: HashMap<String, RateBucket>,
+	/// Normalized words by list name
+	word_lists: HashMap<String, HashSet<String>>,
+}
+
+impl ChatFilter {
+	pub(crate) fn new(max_length: usize, burst: u32, interval: Duration) -> FerriciaResult<Self> {
+		if burst == 0 || interval.is_zero() {
+			return Err("Chat rate limit must allow messages".to_string().into());
+		}
+		Ok(Self {
+			max_length,
+			burst,
+			interval,
+			buckets: HashMap::new(),
+			word_lists: HashMap::new(),
+		})
+	}
+
+	/// Replaces the word list of the name.
+	pub(crate) fn set_word_list(&mut self, name: String, words: Vec<String>) {
+		let words = words.iter()
+			.map(|v| normalize_word(v))
+			.filter(|v| !v.is_empty())
+			.collect();
+		self.word_lists.insert(name, words);
+	}
+
+	pub(crate) fn remove_word_list(&mut self, name: &str) {
+		self.word_lists.remove(name);
+	}
+
+	/// Clears the rate limit state of the player, such as on disconnection.
+	pub(crate) fn forget_player(&mut self, player: &str) {
+		self.buckets.remove(player);
+	}
+
+	pub(crate) fn check(&mut self, player: &str, message: &[u8]) -> ChatVerdict {
+		let Ok(message) = std::str::from_utf8(message) else { return ChatVerdict::Malformed };
+		if message.chars().any(char::is_control) {
+			return ChatVerdict::Malformed;
+		}
+		if message.trim().is_empty() {
+			return ChatVerdict::Empty;
+		}
+		if message.chars().count() > self.max_length {
+			return ChatVerdict::TooLong;
+		}
+		if !self.take_token(player) {
+			return ChatVerdict::RateLimited;
+		}
+		if self.contains_filtered(message) {
+			return ChatVerdict::Filtered;
+		}
+		ChatVerdict::Allowed
+	}
+
+	fn take_token(&mut self, player: &str) -> bool {
+		let now = Instant::now();
+		let bucket = self.buckets.entry(player.to_string())
+			.or_insert(RateBucket { tokens: self.burst as f32, updated: now });
+		let regained = (now - bucket.updated).as_secs_f32() / self.interval.as_secs_f32();
+		bucket.tokens = (bucket.tokens + regained).min(self.burst as f32);
+		bucket.updated = now;
+		if bucket.tokens >= 1.0 {
+			bucket.tokens -= 1.0;
+			true
+		} else {
+			false
+		}
+	}
+
+	fn contains_filtered(&self, message: &str) -> bool {
+		if self.word_lists.values().all(HashSet::is_empty) {
+			return false;
+		}
+		let mut words = Vec::new();
+		let mut letters = String::new();
+		for token in message.split_whitespace() {
+			let word = normalize_word(token);
+			if word.chars().count() == 1 {
+				letters.push_str(&word);
+				continue;
+			}
+			if !letters.is_empty() {
+				words.push(std::mem::take(&mut letters));
+			}
+			words.push(word);
+		}
+		if !letters.is_empty() {
+			words.push(letters);
+		}
+		words.iter()
+			.flat_map(|w| [collapse_runs(w, 1), collapse_runs(w, 2), w.clone()])
+			.any(|w| self.word_lists.values().any(|l| l.contains(&w)))
+	}
+}
+
+/// Folds the word into plain lowercase letters and digits.
+fn normalize_word(word: &str) -> String {
+	word.trim_matches(|c: char| c.is_ascii_punctuation())
+		.chars()
+		.flat_map(char::to_lowercase)
+		.map(fold_diacritic)
+		.map(fold_leet)
+		.filter(|c| c.is_alphanumeric())
+		.collect()
+}
+
+/// Shortens runs of three or more of a letter to `keep` letters.
+fn collapse_runs(word: &str, keep: usize) -> String {
+	let mut out = String::with_capacity(word.len());
+	let chars = word.chars().collect::<Vec<_>>();
+	for run in chars.chunk_by(|a, b| a == b) {
+		let len = if run.len() >= 3 { keep } else { run.len() };
+		out.extend(&run[..len]);
+	}
+	out
+}
+
+fn fold_leet(c: char) -> char {
+	match c {
+		'0' => 'o',
+		'1' | '!' | '|' => 'i',
+		'3' => 'e',
+		'4' | '@' => 'a',
+		'5' | '$' => 's',
+		'7' | '+' => 't',
+		'8' => 'b',
+		'9' => 'g',
+		_ => c,
+	}
+}
+
+/// Only covers the lowercase letters of Latin-1 Supplement and Latin Extended-A.
+fn fold_diacritic(c: char) -> char {
+	match c {
+		'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+		'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+		'ď' | 'đ' => 'd',
+		'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+		'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+		'ĥ' | 'ħ' => 'h',
+		'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+		'ĵ' => 'j',
+		'ķ' => 'k',
+		'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+		'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+		'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+		'ŕ' | 'ŗ' | 'ř' => 'r',
+		'ś' | 'ŝ' | 'ş' | 'š' => 's',
+		'ţ' | 'ť' | 'ŧ' => 't',
+		'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+		'ŵ' => 'w',
+		'ý' | 'ÿ' | 'ŷ' => 'y',
+		'ź' | 'ż' | 'ž' => 'z',
+		_ => c,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn filter(words: &[&str]) -> ChatFilter {
+		let mut filter = ChatFilter::new(256, 100, Duration::from_secs(1)).unwrap();
+		filter.set_word_list("test".to_string(), words.iter().map(|v| v.to_string()).collect());
+		filter
+	}
+
+	fn check(filter: &mut ChatFilter, message: &str) -> ChatVerdict {
+		filter.check("player", message.as_bytes())
+	}
+
+	#[test]
+	fn normalizes_words() {
+		assert_eq!(normalize_word("B@D"), "bad");
+		assert_eq!(normalize_word("bäd"), "bad");
+		assert_eq!(normalize_word("bad!"), "bad");
+		assert_eq!(normalize_word("(b4d)."), "bad");
+		assert_eq!(normalize_word("b.a.d"), "bad");
+	}
+
+	#[test]
+	fn collapses_only_long_runs() {
+		assert_eq!(collapse_runs("butt", 1), "butt");
+		assert_eq!(collapse_runs("baaad", 1), "bad");
+		assert_eq!(collapse_runs("asssss", 2), "ass");
+	}
+
+	#[test]
+	fn filters_disguised_words() {
+		let mut filter = filter(&["bad", "ass"]);
+		assert_eq!(check(&mut filter, "so bad!"), ChatVerdict::Filtered);
+		assert_eq!(check(&mut filter, "so B4D"), ChatVerdict::Filtered);
+		assert_eq!(check(&mut filter, "so baaaad"), ChatVerdict::Filtered);
+		assert_eq!(check(&mut filter, "so b a d"), ChatVerdict::Filtered);
+		assert_eq!(check(&mut filter, "an asssss"), ChatVerdict::Filtered);
+	}
+
+	#[test]
+	fn keeps_similar_words() {
+		let mut filter = filter(&["ass", "butt"]);
+		assert_eq!(check(&mut filter, "as good as it gets"), ChatVerdict::Allowed);
+		assert_eq!(check(&mut filter, "but why"), ChatVerdict::Allowed);
+		assert_eq!(check(&mut filter, "badass"), ChatVerdict::Allowed);
+	}
+
+	#[test]
+	fn validates_before_filtering() {
+		let mut filter = filter(&["bad"]);
+		assert_eq!(check(&mut filter, "  "), ChatVerdict::Empty);
+		assert_eq!(check(&mut filter, "bad\u{7}"), ChatVerdict::Malformed);
+		assert_eq!(filter.check("player", &[0xff]), ChatVerdict::Malformed);
+	}
+
+	#[test]
+	fn limits_rate() {
+		let mut filter = ChatFilter::new(256, 2, Duration::from_secs(60)).unwrap();
+		assert_eq!(check(&mut filter, "hi"), ChatVerdict::Allowed);
+		assert_eq!(check(&mut filter, "hi"), ChatVerdict::Allowed);
+		assert_eq!(check(&mut filter, "hi"), ChatVerdict::RateLimited);
+		assert_eq!(filter.check("other", b"hi"), ChatVerdict::Allowed);
+		filter.forget_player("player");
+		assert_eq!(check(&mut filter, "hi"), ChatVerdict::Allowed);
+	}
+}
@@ -0,0 +1,33 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Notifications posted by engine subsystems from any thread.
+//!
+//! Events are queued until the next poll merges them into its result, which is `sdlPoll` on the
+//! client and `pollEngineEvents` on the server, so Java receives all engine notifications in one
+//! place. When both features are enabled, only one of them should be polled.
+
+use std::sync::{Mutex, MutexGuard};
+
+pub(crate) struct EngineEvent {
+	/// Name of the posting subsystem, such as `hot-reload` or `gl`
+	pub(crate) source: &'static str,
+	pub(crate) message: String,
+}
+
+static QUEUE: Mutex<Vec<EngineEvent>> = Mutex::new(Vec::new());
+
+fn queue() -> MutexGuard<'static, Vec<EngineEvent>> {
+	QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn post_event(source: &'static str, message: String) {
+	queue().push(EngineEvent { source, message });
+}
+
+/// Returns the queued events in the order of posting.
+pub(crate) fn drain_events() -> Vec<EngineEvent> {
+	std::mem::take(&mut *queue())
+}
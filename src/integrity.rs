@@ -0,0 +1,161 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Content hashing of resource packs and assets.
+//!
+//! A pack is either a directory or a ZIP archive. Its manifest is a JSON object of hashes in hex
+//! by the paths of assets, relative to the pack root and separated by `/`. Files are hashed in
+//! chunks, so large assets are never fully read into memory.
+//!
+//! XXH3 is meant for detecting corrupted downloads, while SHA-256 should be used when
+//! requiring clients to use unmodified packs, since XXH3 is not resistant to forged collisions.
+
+use crate::FerriciaResult;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs::{read_dir, File};
+use std::io::Read;
+use std::path::Path;
+use xxhash_rust::xxh3::Xxh3;
+use zip::ZipArchive;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Copy)]
+pub(crate) enum HashAlgorithm {
+	Xxh3,
+	Sha256,
+}
+
+impl HashAlgorithm {
+	pub(crate) fn from_name(name: &str) -> FerriciaResult<Self> {
+		match name {
+			"xxh3" => Ok(Self::Xxh3),
+			"sha256" => Ok(Self::Sha256),
+			_ => Err(format!("Unknown hash algorithm: {name}").into()),
+		}
+	}
+
+	fn hasher(self) -> ContentHasher {
+		match self {
+			HashAlgorithm::Xxh3 => ContentHasher::Xxh3(Box::new(Xxh3::new())),
+			HashAlgorithm::Sha256 => ContentHasher::Sha256(Sha256::new()),
+		}
+	}
+}
+
+enum ContentHasher {
+	// Boxed for the large internal buffer
+	Xxh3(Box<Xxh3>),
+	Sha256(Sha256),
+}
+
+impl ContentHasher {
+	fn update(&mut self, data: &[u8]) {
+		match self {
+			ContentHasher::Xxh3(v) => v.update(data),
+			ContentHasher::Sha256(v) => v.update(data),
+		}
+	}
+
+	/// Returns the hash in lowercase hex.
+	fn finish(self) -> String {
+		match self {
+			ContentHasher::Xxh3(v) => format!("{:016x}", v.digest()),
+			ContentHasher::Sha256(v) => v.finalize().iter().fold(String::with_capacity(64), |mut s, b| {
+				write!(s, "{b:02x}").expect("should be writable");
+				s
+			}),
+		}
+	}
+}
+
+fn hash_reader(mut reader: impl Read, algorithm: HashAlgorithm) -> std::io::Result<String> {
+	let mut hasher = algorithm.hasher();
+	let mut buf = vec![0; CHUNK_SIZE];
+	loop {
+		match reader.read(&mut buf)? {
+			0 => break,
+			n => hasher.update(&buf[..n]),
+		}
+	}
+	Ok(hasher.finish())
+}
+
+/// Hashes the whole file, such as a downloaded pack archive.
+pub(crate) fn hash_file(path: impl AsRef<Path>, algorithm: HashAlgorithm) -> FerriciaResult<String> {
+	let path = path.as_ref();
+	File::open(path)
+		.and_then(|v| hash_reader(v, algorithm))
+		.map_err(|e| format!("Cannot hash {}: {e}", path.display()).into())
+}
+
+/// Returns the hashes of all the assets in the pack by their paths.
+fn hash_pack(root: &Path, algorithm: HashAlgorithm) -> FerriciaResult<BTreeMap<String, String>> {
+	let mut hashes = BTreeMap::new();
+	if root.is_dir() {
+		hash_dir(root, "", algorithm, &mut hashes)
+			.map_err(|e| format!("Cannot hash pack {}: {e}", root.display()))?;
+	} else {
+		let file = File::open(root).map_err(|e| format!("Cannot open pack {}: {e}", root.display()))?;
+		let mut archive = ZipArchive::new(file).map_err(|e| format!("Cannot read pack {}: {e}", root.display()))?;
+		for i in 0..archive.len() {
+			let entry = archive.by_index(i).map_err(|e| format!("Cannot read pack {}: {e}", root.display()))?;
+			if entry.is_dir() {
+				continue;
+			}
+			let name = entry.name().to_string();
+			let hash = hash_reader(entry, algorithm).map_err(|e| format!("Cannot hash {name}: {e}"))?;
+			hashes.insert(name, hash);
+		}
+	}
+	Ok(hashes)
+}
+
+fn hash_dir(
+	dir: &Path,
+	prefix: &str,
+	algorithm: HashAlgorithm,
+	hashes: &mut BTreeMap<String, String>,
+) -> std::io::Result<()> {
+	for entry in read_dir(dir)? {
+		let entry = entry?;
+		let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+		if entry.file_type()?.is_dir() {
+			hash_dir(&entry.path(), &format!("{name}/"), algorithm, hashes)?;
+		} else {
+			hashes.insert(name, hash_reader(File::open(entry.path())?, algorithm)?);
+		}
+	}
+	Ok(())
+}
+
+/// Creates the manifest of the pack, such as for distribution by servers.
+pub(crate) fn build_manifest(root: impl AsRef<Path>, algorithm: HashAlgorithm) -> FerriciaResult<String> {
+	let hashes = hash_pack(root.as_ref(), algorithm)?;
+	Ok(serde_json::to_string(&hashes).expect("should be serializable"))
+}
+
+/// Returns the paths of assets that are missing, modified or absent from the manifest,
+/// so an empty list means the pack is intact.
+pub(crate) fn verify_manifest(
+	root: impl AsRef<Path>,
+	manifest: &str,
+	algorithm: HashAlgorithm,
+) -> FerriciaResult<Vec<String>> {
+	let expected: BTreeMap<String, String> = serde_json::from_str(manifest)
+		.map_err(|e| format!("Invalid manifest: {e}"))?;
+	let mut actual = hash_pack(root.as_ref(), algorithm)?;
+	let mut mismatched = Vec::new();
+	for (path, hash) in expected {
+		match actual.remove(&path) {
+			Some(v) if v.eq_ignore_ascii_case(&hash) => {}
+			_ => mismatched.push(path),
+		}
+	}
+	mismatched.extend(actual.into_keys());
+	Ok(mismatched)
+}
@@ -0,0 +1,206 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Localized strings of a language, shared by the engine and the Java GUI.
+//!
+//! A language file is a JSON object of entries by keys. An entry is either a string, or an object
+//! of variants, which are entries by plural categories (`zero`, `one`, `two`, `few`, `many`) or
+//! by genders, always with `other` as the fallback. Variants may be nested, such as plurals within
+//! genders:
+//!
+//! ```json
+//! {
+//!   "menu.play": "Play",
+//!   "hud.items": { "one": "{count} item", "other": "{count} items" },
+//!   "chat.joined": { "feminine": "{name} joined her friends", "other": "{name} joined their friends" }
+//! }
+//! ```
+//!
+//! Plural categories follow the CLDR cardinal rules of the locale. Placeholders are written as
+//! `{name}`, with `{count}` being the count of plurals; unknown ones are kept as is.
+//!
+//! Files are merged in loading order, so loading the fallback language first fills missing keys,
+//! and resource packs may override entries. Keys missing from all files resolve to themselves.
+
+use crate::FerriciaResult;
+use icu::locale::Locale;
+use icu::plurals::{PluralCategory, PluralRules};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+	Text(String),
+	Variants(HashMap<String, Entry>),
+}
+
+pub(crate) struct StringTable {
+	plural_rules: PluralRules,
+	entries: HashMap<String, Entry>,
+}
+
+impl StringTable {
+	/// `locale` is a BCP 47 language tag, such as `en-US`; `_` is accepted as the separator too.
+	pub(crate) fn new(locale: &str) -> FerriciaResult<Self> {
+		let locale = Locale::try_from_str(&locale.replace('_', "-"))
+			.map_err(|e| format!("Invalid locale {locale}: {e}"))?;
+		let plural_rules = PluralRules::try_new_cardinal(locale.into())
+			.map_err(|e| format!("Cannot load plural rules: {e}"))?;
+		Ok(Self { plural_rules, entries: HashMap::new() })
+	}
+
+	pub(crate) fn load_file(&mut self, path: &str) -> FerriciaResult<()> {
+		let src = read_to_string(path).map_err(|e| format!("Cannot read language file {path}: {e}"))?;
+		self.load_source(&src).map_err(|e| format!("Invalid language file {path}: {}", e.0).into())
+	}
+
+	/// Entries override the loaded ones of the same keys.
+	pub(crate) fn load_source(&mut self, src: &str) -> FerriciaResult<()> {
+		let entries: HashMap<String, Entry> = serde_json::from_str(src)
+			.map_err(|e| format!("Invalid language source: {e}"))?;
+		self.entries.extend(entries);
+		Ok(())
+	}
+
+	pub(crate) fn contains(&self, key: &str) -> bool {
+		self.entries.contains_key(key)
+	}
+
+	/// `args` are pairs of placeholder names and values; `gender` is empty if not applicable.
+	pub(crate) fn translate(&self, key: &str, count: Option<i64>, gender: &str, args: &[(String, String)]) -> String {
+		let Some(mut entry) = self.entries.get(key) else {
+			return key.to_string();
+		};
+		let category = count.map(|v| category_name(self.plural_rules.category_for(v)));
+		let text = loop {
+			match entry {
+				Entry::Text(v) => break v,
+				Entry::Variants(variants) => {
+					let variant = variants.get(gender)
+						.or_else(|| category.and_then(|v| variants.get(v)))
+						.or_else(|| variants.get("other"));
+					match variant {
+						Some(v) => entry = v,
+						None => return key.to_string(),
+					}
+				}
+			}
+		};
+		let count = count.map(|v| v.to_string());
+		format_placeholders(text, |name| match name {
+			"count" => count.as_deref(),
+			_ => args.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str()),
+		})
+	}
+}
+
+fn category_name(category: PluralCategory) -> &'static str {
+	match category {
+		PluralCategory::Zero => "zero",
+		PluralCategory::One => "one",
+		PluralCategory::Two => "two",
+		PluralCategory::Few => "few",
+		PluralCategory::Many => "many",
+		PluralCategory::Other => "other",
+	}
+}
+
+fn format_placeholders<'a>(text: &str, lookup: impl Fn(&str) -> Option<&'a str>) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('{') {
+		out.push_str(&rest[..start]);
+		let tail = &rest[start..];
+		match tail.find('}').and_then(|end| lookup(&tail[1..end]).map(|v| (end, v))) {
+			Some((end, value)) => {
+				out.push_str(value);
+				rest = &tail[end + 1..];
+			}
+			None => {
+				out.push('{');
+				rest = &tail[1..];
+			}
+		}
+	}
+	out.push_str(rest);
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn table(locale: &str, src: &str) -> StringTable {
+		let mut table = StringTable::new(locale).unwrap();
+		table.load_source(src).unwrap();
+		table
+	}
+
+	fn args(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+		pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+	}
+
+	#[test]
+	fn resolves_plurals_by_locale() {
+		let src = r#"{ "hud.items": { "one": "{count} item", "other": "{count} items" } }"#;
+		let table = table("en-US", src);
+		assert_eq!(table.translate("hud.items", Some(1), "", &[]), "1 item");
+		assert_eq!(table.translate("hud.items", Some(3), "", &[]), "3 items");
+		assert_eq!(table.translate("hud.items", None, "", &[]), "{count} items");
+
+		let src = r#"{ "hud.files": { "one": "{count} plik", "few": "{count} pliki", "other": "{count} plików" } }"#;
+		let table = table("pl_PL", src);
+		assert_eq!(table.translate("hud.files", Some(1), "", &[]), "1 plik");
+		assert_eq!(table.translate("hud.files", Some(3), "", &[]), "3 pliki");
+		assert_eq!(table.translate("hud.files", Some(5), "", &[]), "5 plików");
+		assert_eq!(table.translate("hud.files", Some(22), "", &[]), "22 pliki");
+	}
+
+	#[test]
+	fn resolves_genders_before_plurals() {
+		let src = r#"{ "chat.joined": {
+			"feminine": { "one": "{name} joined her friend", "other": "{name} joined her {count} friends" },
+			"other": { "one": "{name} joined their friend", "other": "{name} joined their {count} friends" }
+		} }"#;
+		let table = table("en", src);
+		let name = args(&[("name", "Ann")]);
+		assert_eq!(table.translate("chat.joined", Some(1), "feminine", &name), "Ann joined her friend");
+		assert_eq!(table.translate("chat.joined", Some(2), "feminine", &name), "Ann joined her 2 friends");
+		assert_eq!(table.translate("chat.joined", Some(2), "", &name), "Ann joined their 2 friends");
+		assert_eq!(table.translate("chat.joined", Some(1), "neuter", &name), "Ann joined their friend");
+	}
+
+	#[test]
+	fn keeps_unknown_placeholders() {
+		let table = table("en", r#"{ "greet": "Hi {name}, {unknown} {count" }"#);
+		assert_eq!(table.translate("greet", None, "", &args(&[("name", "Ann")])), "Hi Ann, {unknown} {count");
+	}
+
+	#[test]
+	fn resolves_missing_keys_to_themselves() {
+		let table = table("en", r#"{ "menu.play": "Play", "partial": { "one": "One" } }"#);
+		assert!(table.contains("menu.play"));
+		assert!(!table.contains("menu.quit"));
+		assert_eq!(table.translate("menu.quit", None, "", &[]), "menu.quit");
+		// No variant matches without `other`
+		assert_eq!(table.translate("partial", Some(2), "", &[]), "partial");
+	}
+
+	#[test]
+	fn overrides_entries_in_loading_order() {
+		let mut table = table("en", r#"{ "menu.play": "Play", "menu.quit": "Quit" }"#);
+		table.load_source(r#"{ "menu.play": "Start" }"#).unwrap();
+		assert_eq!(table.translate("menu.play", None, "", &[]), "Start");
+		assert_eq!(table.translate("menu.quit", None, "", &[]), "Quit");
+		assert!(table.load_source("[]").is_err());
+	}
+
+	#[test]
+	fn rejects_invalid_locales() {
+		assert!(StringTable::new("not a locale").is_err());
+	}
+}
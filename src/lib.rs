@@ -5,13 +5,44 @@
 
 #![feature(fn_traits, ptr_metadata)]
 
+#[cfg(feature = "server")]
+mod chat;
+mod events;
+mod integrity;
+mod l10n;
+mod scheduler;
+mod tasks;
 #[cfg(feature = "client")]
 mod mui;
 mod util;
 
+#[cfg(feature = "server")]
+use crate::chat::ChatFilter;
+#[cfg(feature = "server")]
+use crate::events::drain_events;
+use crate::events::post_event;
+use crate::integrity::{build_manifest, hash_file, verify_manifest, HashAlgorithm};
+use crate::l10n::StringTable;
+use crate::scheduler::{Scheduler, TimerBase};
+use crate::tasks::TaskPump;
+#[cfg(feature = "hot-reload")]
+use crate::mui::hot_reload::HotReloader;
 #[cfg(feature = "client")]
 use crate::mui::{
-	window::WindowHandle,
+	window::{ContextConfig, ContextProfile, FullscreenMode, WindowHandle},
+	render_graph::RenderGraph,
+	gui_cache::GuiCache,
+	atlas::TextureAtlasBuilder,
+	axis_filter::AxisResponse,
+	compute::{ComputeProgram, StorageBuffer},
+	commands::{RenderCommand, ReplayState},
+	cursor::CursorHandle,
+	force_feedback::{ForceFeedbackDevice, Waveform},
+	shadows::ShadowBatch,
+	tilemap::TileMap,
+	picking::PickBuffer,
+	texture_loader::TextureLoader,
+	viewport::split_layout,
 	rendering::{
 		PrimModelTransform,
 		ScalingCenteredTranslateParam,
@@ -19,31 +50,70 @@ use crate::mui::{
 		DrawableSet,
 		GeoProgram,
 		SimpleLineGeom,
+		SimplePointsGeom,
 		TexProgram,
+		ArrayTexProgram,
+		PickProgram,
+		TileMapProgram,
+		UniformValue,
 		clear_canvas,
+		recreate_textures,
+		render_stats,
 		set_clear_color,
+		texture_array_layers,
 		AlphaFilter,
 		PrimColorFilter,
 		SpriteMesh,
+		LayeredSpriteMesh,
 		CanvasHandle,
 		SimpleTranslation,
+		AffineTransform,
+		PivotScaling,
+		FlipSkew,
+		Camera,
+		TintFilter,
+		GrayscaleFilter,
+		HueRotateFilter,
+		BrightnessFilter,
+		ContrastFilter,
+		SaturationFilter,
+		LerpColorFilter,
+		LerpMode,
+		TextureFilter,
+		TextureOptions,
+		TextureWrap,
+		SceneNode,
+		FlashFilter,
 	},
+	DisplayHandle,
+	GamepadAxis,
+	GamepadButton,
+	KeyboardKey,
+	MouseKey,
 	MuiEvent,
 	SdlHandle,
 };
 use derive_more::From;
-use jni::objects::{JClass, JFloatArray, JIntArray, JObject, JString, ReleaseMode};
-use jni::sys::{jbyte, jfloat, jfloatArray, jint, jintArray, jlong, jlongArray, jobjectArray, jsize, jstring};
+use jni::objects::{JByteArray, JByteBuffer, JClass, JFloatArray, JIntArray, JLongArray, JObject, JObjectArray, JString, ReleaseMode};
+use jni::sys::{jboolean, jbyte, jfloat, jfloatArray, jint, jintArray, jlong, jlongArray, jobject, jobjectArray, jsize, jstring};
 use jni::JNIEnv;
+use nalgebra_glm::{vec2, vec4, TMat4};
 use paste::paste;
 use sdl3::pixels::Color;
+use sdl3::rect::Rect;
+use sdl3::sys::gamepad::{SDL_GamepadAxis, SDL_GamepadButton};
 use std::backtrace::Backtrace;
-use std::cell::Cell;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
 use std::env::set_var;
 use std::fmt::Display;
 use std::panic::{catch_unwind, take_hook, AssertUnwindSafe};
-use std::ptr::{from_raw_parts, null};
+use std::process::abort;
+use std::ptr::null;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
 use crate::mui::rendering::{FullScaling, SimpleRectGeom};
+use crate::util::{HandleRegistry, HandleTag, Tagged, TaggedId};
 
 #[derive(From)]
 struct FerriciaError(String);
@@ -79,6 +149,15 @@ macro_rules! resolve_res {
 			}
 		}
 	};
+	($res:expr, $env:expr) => {
+		match $res {
+			Ok(v) => v,
+			Err(err) => {
+				err.throw_jni($env);
+				return
+			}
+		}
+	};
 }
 
 macro_rules! jni_null {
@@ -87,19 +166,18 @@ macro_rules! jni_null {
 	};
 }
 
+/// The reference is bounded by the local frame of the JNI call, as the value may be dropped by any later call.
 #[inline]
-fn jni_ref_ptr<'a, T>(ptr: jlong) -> &'a mut T {
-	unsafe { &mut *(ptr as *mut T) }
-}
-
-/// This may drop the owned value; use this with caution.
-fn jni_from_ptr<T>(ptr: jlong) -> T {
-	unsafe { *Box::from_raw(ptr as *mut T) }
+fn jni_ref_handle<'local, T: Tagged>(_env: &JNIEnv<'local>, handle: jlong) -> FerriciaResult<&'local mut T> {
+	let id = TaggedId::from_raw(handle)?;
+	let ptr = HandleRegistry::lock().get::<T>(id)?;
+	USED_HANDLES.with_borrow_mut(|v| v.push(id));
+	Ok(unsafe { &mut *ptr })
 }
 
-fn jni_res_to_ptr<T>(result: FerriciaResult<T>, env: &mut JNIEnv) -> jlong {
+fn jni_res_to_handle<T: Tagged>(result: FerriciaResult<T>, env: &mut JNIEnv) -> jlong {
 	match result {
-		Ok(v) => jni_to_ptr(v),
+		Ok(v) => jni_to_handle(v),
 		Err(err) => {
 			err.throw_jni(env);
 			jni_null!(jlong)
@@ -107,70 +185,213 @@ fn jni_res_to_ptr<T>(result: FerriciaResult<T>, env: &mut JNIEnv) -> jlong {
 	}
 }
 
-fn jni_to_ptr<T>(val: T) -> jlong {
-	Box::into_raw(Box::new(val)) as jlong
+fn jni_to_handle<T: Tagged>(val: T) -> jlong {
+	HandleRegistry::lock().insert(val).into_raw()
+}
+
+/// Registers the value while also returning a reference to the registered value, bounded as [jni_ref_handle].
+fn jni_to_handle_ref<'local, T: Tagged>(_env: &JNIEnv<'local>, val: T) -> (jlong, &'local T) {
+	let mut registry = HandleRegistry::lock();
+	let id = registry.insert(val);
+	let ptr = registry.get::<T>(id).expect("should be just inserted");
+	(id.into_raw(), unsafe { &*ptr })
 }
 
-fn jni_to_wide_ptr<T: ?Sized>(val: &T) -> jlong {
-	jni_to_ptr((val as *const T).to_raw_parts())
+/// A wide pointer to a trait object, referencing a value owned by another handle.
+///
+/// The handle is removed along with the owner, so the pointer never dangles.
+struct WidePtr<T: ?Sized>(*const T);
+
+fn jni_to_wide_ptr<T: ?Sized>(owner: jlong, val: &T) -> jlong where WidePtr<T>: Tagged {
+	let owner = TaggedId::from_raw(owner).expect("owner should be just inserted");
+	HandleRegistry::lock().insert_dependent(owner, WidePtr(val as *const T))
+		.expect("owner should be just inserted")
+		.into_raw()
 }
 
-fn jni_ref_wide_ptr<'a, T: ?Sized>(ptr: jlong) -> &'a T {
-	unsafe { &*from_raw_parts::<T>.call(*jni_ref_ptr::<(*const (), _)>(ptr)) }
+fn jni_ref_wide_ptr<'local, T: ?Sized>(env: &JNIEnv<'local>, handle: jlong) -> FerriciaResult<&'local T> where WidePtr<T>: Tagged {
+	Ok(unsafe { &*jni_ref_handle::<WidePtr<T>>(env, handle)?.0 })
 }
 
 macro_rules! jni_to_destructed_ptr {
 	($val:expr, $tr:ty, $env:ident) => {
-		let ptr = Box::into_raw(Box::new($val));
+		let (handle, val) = jni_to_handle_ref(&$env, $val);
 		let arr = $env.new_long_array(2).expect("Cannot create JLongArray");
-		$env.set_long_array_region(&arr, 0, &[ptr as jlong, jni_to_wide_ptr(unsafe { &*ptr } as &$tr)])
+		$env.set_long_array_region(&arr, 0, &[handle, jni_to_wide_ptr(handle, val as &$tr)])
 			.expect("Cannot set Java array elements");
 		return arr.into_raw()
 	};
 }
 
-fn jni_drop_with_ptr<T>(ptr: jlong) {
-	drop(unsafe { Box::from_raw(ptr as *mut T) })
+fn jni_drop_with_handle<T: Tagged>(handle: jlong) -> FerriciaResult<()> {
+	let value = HandleRegistry::lock().remove::<T>(TaggedId::from_raw(handle)?)?;
+	// Dropped after the registry is unlocked, as the drop may remove other handles.
+	drop(value);
+	Ok(())
+}
+
+macro_rules! tagged {
+	{ $($t:ty => $tag:ident,)* } => {
+		$(
+			impl Tagged for $t {
+				const TAG: HandleTag = HandleTag::$tag;
+			}
+		)*
+	};
+}
+
+#[cfg(feature = "client")]
+tagged! {
+	SdlHandle => SdlHandle,
+	WindowHandle => WindowHandle,
+	CanvasHandle => CanvasHandle,
+	DisplayHandle => DisplayHandle,
+	GeoProgram => GeoProgram,
+	TexProgram => TexProgram,
+	ArrayTexProgram => ArrayTexProgram,
+	PickProgram => PickProgram,
+	DrawableSet<'_> => DrawableSet,
+	SceneNode<'_> => SceneNode,
+	SmartScaling => SmartScaling,
+	FullScaling => FullScaling,
+	SimpleTranslation => SimpleTranslation,
+	AffineTransform => AffineTransform,
+	PivotScaling => PivotScaling,
+	FlipSkew => FlipSkew,
+	AlphaFilter => AlphaFilter,
+	TintFilter => TintFilter,
+	GrayscaleFilter => GrayscaleFilter,
+	HueRotateFilter => HueRotateFilter,
+	BrightnessFilter => BrightnessFilter,
+	ContrastFilter => ContrastFilter,
+	SaturationFilter => SaturationFilter,
+	LerpColorFilter => LerpColorFilter,
+	FlashFilter => FlashFilter,
+	RenderGraph => RenderGraph,
+	GuiCache => GuiCache,
+	TextureAtlasBuilder => TextureAtlasBuilder,
+	PickBuffer => PickBuffer,
+	TextureLoader => TextureLoader,
+	TileMapProgram => TileMapProgram,
+	ComputeProgram => ComputeProgram,
+	StorageBuffer => StorageBuffer,
+	CursorHandle => CursorHandle,
+	ForceFeedbackDevice => ForceFeedbackDevice,
+	WidePtr<dyn PrimModelTransform + '_> => ModelTransformRef,
+	WidePtr<dyn PrimColorFilter + '_> => ColorFilterRef,
+}
+
+#[cfg(feature = "hot-reload")]
+tagged! {
+	HotReloader => HotReloader,
+}
+
+#[cfg(feature = "server")]
+tagged! {
+	ChatFilter => ChatFilter,
+}
+
+tagged! {
+	Scheduler => Scheduler,
+	StringTable => StringTable,
+	TaskPump => TaskPump,
 }
 
 thread_local! {
 	static BACKTRACE: Cell<Option<Backtrace>> = const { Cell::new(None) };
+	/// Handles referenced by the current JNI call, to be poisoned if it panics.
+	static USED_HANDLES: RefCell<Vec<TaggedId>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Handling of panics caught at the JNI boundary.
+///
+/// Invalid arguments are thrown as `FerriciaEngineFault` without panicking, regardless of the policy.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum PanicPolicy {
+	/// Throws `FerriciaEngineFault`
+	Throw = 0,
+	/// Throws and poisons the handles used by the call, disabling them until dropped,
+	/// as their states may be inconsistent after unwinding.
+	ThrowAndPoison,
+	/// Aborts the process after printing the panic, such as for debugging
+	Abort,
+}
+
+static PANIC_POLICY: AtomicU8 = AtomicU8::new(PanicPolicy::Throw as u8);
+
+fn panic_policy() -> PanicPolicy {
+	match PANIC_POLICY.load(Ordering::Relaxed) {
+		0 => PanicPolicy::Throw,
+		1 => PanicPolicy::ThrowAndPoison,
+		_ => PanicPolicy::Abort,
+	}
+}
+
+fn handle_panic(err: Box<dyn Any + Send>, env: &mut JNIEnv) {
+	let b = BACKTRACE.take().map_or_else(|| "Backtrace not captured".to_string(), |b| format!("{b:?}"));
+	let msg = if let Some(val) = err.downcast_ref::<String>() {
+		format!("{val:?}")
+	} else if let Some(val) = err.downcast_ref::<&str>() {
+		format!("{val:?}")
+	} else {
+		"Unknown".to_string()
+	};
+	let used = USED_HANDLES.take();
+	match panic_policy() {
+		PanicPolicy::Throw => {}
+		PanicPolicy::ThrowAndPoison => {
+			let mut registry = HandleRegistry::lock();
+			used.into_iter().for_each(|v| registry.poison(v));
+		}
+		PanicPolicy::Abort => {
+			eprintln!("{msg}\n{b}");
+			abort();
+		}
+	}
+	FerriciaError(format!("{msg}\n{b}")).throw_jni(env);
+}
+
+/// A panic while handling a panic is contained by aborting, as unwinding across JNI is undefined.
+fn contain_panic(err: Box<dyn Any + Send>, env: &mut JNIEnv) {
+	if catch_unwind(AssertUnwindSafe(|| handle_panic(err, env))).is_err() {
+		eprintln!("Panicked while handling a panic");
+		abort();
+	}
 }
 
 macro_rules! run_catch {
-	($func:block, $t: ty, $env:expr) => {
+	($func:block, $t: ty, $env:expr) => {{
+		USED_HANDLES.with_borrow_mut(Vec::clear);
 		match catch_unwind(AssertUnwindSafe(|| $func)) {
 			Ok(v) => v,
 			Err(err) => {
-				let b = BACKTRACE.take().unwrap();
-				if let Some(val) = err.downcast_ref::<String>() {
-					FerriciaError(format!("{val:?}\n{b:?}")).throw_jni($env);
-				} else {
-					FerriciaError(format!("Unknown\n{b:?}")).throw_jni($env);
-				}
+				contain_panic(err, $env);
 				jni_null!($t)
 			}
 		}
-	};
-	($func:block, $env:expr) => {
+	}};
+	($func:block, $env:expr) => {{
+		USED_HANDLES.with_borrow_mut(Vec::clear);
 		match catch_unwind(AssertUnwindSafe(|| $func)) {
 			Ok(v) => v,
-			Err(err) => {
-				let b = BACKTRACE.take().unwrap();
-				if let Some(val) = err.downcast_ref::<String>() {
-					FerriciaError(format!("{val:?}\n{b:?}")).throw_jni($env);
-				} else {
-					FerriciaError(format!("Unknown\n{b:?}")).throw_jni($env);
-				}
-			}
+			Err(err) => contain_panic(err, $env),
 		}
-	};
+	}};
 }
 
 fn jni_get_string(env: &mut JNIEnv, src: JString) -> String {
 	env.get_string(&src).expect("Cannot get Java string").into()
 }
 
+fn jni_get_string_array(env: &mut JNIEnv, src: JObjectArray) -> Vec<String> {
+	let len = env.get_array_length(&src).expect("Cannot get Java array length");
+	(0..len).map(|i| {
+		let v = env.get_object_array_element(&src, i).expect("Cannot get Java array element");
+		jni_get_string(env, v.into())
+	}).collect()
+}
+
 macro_rules! jni_get_arr {
 	($out:ident = $arr:ty; $var:ident, $env:ident) => {
 		let $var = unsafe { <$arr>::from_raw($var) };
@@ -181,6 +402,19 @@ macro_rules! jni_get_arr {
 	};
 }
 
+/// Copies a Java array that must have exactly the `N` elements, such as the parameters of constructors.
+fn jni_fixed_arr<T: Copy, const N: usize>(arr: &[T], name: &str) -> FerriciaResult<[T; N]> {
+	arr.try_into().map_err(|_| FerriciaError(format!("Expected {N} elements in {name} but got {}", arr.len())))
+}
+
+/// [jni_get_arr] with [jni_fixed_arr], throwing for other lengths; the return type is of the function.
+macro_rules! jni_get_fixed_arr {
+	($out:ident = $arr:ty, $n:literal; $var:ident, $env:ident $(, $t:ty)?) => {
+		jni_get_arr!($out = $arr; $var, $env);
+		let $out: [_; $n] = resolve_res!(jni_fixed_arr(&$out, stringify!($var)), $($t,)? &mut $env);
+	};
+}
+
 // #[allow(non_snake_case)]
 // #[unsafe(no_mangle)]
 // pub extern "system" fn Java_terramodulus_engine_ferricia_Demo_hello(
@@ -288,6 +522,34 @@ macro_rules! jni_ferricia {
 			}
 		}
 	};
+	{ hot_reload:$class:ident.$function:ident( mut $env:ident: JNIEnv, $($params:tt)* ) $body:block } => {
+		paste! {
+			#[allow(unused_mut)]
+			#[allow(unused_variables)]
+			#[allow(non_snake_case)]
+			#[allow(clippy::not_unsafe_ptr_arg_deref)]
+			#[unsafe(no_mangle)]
+			#[cfg(feature = "hot-reload")]
+			pub extern "system" fn [<Java_terramodulus_engine_ferricia_ $class _ $function>]
+			(mut $env: JNIEnv, $($params)*) {
+				run_catch!($body, &mut $env);
+			}
+		}
+	};
+	{ hot_reload:$class:ident.$function:ident( mut $env:ident: JNIEnv, $($params:tt)* ) -> $ret:ty $body:block } => {
+		paste! {
+			#[allow(unused_mut)]
+			#[allow(unused_variables)]
+			#[allow(non_snake_case)]
+			#[allow(clippy::not_unsafe_ptr_arg_deref)]
+			#[unsafe(no_mangle)]
+			#[cfg(feature = "hot-reload")]
+			pub extern "system" fn [<Java_terramodulus_engine_ferricia_ $class _ $function>]
+			(mut $env: JNIEnv, $($params)*) -> $ret {
+				return run_catch!($body, $ret, &mut $env);
+			}
+		}
+	};
 }
 
 jni_ferricia! {
@@ -305,55 +567,140 @@ jni_ferricia! {
 
 jni_ferricia! {
 	client:Mui.initSdlHandle(mut env: JNIEnv, class: JClass) -> jlong {
-		jni_res_to_ptr(SdlHandle::new(), &mut env) as jlong
+		jni_res_to_handle(SdlHandle::new(), &mut env)
 	}
 }
 
 jni_ferricia! {
 	client:Mui.dropSdlHandle(mut env: JNIEnv, class: JClass, handle: jlong) {
-		jni_drop_with_ptr::<SdlHandle>(handle);
+		resolve_res!(jni_drop_with_handle::<SdlHandle>(handle), &mut env);
 	}
 }
 
 jni_ferricia! {
 	client:Mui.initWindowHandle(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
-		jni_res_to_ptr(WindowHandle::new(jni_ref_ptr(handle)), &mut env)
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(WindowHandle::new(sdl_handle, &ContextConfig::default()), &mut env)
+	}
+}
+
+jni_ferricia! {
+	// `config` is `[samples, depth bits, stencil bits, profile, major, minor, float buffers]`, with version `0.0` as
+	// the default. Float buffers is `1` to request 16-bit floating point color buffers, without an HDR colorspace.
+	// Profiles are `0` default, `1` core, `2` compatibility and `3` OpenGL ES.
+	client:Mui.initWindowHandleWithConfig(mut env: JNIEnv, class: JClass, handle: jlong, config: jintArray) -> jlong {
+		jni_get_fixed_arr!(arr = JIntArray, 7; config, env, jlong);
+		if arr.iter().any(|v| !(0..=u8::MAX as jint).contains(v)) {
+			FerriciaError("Invalid context config".to_string()).throw_jni(&mut env);
+			return 0;
+		}
+		let config = ContextConfig {
+			samples: arr[0] as _,
+			depth_bits: arr[1] as _,
+			stencil_bits: arr[2] as _,
+			profile: resolve_res!(ContextProfile::from_id(arr[3]), jlong, &mut env),
+			version: (arr[4] != 0).then_some((arr[4] as _, arr[5] as _)),
+			float_buffers: arr[6] != 0,
+		};
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(WindowHandle::new(sdl_handle, &config), &mut env)
+	}
+}
+
+jni_ferricia! {
+	// The same layout as the config of `initWindowHandleWithConfig`
+	client:Mui.windowContextConfig(mut env: JNIEnv, class: JClass, handle: jlong, sdl: jlong) -> jintArray {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, sdl), jintArray, &mut env);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jintArray, &mut env);
+		let config = window_handle.context_config(sdl_handle);
+		let (major, minor) = config.version.unwrap_or_default();
+		let data = [
+			config.samples as jint,
+			config.depth_bits as jint,
+			config.stencil_bits as jint,
+			config.profile as jint,
+			major as jint,
+			minor as jint,
+			config.float_buffers as jint,
+		];
+		let a = env.new_int_array(data.len() as jsize).expect("Cannot create Java int array");
+		env.set_int_array_region(&a, 0, &data).expect("Cannot set Java int array");
+		a.into_raw()
 	}
 }
 
 jni_ferricia! {
 	client:Mui.dropWindowHandle(mut env: JNIEnv, class: JClass, handle: jlong) {
-		jni_drop_with_ptr::<WindowHandle>(handle);
+		resolve_res!(jni_drop_with_handle::<WindowHandle>(handle), &mut env);
 	}
 }
 
 jni_ferricia! {
 	client:Mui.getGLVersion(mut env: JNIEnv, class: JClass, handle: jlong) -> jstring {
-		env.new_string(jni_ref_ptr::<WindowHandle>(handle).full_gl_version())
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jstring, &mut env);
+		env.new_string(window_handle.full_gl_version())
 			.expect("Cannot create Java string")
 			.into_raw()
 	}
 }
 
+jni_ferricia! {
+	client:Mui.getGLCapabilities(mut env: JNIEnv, class: JClass, handle: jlong) -> jobject {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jobject, &mut env);
+		let caps = window_handle.gl_capabilities();
+		let extensions = env.new_object_array(caps.extensions.len() as jsize, "java/lang/String", JObject::null())
+			.expect("Cannot create Java object array");
+		caps.extensions.into_iter().enumerate().for_each(|(i, v)| {
+			let v = env.new_string(v).expect("Cannot create Java string");
+			env.set_object_array_element(&extensions, i as jsize, v).expect("Cannot set Java object array");
+		});
+		let vendor = env.new_string(caps.vendor).expect("Cannot create Java string");
+		let renderer = env.new_string(caps.renderer).expect("Cannot create Java string");
+		let version = env.new_string(caps.version).expect("Cannot create Java string");
+		let p = vec!(
+			(&vendor).into(),
+			(&renderer).into(),
+			(&version).into(),
+			caps.max_texture_size.into(),
+			caps.max_texture_units.into(),
+			caps.ubo.into(),
+			caps.instancing.into(),
+			(&extensions).into(),
+		);
+		env.new_object(
+			"terramodulus/engine/GLCapabilities",
+			"(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;IIZZ[Ljava/lang/String;)V",
+			p.as_slice(),
+		)
+			.expect("Cannot create Java object")
+			.into_raw()
+	}
+}
+
 jni_ferricia! {
 	client:Mui.sdlPoll(mut env: JNIEnv, class: JClass, handle: jlong) -> jobjectArray {
-		let v = jni_ref_ptr::<SdlHandle>(handle).poll();
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jobjectArray, &mut env);
+		let mut v = sdl_handle.poll();
+		if v.iter().any(|e| matches!(e, MuiEvent::WindowPixelSizeChanged(..))) {
+			auto_resize(&env, sdl_handle);
+		}
+		sdl_handle.filter_events(&mut v);
 		let a = env.new_object_array(v.len() as jsize, "terramodulus/engine/MuiEvent", JObject::null())
 			.expect("Cannot create Java array");
 		v.into_iter().enumerate().for_each(|(i, e)| {
 			let v = match e {
 				MuiEvent::DisplayAdded(handle) => {
-					let p = vec!(jni_to_ptr(handle).into());
+					let p = vec!(jni_to_handle(handle).into());
 					env.new_object("terramodulus/engine/MuiEvent$DisplayAdded", "(J)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
 				MuiEvent::DisplayRemoved(handle) => {
-					let p = vec!(jni_to_ptr(handle).into());
+					let p = vec!(jni_to_handle(handle).into());
 					env.new_object("terramodulus/engine/MuiEvent$DisplayRemoved", "(J)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
 				MuiEvent::DisplayMoved(handle) => {
-					let p = vec!(jni_to_ptr(handle).into());
+					let p = vec!(jni_to_handle(handle).into());
 					env.new_object("terramodulus/engine/MuiEvent$DisplayMoved", "(J)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
@@ -498,14 +845,26 @@ jni_ferricia! {
 						.l()
 						.expect("JObject is expected")
 				}
-				MuiEvent::KeyboardKeyDown(id, k) => {
-					let p = vec!((id as jint).into(), (k as u32 as jint).into());
-					env.new_object("terramodulus/engine/MuiEvent$KeyboardKeyDown", "(II)V", p.as_slice())
+				MuiEvent::KeyboardKeyDown(id, k, m) => {
+					let p = vec!((id as jint).into(), (k as u32 as jint).into(), (m as jint).into());
+					env.new_object("terramodulus/engine/MuiEvent$KeyboardKeyDown", "(III)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
-				MuiEvent::KeyboardKeyUp(id, k) => {
-					let p = vec!((id as jint).into(), (k as u32 as jint).into());
-					env.new_object("terramodulus/engine/MuiEvent$KeyboardKeyUp", "(II)V", p.as_slice())
+				MuiEvent::KeyboardKeyUp(id, k, m) => {
+					let p = vec!((id as jint).into(), (k as u32 as jint).into(), (m as jint).into());
+					env.new_object("terramodulus/engine/MuiEvent$KeyboardKeyUp", "(III)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::ActionPressed(a) => {
+					let ss = env.new_string(a).expect("Cannot create Java string");
+					let p = vec!((&ss).into());
+					env.new_object("terramodulus/engine/MuiEvent$ActionPressed", "(Ljava/lang/String;)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::ActionReleased(a) => {
+					let ss = env.new_string(a).expect("Cannot create Java string");
+					let p = vec!((&ss).into());
+					env.new_object("terramodulus/engine/MuiEvent$ActionReleased", "(Ljava/lang/String;)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
 				MuiEvent::TextEditing(t, s, l) => {
@@ -619,12 +978,10 @@ jni_ferricia! {
 					env.new_object("terramodulus/engine/MuiEvent$JoystickRemoved", "(I)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
-				MuiEvent::JoystickBatteryUpdated => {
-					const CLASS: &str = "terramodulus/engine/MuiEvent$JoystickBatteryUpdated";
-					env.get_static_field(CLASS, "INSTANCE", format!("L{CLASS};"))
-						.expect("Cannot get static field")
-						.l()
-						.expect("JObject is expected")
+				MuiEvent::JoystickBatteryUpdated(id, s, b) => {
+					let p = vec!((id as jint).into(), s.into(), b.into());
+					env.new_object("terramodulus/engine/MuiEvent$JoystickBatteryUpdated", "(III)V", p.as_slice())
+						.expect("Cannot create Java object")
 				}
 				MuiEvent::GamepadAxisMotion(id, a , v) => {
 					let p = vec!((id as jint).into(), (a as u8 as jbyte).into(), v.into());
@@ -671,6 +1028,31 @@ jni_ferricia! {
 					env.new_object("terramodulus/engine/MuiEvent$GamepadTouchpadUp", "(IIIFFF)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
+				MuiEvent::GamepadTouchpadTap(id, t, x, y) => {
+					let p = vec!((id as jint).into(), t.into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$GamepadTouchpadTap", "(IIFF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::GamepadTouchpadDrag(id, t, x, y) => {
+					let p = vec!((id as jint).into(), t.into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$GamepadTouchpadDrag", "(IIFF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::GamepadTouchpadSwipe(id, t, x, y) => {
+					let p = vec!((id as jint).into(), t.into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$GamepadTouchpadSwipe", "(IIFF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PlayerSlotAssigned(slot, id) => {
+					let p = vec!((slot as jint).into(), (id as jint).into());
+					env.new_object("terramodulus/engine/MuiEvent$PlayerSlotAssigned", "(II)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PlayerSlotDisconnected(slot) => {
+					let p = vec!((slot as jint).into());
+					env.new_object("terramodulus/engine/MuiEvent$PlayerSlotDisconnected", "(I)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
 				MuiEvent::GamepadSteamHandleUpdated => {
 					const CLASS: &str = "terramodulus/engine/MuiEvent$GamepadSteamHandleUpdated";
 					env.get_static_field(CLASS, "INSTANCE", format!("L{CLASS};"))
@@ -678,16 +1060,16 @@ jni_ferricia! {
 						.l()
 						.expect("JObject is expected")
 				}
-				MuiEvent::DropFile(f) => {
+				MuiEvent::DropFile(f, x, y) => {
 					let ss = env.new_string(f).expect("Cannot create Java string");
-					let p = vec!((&ss).into());
-					env.new_object("terramodulus/engine/MuiEvent$DropFile", "(Ljava/lang/String;)V", p.as_slice())
+					let p = vec!((&ss).into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$DropFile", "(Ljava/lang/String;FF)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
-				MuiEvent::DropText(t) => {
+				MuiEvent::DropText(t, x, y) => {
 					let ss = env.new_string(t).expect("Cannot create Java string");
-					let p = vec!((&ss).into());
-					env.new_object("terramodulus/engine/MuiEvent$DropText", "(Ljava/lang/String;)V", p.as_slice())
+					let p = vec!((&ss).into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$DropText", "(Ljava/lang/String;FF)V", p.as_slice())
 						.expect("Cannot create Java object")
 				}
 				MuiEvent::DropBegin => {
@@ -704,12 +1086,50 @@ jni_ferricia! {
 						.l()
 						.expect("JObject is expected")
 				}
-				MuiEvent::DropPosition => {
-					const CLASS: &str = "terramodulus/engine/MuiEvent$DropPosition";
-					env.get_static_field(CLASS, "INSTANCE", format!("L{CLASS};"))
-						.expect("Cannot get static field")
-						.l()
-						.expect("JObject is expected")
+				MuiEvent::DropPosition(x, y) => {
+					let p = vec!(x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$DropPosition", "(FF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenProximityIn(id) => {
+					let p = vec!((id as jint).into());
+					env.new_object("terramodulus/engine/MuiEvent$PenProximityIn", "(I)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenProximityOut(id) => {
+					let p = vec!((id as jint).into());
+					env.new_object("terramodulus/engine/MuiEvent$PenProximityOut", "(I)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenDown(id, x, y, e) => {
+					let p = vec!((id as jint).into(), x.into(), y.into(), e.into());
+					env.new_object("terramodulus/engine/MuiEvent$PenDown", "(IFFZ)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenUp(id, x, y, e) => {
+					let p = vec!((id as jint).into(), x.into(), y.into(), e.into());
+					env.new_object("terramodulus/engine/MuiEvent$PenUp", "(IFFZ)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenButtonDown(id, b, x, y) => {
+					let p = vec!((id as jint).into(), (b as jbyte).into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$PenButtonDown", "(IBFF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenButtonUp(id, b, x, y) => {
+					let p = vec!((id as jint).into(), (b as jbyte).into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$PenButtonUp", "(IBFF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenMotion(id, x, y) => {
+					let p = vec!((id as jint).into(), x.into(), y.into());
+					env.new_object("terramodulus/engine/MuiEvent$PenMotion", "(IFF)V", p.as_slice())
+						.expect("Cannot create Java object")
+				}
+				MuiEvent::PenAxis(id, a, v) => {
+					let p = vec!((id as jint).into(), a.into(), v.into());
+					env.new_object("terramodulus/engine/MuiEvent$PenAxis", "(IIF)V", p.as_slice())
+						.expect("Cannot create Java object")
 				}
 				MuiEvent::RenderTargetsReset => {
 					const CLASS: &str = "terramodulus/engine/MuiEvent$RenderTargetsReset";
@@ -732,6 +1152,16 @@ jni_ferricia! {
 						.l()
 						.expect("JObject is expected")
 				}
+				MuiEvent::Engine(e) => {
+					let source = env.new_string(e.source).expect("Cannot create Java string");
+					let message = env.new_string(e.message).expect("Cannot create Java string");
+					let p = vec!((&source).into(), (&message).into());
+					env.new_object(
+						"terramodulus/engine/MuiEvent$Engine",
+						"(Ljava/lang/String;Ljava/lang/String;)V",
+						p.as_slice(),
+					).expect("Cannot create Java object")
+				}
 			};
 			env.set_object_array_element(&a, i as jsize, v).expect("Cannot set Java object array");
 		});
@@ -740,182 +1170,2849 @@ jni_ferricia! {
 }
 
 jni_ferricia! {
-	client:Mui.resizeGLViewport(mut env: JNIEnv, class: JClass, handle: jlong, canvas_handle: jlong) {
-		jni_ref_ptr::<WindowHandle>(handle).gl_resize_viewport(jni_ref_ptr::<CanvasHandle>(canvas_handle));
+	client:Mui.registerRumblePattern(mut env: JNIEnv, class: JClass, handle: jlong, name: JString, data: jfloatArray) {
+		let name = jni_get_string(&mut env, name);
+		jni_get_arr!(arr = JFloatArray; data, env);
+		if arr.len() % 3 != 0 {
+			FerriciaError(format!("Expected keyframes of 3 elements in data but got {} elements", arr.len())).throw_jni(&mut env);
+			return;
+		}
+		if let Some(v) = arr.iter().step_by(3).find(|v| v.is_nan() || **v < 0.0) {
+			FerriciaError(format!("Invalid rumble keyframe time: {v}")).throw_jni(&mut env);
+			return;
+		}
+		let keyframes = arr.chunks_exact(3).map(|v| (v[0] as u64, v[1], v[2])).collect::<Vec<_>>();
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.register_rumble_pattern(name, &keyframes), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.showWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
-		jni_ref_ptr::<WindowHandle>(handle).show_window()
+	client:Mui.playRumblePattern(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		gamepad: jint,
+		name: JString,
+		intensity: jfloat,
+	) {
+		let name = jni_get_string(&mut env, name);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.play_rumble_pattern(gamepad as _, &name, intensity), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.swapWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
-		jni_ref_ptr::<WindowHandle>(handle).swap_window()
+	client:Mui.stopRumblePattern(mut env: JNIEnv, class: JClass, handle: jlong, gamepad: jint) {
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).stop_rumble_pattern(gamepad as _);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.initCanvasHandle(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
-		jni_to_ptr(CanvasHandle::new(jni_ref_ptr::<WindowHandle>(handle)))
+	client:Mui.rumbleGamepad(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		gamepad: jint,
+		low: jfloat,
+		high: jfloat,
+		duration_ms: jint,
+	) {
+		if duration_ms < 0 {
+			FerriciaError(format!("Invalid rumble duration: {duration_ms}")).throw_jni(&mut env);
+			return;
+		}
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.rumble_gamepad(gamepad as _, low, high, duration_ms as _), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.dropCanvasHandle(mut env: JNIEnv, class: JClass, handle: jlong) {
-		jni_drop_with_ptr::<CanvasHandle>(handle);
+	client:Mui.rumbleGamepadTriggers(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		gamepad: jint,
+		left: jfloat,
+		right: jfloat,
+		duration_ms: jint,
+	) {
+		if duration_ms < 0 {
+			FerriciaError(format!("Invalid rumble duration: {duration_ms}")).throw_jni(&mut env);
+			return;
+		}
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.rumble_gamepad_triggers(gamepad as _, left, right, duration_ms as _), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.loadImageToCanvas(mut env: JNIEnv, class: JClass, handle: jlong, path: JString) -> jint {
-		jni_ref_ptr::<CanvasHandle>(handle).load_image(env.get_string(&path)
-			.expect("Cannot get Java string").into()) as jint
+	client:Mui.getHapticDevices(mut env: JNIEnv, class: JClass, handle: jlong) -> jintArray {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jintArray, &mut env);
+		let data = sdl_handle.haptic_devices().into_iter().map(|v| v as jint).collect::<Vec<_>>();
+		let arr = env.new_int_array(data.len() as jsize).expect("Cannot create JIntArray");
+		env.set_int_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
 	}
 }
 
 jni_ferricia! {
-	client:Mui.clearCanvas(mut env: JNIEnv, class: JClass) {
-		clear_canvas()
+	client:Mui.getHapticDeviceName(mut env: JNIEnv, class: JClass, handle: jlong, id: jint) -> jstring {
+		// null if the device is disconnected
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jstring, &mut env);
+		match sdl_handle.haptic_device_name(id as _) {
+			Some(v) => env.new_string(v).expect("Cannot create Java string").into_raw(),
+			None => jni_null!(jstring),
+		}
 	}
 }
 
 jni_ferricia! {
-	client:Mui.setCanvasClearColor(mut env: JNIEnv, class: JClass, r: jfloat, g: jfloat, b: jfloat, a: jfloat) {
-		set_clear_color((r, g, b, a));
+	client:Mui.openHapticDevice(mut env: JNIEnv, class: JClass, id: jint) -> jlong {
+		jni_res_to_handle(ForceFeedbackDevice::open(id as _), &mut env)
 	}
 }
 
 jni_ferricia! {
-	client:Mui.geoShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
-		jni_res_to_ptr(GeoProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	// Effects of the device are destroyed along with it.
+	client:Mui.closeHapticDevice(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<ForceFeedbackDevice>(handle), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.texShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
-		jni_res_to_ptr(TexProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	client:Mui.newConstantHapticEffect(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		direction: jfloat,
+		level: jfloat,
+		duration_ms: jint,
+	) -> jint {
+		if duration_ms < 0 {
+			FerriciaError(format!("Invalid haptic effect duration: {duration_ms}")).throw_jni(&mut env);
+			return 0;
+		}
+		let device = resolve_res!(jni_ref_handle::<ForceFeedbackDevice>(&env, handle), jint, &mut env);
+		resolve_res!(device.new_constant_effect(direction, level, duration_ms as _), jint, &mut env)
 	}
 }
 
 jni_ferricia! {
-	client:Mui.newSimpleLineGeom(mut env: JNIEnv, class: JClass, data: jintArray) -> jlong {
-		jni_get_arr!(arr = JIntArray; data, env);
-		jni_to_ptr(DrawableSet::new(SimpleLineGeom::new(
-			[(arr[0] as f32, arr[1] as f32), (arr[2] as f32, arr[3] as f32)],
-			Color::RGBA(arr[4] as u8, arr[5] as u8, arr[6] as u8, arr[7] as u8),
-		)))
+	// `waveform` is of `Waveform::from_id`, from `0` for sine to `4` for sawtooth down.
+	client:Mui.newPeriodicHapticEffect(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		waveform: jint,
+		direction: jfloat,
+		period_ms: jint,
+		magnitude: jfloat,
+		duration_ms: jint,
+	) -> jint {
+		if period_ms < 0 || period_ms > u16::MAX as jint {
+			FerriciaError(format!("Invalid haptic effect period: {period_ms}")).throw_jni(&mut env);
+			return 0;
+		}
+		if duration_ms < 0 {
+			FerriciaError(format!("Invalid haptic effect duration: {duration_ms}")).throw_jni(&mut env);
+			return 0;
+		}
+		let waveform = resolve_res!(Waveform::from_id(waveform), jint, &mut env);
+		let device = resolve_res!(jni_ref_handle::<ForceFeedbackDevice>(&env, handle), jint, &mut env);
+		let effect = device.new_periodic_effect(waveform, direction, period_ms as _, magnitude, duration_ms as _);
+		resolve_res!(effect, jint, &mut env)
 	}
 }
 
 jni_ferricia! {
-	client:Mui.newSimpleRectGeom(mut env: JNIEnv, class: JClass, data: jintArray) -> jlong {
-		jni_get_arr!(arr = JIntArray; data, env);
-		jni_to_ptr(DrawableSet::new(SimpleRectGeom::new(
-			[arr[0] as f32, arr[1] as f32, arr[2] as f32, arr[3] as f32],
-			Color::RGBA(arr[4] as u8, arr[5] as u8, arr[6] as u8, arr[7] as u8),
-		)))
+	// `iterations` of `0` repeats the effect until stopped.
+	client:Mui.runHapticEffect(mut env: JNIEnv, class: JClass, handle: jlong, effect: jint, iterations: jint) {
+		if iterations < 0 {
+			FerriciaError(format!("Invalid haptic effect iterations: {iterations}")).throw_jni(&mut env);
+			return;
+		}
+		let device = resolve_res!(jni_ref_handle::<ForceFeedbackDevice>(&env, handle), &mut env);
+		resolve_res!(device.run_effect(effect, iterations as _), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.newSpriteMesh(mut env: JNIEnv, class: JClass, data: jintArray) -> jlong {
-		jni_get_arr!(arr = JIntArray; data, env);
-		jni_to_ptr(DrawableSet::new(SpriteMesh::new([arr[0] as _, arr[1] as _, arr[2] as _, arr[3] as _])))
+	client:Mui.stopHapticEffect(mut env: JNIEnv, class: JClass, handle: jlong, effect: jint) {
+		let device = resolve_res!(jni_ref_handle::<ForceFeedbackDevice>(&env, handle), &mut env);
+		resolve_res!(device.stop_effect(effect), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.modelSmartScaling(mut env: JNIEnv, class: JClass, data: jintArray) -> jlongArray {
-		jni_get_arr!(arr = JIntArray; data, env);
-		jni_to_destructed_ptr!(SmartScaling::new((arr[0] as _, arr[1] as _), match arr[2] {
-			0 => None,
-			1 => Some((ScalingCenteredTranslateParam::X, (arr[3] as _, arr[4] as _))),
-			2 => Some((ScalingCenteredTranslateParam::Y, (arr[3] as _, arr[4] as _))),
-			3 => Some((ScalingCenteredTranslateParam::Both, (arr[3] as _, arr[4] as _))),
-			_ => panic!("Invalid Smart Scaling parameter"),
-		}), dyn PrimModelTransform, env);
+	client:Mui.stopAllHapticEffects(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<ForceFeedbackDevice>(&env, handle), &mut env).stop_all();
 	}
 }
 
 jni_ferricia! {
-	client:Mui.modelFullScaling(mut env: JNIEnv, class: JClass, data: jintArray) -> jlongArray {
-		jni_get_arr!(arr = JIntArray; data, env);
-		jni_to_destructed_ptr!(FullScaling::new((arr[0] as _, arr[1] as _)), dyn PrimModelTransform, env);
+	client:Mui.destroyHapticEffect(mut env: JNIEnv, class: JClass, handle: jlong, effect: jint) {
+		resolve_res!(jni_ref_handle::<ForceFeedbackDevice>(&env, handle), &mut env).destroy_effect(effect);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.modelSimpleTranslation(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
-		jni_get_arr!(arr = JFloatArray; data, env);
-		jni_to_destructed_ptr!(SimpleTranslation::new(arr[0], arr[1]), dyn PrimModelTransform, env);
+	client:Mui.getGamepadInfo(mut env: JNIEnv, class: JClass, handle: jlong, gamepad: jint) -> jobject {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jobject, &mut env);
+		let info = resolve_res!(sdl_handle.gamepad_info(gamepad as _), jobject, &mut env);
+		let name = env.new_string(info.name).expect("Cannot create Java string");
+		let guid = env.new_string(info.guid).expect("Cannot create Java string");
+		let p = vec!(
+			(&name).into(),
+			(&guid).into(),
+			info.kind.into(),
+			info.connection.into(),
+			info.power.into(),
+			info.battery.into(),
+		);
+		env.new_object("terramodulus/engine/GamepadInfo", "(Ljava/lang/String;Ljava/lang/String;IIII)V", p.as_slice())
+			.expect("Cannot create Java object")
+			.into_raw()
 	}
 }
 
 jni_ferricia! {
-	client:Mui.filterAlphaFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
-		jni_to_destructed_ptr!(AlphaFilter::new(data), dyn PrimColorFilter, env);
+	// `exponent` of `1` is linear; dead zone and saturation are fractions of the full range.
+	client:Mui.setGamepadAxisResponse(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		axis: jint,
+		dead_zone: jfloat,
+		saturation: jfloat,
+		exponent: jfloat,
+	) {
+		let axis = resolve_res!(jni_gamepad_axis(axis), &mut env);
+		let response = resolve_res!(AxisResponse::new(dead_zone, saturation, exponent), &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_gamepad_axis_response(axis, Some(response));
 	}
 }
 
 jni_ferricia! {
-	client:Mui.editAlphaFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
-		jni_ref_ptr::<AlphaFilter>(filter).set_alpha(data as _);
+	client:Mui.resetGamepadAxisResponse(mut env: JNIEnv, class: JClass, handle: jlong, axis: jint) {
+		let axis = resolve_res!(jni_gamepad_axis(axis), &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_gamepad_axis_response(axis, None);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.addModelTransform(mut env: JNIEnv, class: JClass, set_handle: jlong, model_handle: jlong) {
-		jni_ref_ptr::<DrawableSet>(set_handle).add_model_transform(jni_ref_wide_ptr(model_handle))
+	client:Mui.setJoystickAxisResponse(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		axis: jint,
+		dead_zone: jfloat,
+		saturation: jfloat,
+		exponent: jfloat,
+	) {
+		if axis < 0 || axis > u8::MAX as jint {
+			FerriciaError(format!("Invalid joystick axis: {axis}")).throw_jni(&mut env);
+			return;
+		}
+		let response = resolve_res!(AxisResponse::new(dead_zone, saturation, exponent), &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_joystick_axis_response(axis as _, Some(response));
 	}
 }
 
 jni_ferricia! {
-	client:Mui.removeModelTransform(mut env: JNIEnv, class: JClass, set_handle: jlong, model_handle: jlong) {
-		jni_ref_ptr::<DrawableSet>(set_handle).remove_model_transform(jni_ref_wide_ptr(model_handle))
+	client:Mui.resetJoystickAxisResponse(mut env: JNIEnv, class: JClass, handle: jlong, axis: jint) {
+		if axis < 0 || axis > u8::MAX as jint {
+			FerriciaError(format!("Invalid joystick axis: {axis}")).throw_jni(&mut env);
+			return;
+		}
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_joystick_axis_response(axis as _, None);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.addColorFilter(mut env: JNIEnv, class: JClass, set_handle: jlong, filter_handle: jlong) {
-		jni_ref_ptr::<DrawableSet>(set_handle).add_filter_transform(jni_ref_wide_ptr(filter_handle))
+	client:Mui.clearAxisResponses(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).clear_axis_responses();
 	}
 }
 
 jni_ferricia! {
-	client:Mui.removeColorFilter(mut env: JNIEnv, class: JClass, set_handle: jlong, filter_handle: jlong) {
-		jni_ref_ptr::<DrawableSet>(set_handle).remove_filter_transform(jni_ref_wide_ptr(filter_handle))
+	client:Mui.setPlayerSlotCount(mut env: JNIEnv, class: JClass, handle: jlong, count: jint) {
+		if count < 0 {
+			FerriciaError(format!("Invalid player slot count: {count}")).throw_jni(&mut env);
+			return;
+		}
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_player_slot_count(count as _);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.drawGuiGeo(
-		mut env: JNIEnv,
-		class: JClass,
-		canvas_handle: jlong,
-		drawable_handle: jlong,
-		program_handle: jlong,
-	) {
-		jni_ref_ptr::<CanvasHandle>(canvas_handle)
-			.draw_gui(jni_ref_ptr::<DrawableSet>(drawable_handle), jni_ref_ptr::<GeoProgram>(program_handle), None)
+	client:Mui.assignPlayerSlot(mut env: JNIEnv, class: JClass, handle: jlong, slot: jint, gamepad: jint) {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.assign_player_slot(slot as _, gamepad as _), &mut env);
 	}
 }
 
 jni_ferricia! {
-	client:Mui.drawGuiTex(
-		mut env: JNIEnv,
-		class: JClass,
-		canvas_handle: jlong,
-		drawable_handle: jlong,
-		program_handle: jlong,
-		texture_handle: jint,
-	) {
-		jni_ref_ptr::<CanvasHandle>(canvas_handle).draw_gui(
-			jni_ref_ptr::<DrawableSet>(drawable_handle),
-			jni_ref_ptr::<TexProgram>(program_handle),
-			Some(texture_handle as _),
-		)
+	client:Mui.playerSlotGamepad(mut env: JNIEnv, class: JClass, handle: jlong, slot: jint) -> jint {
+		// -1 if no gamepad is assigned
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jint, &mut env)
+			.player_slot_gamepad(slot as _).map_or(-1, |v| v as jint)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.exportPlayerSlots(mut env: JNIEnv, class: JClass, handle: jlong) -> jstring {
+		let json = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jstring, &mut env).export_player_slots();
+		env.new_string(json).expect("Cannot create Java string").into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.importPlayerSlots(mut env: JNIEnv, class: JClass, handle: jlong, json: JString) {
+		let json = jni_get_string(&mut env, json);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.import_player_slots(&json), &mut env);
+	}
+}
+
+#[cfg(feature = "client")]
+fn jni_keyboard_key(key: jint) -> FerriciaResult<KeyboardKey> {
+	KeyboardKey::from_ordinal(key as _).ok_or_else(|| FerriciaError(format!("Invalid keyboard key: {key}")))
+}
+
+#[cfg(feature = "client")]
+fn jni_mouse_key(key: jint) -> FerriciaResult<MouseKey> {
+	u8::try_from(key).ok().and_then(MouseKey::from_ordinal)
+		.ok_or_else(|| FerriciaError(format!("Invalid mouse key: {key}")))
+}
+
+/// Buttons and axes are of their values in SDL, as in the events.
+#[cfg(feature = "client")]
+fn jni_gamepad_button(button: jint) -> FerriciaResult<GamepadButton> {
+	GamepadButton::from_ll(SDL_GamepadButton(button))
+		.ok_or_else(|| FerriciaError(format!("Invalid gamepad button: {button}")))
+}
+
+#[cfg(feature = "client")]
+fn jni_gamepad_axis(axis: jint) -> FerriciaResult<GamepadAxis> {
+	GamepadAxis::from_ll(SDL_GamepadAxis(axis))
+		.ok_or_else(|| FerriciaError(format!("Invalid gamepad axis: {axis}")))
+}
+
+jni_ferricia! {
+	client:Mui.bindActionPhysical(mut env: JNIEnv, class: JClass, handle: jlong, action: JString, key: jint) {
+		let action = jni_get_string(&mut env, action);
+		let key = resolve_res!(jni_keyboard_key(key), &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).bind_action_physical(action, key);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.bindActionLayout(mut env: JNIEnv, class: JClass, handle: jlong, action: JString, symbol: JString) {
+		let action = jni_get_string(&mut env, action);
+		let symbol = jni_get_string(&mut env, symbol);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.bind_action_layout(action, &symbol), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.bindActionMouse(mut env: JNIEnv, class: JClass, handle: jlong, action: JString, key: jint) {
+		let action = jni_get_string(&mut env, action);
+		let key = resolve_res!(jni_mouse_key(key), &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).bind_action_mouse(action, key);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.bindActionGamepadButton(mut env: JNIEnv, class: JClass, handle: jlong, action: JString, button: jint) {
+		let action = jni_get_string(&mut env, action);
+		let button = resolve_res!(jni_gamepad_button(button), &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).bind_action_gamepad_button(action, button);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.bindActionGamepadAxis(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		action: JString,
+		axis: jint,
+		positive: jboolean,
+		dead_zone: jfloat,
+	) {
+		let action = jni_get_string(&mut env, action);
+		let axis = resolve_res!(jni_gamepad_axis(axis), &mut env);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		resolve_res!(sdl_handle.bind_action_gamepad_axis(action, axis, positive != 0, dead_zone), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.isActionHeld(mut env: JNIEnv, class: JClass, handle: jlong, action: JString) -> jboolean {
+		let action = jni_get_string(&mut env, action);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jboolean, &mut env).action_held(&action) as jboolean
+	}
+}
+
+jni_ferricia! {
+	client:Mui.getActionValue(mut env: JNIEnv, class: JClass, handle: jlong, action: JString) -> jfloat {
+		let action = jni_get_string(&mut env, action);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jfloat, &mut env).action_value(&action)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.unbindAction(mut env: JNIEnv, class: JClass, handle: jlong, action: JString) {
+		let action = jni_get_string(&mut env, action);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).unbind_action(&action);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.keyLayoutSymbol(mut env: JNIEnv, class: JClass, handle: jlong, key: jint) -> jstring {
+		// null if the key produces no symbol
+		let key = resolve_res!(jni_keyboard_key(key), jstring, &mut env);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jstring, &mut env);
+		match sdl_handle.key_layout_symbol(key) {
+			Some(v) => env.new_string(v).expect("Cannot create Java string").into_raw(),
+			None => jni_null!(jstring),
+		}
+	}
+}
+
+jni_ferricia! {
+	client:Mui.keyLayoutCode(mut env: JNIEnv, class: JClass, handle: jlong, key: jint) -> jint {
+		// -1 if the key has no keycode under the layout
+		let key = resolve_res!(jni_keyboard_key(key), jint, &mut env);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jint, &mut env)
+			.key_layout_code(key).map_or(-1, |v| v as jint)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.keyOfLayoutSymbol(mut env: JNIEnv, class: JClass, handle: jlong, symbol: JString) -> jint {
+		// -1 if no key produces the symbol
+		let symbol = jni_get_string(&mut env, symbol);
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jint, &mut env)
+			.key_of_layout_symbol(&symbol).map_or(-1, |v| v as u32 as jint)
+	}
+}
+
+/// Resizes the registered canvases, forgetting those of dropped handles.
+#[cfg(feature = "client")]
+fn auto_resize(env: &JNIEnv, sdl_handle: &mut SdlHandle) {
+	sdl_handle.retain_auto_resize(|(window, canvas)| {
+		let window_handle = jni_ref_handle::<WindowHandle>(env, window.into_raw());
+		let canvas_handle = jni_ref_handle::<CanvasHandle>(env, canvas.into_raw());
+		match (window_handle, canvas_handle) {
+			(Ok(window_handle), Ok(canvas_handle)) => {
+				window_handle.gl_resize_viewport(canvas_handle);
+				true
+			}
+			_ => false,
+		}
+	});
+	sdl_handle.retain_auto_resize_targets(|(canvas, target)| {
+		let Ok(canvas_handle) = jni_ref_handle::<CanvasHandle>(env, canvas.into_raw()) else { return false };
+		match resize_render_target(env, *target, *canvas_handle.size()) {
+			Ok(Ok(())) => true,
+			// Kept as the target is still alive, so it may be resized again later
+			Ok(Err(err)) => {
+				post_event("mui", format!("Cannot resize render target: {}", err.0));
+				true
+			}
+			Err(_) => false,
+		}
+	});
+}
+
+/// Returns the result of resizing, or `Err` if the target is not a render target or has been dropped.
+#[cfg(feature = "client")]
+fn resize_render_target(env: &JNIEnv, target: TaggedId, size: (u32, u32)) -> FerriciaResult<FerriciaResult<()>> {
+	match target.tag() {
+		HandleTag::PickBuffer => Ok(jni_ref_handle::<PickBuffer>(env, target.into_raw())?.resize(size)),
+		HandleTag::GuiCache => Ok(jni_ref_handle::<GuiCache>(env, target.into_raw())?.resize(size)),
+		HandleTag::RenderGraph => Ok(jni_ref_handle::<RenderGraph>(env, target.into_raw())?.resize(size)),
+		tag => Err(format!("{tag:?} is not a render target").into()),
+	}
+}
+
+jni_ferricia! {
+	// Bits of categories from `1` for display events, in the order of `MuiEvent::DISPLAY` to `MuiEvent::RENDER`;
+	// actions, player slots and engine events are always reported.
+	client:Mui.setEventMask(mut env: JNIEnv, class: JClass, handle: jlong, mask: jint) {
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_event_mask(mask as _);
+	}
+}
+
+jni_ferricia! {
+	// The canvas is resized on `WindowPixelSizeChanged` during `sdlPoll`, instead of by `resizeGLViewport`.
+	client:Mui.setAutoResize(mut env: JNIEnv, class: JClass, handle: jlong, window: jlong, canvas: jlong, enabled: jboolean) {
+		// Checked for the types, since mismatched pairs would only be forgotten silently later
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, window), &mut env);
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas), &mut env);
+		let window = resolve_res!(TaggedId::from_raw(window), &mut env);
+		let canvas = resolve_res!(TaggedId::from_raw(canvas), &mut env);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		sdl_handle.set_auto_resize(window, canvas, enabled != 0);
+	}
+}
+
+jni_ferricia! {
+	// Pick buffers, GUI caches and render graphs are resized along with the canvas during `sdlPoll`,
+	// after it is resized automatically; failures are posted as engine events.
+	client:Mui.setAutoResizeTarget(mut env: JNIEnv, class: JClass, handle: jlong, canvas: jlong, target: jlong, enabled: jboolean) {
+		let size = *resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas), &mut env).size();
+		let canvas = resolve_res!(TaggedId::from_raw(canvas), &mut env);
+		let target = resolve_res!(TaggedId::from_raw(target), &mut env);
+		// Also catches up with the current size, as the canvas may have been resized since
+		resolve_res!(resolve_res!(resize_render_target(&env, target, size), &mut env), &mut env);
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		sdl_handle.set_auto_resize_target(canvas, target, enabled != 0);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.resizeGLViewport(mut env: JNIEnv, class: JClass, handle: jlong, canvas_handle: jlong) {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		window_handle.gl_resize_viewport(resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env));
+	}
+}
+
+/// Calls `recreate` with each live value of the type, posting the failures as `gl` engine events.
+#[cfg(feature = "client")]
+fn recreate_all<T: Tagged>(env: &JNIEnv, recreate: impl Fn(&mut T) -> FerriciaResult<()>) {
+	// The registry is locked again for each value.
+	let ids = HandleRegistry::lock().live(T::TAG);
+	for id in ids {
+		let Ok(value) = jni_ref_handle::<T>(env, id.into_raw()) else { continue };
+		if let Err(err) = recreate(value) {
+			post_event("gl", format!("Cannot recreate {:?}: {}", T::TAG, err.0));
+		}
+	}
+}
+
+/// Makes the canvases bind their programs again on their next draws, after a program is deleted
+/// or replaced, or another one is bound outside them.
+#[cfg(feature = "client")]
+fn forget_used_programs(env: &JNIEnv) {
+	let ids = HandleRegistry::lock().live(CanvasHandle::TAG);
+	for id in ids {
+		if let Ok(canvas_handle) = jni_ref_handle::<CanvasHandle>(env, id.into_raw()) {
+			canvas_handle.forget_used_program();
+		}
+	}
+}
+
+/// Creates all the GL objects again on the new context of the window, returning pairs of the previous
+/// and the new textures, where the new texture is zero if failed.
+#[cfg(feature = "client")]
+fn recreate_gl_objects(env: &JNIEnv, window_handle: &WindowHandle) -> Vec<(u32, u32)> {
+	recreate_all(env, |v: &mut CanvasHandle| v.recreate(window_handle));
+	recreate_all(env, GeoProgram::recreate);
+	recreate_all(env, TexProgram::recreate);
+	recreate_all(env, ArrayTexProgram::recreate);
+	recreate_all(env, PickProgram::recreate);
+	recreate_all(env, TileMapProgram::recreate);
+	recreate_all(env, ComputeProgram::recreate);
+	recreate_all(env, |v: &mut DrawableSet| {
+		v.recreate();
+		Ok(())
+	});
+	recreate_all(env, |v: &mut StorageBuffer| {
+		v.recreate();
+		Ok(())
+	});
+	recreate_all(env, PickBuffer::recreate);
+	recreate_all(env, GuiCache::recreate);
+	recreate_all(env, RenderGraph::recreate);
+	let textures = recreate_textures(window_handle);
+	#[cfg(feature = "hot-reload")]
+	recreate_all(env, |v: &mut HotReloader| {
+		v.remap_textures(&textures);
+		Ok(())
+	});
+	textures
+}
+
+jni_ferricia! {
+	client:Mui.recreateGLResources(mut env: JNIEnv, class: JClass, handle: jlong, sdl: jlong) -> jintArray {
+		// Should be called on `RenderDeviceReset`, before any further draw; returns pairs of the previous
+		// and the new textures, where the new texture is 0 if failed, with the error posted as an engine event
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, sdl), jintArray, &mut env);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jintArray, &mut env);
+		resolve_res!(window_handle.recreate_gl_context(sdl_handle), jintArray, &mut env);
+		let data = recreate_gl_objects(&env, window_handle).into_iter()
+			.flat_map(|(previous, texture)| [previous as jint, texture as jint])
+			.collect::<Vec<_>>();
+		let arr = env.new_int_array(data.len() as jsize).expect("Cannot create JIntArray");
+		env.set_int_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setCursorVisible(mut env: JNIEnv, class: JClass, handle: jlong, visible: jboolean) {
+		resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env).set_cursor_visible(visible != 0);
+	}
+}
+
+jni_ferricia! {
+	// Relative motion is reported by `MouseMotion` in both modes.
+	client:Mui.setRelativeMouseMode(mut env: JNIEnv, class: JClass, handle: jlong, window: jlong, enabled: jboolean) {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, window), &mut env);
+		sdl_handle.set_relative_mouse_mode(window_handle, enabled != 0);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.startTextInput(mut env: JNIEnv, class: JClass, handle: jlong, window: jlong) {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		sdl_handle.start_text_input(resolve_res!(jni_ref_handle::<WindowHandle>(&env, window), &mut env));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.stopTextInput(mut env: JNIEnv, class: JClass, handle: jlong, window: jlong) {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		sdl_handle.stop_text_input(resolve_res!(jni_ref_handle::<WindowHandle>(&env, window), &mut env));
+	}
+}
+
+jni_ferricia! {
+	// In logical window coordinates from the top-left corner; `cursor` is relative to `x`.
+	client:Mui.setTextInputArea(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		window: jlong,
+		x: jint,
+		y: jint,
+		w: jint,
+		h: jint,
+		cursor: jint,
+	) {
+		if w < 0 || h < 0 {
+			FerriciaError(format!("Invalid text input area size: {w}x{h}")).throw_jni(&mut env);
+			return;
+		}
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), &mut env);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, window), &mut env);
+		let area = Rect::new(x, y, w as _, h as _);
+		resolve_res!(sdl_handle.set_text_input_area(window_handle, area, cursor), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newCursor(mut env: JNIEnv, class: JClass, path: JString, hot_x: jint, hot_y: jint) -> jlong {
+		let path = jni_get_string(&mut env, path);
+		jni_res_to_handle(CursorHandle::from_file(&path, (hot_x, hot_y)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newCursorFromBytes(mut env: JNIEnv, class: JClass, data: JByteArray, hot_x: jint, hot_y: jint) -> jlong {
+		let bytes = env.convert_byte_array(&data).expect("Cannot get Java byte array");
+		jni_res_to_handle(CursorHandle::from_bytes(&bytes, (hot_x, hot_y)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	// `id` is of `CursorHandle::system`, such as `1` for the I-beam and `11` for the hand.
+	client:Mui.newSystemCursor(mut env: JNIEnv, class: JClass, id: jint) -> jlong {
+		jni_res_to_handle(CursorHandle::system(id), &mut env)
+	}
+}
+
+jni_ferricia! {
+	// Dropping the active cursor restores the default one.
+	client:Mui.dropCursor(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<CursorHandle>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setCursor(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<CursorHandle>(&env, handle), &mut env).set();
+	}
+}
+
+jni_ferricia! {
+	// Display scale and pixel density
+	client:Mui.getWindowContentScale(mut env: JNIEnv, class: JClass, handle: jlong) -> jfloatArray {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jfloatArray, &mut env);
+		let data = [window_handle.display_scale(), window_handle.pixel_density()];
+		let arr = env.new_float_array(data.len() as jsize).expect("Cannot create JFloatArray");
+		env.set_float_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setCanvasGuiScale(mut env: JNIEnv, class: JClass, canvas_handle: jlong, scale: jfloat) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		resolve_res!(canvas_handle.set_gui_scale(scale), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// The display scale is applied now and on every resize of the canvas by the window.
+	client:Mui.setCanvasAutoGuiScale(mut env: JNIEnv, class: JClass, handle: jlong, canvas_handle: jlong, enabled: jboolean) {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		window_handle.set_auto_gui_scale(canvas_handle, enabled != 0);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.showWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).show_window()
+	}
+}
+
+jni_ferricia! {
+	// In logical size, as saved from `WindowResized`
+	client:Mui.setWindowSize(mut env: JNIEnv, class: JClass, handle: jlong, width: jint, height: jint) {
+		if width <= 0 || height <= 0 {
+			FerriciaError(format!("Invalid window size: {width}x{height}")).throw_jni(&mut env);
+			return;
+		}
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_window_size(width as _, height as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setWindowMinimumSize(mut env: JNIEnv, class: JClass, handle: jlong, width: jint, height: jint) {
+		if width < 0 || height < 0 {
+			FerriciaError(format!("Invalid window minimum size: {width}x{height}")).throw_jni(&mut env);
+			return;
+		}
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_minimum_size(width as _, height as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// `0x0` removes the limit.
+	client:Mui.setWindowMaximumSize(mut env: JNIEnv, class: JClass, handle: jlong, width: jint, height: jint) {
+		if width < 0 || height < 0 {
+			FerriciaError(format!("Invalid window maximum size: {width}x{height}")).throw_jni(&mut env);
+			return;
+		}
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_maximum_size(width as _, height as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.maximizeWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).maximize_window()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.minimizeWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).minimize_window()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.restoreWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).restore_window()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setMouseGrab(mut env: JNIEnv, class: JClass, handle: jlong, grabbed: jboolean) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).set_mouse_grab(grabbed != 0)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setMouseArea(mut env: JNIEnv, class: JClass, handle: jlong, x: jint, y: jint, width: jint, height: jint) {
+		if width <= 0 || height <= 0 {
+			FerriciaError(format!("Invalid mouse area size: {width}x{height}")).throw_jni(&mut env);
+			return;
+		}
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_mouse_area(Some(Rect::new(x, y, width as _, height as _))), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.clearMouseArea(mut env: JNIEnv, class: JClass, handle: jlong) {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_mouse_area(None), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.isDisplayHdrEnabled(mut env: JNIEnv, class: JClass, handle: jlong, display: jlong) -> jboolean {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jboolean, &mut env);
+		let display = resolve_res!(jni_ref_handle::<DisplayHandle>(&env, display), jboolean, &mut env);
+		resolve_res!(sdl_handle.display_hdr_enabled(display), jboolean, &mut env) as jboolean
+	}
+}
+
+jni_ferricia! {
+	// Triples of width, height and refresh rate in millihertz, by the indices for `setExclusiveFullscreen`
+	client:Mui.getFullscreenModes(mut env: JNIEnv, class: JClass, handle: jlong, display: jlong) -> jintArray {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, handle), jintArray, &mut env);
+		let display = resolve_res!(jni_ref_handle::<DisplayHandle>(&env, display), jintArray, &mut env);
+		let modes = resolve_res!(sdl_handle.fullscreen_modes(display), jintArray, &mut env);
+		let data = modes.iter()
+			.flat_map(|v| [v.w, v.h, (v.refresh_rate * 1000.0).round() as jint])
+			.collect::<Vec<_>>();
+		let arr = env.new_int_array(data.len() as jsize).expect("Cannot create JIntArray");
+		env.set_int_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	// `borderless` chooses between borderless fullscreen and windowed.
+	client:Mui.setFullscreen(mut env: JNIEnv, class: JClass, handle: jlong, borderless: jboolean) {
+		let mode = if borderless != 0 { FullscreenMode::Borderless } else { FullscreenMode::Windowed };
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_fullscreen(mode), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// `mode` is the index of the mode in `getFullscreenModes` of the display.
+	client:Mui.setExclusiveFullscreen(mut env: JNIEnv, class: JClass, handle: jlong, sdl_handle: jlong, display: jlong, mode: jint) {
+		let sdl_handle = resolve_res!(jni_ref_handle::<SdlHandle>(&env, sdl_handle), &mut env);
+		let display = resolve_res!(jni_ref_handle::<DisplayHandle>(&env, display), &mut env);
+		let modes = resolve_res!(sdl_handle.fullscreen_modes(display), &mut env);
+		let Some(mode) = usize::try_from(mode).ok().and_then(|i| modes.get(i)) else {
+			FerriciaError(format!("Invalid fullscreen mode: {mode}")).throw_jni(&mut env);
+			return;
+		};
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_fullscreen(FullscreenMode::Exclusive(*mode)), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.swapWindow(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).swap_window()
+	}
+}
+
+jni_ferricia! {
+	// `fps` of 0 removes the limit
+	client:Mui.setFrameLimit(mut env: JNIEnv, class: JClass, handle: jlong, fps: jint, spin_micros: jlong) {
+		if fps < 0 || spin_micros < 0 {
+			FerriciaError("Invalid frame limit".to_string()).throw_jni(&mut env);
+			return;
+		}
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		resolve_res!(window_handle.set_frame_limit(fps as _, Duration::from_micros(spin_micros as _)), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.limitFrame(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env).limit_frame()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.initCanvasHandle(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_to_handle(CanvasHandle::new(window_handle))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropCanvasHandle(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<CanvasHandle>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setCamera(mut env: JNIEnv, class: JClass, handle: jlong, x: jfloat, y: jfloat, zoom: jfloat) {
+		let camera = resolve_res!(Camera::new(x, y, zoom), &mut env);
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env).set_camera(Some(camera));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.resetCamera(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env).set_camera(None);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setViewportLayout(mut env: JNIEnv, class: JClass, handle: jlong, rects: jfloatArray) {
+		jni_get_arr!(arr = JFloatArray; rects, env);
+		if arr.len() % 4 != 0 {
+			FerriciaError(format!("Expected regions of 4 elements in rects but got {} elements", arr.len())).throw_jni(&mut env);
+			return;
+		}
+		let rects = arr.chunks_exact(4).map(|v| [v[0], v[1], v[2], v[3]]).collect();
+		drop(arr);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.set_viewports(rects), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.splitViewports(mut env: JNIEnv, class: JClass, handle: jlong, count: jint) {
+		if count < 0 {
+			FerriciaError(format!("Invalid viewport count: {count}")).throw_jni(&mut env);
+			return;
+		}
+		let rects = resolve_res!(split_layout(count as _), &mut env);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.set_viewports(rects), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setViewportCamera(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		index: jint,
+		x: jfloat,
+		y: jfloat,
+		zoom: jfloat,
+	) {
+		let camera = resolve_res!(Camera::new(x, y, zoom), &mut env);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.set_viewport_camera(index as _, camera), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.beginViewport(mut env: JNIEnv, class: JClass, handle: jlong, index: jint) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.begin_viewport(index as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.endViewport(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env).end_viewport();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.viewportListeners(mut env: JNIEnv, class: JClass, handle: jlong) -> jfloatArray {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jfloatArray, &mut env);
+		let data = canvas_handle.viewport_listeners().into_iter().flat_map(|(x, y)| [x, y]).collect::<Vec<_>>();
+		let arr = env.new_float_array(data.len() as jsize).expect("Cannot create JFloatArray");
+		env.set_float_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+/// `filter`: 0 for nearest, 1 for linear; `wrap`: 0 for clamp, 1 for repeat, 2 for mirrored repeat
+///
+/// `anisotropy` of `1` disables anisotropic filtering; the mipmap levels are inclusive.
+#[cfg(feature = "client")]
+fn jni_texture_options(
+	filter: jint,
+	wrap: jint,
+	mipmaps: jboolean,
+	anisotropy: jfloat,
+	base_level: jint,
+	max_level: jint,
+) -> FerriciaResult<TextureOptions> {
+	if anisotropy.is_nan() || anisotropy < 1.0 {
+		return Err(format!("Invalid anisotropy degree: {anisotropy}").into());
+	}
+	if base_level < 0 || max_level < base_level {
+		return Err(format!("Invalid mipmap levels: {base_level} to {max_level}").into());
+	}
+	Ok(TextureOptions {
+		filter: match filter {
+			0 => TextureFilter::Nearest,
+			1 => TextureFilter::Linear,
+			_ => return Err(format!("Invalid texture filter: {filter}").into()),
+		},
+		wrap: match wrap {
+			0 => TextureWrap::Clamp,
+			1 => TextureWrap::Repeat,
+			2 => TextureWrap::Mirror,
+			_ => return Err(format!("Invalid texture wrap mode: {wrap}").into()),
+		},
+		mipmaps: mipmaps != 0,
+		anisotropy,
+		levels: (base_level as _, max_level as _),
+	})
+}
+
+jni_ferricia! {
+	client:Mui.maxTextureAnisotropy(mut env: JNIEnv, class: JClass, handle: jlong) -> jfloat {
+		match jni_ref_handle::<CanvasHandle>(&env, handle) {
+			Ok(v) => v.max_anisotropy(),
+			Err(err) => {
+				err.throw_jni(&mut env);
+				1.0
+			}
+		}
+	}
+}
+
+jni_ferricia! {
+	client:Mui.loadImageToCanvas(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		path: JString,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let path = jni_get_string(&mut env, path);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jint, &mut env);
+		resolve_res!(canvas_handle.load_image(path, &options), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.loadCompressedImageToCanvas(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		path: JString,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let path = jni_get_string(&mut env, path);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jint, &mut env);
+		resolve_res!(canvas_handle.load_compressed_image(path, &options), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.loadTextureArrayToCanvas(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		paths: JObjectArray,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let paths = jni_get_string_array(&mut env, paths);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jint, &mut env);
+		resolve_res!(canvas_handle.load_texture_array(paths, &options), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newTextureLoader(mut env: JNIEnv, class: JClass, threads: jint) -> jlong {
+		if threads <= 0 {
+			FerriciaError(format!("Invalid texture loader threads: {threads}")).throw_jni(&mut env);
+			return 0;
+		}
+		jni_res_to_handle(TextureLoader::new(threads as _), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropTextureLoader(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<TextureLoader>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.requestTexture(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		path: JString,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let path = jni_get_string(&mut env, path);
+		let loader = resolve_res!(jni_ref_handle::<TextureLoader>(&env, handle), jint, &mut env);
+		loader.request(path, options) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.pollLoadedTextures(mut env: JNIEnv, class: JClass, handle: jlong) -> jintArray {
+		// Pairs of request and texture, where the texture is -1 if failed, with the error posted as an engine event
+		let loader = resolve_res!(jni_ref_handle::<TextureLoader>(&env, handle), jintArray, &mut env);
+		let data = resolve_res!(loader.poll(), jintArray, &mut env).into_iter()
+			.flat_map(|(request, texture)| [request as jint, texture.map_or(-1, |v| v as jint)])
+			.collect::<Vec<_>>();
+		let arr = env.new_int_array(data.len() as jsize).expect("Cannot create JIntArray");
+		env.set_int_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.deleteTexture(mut env: JNIEnv, class: JClass, handle: jlong, texture: jint) {
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env).delete_texture(texture as _);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.loadImageBytesToCanvas(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		data: JByteArray,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let bytes = env.convert_byte_array(&data).expect("Cannot get Java byte array");
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jint, &mut env);
+		resolve_res!(canvas_handle.load_image_from_bytes(&bytes, &options), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.loadImageBufferToCanvas(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		data: JByteBuffer,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let ptr = resolve_res!(env.get_direct_buffer_address(&data)
+			.map_err(|e| FerriciaError(format!("Not a direct buffer: {e}"))), jint, &mut env);
+		let len = env.get_direct_buffer_capacity(&data).expect("Cannot get direct buffer capacity");
+		// The buffer is kept alive by the caller during this call.
+		let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jint, &mut env);
+		resolve_res!(canvas_handle.load_image_from_bytes(bytes, &options), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newTextureAtlas(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_to_handle(TextureAtlasBuilder::new())
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropTextureAtlas(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<TextureAtlasBuilder>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addAtlasImage(mut env: JNIEnv, class: JClass, handle: jlong, path: JString) -> jint {
+		let path = jni_get_string(&mut env, path);
+		let builder = resolve_res!(jni_ref_handle::<TextureAtlasBuilder>(&env, handle), jint, &mut env);
+		resolve_res!(builder.add_path(path), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addAtlasImageBytes(mut env: JNIEnv, class: JClass, handle: jlong, data: JByteArray) -> jint {
+		let bytes = env.convert_byte_array(&data).expect("Cannot get Java byte array");
+		let builder = resolve_res!(jni_ref_handle::<TextureAtlasBuilder>(&env, handle), jint, &mut env);
+		resolve_res!(builder.add_bytes(&bytes), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.buildTextureAtlas(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		window: jlong,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) -> jint {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), jint, &mut env);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, window), jint, &mut env);
+		let builder = resolve_res!(jni_ref_handle::<TextureAtlasBuilder>(&env, handle), jint, &mut env);
+		resolve_res!(builder.build(window_handle, &options), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.textureAtlasUvs(mut env: JNIEnv, class: JClass, handle: jlong) -> jfloatArray {
+		let builder = resolve_res!(jni_ref_handle::<TextureAtlasBuilder>(&env, handle), jfloatArray, &mut env);
+		let data = builder.uvs().concat();
+		let arr = env.new_float_array(data.len() as jsize).expect("Cannot create JFloatArray");
+		env.set_float_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.getRenderStats(mut env: JNIEnv, class: JClass) -> jlongArray {
+		// Textures, texture bytes, buffers, buffer bytes, VAOs and programs
+		let stats = render_stats();
+		let data = [
+			stats.textures,
+			stats.texture_bytes,
+			stats.buffers,
+			stats.buffer_bytes,
+			stats.vaos,
+			stats.programs,
+		].map(|v| v as jlong);
+		let arr = env.new_long_array(data.len() as jsize).expect("Cannot create JLongArray");
+		env.set_long_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.beginFrame(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env).begin_frame();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.endFrame(mut env: JNIEnv, class: JClass, handle: jlong) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.end_frame(), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// Passes are not timed without timer queries, reported by `getFrameTimings` without passes.
+	client:Mui.beginTimedPass(mut env: JNIEnv, class: JClass, handle: jlong, name: JString) {
+		let name = jni_get_string(&mut env, name);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.begin_timed_pass(&name), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.endTimedPass(mut env: JNIEnv, class: JClass, handle: jlong) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), &mut env);
+		resolve_res!(canvas_handle.end_timed_pass(), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// In nanoseconds, with `-1` for GPU times not yet available
+	client:Mui.getFrameTimings(mut env: JNIEnv, class: JClass, handle: jlong) -> jobject {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, handle), jobject, &mut env);
+		let timings = canvas_handle.frame_timings();
+		let names = env.new_object_array(timings.passes.len() as jsize, "java/lang/String", JObject::null())
+			.expect("Cannot create Java object array");
+		let mut gpu_times = Vec::with_capacity(timings.passes.len());
+		timings.passes.into_iter().enumerate().for_each(|(i, (name, time))| {
+			let v = env.new_string(name).expect("Cannot create Java string");
+			env.set_object_array_element(&names, i as jsize, v).expect("Cannot set Java object array");
+			gpu_times.push(time.map_or(-1, |v| v.as_nanos() as jlong));
+		});
+		let gpu_arr = env.new_long_array(gpu_times.len() as jsize).expect("Cannot create JLongArray");
+		env.set_long_array_region(&gpu_arr, 0, &gpu_times).expect("Cannot set Java array elements");
+		let p = vec!(
+			(timings.cpu_time.as_nanos() as jlong).into(),
+			(timings.interval.as_nanos() as jlong).into(),
+			(&names).into(),
+			(&gpu_arr).into(),
+		);
+		env.new_object("terramodulus/engine/FrameTimings", "(JJ[Ljava/lang/String;[J)V", p.as_slice())
+			.expect("Cannot create Java object")
+			.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.clearCanvas(mut env: JNIEnv, class: JClass) {
+		clear_canvas()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setCanvasClearColor(mut env: JNIEnv, class: JClass, r: jfloat, g: jfloat, b: jfloat, a: jfloat) {
+		set_clear_color((r, g, b, a));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newRenderGraph(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_to_handle(RenderGraph::new())
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropRenderGraph(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<RenderGraph>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addRenderPass(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		name: JString,
+		inputs: JObjectArray,
+		output: JString,
+	) -> jint {
+		let name = jni_get_string(&mut env, name);
+		let inputs = jni_get_string_array(&mut env, inputs);
+		let output = (!output.is_null()).then(|| jni_get_string(&mut env, output));
+		let graph = resolve_res!(jni_ref_handle::<RenderGraph>(&env, handle), jint, &mut env);
+		graph.add_pass(name, inputs, output) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.compileRenderGraph(mut env: JNIEnv, class: JClass, handle: jlong, canvas: jlong) -> jintArray {
+		let size = *resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas), jintArray, &mut env).size();
+		let graph = resolve_res!(jni_ref_handle::<RenderGraph>(&env, handle), jintArray, &mut env);
+		let order = resolve_res!(graph.compile(size), jintArray, &mut env)
+			.iter().map(|v| *v as jint).collect::<Vec<_>>();
+		let arr = env.new_int_array(order.len() as jsize).expect("Cannot create JIntArray");
+		env.set_int_array_region(&arr, 0, &order).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.beginRenderPass(mut env: JNIEnv, class: JClass, handle: jlong, pass: jint) -> jintArray {
+		let graph = resolve_res!(jni_ref_handle::<RenderGraph>(&env, handle), jintArray, &mut env);
+		let textures = resolve_res!(graph.begin_pass(pass as _), jintArray, &mut env)
+			.into_iter().map(|v| v as jint).collect::<Vec<_>>();
+		let arr = env.new_int_array(textures.len() as jsize).expect("Cannot create JIntArray");
+		env.set_int_array_region(&arr, 0, &textures).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.endRenderPass(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<RenderGraph>(&env, handle), &mut env).end_pass();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newGuiCache(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_to_handle(GuiCache::new())
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropGuiCache(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<GuiCache>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.markGuiCacheDirty(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<GuiCache>(&env, handle), &mut env).mark_dirty();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.beginGuiCache(mut env: JNIEnv, class: JClass, handle: jlong, canvas: jlong) -> jboolean {
+		let size = *resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas), jboolean, &mut env).size();
+		let cache = resolve_res!(jni_ref_handle::<GuiCache>(&env, handle), jboolean, &mut env);
+		resolve_res!(cache.begin(size), jboolean, &mut env) as jboolean
+	}
+}
+
+jni_ferricia! {
+	client:Mui.endGuiCache(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<GuiCache>(&env, handle), &mut env).end();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.guiCacheTexture(mut env: JNIEnv, class: JClass, handle: jlong) -> jint {
+		resolve_res!(jni_ref_handle::<GuiCache>(&env, handle), jint, &mut env).texture() as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.geoShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(GeoProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.texShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(TexProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.preprocessShader(mut env: JNIEnv, class: JClass, handle: jlong, path: JString) -> jstring {
+		let path = jni_get_string(&mut env, path);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jstring, &mut env);
+		let src = resolve_res!(window_handle.preprocess_shader(path), jstring, &mut env);
+		env.new_string(src).expect("Cannot create Java string").into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.preprocessShaderSource(mut env: JNIEnv, class: JClass, handle: jlong, src: JString) -> jstring {
+		let src = jni_get_string(&mut env, src);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jstring, &mut env);
+		let src = resolve_res!(window_handle.preprocess_shader_source(&src), jstring, &mut env);
+		env.new_string(src).expect("Cannot create Java string").into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.geoShadersFromSource(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(GeoProgram::from_source(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.texShadersFromSource(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(TexProgram::from_source(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.defaultGeoProgram(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(GeoProgram::default_program(window_handle), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.defaultTexProgram(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(TexProgram::default_program(window_handle), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.defaultShadowProgram(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(GeoProgram::default_shadow_program(window_handle), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.geometryShaderSupported(mut env: JNIEnv, class: JClass, handle: jlong) -> jboolean {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jboolean, &mut env).geometry_shader_supported() as jboolean
+	}
+}
+
+jni_ferricia! {
+	client:Mui.geoShadersWithGeometry(mut env: JNIEnv, class: JClass, handle: jlong, vsh: JString, gsh: JString, fsh: JString) -> jlong {
+		let vsh = jni_get_string(&mut env, vsh);
+		let gsh = jni_get_string(&mut env, gsh);
+		let fsh = jni_get_string(&mut env, fsh);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(GeoProgram::with_geometry_shader(window_handle, vsh, gsh, fsh), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.defaultWideLineProgram(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(GeoProgram::default_wide_line_program(window_handle), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.defaultWidePointProgram(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(GeoProgram::default_wide_point_program(window_handle), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.computeSupported(mut env: JNIEnv, class: JClass, handle: jlong) -> jboolean {
+		resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jboolean, &mut env).compute_supported() as jboolean
+	}
+}
+
+jni_ferricia! {
+	client:Mui.computeShader(mut env: JNIEnv, class: JClass, handle: jlong, path: JString) -> jlong {
+		let path = jni_get_string(&mut env, path);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(ComputeProgram::new(window_handle, path), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.computeShaderFromSource(mut env: JNIEnv, class: JClass, handle: jlong, src: JString) -> jlong {
+		let src = jni_get_string(&mut env, src);
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(ComputeProgram::from_source(window_handle, src), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropComputeProgram(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<ComputeProgram>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dispatchCompute(mut env: JNIEnv, class: JClass, handle: jlong, x: jint, y: jint, z: jint) {
+		if x <= 0 || y <= 0 || z <= 0 {
+			FerriciaError(format!("Invalid work groups: {x}x{y}x{z}")).throw_jni(&mut env);
+			return;
+		}
+		let program = resolve_res!(jni_ref_handle::<ComputeProgram>(&env, handle), &mut env);
+		resolve_res!(program.dispatch((x as _, y as _, z as _)), &mut env);
+		forget_used_programs(&env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newStorageBuffer(mut env: JNIEnv, class: JClass, handle: jlong, data: jfloatArray) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_get_arr!(arr = JFloatArray; data, env);
+		jni_res_to_handle(StorageBuffer::new(window_handle, &arr), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropStorageBuffer(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<StorageBuffer>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.writeStorageBuffer(mut env: JNIEnv, class: JClass, handle: jlong, offset: jint, data: jfloatArray) {
+		let buffer = resolve_res!(jni_ref_handle::<StorageBuffer>(&env, handle), &mut env);
+		jni_get_arr!(arr = JFloatArray; data, env);
+		resolve_res!(buffer.write(offset as _, &arr), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.readStorageBuffer(mut env: JNIEnv, class: JClass, handle: jlong, offset: jint, len: jint) -> jfloatArray {
+		let buffer = resolve_res!(jni_ref_handle::<StorageBuffer>(&env, handle), jfloatArray, &mut env);
+		let data = resolve_res!(buffer.read(offset as _, len as _), jfloatArray, &mut env);
+		let arr = env.new_float_array(data.len() as jsize).expect("Cannot create JFloatArray");
+		env.set_float_array_region(&arr, 0, &data).expect("Cannot set Java array elements");
+		arr.into_raw()
+	}
+}
+
+jni_ferricia! {
+	client:Mui.bindStorageBuffer(mut env: JNIEnv, class: JClass, handle: jlong, binding: jint) {
+		if binding < 0 {
+			FerriciaError(format!("Invalid storage buffer binding: {binding}")).throw_jni(&mut env);
+			return;
+		}
+		let buffer = resolve_res!(jni_ref_handle::<StorageBuffer>(&env, handle), &mut env);
+		resolve_res!(buffer.bind(binding as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.arrayTexShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(ArrayTexProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.tileMapShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(TileMapProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.defaultTileMapProgram(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_res_to_handle(TileMapProgram::default_program(window_handle), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.pickShaders(mut env: JNIEnv, class: JClass, vsh: JString, fsh: JString) -> jlong {
+		jni_res_to_handle(PickProgram::new(jni_get_string(&mut env, vsh), jni_get_string(&mut env, fsh)), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropGeoProgram(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<GeoProgram>(handle), &mut env);
+		forget_used_programs(&env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropTexProgram(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<TexProgram>(handle), &mut env);
+		forget_used_programs(&env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropArrayTexProgram(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<ArrayTexProgram>(handle), &mut env);
+		forget_used_programs(&env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropTileMapProgram(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<TileMapProgram>(handle), &mut env);
+		forget_used_programs(&env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropPickProgram(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<PickProgram>(handle), &mut env);
+		forget_used_programs(&env);
+	}
+}
+
+/// Only [GeoProgram] and [TexProgram] support custom uniforms.
+#[cfg(feature = "client")]
+fn jni_set_uniform(env: &JNIEnv, program: jlong, name: String, value: UniformValue) -> FerriciaResult<()> {
+	match TaggedId::from_raw(program)?.tag() {
+		HandleTag::GeoProgram => jni_ref_handle::<GeoProgram>(env, program)?.set_uniform(name, value),
+		HandleTag::TexProgram => jni_ref_handle::<TexProgram>(env, program)?.set_uniform(name, value),
+		HandleTag::ComputeProgram => jni_ref_handle::<ComputeProgram>(env, program)?.set_uniform(name, value),
+		tag => Err(FerriciaError(format!("{tag:?} does not support custom uniforms"))),
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setUniform1f(mut env: JNIEnv, class: JClass, program: jlong, name: JString, x: jfloat) {
+		let name = jni_get_string(&mut env, name);
+		resolve_res!(jni_set_uniform(&env, program, name, UniformValue::Float(x)), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setUniform2f(mut env: JNIEnv, class: JClass, program: jlong, name: JString, x: jfloat, y: jfloat) {
+		let name = jni_get_string(&mut env, name);
+		resolve_res!(jni_set_uniform(&env, program, name, UniformValue::Vec2(vec2(x, y))), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setUniform4f(
+		mut env: JNIEnv,
+		class: JClass,
+		program: jlong,
+		name: JString,
+		x: jfloat,
+		y: jfloat,
+		z: jfloat,
+		w: jfloat,
+	) {
+		let name = jni_get_string(&mut env, name);
+		resolve_res!(jni_set_uniform(&env, program, name, UniformValue::Vec4(vec4(x, y, z, w))), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setUniform1i(mut env: JNIEnv, class: JClass, program: jlong, name: JString, x: jint) {
+		let name = jni_get_string(&mut env, name);
+		resolve_res!(jni_set_uniform(&env, program, name, UniformValue::Int(x)), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// `data` is in column-major order.
+	client:Mui.setUniformMat4(mut env: JNIEnv, class: JClass, program: jlong, name: JString, data: jfloatArray) {
+		let name = jni_get_string(&mut env, name);
+		jni_get_fixed_arr!(arr = JFloatArray, 16; data, env);
+		let mat = TMat4::from_column_slice(&arr);
+		resolve_res!(jni_set_uniform(&env, program, name, UniformValue::Mat4(mat)), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newPickBuffer(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_to_handle(PickBuffer::new())
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropPickBuffer(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<PickBuffer>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.beginPicking(mut env: JNIEnv, class: JClass, handle: jlong, canvas: jlong) {
+		let size = *resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas), &mut env).size();
+		let buffer = resolve_res!(jni_ref_handle::<PickBuffer>(&env, handle), &mut env);
+		resolve_res!(buffer.begin(size), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.endPicking(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<PickBuffer>(&env, handle), &mut env).end();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.pickPrecise(mut env: JNIEnv, class: JClass, handle: jlong, x: jint, y: jint) -> jint {
+		resolve_res!(jni_ref_handle::<PickBuffer>(&env, handle), jint, &mut env).pick(x, y) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newSimpleLineGeom(mut env: JNIEnv, class: JClass, data: jintArray) -> jlong {
+		jni_get_fixed_arr!(arr = JIntArray, 8; data, env, jlong);
+		jni_to_handle(DrawableSet::new(SimpleLineGeom::new(
+			[(arr[0] as f32, arr[1] as f32), (arr[2] as f32, arr[3] as f32)],
+			Color::RGBA(arr[4] as u8, arr[5] as u8, arr[6] as u8, arr[7] as u8),
+		)))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newSimplePointsGeom(mut env: JNIEnv, class: JClass, data: jfloatArray, color: jintArray) -> jlong {
+		jni_get_arr!(arr = JFloatArray; data, env);
+		jni_get_fixed_arr!(color_arr = JIntArray, 4; color, env, jlong);
+		if arr.len() % 2 != 0 {
+			FerriciaError(format!("Expected pairs of coordinates in data but got {} elements", arr.len())).throw_jni(&mut env);
+			return 0;
+		}
+		let points = arr.chunks_exact(2).map(|v| (v[0], v[1])).collect::<Vec<_>>();
+		jni_to_handle(DrawableSet::new(SimplePointsGeom::new(
+			&points,
+			Color::RGBA(color_arr[0] as u8, color_arr[1] as u8, color_arr[2] as u8, color_arr[3] as u8),
+		)))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newSimpleRectGeom(mut env: JNIEnv, class: JClass, data: jintArray) -> jlong {
+		jni_get_fixed_arr!(arr = JIntArray, 8; data, env, jlong);
+		jni_to_handle(DrawableSet::new(SimpleRectGeom::new(
+			[arr[0] as f32, arr[1] as f32, arr[2] as f32, arr[3] as f32],
+			Color::RGBA(arr[4] as u8, arr[5] as u8, arr[6] as u8, arr[7] as u8),
+		)))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newSpriteMesh(mut env: JNIEnv, class: JClass, data: jintArray) -> jlong {
+		jni_get_fixed_arr!(arr = JIntArray, 4; data, env, jlong);
+		jni_to_handle(DrawableSet::new(SpriteMesh::new([arr[0] as _, arr[1] as _, arr[2] as _, arr[3] as _])))
+	}
+}
+
+jni_ferricia! {
+	// `texture` is the texture array to sample, of which the layers are counted.
+	client:Mui.newLayeredSpriteMesh(mut env: JNIEnv, class: JClass, data: jintArray, texture: jint, layer: jint) -> jlong {
+		jni_get_fixed_arr!(arr = JIntArray, 4; data, env, jlong);
+		if layer < 0 {
+			FerriciaError(format!("Invalid texture layer: {layer}")).throw_jni(&mut env);
+			return 0;
+		}
+		let layers = resolve_res!(texture_array_layers(texture as _), jlong, &mut env);
+		let mesh = LayeredSpriteMesh::new([arr[0] as _, arr[1] as _, arr[2] as _, arr[3] as _], layers, layer as _);
+		jni_res_to_handle(mesh.map(DrawableSet::new), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editTextureLayer(mut env: JNIEnv, class: JClass, handle: jlong, layer: jint) {
+		if layer < 0 {
+			FerriciaError(format!("Invalid texture layer: {layer}")).throw_jni(&mut env);
+			return;
+		}
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, handle), &mut env);
+		resolve_res!(set.set_texture_layer(layer as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newAtlasSpriteMesh(mut env: JNIEnv, class: JClass, data: jintArray, uv: jfloatArray) -> jlong {
+		jni_get_fixed_arr!(arr = JIntArray, 4; data, env, jlong);
+		jni_get_fixed_arr!(uv_arr = JFloatArray, 4; uv, env, jlong);
+		jni_to_handle(DrawableSet::new(SpriteMesh::with_uv(
+			[arr[0] as _, arr[1] as _, arr[2] as _, arr[3] as _],
+			[uv_arr[0], uv_arr[1], uv_arr[2], uv_arr[3]],
+		)))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newTileMap(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		width: jint,
+		height: jint,
+		tile_size: jint,
+		tiles: jintArray,
+		palette: jfloatArray,
+	) -> jlong {
+		if width <= 0 || height <= 0 || tile_size <= 0 {
+			FerriciaError(format!("Invalid tile map size: {width}x{height} of {tile_size} pixels")).throw_jni(&mut env);
+			return 0;
+		}
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), jlong, &mut env);
+		jni_get_arr!(tiles_arr = JIntArray; tiles, env);
+		jni_get_arr!(palette_arr = JFloatArray; palette, env);
+		if palette_arr.len() % 4 != 0 {
+			FerriciaError(format!("Expected regions of 4 elements in palette but got {} elements", palette_arr.len())).throw_jni(&mut env);
+			return 0;
+		}
+		// Palette as `[u0, v0, u1, v1]` of each ID
+		let palette = palette_arr.chunks_exact(4).map(|v| [v[0], v[1], v[2], v[3]]).collect();
+		let tiles = tiles_arr.iter().map(|v| *v as u32).collect();
+		let map = TileMap::new(window_handle, (width as _, height as _), tile_size as _, tiles, palette);
+		jni_res_to_handle(map.map(DrawableSet::new), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.setTile(mut env: JNIEnv, class: JClass, handle: jlong, x: jint, y: jint, id: jint) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, handle), &mut env);
+		let map = resolve_res!(set.tile_map(), &mut env);
+		resolve_res!(map.set_tile(x as _, y as _, id as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.isTileMapIndexed(mut env: JNIEnv, class: JClass, handle: jlong) -> jboolean {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, handle), jboolean, &mut env);
+		resolve_res!(set.tile_map(), jboolean, &mut env).is_indexed() as jboolean
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newShadowBatch(mut env: JNIEnv, class: JClass, opacity: jfloat, falloff: jfloat) -> jlong {
+		jni_res_to_handle(ShadowBatch::new(opacity, falloff).map(DrawableSet::new), &mut env)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addShadow(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		x: jfloat,
+		y: jfloat,
+		width: jfloat,
+		height: jfloat,
+	) -> jint {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, handle), jint, &mut env);
+		resolve_res!(set.shadow_batch(), jint, &mut env).add((x, y), width, height) as jint
+	}
+}
+
+jni_ferricia! {
+	client:Mui.updateShadow(mut env: JNIEnv, class: JClass, handle: jlong, id: jint, x: jfloat, y: jfloat, height: jfloat) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, handle), &mut env);
+		let batch = resolve_res!(set.shadow_batch(), &mut env);
+		resolve_res!(batch.update(id as _, (x, y), height), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.removeShadow(mut env: JNIEnv, class: JClass, handle: jlong, id: jint) -> jboolean {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, handle), jboolean, &mut env);
+		resolve_res!(set.shadow_batch(), jboolean, &mut env).remove(id as _) as jboolean
+	}
+}
+
+jni_ferricia! {
+	client:Mui.dropDrawableSet(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<DrawableSet>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// References are also dropped along with their transforms.
+	client:Mui.dropModelTransformRef(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<WidePtr<dyn PrimModelTransform>>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// References are also dropped along with their filters.
+	client:Mui.dropColorFilterRef(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<WidePtr<dyn PrimColorFilter>>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.modelSmartScaling(mut env: JNIEnv, class: JClass, data: jintArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JIntArray, 5; data, env, jlongArray);
+		let translate = resolve_res!(match arr[2] {
+			0 => Ok(None),
+			1 => Ok(Some((ScalingCenteredTranslateParam::X, (arr[3] as _, arr[4] as _)))),
+			2 => Ok(Some((ScalingCenteredTranslateParam::Y, (arr[3] as _, arr[4] as _)))),
+			3 => Ok(Some((ScalingCenteredTranslateParam::Both, (arr[3] as _, arr[4] as _)))),
+			v => Err(FerriciaError(format!("Invalid Smart Scaling parameter: {v}"))),
+		}, jlongArray, &mut env);
+		jni_to_destructed_ptr!(SmartScaling::new((arr[0] as _, arr[1] as _), translate), dyn PrimModelTransform, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.modelFullScaling(mut env: JNIEnv, class: JClass, data: jintArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JIntArray, 2; data, env, jlongArray);
+		jni_to_destructed_ptr!(FullScaling::new((arr[0] as _, arr[1] as _)), dyn PrimModelTransform, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.modelSimpleTranslation(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JFloatArray, 2; data, env, jlongArray);
+		jni_to_destructed_ptr!(SimpleTranslation::new(arr[0], arr[1]), dyn PrimModelTransform, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.modelAffineTransform(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JFloatArray, 6; data, env, jlongArray);
+		jni_to_destructed_ptr!(AffineTransform::new(arr), dyn PrimModelTransform, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editAffineTransform(mut env: JNIEnv, class: JClass, model: jlong, data: jfloatArray) {
+		jni_get_fixed_arr!(arr = JFloatArray, 6; data, env);
+		resolve_res!(jni_ref_handle::<AffineTransform>(&env, model), &mut env).set_matrix(arr);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.modelPivotScaling(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JFloatArray, 4; data, env, jlongArray);
+		jni_to_destructed_ptr!(PivotScaling::new((arr[0], arr[1]), (arr[2], arr[3])), dyn PrimModelTransform, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editPivotScalingAnchor(mut env: JNIEnv, class: JClass, model: jlong, x: jfloat, y: jfloat) {
+		resolve_res!(jni_ref_handle::<PivotScaling>(&env, model), &mut env).set_anchor(vec2(x, y));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editPivotScalingFactor(mut env: JNIEnv, class: JClass, model: jlong, x: jfloat, y: jfloat) {
+		resolve_res!(jni_ref_handle::<PivotScaling>(&env, model), &mut env).set_factor(vec2(x, y));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.modelFlipSkew(mut env: JNIEnv, class: JClass, x: jfloat, y: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(FlipSkew::new((x, y)), dyn PrimModelTransform, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editFlipSkewAnchor(mut env: JNIEnv, class: JClass, model: jlong, x: jfloat, y: jfloat) {
+		resolve_res!(jni_ref_handle::<FlipSkew>(&env, model), &mut env).set_anchor(vec2(x, y));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editFlipSkewFlip(mut env: JNIEnv, class: JClass, model: jlong, horizontal: jboolean, vertical: jboolean) {
+		resolve_res!(jni_ref_handle::<FlipSkew>(&env, model), &mut env).set_flip(horizontal != 0, vertical != 0);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editFlipSkewSkew(mut env: JNIEnv, class: JClass, model: jlong, x: jfloat, y: jfloat) {
+		resolve_res!(jni_ref_handle::<FlipSkew>(&env, model), &mut env).set_skew(vec2(x, y));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterAlphaFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(AlphaFilter::new(data), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editAlphaFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<AlphaFilter>(&env, filter), &mut env).set_alpha(data as _);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.fadeAlphaFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat, duration_ms: jlong) {
+		resolve_res!(jni_ref_handle::<AlphaFilter>(&env, filter), &mut env)
+			.fade_to(data, Duration::from_millis(duration_ms.max(0) as _));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.stopAlphaFilterFade(mut env: JNIEnv, class: JClass, filter: jlong) {
+		resolve_res!(jni_ref_handle::<AlphaFilter>(&env, filter), &mut env).stop_fade();
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterTintFilter(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JFloatArray, 4; data, env, jlongArray);
+		jni_to_destructed_ptr!(TintFilter::new((arr[0], arr[1], arr[2]), arr[3]), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editTintFilterColor(mut env: JNIEnv, class: JClass, filter: jlong, r: jfloat, g: jfloat, b: jfloat) {
+		resolve_res!(jni_ref_handle::<TintFilter>(&env, filter), &mut env).set_color((r, g, b));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editTintFilterStrength(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<TintFilter>(&env, filter), &mut env).set_strength(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterGrayscaleFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(GrayscaleFilter::new(data), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editGrayscaleFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<GrayscaleFilter>(&env, filter), &mut env).set_amount(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterHueRotateFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(HueRotateFilter::new(data), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editHueRotateFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<HueRotateFilter>(&env, filter), &mut env).set_degrees(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterBrightnessFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(BrightnessFilter::new(data), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editBrightnessFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<BrightnessFilter>(&env, filter), &mut env).set_brightness(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterContrastFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(ContrastFilter::new(data), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editContrastFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<ContrastFilter>(&env, filter), &mut env).set_contrast(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterSaturationFilter(mut env: JNIEnv, class: JClass, data: jfloat) -> jlongArray {
+		jni_to_destructed_ptr!(SaturationFilter::new(data), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editSaturationFilter(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<SaturationFilter>(&env, filter), &mut env).set_saturation(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterLerpColorFilter(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JFloatArray, 32; data, env, jlongArray);
+		jni_to_destructed_ptr!(LerpColorFilter::new(
+			TMat4::from_column_slice(&arr[0..16]),
+			TMat4::from_column_slice(&arr[16..32]),
+		), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editLerpColorFilterProgress(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<LerpColorFilter>(&env, filter), &mut env).set_progress(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.startLerpColorFilter(mut env: JNIEnv, class: JClass, filter: jlong, duration_ms: jlong, mode: jint) {
+		let mode = match mode {
+			0 => LerpMode::Once,
+			1 => LerpMode::Bounce,
+			2 => LerpMode::PingPong,
+			_ => {
+				FerriciaError("Invalid Lerp Color Filter mode".to_string()).throw_jni(&mut env);
+				return;
+			}
+		};
+		resolve_res!(jni_ref_handle::<LerpColorFilter>(&env, filter), &mut env)
+			.start(Duration::from_millis(duration_ms.max(0) as _), mode);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.filterFlashFilter(mut env: JNIEnv, class: JClass, data: jfloatArray) -> jlongArray {
+		jni_get_fixed_arr!(arr = JFloatArray, 4; data, env, jlongArray);
+		jni_to_destructed_ptr!(FlashFilter::new((arr[0], arr[1], arr[2]), arr[3]), dyn PrimColorFilter, env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editFlashFilterColor(mut env: JNIEnv, class: JClass, filter: jlong, r: jfloat, g: jfloat, b: jfloat) {
+		resolve_res!(jni_ref_handle::<FlashFilter>(&env, filter), &mut env).set_color((r, g, b));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.editFlashFilterStrength(mut env: JNIEnv, class: JClass, filter: jlong, data: jfloat) {
+		resolve_res!(jni_ref_handle::<FlashFilter>(&env, filter), &mut env).set_strength(data);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addModelTransform(mut env: JNIEnv, class: JClass, set_handle: jlong, model_handle: jlong) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, set_handle), &mut env);
+		set.add_model_transform(resolve_res!(jni_ref_wide_ptr::<dyn PrimModelTransform>(&env, model_handle), &mut env))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.removeModelTransform(mut env: JNIEnv, class: JClass, set_handle: jlong, model_handle: jlong) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, set_handle), &mut env);
+		set.remove_model_transform(resolve_res!(jni_ref_wide_ptr::<dyn PrimModelTransform>(&env, model_handle), &mut env))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addColorFilter(mut env: JNIEnv, class: JClass, set_handle: jlong, filter_handle: jlong) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, set_handle), &mut env);
+		set.add_filter_transform(resolve_res!(jni_ref_wide_ptr::<dyn PrimColorFilter>(&env, filter_handle), &mut env))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.removeColorFilter(mut env: JNIEnv, class: JClass, set_handle: jlong, filter_handle: jlong) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, set_handle), &mut env);
+		set.remove_filter_transform(resolve_res!(jni_ref_wide_ptr::<dyn PrimColorFilter>(&env, filter_handle), &mut env))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.newSceneNode(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_to_handle(SceneNode::new())
+	}
+}
+
+jni_ferricia! {
+	client:Mui.addSceneNodeModelTransform(mut env: JNIEnv, class: JClass, node_handle: jlong, model_handle: jlong) {
+		let node = resolve_res!(jni_ref_handle::<SceneNode>(&env, node_handle), &mut env);
+		node.add_model_transform(resolve_res!(jni_ref_wide_ptr::<dyn PrimModelTransform>(&env, model_handle), &mut env))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.removeSceneNodeModelTransform(mut env: JNIEnv, class: JClass, node_handle: jlong, model_handle: jlong) {
+		let node = resolve_res!(jni_ref_handle::<SceneNode>(&env, node_handle), &mut env);
+		node.remove_model_transform(resolve_res!(jni_ref_wide_ptr::<dyn PrimModelTransform>(&env, model_handle), &mut env))
+	}
+}
+
+jni_ferricia! {
+	// Children and the parent are detached from the node.
+	client:Mui.dropSceneNode(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<SceneNode>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.attachSceneNode(mut env: JNIEnv, class: JClass, node_handle: jlong, parent_handle: jlong) {
+		if node_handle == parent_handle {
+			FerriciaError("Scene node cannot be attached to itself".to_string()).throw_jni(&mut env);
+			return;
+		}
+		let node = resolve_res!(jni_ref_handle::<SceneNode>(&env, node_handle), &mut env);
+		let parent = resolve_res!(jni_ref_handle::<SceneNode>(&env, parent_handle), &mut env);
+		resolve_res!(node.set_parent(Some(parent)), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.detachSceneNode(mut env: JNIEnv, class: JClass, node_handle: jlong) {
+		let node = resolve_res!(jni_ref_handle::<SceneNode>(&env, node_handle), &mut env);
+		resolve_res!(node.set_parent(None), &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.attachDrawableSet(mut env: JNIEnv, class: JClass, set_handle: jlong, parent_handle: jlong) {
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, set_handle), &mut env);
+		set.set_parent(Some(resolve_res!(jni_ref_handle::<SceneNode>(&env, parent_handle), &mut env)));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.detachDrawableSet(mut env: JNIEnv, class: JClass, set_handle: jlong) {
+		resolve_res!(jni_ref_handle::<DrawableSet>(&env, set_handle), &mut env).set_parent(None);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.drawGuiGeo(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+	) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, drawable_handle), &mut env);
+		let program = resolve_res!(jni_ref_handle::<GeoProgram>(&env, program_handle), &mut env);
+		canvas_handle.draw_gui(set, program, None)
+	}
+}
+
+jni_ferricia! {
+	client:Mui.drawGuiTex(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+		texture_handle: jint,
+	) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, drawable_handle), &mut env);
+		let program = resolve_res!(jni_ref_handle::<TexProgram>(&env, program_handle), &mut env);
+		canvas_handle.draw_gui(set, program, Some(texture_handle as _))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.drawGuiArrayTex(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+		texture_handle: jint,
+	) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, drawable_handle), &mut env);
+		let program = resolve_res!(jni_ref_handle::<ArrayTexProgram>(&env, program_handle), &mut env);
+		canvas_handle.draw_gui(set, program, Some(texture_handle as _))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.drawGuiTileMap(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+		texture_handle: jint,
+	) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, drawable_handle), &mut env);
+		let program = resolve_res!(jni_ref_handle::<TileMapProgram>(&env, program_handle), &mut env);
+		canvas_handle.draw_gui(set, program, Some(texture_handle as _))
+	}
+}
+
+jni_ferricia! {
+	client:Mui.drawGuiTextures(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+		samplers: JObjectArray,
+		textures: jintArray,
+	) {
+		let samplers = jni_get_string_array(&mut env, samplers);
+		jni_get_arr!(arr = JIntArray; textures, env);
+		let textures = arr.iter().map(|v| *v as u32).collect::<Vec<_>>();
+		if samplers.len() != textures.len() {
+			FerriciaError("Samplers and textures differ in length".to_string()).throw_jni(&mut env);
+			return;
+		}
+		let textures = samplers.into_iter().zip(textures).collect::<Vec<_>>();
+		let window_handle = resolve_res!(jni_ref_handle::<WindowHandle>(&env, handle), &mut env);
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, drawable_handle), &mut env);
+		let res = match resolve_res!(TaggedId::from_raw(program_handle), &mut env).tag() {
+			HandleTag::GeoProgram => jni_ref_handle::<GeoProgram>(&env, program_handle)
+				.and_then(|v| canvas_handle.draw_gui_textures(window_handle, set, v, &textures)),
+			HandleTag::TexProgram => jni_ref_handle::<TexProgram>(&env, program_handle)
+				.and_then(|v| canvas_handle.draw_gui_textures(window_handle, set, v, &textures)),
+			tag => Err(FerriciaError(format!("{tag:?} does not support named samplers"))),
+		};
+		resolve_res!(res, &mut env);
+	}
+}
+
+jni_ferricia! {
+	client:Mui.drawGuiPick(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+		texture_handle: jint,
+		pick_id: jint,
+	) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let set = resolve_res!(jni_ref_handle::<DrawableSet>(&env, drawable_handle), &mut env);
+		let program = resolve_res!(jni_ref_handle::<PickProgram>(&env, program_handle), &mut env);
+		program.set_pick_id(pick_id as _);
+		// Negative for geometries without textures
+		canvas_handle.draw_gui(set, program, (texture_handle >= 0).then_some(texture_handle as _))
+	}
+}
+
+/// Draws with the program of any type drawing a single texture, as `drawGuiGeo` or `drawGuiTex`.
+#[cfg(feature = "client")]
+fn draw_gui_by_tag(
+	env: &JNIEnv,
+	canvas_handle: &CanvasHandle,
+	drawable: jlong,
+	program: jlong,
+	texture: Option<u32>,
+	state: &mut ReplayState,
+) -> FerriciaResult<()> {
+	let set = jni_ref_handle::<DrawableSet>(env, drawable)?;
+	match TaggedId::from_raw(program)?.tag() {
+		HandleTag::GeoProgram => {
+			let program = jni_ref_handle::<GeoProgram>(env, program)?;
+			canvas_handle.replay_gui(set, program, None, state);
+		}
+		HandleTag::TexProgram => {
+			let program = jni_ref_handle::<TexProgram>(env, program)?;
+			canvas_handle.replay_gui(set, program, texture, state);
+		}
+		HandleTag::ArrayTexProgram => {
+			let program = jni_ref_handle::<ArrayTexProgram>(env, program)?;
+			canvas_handle.replay_gui(set, program, texture, state);
+		}
+		HandleTag::TileMapProgram => {
+			let program = jni_ref_handle::<TileMapProgram>(env, program)?;
+			canvas_handle.replay_gui(set, program, texture, state);
+		}
+		tag => return Err(FerriciaError(format!("{tag:?} cannot be drawn in batches"))),
+	}
+	Ok(())
+}
+
+/// Replays the recorded commands, resolving the handles only now.
+#[cfg(feature = "client")]
+fn render_frame(env: &JNIEnv, canvas_handle: &mut CanvasHandle) -> FerriciaResult<()> {
+	let mut state = ReplayState::default();
+	for command in canvas_handle.take_commands() {
+		match command {
+			RenderCommand::Clear => clear_canvas(),
+			RenderCommand::DrawGui { drawable, program, texture } =>
+				draw_gui_by_tag(env, canvas_handle, drawable, program, texture, &mut state)?,
+			RenderCommand::BeginViewport(index) => canvas_handle.begin_viewport(index)?,
+			RenderCommand::EndViewport => canvas_handle.end_viewport(),
+		}
+	}
+	Ok(())
+}
+
+jni_ferricia! {
+	// One crossing for many draws; the arrays are parallel, with negative textures for geometry programs.
+	// Draws before a failing one are kept, and the rest are skipped.
+	client:Mui.drawGuiBatch(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawables: jlongArray,
+		programs: jlongArray,
+		textures: jintArray,
+	) {
+		jni_get_arr!(drawable_arr = JLongArray; drawables, env);
+		jni_get_arr!(program_arr = JLongArray; programs, env);
+		jni_get_arr!(texture_arr = JIntArray; textures, env);
+		if drawable_arr.len() != program_arr.len() || drawable_arr.len() != texture_arr.len() {
+			FerriciaError("Drawables, programs and textures differ in length".to_string()).throw_jni(&mut env);
+			return;
+		}
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		let mut state = ReplayState::default();
+		for ((drawable, program), texture) in drawable_arr.iter().zip(program_arr.iter()).zip(texture_arr.iter()) {
+			let texture = (*texture >= 0).then_some(*texture as _);
+			resolve_res!(draw_gui_by_tag(&env, canvas_handle, *drawable, *program, texture, &mut state), &mut env);
+		}
+	}
+}
+
+jni_ferricia! {
+	client:Mui.recordClear(mut env: JNIEnv, class: JClass, canvas_handle: jlong) {
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env).record(RenderCommand::Clear);
+	}
+}
+
+jni_ferricia! {
+	// `texture_handle` is negative for geometry programs, as in `drawGuiGeo`.
+	client:Mui.recordGuiDraw(
+		mut env: JNIEnv,
+		class: JClass,
+		canvas_handle: jlong,
+		drawable_handle: jlong,
+		program_handle: jlong,
+		texture_handle: jint,
+	) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		canvas_handle.record(RenderCommand::DrawGui {
+			drawable: drawable_handle,
+			program: program_handle,
+			texture: (texture_handle >= 0).then_some(texture_handle as _),
+		});
+	}
+}
+
+jni_ferricia! {
+	client:Mui.recordBeginViewport(mut env: JNIEnv, class: JClass, canvas_handle: jlong, index: jint) {
+		if index < 0 {
+			FerriciaError(format!("Invalid viewport index: {index}")).throw_jni(&mut env);
+			return;
+		}
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		canvas_handle.record(RenderCommand::BeginViewport(index as _));
+	}
+}
+
+jni_ferricia! {
+	client:Mui.recordEndViewport(mut env: JNIEnv, class: JClass, canvas_handle: jlong) {
+		resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env).record(RenderCommand::EndViewport);
+	}
+}
+
+jni_ferricia! {
+	// The recorded commands are consumed even if the replay fails, so that they do not pile up.
+	client:Mui.renderFrame(mut env: JNIEnv, class: JClass, canvas_handle: jlong) {
+		let canvas_handle = resolve_res!(jni_ref_handle::<CanvasHandle>(&env, canvas_handle), &mut env);
+		resolve_res!(render_frame(&env, canvas_handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	hot_reload:Mui.newHotReloader(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_res_to_handle(HotReloader::new(), &mut env)
+	}
+}
+
+jni_ferricia! {
+	hot_reload:Mui.dropHotReloader(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<HotReloader>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	hot_reload:Mui.watchTexture(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		texture: jint,
+		path: JString,
+		filter: jint,
+		wrap: jint,
+		mipmaps: jboolean,
+		anisotropy: jfloat,
+		base_level: jint,
+		max_level: jint,
+	) {
+		let options = resolve_res!(jni_texture_options(filter, wrap, mipmaps, anisotropy, base_level, max_level), &mut env);
+		let path = jni_get_string(&mut env, path);
+		let reloader = resolve_res!(jni_ref_handle::<HotReloader>(&env, handle), &mut env);
+		resolve_res!(reloader.watch_texture(path, texture as _, options), &mut env);
+	}
+}
+
+jni_ferricia! {
+	hot_reload:Mui.watchShaders(mut env: JNIEnv, class: JClass, handle: jlong, program: jlong, vsh: JString, fsh: JString) {
+		let vsh = jni_get_string(&mut env, vsh);
+		let fsh = jni_get_string(&mut env, fsh);
+		let program = resolve_res!(TaggedId::from_raw(program), &mut env);
+		let reloader = resolve_res!(jni_ref_handle::<HotReloader>(&env, handle), &mut env);
+		let res = match program.tag() {
+			HandleTag::GeoProgram => reloader.watch_program::<GeoProgram>(program, vsh, fsh),
+			HandleTag::TexProgram => reloader.watch_program::<TexProgram>(program, vsh, fsh),
+			HandleTag::ArrayTexProgram => reloader.watch_program::<ArrayTexProgram>(program, vsh, fsh),
+			HandleTag::PickProgram => reloader.watch_program::<PickProgram>(program, vsh, fsh),
+			HandleTag::TileMapProgram => reloader.watch_program::<TileMapProgram>(program, vsh, fsh),
+			tag => Err(FerriciaError(format!("{tag:?} is not a shader program"))),
+		};
+		resolve_res!(res, &mut env);
+	}
+}
+
+jni_ferricia! {
+	hot_reload:Mui.pollHotReload(mut env: JNIEnv, class: JClass, handle: jlong) -> jobjectArray {
+		let reloader = resolve_res!(jni_ref_handle::<HotReloader>(&env, handle), jobjectArray, &mut env);
+		let reloaded = resolve_res!(reloader.poll(), jobjectArray, &mut env);
+		if !reloaded.is_empty() {
+			forget_used_programs(&env);
+		}
+		let a = env.new_object_array(reloaded.len() as jsize, "java/lang/String", JObject::null())
+			.expect("Cannot create Java object array");
+		reloaded.into_iter().enumerate().for_each(|(i, v)| {
+			let v = env.new_string(v).expect("Cannot create Java string");
+			env.set_object_array_element(&a, i as jsize, v).expect("Cannot set Java object array");
+		});
+		a.into_raw()
+	}
+}
+
+jni_ferricia! {
+	server:Chat.newChatFilter(mut env: JNIEnv, class: JClass, max_length: jint, burst: jint, interval_ms: jlong) -> jlong {
+		if max_length < 0 {
+			FerriciaError(format!("Invalid chat message length: {max_length}")).throw_jni(&mut env);
+			return 0;
+		}
+		if burst < 0 {
+			FerriciaError(format!("Invalid chat burst: {burst}")).throw_jni(&mut env);
+			return 0;
+		}
+		if interval_ms < 0 {
+			FerriciaError(format!("Invalid chat interval: {interval_ms}")).throw_jni(&mut env);
+			return 0;
+		}
+		let filter = ChatFilter::new(max_length as _, burst as _, Duration::from_millis(interval_ms as _));
+		jni_to_handle(resolve_res!(filter, jlong, &mut env))
+	}
+}
+
+jni_ferricia! {
+	server:Chat.dropChatFilter(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<ChatFilter>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	server:Chat.setChatWordList(mut env: JNIEnv, class: JClass, handle: jlong, name: JString, words: JObjectArray) {
+		let name = jni_get_string(&mut env, name);
+		let words = jni_get_string_array(&mut env, words);
+		resolve_res!(jni_ref_handle::<ChatFilter>(&env, handle), &mut env).set_word_list(name, words);
+	}
+}
+
+jni_ferricia! {
+	server:Chat.removeChatWordList(mut env: JNIEnv, class: JClass, handle: jlong, name: JString) {
+		let name = jni_get_string(&mut env, name);
+		resolve_res!(jni_ref_handle::<ChatFilter>(&env, handle), &mut env).remove_word_list(&name);
+	}
+}
+
+jni_ferricia! {
+	server:Chat.forgetChatPlayer(mut env: JNIEnv, class: JClass, handle: jlong, player: JString) {
+		let player = jni_get_string(&mut env, player);
+		resolve_res!(jni_ref_handle::<ChatFilter>(&env, handle), &mut env).forget_player(&player);
+	}
+}
+
+jni_ferricia! {
+	server:Chat.checkChatMessage(mut env: JNIEnv, class: JClass, handle: jlong, player: JString, message: JByteArray) -> jint {
+		// The message is passed as UTF-8 bytes from the connection, so that it is validated here.
+		let player = jni_get_string(&mut env, player);
+		let message = env.convert_byte_array(&message).expect("Cannot get Java byte array");
+		let filter = resolve_res!(jni_ref_handle::<ChatFilter>(&env, handle), jint, &mut env);
+		filter.check(&player, &message) as jint
+	}
+}
+
+jni_ferricia! {
+	Assets.hashFile(mut env: JNIEnv, class: JClass, path: JString, algorithm: JString) -> jstring {
+		let path = jni_get_string(&mut env, path);
+		let algorithm = resolve_res!(HashAlgorithm::from_name(&jni_get_string(&mut env, algorithm)), jstring, &mut env);
+		let hash = resolve_res!(hash_file(path, algorithm), jstring, &mut env);
+		env.new_string(hash).expect("Cannot create Java string").into_raw()
+	}
+}
+
+jni_ferricia! {
+	Assets.buildAssetManifest(mut env: JNIEnv, class: JClass, root: JString, algorithm: JString) -> jstring {
+		let root = jni_get_string(&mut env, root);
+		let algorithm = resolve_res!(HashAlgorithm::from_name(&jni_get_string(&mut env, algorithm)), jstring, &mut env);
+		let manifest = resolve_res!(build_manifest(root, algorithm), jstring, &mut env);
+		env.new_string(manifest).expect("Cannot create Java string").into_raw()
+	}
+}
+
+jni_ferricia! {
+	Assets.verifyAssetManifest(
+		mut env: JNIEnv,
+		class: JClass,
+		root: JString,
+		manifest: JString,
+		algorithm: JString,
+	) -> jobjectArray {
+		let root = jni_get_string(&mut env, root);
+		let manifest = jni_get_string(&mut env, manifest);
+		let algorithm = resolve_res!(HashAlgorithm::from_name(&jni_get_string(&mut env, algorithm)), jobjectArray, &mut env);
+		let mismatched = resolve_res!(verify_manifest(root, &manifest, algorithm), jobjectArray, &mut env);
+		let a = env.new_object_array(mismatched.len() as jsize, "java/lang/String", JObject::null())
+			.expect("Cannot create Java object array");
+		mismatched.into_iter().enumerate().for_each(|(i, v)| {
+			let v = env.new_string(v).expect("Cannot create Java string");
+			env.set_object_array_element(&a, i as jsize, v).expect("Cannot set Java object array");
+		});
+		a.into_raw()
+	}
+}
+
+jni_ferricia! {
+	L10n.newStringTable(mut env: JNIEnv, class: JClass, locale: JString) -> jlong {
+		let locale = jni_get_string(&mut env, locale);
+		jni_res_to_handle(StringTable::new(&locale), &mut env)
+	}
+}
+
+jni_ferricia! {
+	L10n.dropStringTable(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<StringTable>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	L10n.loadLanguageFile(mut env: JNIEnv, class: JClass, handle: jlong, path: JString) {
+		let path = jni_get_string(&mut env, path);
+		let table = resolve_res!(jni_ref_handle::<StringTable>(&env, handle), &mut env);
+		resolve_res!(table.load_file(&path), &mut env);
+	}
+}
+
+jni_ferricia! {
+	L10n.loadLanguageSource(mut env: JNIEnv, class: JClass, handle: jlong, src: JString) {
+		let src = jni_get_string(&mut env, src);
+		let table = resolve_res!(jni_ref_handle::<StringTable>(&env, handle), &mut env);
+		resolve_res!(table.load_source(&src), &mut env);
+	}
+}
+
+jni_ferricia! {
+	L10n.hasTranslation(mut env: JNIEnv, class: JClass, handle: jlong, key: JString) -> jboolean {
+		let key = jni_get_string(&mut env, key);
+		resolve_res!(jni_ref_handle::<StringTable>(&env, handle), jboolean, &mut env).contains(&key) as jboolean
+	}
+}
+
+jni_ferricia! {
+	// `args` are pairs of placeholder name and value; `gender` is empty if not applicable.
+	L10n.translate(mut env: JNIEnv, class: JClass, handle: jlong, key: JString, gender: JString, args: JObjectArray) -> jstring {
+		let key = jni_get_string(&mut env, key);
+		let gender = jni_get_string(&mut env, gender);
+		let args = resolve_res!(jni_get_l10n_args(&mut env, args), jstring, &mut env);
+		let table = resolve_res!(jni_ref_handle::<StringTable>(&env, handle), jstring, &mut env);
+		let text = table.translate(&key, None, &gender, &args);
+		env.new_string(text).expect("Cannot create Java string").into_raw()
+	}
+}
+
+jni_ferricia! {
+	// The same as `translate` with plurals resolved by `count`, which is also `{count}`.
+	L10n.translateCount(
+		mut env: JNIEnv,
+		class: JClass,
+		handle: jlong,
+		key: JString,
+		count: jlong,
+		gender: JString,
+		args: JObjectArray,
+	) -> jstring {
+		let key = jni_get_string(&mut env, key);
+		let gender = jni_get_string(&mut env, gender);
+		let args = resolve_res!(jni_get_l10n_args(&mut env, args), jstring, &mut env);
+		let table = resolve_res!(jni_ref_handle::<StringTable>(&env, handle), jstring, &mut env);
+		let text = table.translate(&key, Some(count), &gender, &args);
+		env.new_string(text).expect("Cannot create Java string").into_raw()
+	}
+}
+
+fn jni_get_l10n_args(env: &mut JNIEnv, args: JObjectArray) -> FerriciaResult<Vec<(String, String)>> {
+	let args = jni_get_string_array(env, args);
+	if args.len() % 2 != 0 {
+		return Err(format!("Expected pairs of placeholder name and value in args but got {} elements", args.len()).into());
+	}
+	Ok(args.chunks_exact(2).map(|v| (v[0].clone(), v[1].clone())).collect())
+}
+
+jni_ferricia! {
+	Core.newScheduler(mut env: JNIEnv, class: JClass) -> jlong {
+		jni_to_handle(Scheduler::new())
+	}
+}
+
+jni_ferricia! {
+	Core.dropScheduler(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<Scheduler>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// `period` of `0` makes a one-shot timer.
+	Core.scheduleTimer(mut env: JNIEnv, class: JClass, handle: jlong, wall_clock: jboolean, delay: jlong, period: jlong) -> jint {
+		if delay < 0 || period < 0 {
+			FerriciaError("Invalid timer duration".to_string()).throw_jni(&mut env);
+			return 0;
+		}
+		let base = if wall_clock != 0 { TimerBase::WallClock } else { TimerBase::Tick };
+		let period = (period > 0).then_some(period as u64);
+		let scheduler = resolve_res!(jni_ref_handle::<Scheduler>(&env, handle), jint, &mut env);
+		resolve_res!(scheduler.schedule(base, delay as _, period), jint, &mut env) as jint
+	}
+}
+
+jni_ferricia! {
+	Core.cancelTimer(mut env: JNIEnv, class: JClass, handle: jlong, timer: jint) -> jboolean {
+		resolve_res!(jni_ref_handle::<Scheduler>(&env, handle), jboolean, &mut env).cancel(timer as _) as jboolean
+	}
+}
+
+jni_ferricia! {
+	Core.advanceSchedulerTicks(mut env: JNIEnv, class: JClass, handle: jlong, ticks: jlong) {
+		if ticks < 0 {
+			FerriciaError("Invalid tick count".to_string()).throw_jni(&mut env);
+			return;
+		}
+		resolve_res!(jni_ref_handle::<Scheduler>(&env, handle), &mut env).advance_ticks(ticks as _);
+	}
+}
+
+jni_ferricia! {
+	// Fired timers are posted as engine events, with messages of timer and number of periods elapsed.
+	Core.pollTimers(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_ref_handle::<Scheduler>(&env, handle), &mut env).fire();
+	}
+}
+
+jni_ferricia! {
+	Core.nextTimerWakeup(mut env: JNIEnv, class: JClass, handle: jlong) -> jlong {
+		// -1 if no wall-clock timer is pending
+		resolve_res!(jni_ref_handle::<Scheduler>(&env, handle), jlong, &mut env).next_wakeup().map_or(-1, |v| v as jlong)
+	}
+}
+
+jni_ferricia! {
+	Core.newTaskPump(mut env: JNIEnv, class: JClass, budget_micros: jlong, min_tasks: jint) -> jlong {
+		if budget_micros < 0 || min_tasks < 0 {
+			FerriciaError("Invalid task pump budget".to_string()).throw_jni(&mut env);
+			return 0;
+		}
+		jni_res_to_handle(TaskPump::new(Duration::from_micros(budget_micros as _), min_tasks as _), &mut env)
+	}
+}
+
+jni_ferricia! {
+	Core.dropTaskPump(mut env: JNIEnv, class: JClass, handle: jlong) {
+		resolve_res!(jni_drop_with_handle::<TaskPump>(handle), &mut env);
+	}
+}
+
+jni_ferricia! {
+	Core.setTaskPumpBudget(mut env: JNIEnv, class: JClass, handle: jlong, budget_micros: jlong, min_tasks: jint) {
+		if budget_micros < 0 || min_tasks < 0 {
+			FerriciaError("Invalid task pump budget".to_string()).throw_jni(&mut env);
+			return;
+		}
+		let pump = resolve_res!(jni_ref_handle::<TaskPump>(&env, handle), &mut env);
+		resolve_res!(pump.set_budget(Duration::from_micros(budget_micros as _), min_tasks as _), &mut env);
+	}
+}
+
+jni_ferricia! {
+	// Called every frame or tick by the owning loop; returns the number of tasks run.
+	Core.runTaskPump(mut env: JNIEnv, class: JClass, handle: jlong) -> jint {
+		resolve_res!(jni_ref_handle::<TaskPump>(&env, handle), jint, &mut env).pump() as jint
+	}
+}
+
+jni_ferricia! {
+	Core.pendingTasks(mut env: JNIEnv, class: JClass, handle: jlong) -> jint {
+		resolve_res!(jni_ref_handle::<TaskPump>(&env, handle), jint, &mut env).pending() as jint
+	}
+}
+
+jni_ferricia! {
+	// Drops any handle on the thread of the pump, such as from a cleaner thread for GL objects.
+	// Failures are posted as engine events, since the caller has already returned.
+	Core.queueHandleDrop(mut env: JNIEnv, class: JClass, pump: jlong, handle: jlong) {
+		let id = resolve_res!(TaggedId::from_raw(handle), &mut env);
+		resolve_res!(jni_ref_handle::<TaskPump>(&env, pump), &mut env).push(move || {
+			let value = HandleRegistry::lock().remove_any(id);
+			match value {
+				Ok(value) => drop(value),
+				Err(err) => post_event("tasks", format!("Cannot drop handle: {err}")),
+			}
+		});
+	}
+}
+
+jni_ferricia! {
+	// Pairs of source and message
+	server:Core.pollEngineEvents(mut env: JNIEnv, class: JClass) -> jobjectArray {
+		let events = drain_events();
+		let a = env.new_object_array(events.len() as jsize * 2, "java/lang/String", JObject::null())
+			.expect("Cannot create Java object array");
+		events.into_iter().enumerate().for_each(|(i, e)| {
+			let source = env.new_string(e.source).expect("Cannot create Java string");
+			env.set_object_array_element(&a, i as jsize * 2, source).expect("Cannot set Java object array");
+			let message = env.new_string(e.message).expect("Cannot create Java string");
+			env.set_object_array_element(&a, i as jsize * 2 + 1, message).expect("Cannot set Java object array");
+		});
+		a.into_raw()
+	}
+}
+
+jni_ferricia! {
+	// 0 to throw, 1 to throw and poison used handles, 2 to abort
+	Core.setPanicPolicy(mut env: JNIEnv, class: JClass, policy: jint) {
+		let policy = match policy {
+			0 => PanicPolicy::Throw,
+			1 => PanicPolicy::ThrowAndPoison,
+			2 => PanicPolicy::Abort,
+			_ => {
+				FerriciaError("Invalid panic policy".to_string()).throw_jni(&mut env);
+				return;
+			}
+		};
+		PANIC_POLICY.store(policy as u8, Ordering::Relaxed);
 	}
 }
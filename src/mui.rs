@@ -5,9 +5,10 @@
 
 //! MUI - Multimodal User Interface
 
+use crate::events::{drain_events, EngineEvent};
 use crate::{FerriciaError, FerriciaResult};
 use sdl3::event::{DisplayEvent, Event, WindowEvent};
-use sdl3::keyboard::Scancode;
+use sdl3::keyboard::{Mod, Scancode};
 use sdl3::mouse::MouseButton;
 use sdl3::video::{Display, DisplayMode};
 use sdl3::{AudioSubsystem, EventPump, EventSubsystem, GamepadSubsystem, HapticSubsystem, JoystickSubsystem, Sdl, VideoSubsystem};
@@ -15,16 +16,60 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use sdl3::properties::PropertiesError;
 use sdl3::rect::Rect;
+use crate::mui::axis_filter::{AxisFilter, AxisResponse};
+use crate::mui::force_feedback::{haptic_device_name, haptic_devices};
+use crate::mui::gamepad_info::{gamepad_info, GamepadInfo};
+use crate::mui::gesture::GestureRecognizer;
+use crate::mui::input::{layout_keycode, layout_symbol, physical_key, ActionMap};
+use crate::mui::haptics::{HapticsScheduler, RumbleKeyframe, RumblePattern};
+use crate::mui::slots::PlayerSlots;
+use crate::mui::window::WindowHandle;
+use crate::util::TaggedId;
+use sdl3::gamepad::Gamepad;
+use sdl3::sys::events::{
+	SDL_PeepEvents, SDL_Event, SDL_EVENT_DROP_BEGIN, SDL_EVENT_DROP_COMPLETE, SDL_EVENT_DROP_FILE,
+	SDL_EVENT_DROP_POSITION, SDL_EVENT_DROP_TEXT, SDL_EVENT_JOYSTICK_BATTERY_UPDATED, SDL_EVENT_PEN_AXIS,
+	SDL_EVENT_PEN_BUTTON_DOWN, SDL_EVENT_PEN_BUTTON_UP, SDL_EVENT_PEN_DOWN, SDL_EVENT_PEN_MOTION,
+	SDL_EVENT_PEN_PROXIMITY_IN, SDL_EVENT_PEN_PROXIMITY_OUT, SDL_EVENT_PEN_UP, SDL_EVENT_WINDOW_HDR_STATE_CHANGED,
+	SDL_EventType, SDL_EVENT_FIRST, SDL_EVENT_LAST, SDL_GETEVENT,
+};
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
 
 pub use sdl3::gamepad::Axis as GamepadAxis;
 pub use sdl3::gamepad::Button as GamepadButton;
 pub use sdl3::joystick::HatState as JoystickHatState;
 
+pub(crate) mod atlas;
+pub(crate) mod axis_filter;
+pub(crate) mod commands;
+pub(crate) mod compute;
+pub(crate) mod cursor;
+pub(crate) mod force_feedback;
+pub(crate) mod gamepad_info;
+pub(crate) mod gui_cache;
+#[cfg(feature = "hot-reload")]
+pub(crate) mod hot_reload;
+pub(crate) mod picking;
+pub(crate) mod render_graph;
 pub(crate) mod rendering;
+pub(crate) mod shadows;
+pub(crate) mod texture_loader;
+pub(crate) mod tilemap;
+pub(crate) mod viewport;
 pub(crate) mod window;
 mod audio;
+mod backend;
+mod compressed;
+mod gesture;
+mod glsl;
+mod haptics;
+mod input;
+mod slots;
 mod oal;
 mod ogl;
+mod pacing;
+mod timing;
 
 pub(crate) struct SdlHandle {
 	events: EventSubsystem,
@@ -36,6 +81,19 @@ pub(crate) struct SdlHandle {
 	sdl_context: Sdl,
 	// This is made because Display ID is opaque from sdl3-rs.
 	displays: RefCell<HashMap<Display, SdlDisplay>>,
+	gestures: GestureRecognizer,
+	/// Opened gamepads by ID; gamepads are opened once added.
+	gamepads: HashMap<u32, Gamepad>,
+	haptics: HapticsScheduler,
+	slots: PlayerSlots,
+	actions: ActionMap,
+	axis_filter: AxisFilter,
+	/// Pairs of window and canvas handles resized by the engine on pixel size changes
+	auto_resize: Vec<(TaggedId, TaggedId)>,
+	/// Pairs of canvas and render target handles resized along with the canvases
+	auto_resize_targets: Vec<(TaggedId, TaggedId)>,
+	/// Categories of [MuiEvent] reported to Java
+	event_mask: u32,
 }
 
 impl From<sdl3::Error> for FerriciaError {
@@ -69,92 +127,453 @@ impl SdlHandle {
 			event_pump: sdl_context.event_pump()?,
 			sdl_context,
 			displays: RefCell::new(displays),
+			gestures: GestureRecognizer::default(),
+			gamepads: HashMap::new(),
+			haptics: HapticsScheduler::new(),
+			slots: PlayerSlots::default(),
+			actions: ActionMap::default(),
+			axis_filter: AxisFilter::default(),
+			auto_resize: Vec::new(),
+			auto_resize_targets: Vec::new(),
+			event_mask: u32::MAX,
 		})
 	}
 
+	/// Converts the events which sdl3-rs would not convert fully from raw SDL events;
+	/// returns `None` for the other events.
+	///
+	/// Drops are converted for their positions, and the others as they are not yet ported to sdl3-rs.
+	/// Pens also move the mouse by default, so pen-aware GUI may ignore the mouse events meanwhile.
+	fn convert_raw_event(&mut self, event: &SDL_Event) -> Option<Option<MuiEvent>> {
+		let event_type = SDL_EventType(unsafe { event.r#type });
+		Some(match event_type {
+			SDL_EVENT_DROP_FILE | SDL_EVENT_DROP_TEXT | SDL_EVENT_DROP_BEGIN | SDL_EVENT_DROP_COMPLETE
+			| SDL_EVENT_DROP_POSITION => {
+				let event = unsafe { event.drop };
+				// The data is only valid until the next poll, so it is copied.
+				let data = || match event.data.is_null() {
+					true => String::new(),
+					false => unsafe { CStr::from_ptr(event.data) }.to_string_lossy().into_owned(),
+				};
+				let (x, y) = (event.x, event.y);
+				match event_type {
+					SDL_EVENT_DROP_FILE => Some(MuiEvent::DropFile(data(), x, y)),
+					SDL_EVENT_DROP_TEXT => Some(MuiEvent::DropText(data(), x, y)),
+					SDL_EVENT_DROP_BEGIN => Some(MuiEvent::DropBegin),
+					SDL_EVENT_DROP_COMPLETE => Some(MuiEvent::DropComplete),
+					_ => Some(MuiEvent::DropPosition(x, y)),
+				}
+			}
+			SDL_EVENT_WINDOW_HDR_STATE_CHANGED => {
+				// The display of the window is opaque, so all displays are updated.
+				self.displays.borrow_mut().iter_mut().for_each(|(display, v)| {
+					let _ = v.update_hdr(display);
+				});
+				Some(MuiEvent::WindowHdrStateChanged)
+			}
+			SDL_EVENT_JOYSTICK_BATTERY_UPDATED => {
+				let event = unsafe { event.jbattery };
+				Some(MuiEvent::JoystickBatteryUpdated(event.which.0, event.state.0 as _, event.percent))
+			}
+			SDL_EVENT_PEN_PROXIMITY_IN => Some(MuiEvent::PenProximityIn(unsafe { event.pproximity }.which.0)),
+			SDL_EVENT_PEN_PROXIMITY_OUT => Some(MuiEvent::PenProximityOut(unsafe { event.pproximity }.which.0)),
+			SDL_EVENT_PEN_DOWN | SDL_EVENT_PEN_UP => {
+				let event = unsafe { event.ptouch };
+				Some(match event.down {
+					true => MuiEvent::PenDown(event.which.0, event.x, event.y, event.eraser),
+					false => MuiEvent::PenUp(event.which.0, event.x, event.y, event.eraser),
+				})
+			}
+			SDL_EVENT_PEN_BUTTON_DOWN | SDL_EVENT_PEN_BUTTON_UP => {
+				let event = unsafe { event.pbutton };
+				Some(match event.down {
+					true => MuiEvent::PenButtonDown(event.which.0, event.button, event.x, event.y),
+					false => MuiEvent::PenButtonUp(event.which.0, event.button, event.x, event.y),
+				})
+			}
+			SDL_EVENT_PEN_MOTION => {
+				let event = unsafe { event.pmotion };
+				Some(MuiEvent::PenMotion(event.which.0, event.x, event.y))
+			}
+			SDL_EVENT_PEN_AXIS => {
+				let event = unsafe { event.paxis };
+				Some(MuiEvent::PenAxis(event.which.0, event.axis.0 as _, event.value))
+			}
+			_ => return None,
+		})
+	}
+
+	/// Whether the display is currently in HDR mode; updated on [MuiEvent::WindowHdrStateChanged].
+	pub(crate) fn display_hdr_enabled(&self, display: &DisplayHandle) -> FerriciaResult<bool> {
+		self.displays.borrow().get(&display.display)
+			.map(SdlDisplay::hdr_enabled)
+			.ok_or_else(|| "Unknown display".to_string().into())
+	}
+
 	pub(crate) fn poll(&mut self) -> Vec<MuiEvent> {
 		self.event_pump.pump_events();
 		let mut events = Vec::new();
-		self.event_pump.poll_iter().for_each(|event| {
-			if let Some(v) = match event {
-				// Only one window is available, so the window ID is ignored.
-				// SDL only reports events made through the window created by this application.
-				Event::Window { win_event, .. } => match win_event {
-					WindowEvent::Shown => Some(MuiEvent::WindowShown),
-					WindowEvent::Hidden => Some(MuiEvent::WindowHidden),
-					WindowEvent::Exposed => Some(MuiEvent::WindowExposed),
-					WindowEvent::Moved(x, y) => Some(MuiEvent::WindowMoved(x, y)),
-					WindowEvent::Resized(w, h) => Some(MuiEvent::WindowResized(w, h)),
-					WindowEvent::PixelSizeChanged(w, h) => Some(MuiEvent::WindowPixelSizeChanged(w, h)),
-					WindowEvent::Minimized => Some(MuiEvent::WindowMinimized),
-					WindowEvent::Maximized => Some(MuiEvent::WindowMaximized),
-					WindowEvent::Restored => Some(MuiEvent::WindowRestored),
-					WindowEvent::MouseEnter => Some(MuiEvent::WindowMouseEnter),
-					WindowEvent::MouseLeave => Some(MuiEvent::WindowMouseLeave),
-					WindowEvent::FocusGained => Some(MuiEvent::WindowFocusGained),
-					WindowEvent::FocusLost => Some(MuiEvent::WindowFocusLost),
-					WindowEvent::CloseRequested => Some(MuiEvent::WindowCloseRequested),
-					WindowEvent::ICCProfChanged => Some(MuiEvent::WindowIccProfChanged),
-					_ => None,
-				}
-				Event::KeyDown { scancode, repeat, which, .. } =>
-					scancode.filter(|v| !repeat || v != &Scancode::Unknown).and_then(KeyboardKey::from_sdl)
-						.map(|v| MuiEvent::KeyboardKeyDown(which, v)),
-				Event::KeyUp { scancode, repeat, which, .. } =>
-					scancode.filter(|v| !repeat || v != &Scancode::Unknown).and_then(KeyboardKey::from_sdl)
-						.map(|v| MuiEvent::KeyboardKeyUp(which, v)),
-				Event::TextEditing { text, start, length, .. } => Some(MuiEvent::TextEditing(text, start, length)),
-				Event::TextInput { text, .. } => Some(MuiEvent::TextInput(text)),
-				Event::MouseMotion { which, xrel, yrel, .. } => Some(MuiEvent::MouseMotion(which, xrel, yrel)),
-				Event::MouseButtonDown { which, mouse_btn, .. } =>
-					MouseKey::from_sdl(mouse_btn).map(|v| MuiEvent::MouseButtonDown(which, v)),
-				Event::MouseButtonUp { which, mouse_btn, .. } =>
-					MouseKey::from_sdl(mouse_btn).map(|v| MuiEvent::MouseButtonUp(which, v)),
-				Event::MouseWheel { which, x, y, .. } => Some(MuiEvent::MouseWheel(which, x, -y)),
-				Event::JoyAxisMotion { which, axis_idx, value, .. } =>
-					Some(MuiEvent::JoystickAxisMotion(which, axis_idx, value)),
-				Event::JoyHatMotion { which, hat_idx, state, .. } =>
-					Some(MuiEvent::JoystickHatMotion(which, hat_idx, state)),
-				Event::JoyButtonDown { which, button_idx, .. } =>
-					Some(MuiEvent::JoystickButtonDown(which, button_idx)),
-				Event::JoyButtonUp { which, button_idx, .. } =>
-					Some(MuiEvent::JoystickButtonUp(which, button_idx)),
-				Event::JoyDeviceAdded { which, .. } => Some(MuiEvent::JoystickAdded(which)),
-				Event::JoyDeviceRemoved { which, .. } => Some(MuiEvent::JoystickRemoved(which)),
-				Event::ControllerAxisMotion { which, axis, value, .. } =>
-					Some(MuiEvent::GamepadAxisMotion(which, axis, value)),
-				Event::ControllerButtonDown { which, button, .. } =>
-					Some(MuiEvent::GamepadButtonDown(which, button)),
-				Event::ControllerButtonUp { which, button, .. } =>
-					Some(MuiEvent::GamepadButtonUp(which, button)),
-				Event::ControllerDeviceAdded { which, .. } => Some(MuiEvent::GamepadAdded(which)),
-				Event::ControllerDeviceRemoved { which, .. } => Some(MuiEvent::GamepadRemoved(which)),
-				Event::ControllerDeviceRemapped { which, .. } => Some(MuiEvent::GamepadRemapped(which)),
-				Event::ControllerTouchpadDown { which, touchpad, finger, x, y, pressure, .. } =>
-					Some(MuiEvent::GamepadTouchpadDown(which, touchpad, finger, x, y, pressure)),
-				Event::ControllerTouchpadMotion { which, touchpad, finger, x, y, pressure, .. } =>
-					Some(MuiEvent::GamepadTouchpadMotion(which, touchpad, finger, x, y, pressure)),
-				Event::ControllerTouchpadUp { which, touchpad, finger, x, y, pressure, .. } =>
-					Some(MuiEvent::GamepadTouchpadUp(which, touchpad, finger, x, y, pressure)),
-				Event::DropFile { filename, .. } => Some(MuiEvent::DropFile(filename)),
-				Event::DropText { filename: text, .. } => Some(MuiEvent::DropText(text)),
-				Event::DropBegin { .. } => Some(MuiEvent::DropBegin),
-				Event::DropComplete { .. } => Some(MuiEvent::DropComplete),
-				Event::RenderTargetsReset { .. } => Some(MuiEvent::RenderTargetsReset),
-				Event::RenderDeviceReset { .. } => Some(MuiEvent::RenderDeviceReset),
-				Event::Display { display, display_event, .. } => match display_event {
-					DisplayEvent::Added => Some(MuiEvent::DisplayAdded(DisplayHandle { display })),
-					DisplayEvent::Removed => Some(MuiEvent::DisplayRemoved(DisplayHandle { display })),
-					DisplayEvent::Moved => Some(MuiEvent::DisplayMoved(DisplayHandle { display })),
-					_ => None,
-				},
-				_ => None,
-			} {
-				events.push(v);
+		// The queue is taken at once and converted in order, as polling through sdl3-rs pumps again,
+		// and the events arriving meanwhile would not be converted as raw events.
+		for raw in take_raw_events() {
+			match self.convert_raw_event(&raw) {
+				Some(v) => events.extend(v),
+				None => self.convert_event(Event::from_ll(raw), &mut events),
 			}
-		});
+		}
+		self.haptics.tick(&mut self.gamepads);
+		events.extend(self.slots.drain_events());
+		events.extend(drain_events().into_iter().map(MuiEvent::Engine));
 		events
 	}
+
+	/// Converts the event through sdl3-rs, with the events triggered by it.
+	fn convert_event(&mut self, event: Event, events: &mut Vec<MuiEvent>) {
+		if let Some(v) = match event {
+			// Only one window is available, so the window ID is ignored.
+			// SDL only reports events made through the window created by this application.
+			Event::Window { win_event, .. } => match win_event {
+				WindowEvent::Shown => Some(MuiEvent::WindowShown),
+				WindowEvent::Hidden => Some(MuiEvent::WindowHidden),
+				WindowEvent::Exposed => Some(MuiEvent::WindowExposed),
+				WindowEvent::Moved(x, y) => Some(MuiEvent::WindowMoved(x, y)),
+				WindowEvent::Resized(w, h) => Some(MuiEvent::WindowResized(w, h)),
+				WindowEvent::PixelSizeChanged(w, h) => Some(MuiEvent::WindowPixelSizeChanged(w, h)),
+				WindowEvent::Minimized => Some(MuiEvent::WindowMinimized),
+				WindowEvent::Maximized => Some(MuiEvent::WindowMaximized),
+				WindowEvent::Restored => Some(MuiEvent::WindowRestored),
+				WindowEvent::MouseEnter => Some(MuiEvent::WindowMouseEnter),
+				WindowEvent::MouseLeave => Some(MuiEvent::WindowMouseLeave),
+				WindowEvent::FocusGained => Some(MuiEvent::WindowFocusGained),
+				WindowEvent::FocusLost => Some(MuiEvent::WindowFocusLost),
+				WindowEvent::CloseRequested => Some(MuiEvent::WindowCloseRequested),
+				WindowEvent::ICCProfChanged => Some(MuiEvent::WindowIccProfChanged),
+				_ => None,
+			}
+			// Actions are reported right after the raw key events triggering them.
+			Event::KeyDown { scancode, keycode, keymod, repeat, which, .. } => {
+				events.extend(scancode.filter(|v| !repeat || v != &Scancode::Unknown).and_then(KeyboardKey::from_sdl)
+					.map(|v| MuiEvent::KeyboardKeyDown(which, v, key_modifiers(keymod))));
+				if let Some(scancode) = scancode.filter(|_| !repeat) {
+					events.extend(self.actions.key_down(scancode, keycode).into_iter().map(MuiEvent::ActionPressed));
+				}
+				None
+			}
+			Event::KeyUp { scancode, keymod, repeat, which, .. } => {
+				events.extend(scancode.filter(|v| !repeat || v != &Scancode::Unknown).and_then(KeyboardKey::from_sdl)
+					.map(|v| MuiEvent::KeyboardKeyUp(which, v, key_modifiers(keymod))));
+				if let Some(scancode) = scancode {
+					events.extend(self.actions.key_up(scancode).into_iter().map(MuiEvent::ActionReleased));
+				}
+				None
+			}
+			Event::TextEditing { text, start, length, .. } => Some(MuiEvent::TextEditing(text, start, length)),
+			Event::TextInput { text, .. } => Some(MuiEvent::TextInput(text)),
+			Event::MouseMotion { which, xrel, yrel, .. } => Some(MuiEvent::MouseMotion(which, xrel, yrel)),
+			Event::MouseButtonDown { which, mouse_btn, .. } => {
+				let Some(key) = MouseKey::from_sdl(mouse_btn) else { return };
+				events.push(MuiEvent::MouseButtonDown(which, key));
+				events.extend(self.actions.mouse_down(key).into_iter().map(MuiEvent::ActionPressed));
+				None
+			}
+			Event::MouseButtonUp { which, mouse_btn, .. } => {
+				let Some(key) = MouseKey::from_sdl(mouse_btn) else { return };
+				events.push(MuiEvent::MouseButtonUp(which, key));
+				events.extend(self.actions.mouse_up(key).into_iter().map(MuiEvent::ActionReleased));
+				None
+			}
+			Event::MouseWheel { which, x, y, .. } => Some(MuiEvent::MouseWheel(which, x, -y)),
+			Event::JoyAxisMotion { which, axis_idx, value, .. } =>
+				self.axis_filter.joystick_axis(which, axis_idx, value).map(|v| MuiEvent::JoystickAxisMotion(which, axis_idx, v)),
+			Event::JoyHatMotion { which, hat_idx, state, .. } =>
+				Some(MuiEvent::JoystickHatMotion(which, hat_idx, state)),
+			Event::JoyButtonDown { which, button_idx, .. } =>
+				Some(MuiEvent::JoystickButtonDown(which, button_idx)),
+			Event::JoyButtonUp { which, button_idx, .. } =>
+				Some(MuiEvent::JoystickButtonUp(which, button_idx)),
+			Event::JoyDeviceAdded { which, .. } => Some(MuiEvent::JoystickAdded(which)),
+			Event::JoyDeviceRemoved { which, .. } => {
+				self.axis_filter.device_removed(which);
+				Some(MuiEvent::JoystickRemoved(which))
+			}
+			Event::ControllerAxisMotion { which, axis, value, .. } => {
+				events.extend(self.axis_filter.gamepad_axis(which, axis, value)
+					.map(|v| MuiEvent::GamepadAxisMotion(which, axis, v)));
+				let (pressed, released) = self.actions.gamepad_axis(which, axis, value);
+				events.extend(pressed.into_iter().map(MuiEvent::ActionPressed));
+				events.extend(released.into_iter().map(MuiEvent::ActionReleased));
+				None
+			}
+			Event::ControllerButtonDown { which, button, .. } => {
+				events.push(MuiEvent::GamepadButtonDown(which, button));
+				events.extend(self.actions.gamepad_button_down(which, button).into_iter().map(MuiEvent::ActionPressed));
+				None
+			}
+			Event::ControllerButtonUp { which, button, .. } => {
+				events.push(MuiEvent::GamepadButtonUp(which, button));
+				events.extend(self.actions.gamepad_button_up(which, button).into_iter().map(MuiEvent::ActionReleased));
+				None
+			}
+			Event::ControllerDeviceAdded { which, .. } => {
+				if let Ok(v) = self.gamepad.open(which) {
+					self.gamepads.insert(which, v);
+				}
+				self.slots.gamepad_added(which);
+				Some(MuiEvent::GamepadAdded(which))
+			}
+			Event::ControllerDeviceRemoved { which, .. } => {
+				self.gestures.remove_gamepad(which);
+				self.axis_filter.device_removed(which);
+				events.extend(self.actions.gamepad_removed(which).into_iter().map(MuiEvent::ActionReleased));
+				self.haptics.stop(which, &mut self.gamepads);
+				self.gamepads.remove(&which);
+				self.slots.gamepad_removed(which);
+				Some(MuiEvent::GamepadRemoved(which))
+			}
+			Event::ControllerDeviceRemapped { which, .. } => Some(MuiEvent::GamepadRemapped(which)),
+			// Recognized gestures are reported right after the raw events triggering them.
+			Event::ControllerTouchpadDown { which, touchpad, finger, x, y, pressure, .. } => {
+				self.gestures.finger_down(which, touchpad, finger, x, y);
+				Some(MuiEvent::GamepadTouchpadDown(which, touchpad, finger, x, y, pressure))
+			}
+			Event::ControllerTouchpadMotion { which, touchpad, finger, x, y, pressure, .. } => {
+				events.push(MuiEvent::GamepadTouchpadMotion(which, touchpad, finger, x, y, pressure));
+				self.gestures.finger_motion(which, touchpad, finger, x, y)
+			}
+			Event::ControllerTouchpadUp { which, touchpad, finger, x, y, pressure, .. } => {
+				events.push(MuiEvent::GamepadTouchpadUp(which, touchpad, finger, x, y, pressure));
+				self.gestures.finger_up(which, touchpad, finger, x, y)
+			}
+			Event::RenderTargetsReset { .. } => Some(MuiEvent::RenderTargetsReset),
+			Event::RenderDeviceReset { .. } => Some(MuiEvent::RenderDeviceReset),
+			Event::Display { display, display_event, .. } => match display_event {
+				DisplayEvent::Added => Some(MuiEvent::DisplayAdded(DisplayHandle { display })),
+				DisplayEvent::Removed => Some(MuiEvent::DisplayRemoved(DisplayHandle { display })),
+				DisplayEvent::Moved => Some(MuiEvent::DisplayMoved(DisplayHandle { display })),
+				_ => None,
+			},
+			_ => None,
+		} {
+			events.push(v);
+		}
+	}
+
+	/// `keyframes` are `(time in ms, low frequency intensity, high frequency intensity)`.
+	pub(crate) fn register_rumble_pattern(&mut self, name: String, keyframes: &[(u64, f32, f32)]) -> FerriciaResult<()> {
+		let keyframes = keyframes.iter().map(|(t, l, h)| RumbleKeyframe::new(*t, *l, *h)).collect();
+		self.haptics.register(name, RumblePattern::new(keyframes)?);
+		Ok(())
+	}
+
+	pub(crate) fn play_rumble_pattern(&mut self, gamepad: u32, name: &str, intensity: f32) -> FerriciaResult<()> {
+		if !self.gamepads.contains_key(&gamepad) {
+			return Err(FerriciaError(format!("Gamepad {gamepad} is not connected")));
+		}
+		self.haptics.play(gamepad, name, intensity)
+	}
+
+	pub(crate) fn stop_rumble_pattern(&mut self, gamepad: u32) {
+		self.haptics.stop(gamepad, &mut self.gamepads);
+	}
+
+	/// Rumbles the motors once, replacing the playing pattern; intensities are clamped in `[0, 1]`.
+	pub(crate) fn rumble_gamepad(&mut self, gamepad: u32, low: f32, high: f32, duration_ms: u32) -> FerriciaResult<()> {
+		self.haptics.stop(gamepad, &mut self.gamepads);
+		let gamepad = self.gamepads.get_mut(&gamepad)
+			.ok_or_else(|| FerriciaError(format!("Gamepad {gamepad} is not connected")))?;
+		Ok(gamepad.set_rumble(rumble_intensity(low), rumble_intensity(high), duration_ms)?)
+	}
+
+	/// Rumbles the motors in the triggers, such as of Xbox One controllers; fails if not supported.
+	pub(crate) fn rumble_gamepad_triggers(&mut self, gamepad: u32, left: f32, right: f32, duration_ms: u32) -> FerriciaResult<()> {
+		let gamepad = self.gamepads.get_mut(&gamepad)
+			.ok_or_else(|| FerriciaError(format!("Gamepad {gamepad} is not connected")))?;
+		if !gamepad.has_rumble_triggers() {
+			return Err(FerriciaError(format!("Gamepad {} does not support trigger rumble", gamepad.name())));
+		}
+		Ok(gamepad.set_rumble_triggers(rumble_intensity(left), rumble_intensity(right), duration_ms)?)
+	}
+
+	pub(crate) fn bind_action_physical(&mut self, action: String, key: KeyboardKey) {
+		self.actions.bind_physical(action, key);
+	}
+
+	pub(crate) fn bind_action_layout(&mut self, action: String, symbol: &str) -> FerriciaResult<()> {
+		self.actions.bind_layout(action, symbol)
+	}
+
+	pub(crate) fn bind_action_mouse(&mut self, action: String, key: MouseKey) {
+		self.actions.bind_mouse(action, key);
+	}
+
+	pub(crate) fn bind_action_gamepad_button(&mut self, action: String, button: GamepadButton) {
+		self.actions.bind_gamepad_button(action, button);
+	}
+
+	/// The action is pressed when the axis is pushed beyond the dead zone in the direction.
+	pub(crate) fn bind_action_gamepad_axis(&mut self, action: String, axis: GamepadAxis, positive: bool, dead_zone: f32) -> FerriciaResult<()> {
+		self.actions.bind_gamepad_axis(action, axis, positive, dead_zone)
+	}
+
+	pub(crate) fn unbind_action(&mut self, action: &str) {
+		self.actions.unbind(action);
+	}
+
+	/// Whether the action is held by any source as of the last poll.
+	pub(crate) fn action_held(&self, action: &str) -> bool {
+		self.actions.is_held(action)
+	}
+
+	/// Analog value of the action in `[0, 1]` as of the last poll, such as for movement by sticks.
+	pub(crate) fn action_value(&self, action: &str) -> f32 {
+		self.actions.value(action)
+	}
+
+	/// Converts the physical key to its symbol under the current layout.
+	pub(crate) fn key_layout_symbol(&self, key: KeyboardKey) -> Option<String> {
+		layout_symbol(key)
+	}
+
+	/// Converts the physical key to its SDL keycode under the current layout.
+	pub(crate) fn key_layout_code(&self, key: KeyboardKey) -> Option<u32> {
+		layout_keycode(key)
+	}
+
+	/// Converts the symbol under the current layout to its physical key.
+	pub(crate) fn key_of_layout_symbol(&self, symbol: &str) -> Option<KeyboardKey> {
+		physical_key(symbol)
+	}
+
+	pub(crate) fn gamepad_info(&self, gamepad: u32) -> FerriciaResult<GamepadInfo> {
+		if !self.gamepads.contains_key(&gamepad) {
+			return Err(FerriciaError(format!("Gamepad {gamepad} is not connected")));
+		}
+		gamepad_info(gamepad)
+	}
+
+	/// `None` reports the axis of all gamepads as is.
+	pub(crate) fn set_gamepad_axis_response(&mut self, axis: GamepadAxis, response: Option<AxisResponse>) {
+		self.axis_filter.set_gamepad_response(axis, response);
+	}
+
+	/// `axis` is the index of the axis of all raw joysticks.
+	pub(crate) fn set_joystick_axis_response(&mut self, axis: u8, response: Option<AxisResponse>) {
+		self.axis_filter.set_joystick_response(axis, response);
+	}
+
+	pub(crate) fn clear_axis_responses(&mut self) {
+		self.axis_filter.clear();
+	}
+
+	/// Connected gamepads are assigned to new slots right away.
+	pub(crate) fn set_player_slot_count(&mut self, count: usize) {
+		self.slots.set_count(count, self.gamepads.keys().copied());
+	}
+
+	pub(crate) fn assign_player_slot(&mut self, slot: usize, gamepad: u32) -> FerriciaResult<()> {
+		if !self.gamepads.contains_key(&gamepad) {
+			return Err(FerriciaError(format!("Gamepad {gamepad} is not connected")));
+		}
+		self.slots.assign(slot, gamepad)
+	}
+
+	/// Returns the gamepad assigned to the slot.
+	pub(crate) fn player_slot_gamepad(&self, slot: usize) -> Option<u32> {
+		self.slots.gamepad(slot)
+	}
+
+	/// Returns the GUIDs remembered by slots as a JSON array, with `null` for unused slots.
+	pub(crate) fn export_player_slots(&self) -> String {
+		serde_json::to_string(&self.slots.export()).expect("should be serializable")
+	}
+
+	/// Restores the GUIDs exported by [SdlHandle::export_player_slots] for reconnection.
+	pub(crate) fn import_player_slots(&mut self, json: &str) -> FerriciaResult<()> {
+		let guids = serde_json::from_str(json).map_err(|e| format!("Invalid player slots: {e}"))?;
+		self.slots.import(guids);
+		Ok(())
+	}
+
+	/// IDs of connected devices of force feedback, such as racing wheels and some gamepads.
+	pub(crate) fn haptic_devices(&self) -> Vec<u32> {
+		haptic_devices()
+	}
+
+	pub(crate) fn haptic_device_name(&self, id: u32) -> Option<String> {
+		haptic_device_name(id)
+	}
+
+	/// The cursor is shown over all windows by default, such as for menus.
+	pub(crate) fn set_cursor_visible(&self, visible: bool) {
+		self.sdl_context.mouse().show_cursor(visible);
+	}
+
+	/// Hides and confines the cursor to the window, such as for camera control in gameplay;
+	/// [MuiEvent::MouseMotion] keeps reporting the motion while the cursor does not move.
+	pub(crate) fn set_relative_mouse_mode(&self, window_handle: &WindowHandle, enabled: bool) {
+		self.sdl_context.mouse().set_relative_mouse_mode(window_handle.window(), enabled);
+	}
+
+	/// Starts reporting [MuiEvent::TextInput] and [MuiEvent::TextEditing] of the window, such as when
+	/// a text field is focused; this may show the on-screen keyboard and enable the IME.
+	pub(crate) fn start_text_input(&self, window_handle: &WindowHandle) {
+		self.video.text_input().start(window_handle.window());
+	}
+
+	pub(crate) fn stop_text_input(&self, window_handle: &WindowHandle) {
+		self.video.text_input().stop(window_handle.window());
+	}
+
+	/// Hints the area of the active text field in window coordinates, so that the candidate window
+	/// of the IME is placed next to it; `cursor` is the offset of the caret from the left of the area.
+	pub(crate) fn set_text_input_area(&self, window_handle: &WindowHandle, area: Rect, cursor: i32) -> FerriciaResult<()> {
+		Ok(self.video.text_input().set_rect(window_handle.window(), area, cursor)?)
+	}
+
+	/// Modes of exclusive fullscreen of the display, from the highest resolution and refresh rate.
+	pub(crate) fn fullscreen_modes(&self, display: &DisplayHandle) -> FerriciaResult<Vec<DisplayMode>> {
+		self.displays.borrow().get(&display.display)
+			.map(|v| v.fullscreen_modes().to_vec())
+			.ok_or_else(|| "Unknown display".to_string().into())
+	}
+
+	/// `mask` is a combination of the categories of [MuiEvent]; all are reported by default.
+	pub(crate) fn set_event_mask(&mut self, mask: u32) {
+		self.event_mask = mask;
+	}
+
+	/// Drops the events of the categories not in the mask, before they are converted for Java.
+	/// This is done after handling the events internally, such as actions and automatic resizing.
+	pub(crate) fn filter_events(&self, events: &mut Vec<MuiEvent>) {
+		events.retain(|v| v.category().is_none_or(|c| self.event_mask & c != 0));
+	}
+
+	/// Whether the canvas is resized along with the viewport of the window on pixel size changes
+	/// while polling; [MuiEvent::WindowPixelSizeChanged] is still reported.
+	pub(crate) fn set_auto_resize(&mut self, window: TaggedId, canvas: TaggedId, enabled: bool) {
+		self.auto_resize.retain(|v| *v != (window, canvas));
+		if enabled {
+			self.auto_resize.push((window, canvas));
+		}
+	}
+
+	/// Forgets the pairs of which the handles have been dropped.
+	pub(crate) fn retain_auto_resize(&mut self, f: impl FnMut(&(TaggedId, TaggedId)) -> bool) {
+		self.auto_resize.retain(f);
+	}
+
+	/// Whether the render target is resized to the canvas after the canvas is resized automatically.
+	pub(crate) fn set_auto_resize_target(&mut self, canvas: TaggedId, target: TaggedId, enabled: bool) {
+		self.auto_resize_targets.retain(|v| *v != (canvas, target));
+		if enabled {
+			self.auto_resize_targets.push((canvas, target));
+		}
+	}
+
+	/// Forgets the pairs of which the handles have been dropped.
+	pub(crate) fn retain_auto_resize_targets(&mut self, f: impl FnMut(&(TaggedId, TaggedId)) -> bool) {
+		self.auto_resize_targets.retain(f);
+	}
 }
 
 /// This list is made and filtered according to SDL 3 documentation of `SDL_EventType`.
@@ -168,7 +587,7 @@ pub(crate) enum MuiEvent {
 	WindowExposed,
 	WindowMoved(i32, i32),
 	WindowResized(i32, i32),
-	WindowPixelSizeChanged(i32, i32), // Only use this to update size of viewport
+	WindowPixelSizeChanged(i32, i32), // Only use this to update size of viewport, unless resized automatically
 	WindowMetalViewResized, // Not yet ported by sdl3-rs
 	WindowMinimized,
 	WindowMaximized,
@@ -183,9 +602,11 @@ pub(crate) enum MuiEvent {
 	WindowEnterFullscreen, // Not yet ported to sdl3-rs
 	WindowLeaveFullscreen, // Not yet ported to sdl3-rs
 	WindowDestroyed, // Not yet ported to sdl3-rs
-	WindowHdrStateChanged, // Not yet ported to sdl3-rs
-	KeyboardKeyDown(u32, KeyboardKey),
-	KeyboardKeyUp(u32, KeyboardKey),
+	WindowHdrStateChanged, // Taken from raw SDL events, as not yet ported to sdl3-rs
+	KeyboardKeyDown(u32, KeyboardKey, u8), // With the modifier mask of `key_modifiers`
+	KeyboardKeyUp(u32, KeyboardKey, u8),
+	ActionPressed(String), // By the action map
+	ActionReleased(String), // By the action map
 	TextEditing(String, i32, i32),
 	TextInput(String),
 	KeymapChanged, // Not yet ported to sdl3-rs; not used at the moment
@@ -205,26 +626,96 @@ pub(crate) enum MuiEvent {
 	JoystickButtonUp(u32, u8),
 	JoystickAdded(u32),
 	JoystickRemoved(u32),
-	JoystickBatteryUpdated, // Not yet ported to sdl3-rs
+	JoystickBatteryUpdated(u32, i32, i32), // Power state and percent as in `GamepadInfo`; taken from raw SDL events
 	GamepadAxisMotion(u32, GamepadAxis, i16),
 	GamepadButtonDown(u32, GamepadButton),
 	GamepadButtonUp(u32, GamepadButton),
 	GamepadAdded(u32),
 	GamepadRemoved(u32),
 	GamepadRemapped(u32),
-	// No idea how to use touchpad, so just kept as is.
+	// Raw touchpad events are kept as is; see `gesture` for the recognized ones.
 	GamepadTouchpadDown(u32, i32, i32, f32, f32, f32),
 	GamepadTouchpadMotion(u32, i32, i32, f32, f32, f32),
 	GamepadTouchpadUp(u32, i32, i32, f32, f32, f32),
+	GamepadTouchpadTap(u32, i32, f32, f32), // Position of the tap
+	GamepadTouchpadDrag(u32, i32, f32, f32), // Delta since the last drag event
+	GamepadTouchpadSwipe(u32, i32, f32, f32), // Average displacement of both fingers
+	PlayerSlotAssigned(u32, u32), // Slot and gamepad
+	PlayerSlotDisconnected(u32), // Slot
 	GamepadSteamHandleUpdated, // Not yet ported to sdl3-rs
-	DropFile(String),
-	DropText(String),
+	DropFile(String, f32, f32), // Positions are in window coordinates.
+	DropText(String, f32, f32),
 	DropBegin,
 	DropComplete,
-	DropPosition, // Not yet ported to sdl3-rs
+	DropPosition(f32, f32), // Taken from raw SDL events, as not yet ported to sdl3-rs
+	// Pen events are taken from raw SDL events; positions are in window coordinates.
+	PenProximityIn(u32),
+	PenProximityOut(u32),
+	PenDown(u32, f32, f32, bool), // Whether the eraser end touches
+	PenUp(u32, f32, f32, bool),
+	PenButtonDown(u32, u8, f32, f32), // Buttons are numbered from 1.
+	PenButtonUp(u32, u8, f32, f32),
+	PenMotion(u32, f32, f32),
+	// `SDL_PenAxis`: pressure, X tilt, Y tilt, distance, rotation, slider and tangential pressure from 0
+	PenAxis(u32, i32, f32),
 	RenderTargetsReset,
 	RenderDeviceReset,
 	RenderDeviceLost, // Not yet ported to sdl3-rs
+	Engine(EngineEvent), // Posted by engine subsystems
+}
+
+impl MuiEvent {
+	pub(crate) const DISPLAY: u32 = 1 << 0;
+	pub(crate) const WINDOW: u32 = 1 << 1;
+	pub(crate) const KEYBOARD: u32 = 1 << 2;
+	pub(crate) const TEXT: u32 = 1 << 3;
+	pub(crate) const MOUSE: u32 = 1 << 4;
+	pub(crate) const JOYSTICK: u32 = 1 << 5;
+	pub(crate) const GAMEPAD: u32 = 1 << 6;
+	/// Raw touchpad events of gamepads
+	pub(crate) const TOUCHPAD: u32 = 1 << 7;
+	/// Recognized touchpad gestures
+	pub(crate) const GESTURE: u32 = 1 << 8;
+	pub(crate) const DROP: u32 = 1 << 9;
+	pub(crate) const PEN: u32 = 1 << 10;
+	pub(crate) const RENDER: u32 = 1 << 11;
+
+	/// `None` for the events always reported, which are actions, player slots and engine events.
+	fn category(&self) -> Option<u32> {
+		match self {
+			MuiEvent::DisplayAdded(..) | MuiEvent::DisplayRemoved(..) | MuiEvent::DisplayMoved(..) => Some(Self::DISPLAY),
+			MuiEvent::WindowShown | MuiEvent::WindowHidden | MuiEvent::WindowExposed | MuiEvent::WindowMoved(..) |
+			MuiEvent::WindowResized(..) | MuiEvent::WindowPixelSizeChanged(..) | MuiEvent::WindowMetalViewResized |
+			MuiEvent::WindowMinimized | MuiEvent::WindowMaximized | MuiEvent::WindowRestored |
+			MuiEvent::WindowMouseEnter | MuiEvent::WindowMouseLeave | MuiEvent::WindowFocusGained |
+			MuiEvent::WindowFocusLost | MuiEvent::WindowCloseRequested | MuiEvent::WindowIccProfChanged |
+			MuiEvent::WindowOccluded | MuiEvent::WindowEnterFullscreen | MuiEvent::WindowLeaveFullscreen |
+			MuiEvent::WindowDestroyed | MuiEvent::WindowHdrStateChanged => Some(Self::WINDOW),
+			MuiEvent::KeyboardKeyDown(..) | MuiEvent::KeyboardKeyUp(..) | MuiEvent::KeymapChanged |
+			MuiEvent::KeyboardAdded | MuiEvent::KeyboardRemoved => Some(Self::KEYBOARD),
+			MuiEvent::TextEditing(..) | MuiEvent::TextInput(..) | MuiEvent::TextEditingCandidates => Some(Self::TEXT),
+			MuiEvent::MouseMotion(..) | MuiEvent::MouseButtonDown(..) | MuiEvent::MouseButtonUp(..) |
+			MuiEvent::MouseWheel(..) | MuiEvent::MouseAdded | MuiEvent::MouseRemoved => Some(Self::MOUSE),
+			MuiEvent::JoystickAxisMotion(..) | MuiEvent::JoystickBallMotion | MuiEvent::JoystickHatMotion(..) |
+			MuiEvent::JoystickButtonDown(..) | MuiEvent::JoystickButtonUp(..) | MuiEvent::JoystickAdded(..) |
+			MuiEvent::JoystickRemoved(..) | MuiEvent::JoystickBatteryUpdated(..) => Some(Self::JOYSTICK),
+			MuiEvent::GamepadAxisMotion(..) | MuiEvent::GamepadButtonDown(..) | MuiEvent::GamepadButtonUp(..) |
+			MuiEvent::GamepadAdded(..) | MuiEvent::GamepadRemoved(..) | MuiEvent::GamepadRemapped(..) |
+			MuiEvent::GamepadSteamHandleUpdated => Some(Self::GAMEPAD),
+			MuiEvent::GamepadTouchpadDown(..) | MuiEvent::GamepadTouchpadMotion(..) |
+			MuiEvent::GamepadTouchpadUp(..) => Some(Self::TOUCHPAD),
+			MuiEvent::GamepadTouchpadTap(..) | MuiEvent::GamepadTouchpadDrag(..) |
+			MuiEvent::GamepadTouchpadSwipe(..) => Some(Self::GESTURE),
+			MuiEvent::DropFile(..) | MuiEvent::DropText(..) | MuiEvent::DropBegin | MuiEvent::DropComplete |
+			MuiEvent::DropPosition(..) => Some(Self::DROP),
+			MuiEvent::PenProximityIn(..) | MuiEvent::PenProximityOut(..) | MuiEvent::PenDown(..) |
+			MuiEvent::PenUp(..) | MuiEvent::PenButtonDown(..) | MuiEvent::PenButtonUp(..) |
+			MuiEvent::PenMotion(..) | MuiEvent::PenAxis(..) => Some(Self::PEN),
+			MuiEvent::RenderTargetsReset | MuiEvent::RenderDeviceReset | MuiEvent::RenderDeviceLost => Some(Self::RENDER),
+			MuiEvent::ActionPressed(..) | MuiEvent::ActionReleased(..) | MuiEvent::PlayerSlotAssigned(..) |
+			MuiEvent::PlayerSlotDisconnected(..) | MuiEvent::Engine(..) => None,
+		}
+	}
 }
 
 pub(crate) struct DisplayHandle {
@@ -246,16 +737,23 @@ impl SdlDisplay {
 			bounds: display.get_bounds()?,
 			usable_bounds: display.get_usable_bounds()?,
 			fullscreen_modes: display.get_fullscreen_modes()?,
-			hdr_enabled: display.get_properties().map_err(|e| match e {
-				PropertiesError::SdlError(e) => e,
-				_ => panic!("{:?}", e),
-			})?.contains("SDL.display.HDR_enabled").map_err(|e| match e {
-				PropertiesError::SdlError(e) => e,
-				_ => panic!("{:?}", e),
-			})?,
+			hdr_enabled: read_hdr_enabled(display)?,
 		})
 	}
 	
+	pub(crate) fn fullscreen_modes(&self) -> &[DisplayMode] {
+		&self.fullscreen_modes
+	}
+
+	pub(crate) fn hdr_enabled(&self) -> bool {
+		self.hdr_enabled
+	}
+
+	fn update_hdr(&mut self, display: &Display) -> Result<(), sdl3::Error> {
+		self.hdr_enabled = read_hdr_enabled(display)?;
+		Ok(())
+	}
+
 	fn update_bounds(&mut self, display: Display) -> Result<(), sdl3::Error> {
 		self.bounds = display.get_bounds()?;
 		self.usable_bounds = display.get_usable_bounds()?;
@@ -263,7 +761,39 @@ impl SdlDisplay {
 	}
 }
 
+/// Takes all the events from the queue without pumping.
+fn take_raw_events() -> Vec<SDL_Event> {
+	let mut events = Vec::new();
+	let mut buf = [MaybeUninit::<SDL_Event>::uninit(); 16];
+	loop {
+		let num = unsafe {
+			SDL_PeepEvents(buf.as_mut_ptr() as *mut SDL_Event, buf.len() as _, SDL_GETEVENT, SDL_EVENT_FIRST.0, SDL_EVENT_LAST.0)
+		};
+		if num <= 0 {
+			break;
+		}
+		events.extend(buf[..num as usize].iter().map(|v| unsafe { v.assume_init() }));
+	}
+	events
+}
+
+fn rumble_intensity(v: f32) -> u16 {
+	(v.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
+
+fn read_hdr_enabled(display: &Display) -> Result<bool, sdl3::Error> {
+	display.get_properties().map_err(|e| match e {
+		PropertiesError::SdlError(e) => e,
+		_ => panic!("{:?}", e),
+	})?.contains("SDL.display.HDR_enabled").map_err(|e| match e {
+		PropertiesError::SdlError(e) => e,
+		_ => panic!("{:?}", e),
+	})
+}
+
 /// This list is made and filtered according to SDL 3 documentation of `SDL_Scancode`.
+#[derive(Clone, Copy)]
+#[repr(u32)]
 pub(crate) enum KeyboardKey {
 	// Unknown is skipped.
 	A,
@@ -512,6 +1042,261 @@ pub(crate) enum KeyboardKey {
 }
 
 impl KeyboardKey {
+	/// Number of keys, as the ordinals used by Java are contiguous
+	const COUNT: u32 = KeyboardKey::AcBookmarks as u32 + 1;
+
+	pub(crate) fn from_ordinal(ordinal: u32) -> Option<Self> {
+		// SAFETY: The enum is field-less with implicit discriminants counting from zero.
+		(ordinal < Self::COUNT).then(|| unsafe { std::mem::transmute::<u32, Self>(ordinal) })
+	}
+
+	fn to_sdl(self) -> Scancode {
+		match self {
+			KeyboardKey::A => Scancode::A,
+			KeyboardKey::B => Scancode::B,
+			KeyboardKey::C => Scancode::C,
+			KeyboardKey::D => Scancode::D,
+			KeyboardKey::E => Scancode::E,
+			KeyboardKey::F => Scancode::F,
+			KeyboardKey::G => Scancode::G,
+			KeyboardKey::H => Scancode::H,
+			KeyboardKey::I => Scancode::I,
+			KeyboardKey::J => Scancode::J,
+			KeyboardKey::K => Scancode::K,
+			KeyboardKey::L => Scancode::L,
+			KeyboardKey::M => Scancode::M,
+			KeyboardKey::N => Scancode::N,
+			KeyboardKey::O => Scancode::O,
+			KeyboardKey::P => Scancode::P,
+			KeyboardKey::Q => Scancode::Q,
+			KeyboardKey::R => Scancode::R,
+			KeyboardKey::S => Scancode::S,
+			KeyboardKey::T => Scancode::T,
+			KeyboardKey::U => Scancode::U,
+			KeyboardKey::V => Scancode::V,
+			KeyboardKey::W => Scancode::W,
+			KeyboardKey::X => Scancode::X,
+			KeyboardKey::Y => Scancode::Y,
+			KeyboardKey::Z => Scancode::Z,
+			KeyboardKey::_1 => Scancode::_1,
+			KeyboardKey::_2 => Scancode::_2,
+			KeyboardKey::_3 => Scancode::_3,
+			KeyboardKey::_4 => Scancode::_4,
+			KeyboardKey::_5 => Scancode::_5,
+			KeyboardKey::_6 => Scancode::_6,
+			KeyboardKey::_7 => Scancode::_7,
+			KeyboardKey::_8 => Scancode::_8,
+			KeyboardKey::_9 => Scancode::_9,
+			KeyboardKey::_0 => Scancode::_0,
+			KeyboardKey::Return => Scancode::Return,
+			KeyboardKey::Escape => Scancode::Escape,
+			KeyboardKey::Backspace => Scancode::Backspace,
+			KeyboardKey::Tab => Scancode::Tab,
+			KeyboardKey::Space => Scancode::Space,
+			KeyboardKey::Minus => Scancode::Minus,
+			KeyboardKey::Equals => Scancode::Equals,
+			KeyboardKey::LeftBracket => Scancode::LeftBracket,
+			KeyboardKey::RightBracket => Scancode::RightBracket,
+			KeyboardKey::Backslash => Scancode::Backslash,
+			KeyboardKey::NonUsHash => Scancode::NonUsHash,
+			KeyboardKey::Semicolon => Scancode::Semicolon,
+			KeyboardKey::Apostrophe => Scancode::Apostrophe,
+			KeyboardKey::Grave => Scancode::Grave,
+			KeyboardKey::Comma => Scancode::Comma,
+			KeyboardKey::Period => Scancode::Period,
+			KeyboardKey::Slash => Scancode::Slash,
+			KeyboardKey::CapsLock => Scancode::CapsLock,
+			KeyboardKey::F1 => Scancode::F1,
+			KeyboardKey::F2 => Scancode::F2,
+			KeyboardKey::F3 => Scancode::F3,
+			KeyboardKey::F4 => Scancode::F4,
+			KeyboardKey::F5 => Scancode::F5,
+			KeyboardKey::F6 => Scancode::F6,
+			KeyboardKey::F7 => Scancode::F7,
+			KeyboardKey::F8 => Scancode::F8,
+			KeyboardKey::F9 => Scancode::F9,
+			KeyboardKey::F10 => Scancode::F10,
+			KeyboardKey::F11 => Scancode::F11,
+			KeyboardKey::F12 => Scancode::F12,
+			KeyboardKey::PrintScreen => Scancode::PrintScreen,
+			KeyboardKey::ScrollLock => Scancode::ScrollLock,
+			KeyboardKey::Pause => Scancode::Pause,
+			KeyboardKey::Insert => Scancode::Insert,
+			KeyboardKey::Home => Scancode::Home,
+			KeyboardKey::PageUp => Scancode::PageUp,
+			KeyboardKey::Delete => Scancode::Delete,
+			KeyboardKey::End => Scancode::End,
+			KeyboardKey::PageDown => Scancode::PageDown,
+			KeyboardKey::Right => Scancode::Right,
+			KeyboardKey::Left => Scancode::Left,
+			KeyboardKey::Down => Scancode::Down,
+			KeyboardKey::Up => Scancode::Up,
+			KeyboardKey::NumLockClear => Scancode::NumLockClear,
+			KeyboardKey::KpDivide => Scancode::KpDivide,
+			KeyboardKey::KpMultiply => Scancode::KpMultiply,
+			KeyboardKey::KpMinus => Scancode::KpMinus,
+			KeyboardKey::KpPlus => Scancode::KpPlus,
+			KeyboardKey::KpEnter => Scancode::KpEnter,
+			KeyboardKey::Kp1 => Scancode::Kp1,
+			KeyboardKey::Kp2 => Scancode::Kp2,
+			KeyboardKey::Kp3 => Scancode::Kp3,
+			KeyboardKey::Kp4 => Scancode::Kp4,
+			KeyboardKey::Kp5 => Scancode::Kp5,
+			KeyboardKey::Kp6 => Scancode::Kp6,
+			KeyboardKey::Kp7 => Scancode::Kp7,
+			KeyboardKey::Kp8 => Scancode::Kp8,
+			KeyboardKey::Kp9 => Scancode::Kp9,
+			KeyboardKey::Kp0 => Scancode::Kp0,
+			KeyboardKey::KpPeriod => Scancode::KpPeriod,
+			KeyboardKey::NonUsBackslash => Scancode::NonUsBackslash,
+			KeyboardKey::Application => Scancode::Application,
+			KeyboardKey::Power => Scancode::Power,
+			KeyboardKey::KpEquals => Scancode::KpEquals,
+			KeyboardKey::F13 => Scancode::F13,
+			KeyboardKey::F14 => Scancode::F14,
+			KeyboardKey::F15 => Scancode::F15,
+			KeyboardKey::F16 => Scancode::F16,
+			KeyboardKey::F17 => Scancode::F17,
+			KeyboardKey::F18 => Scancode::F18,
+			KeyboardKey::F19 => Scancode::F19,
+			KeyboardKey::F20 => Scancode::F20,
+			KeyboardKey::F21 => Scancode::F21,
+			KeyboardKey::F22 => Scancode::F22,
+			KeyboardKey::F23 => Scancode::F23,
+			KeyboardKey::F24 => Scancode::F24,
+			KeyboardKey::Execute => Scancode::Execute,
+			KeyboardKey::Help => Scancode::Help,
+			KeyboardKey::Menu => Scancode::Menu,
+			KeyboardKey::Select => Scancode::Select,
+			KeyboardKey::Stop => Scancode::Stop,
+			KeyboardKey::Again => Scancode::Again,
+			KeyboardKey::Undo => Scancode::Undo,
+			KeyboardKey::Cut => Scancode::Cut,
+			KeyboardKey::Copy => Scancode::Copy,
+			KeyboardKey::Paste => Scancode::Paste,
+			KeyboardKey::Find => Scancode::Find,
+			KeyboardKey::Mute => Scancode::Mute,
+			KeyboardKey::VolumeUp => Scancode::VolumeUp,
+			KeyboardKey::VolumeDown => Scancode::VolumeDown,
+			KeyboardKey::KpComma => Scancode::KpComma,
+			KeyboardKey::KpEqualsAs400 => Scancode::KpEqualsAs400,
+			KeyboardKey::International1 => Scancode::International1,
+			KeyboardKey::International2 => Scancode::International2,
+			KeyboardKey::International3 => Scancode::International3,
+			KeyboardKey::International4 => Scancode::International4,
+			KeyboardKey::International5 => Scancode::International5,
+			KeyboardKey::International6 => Scancode::International6,
+			KeyboardKey::International7 => Scancode::International7,
+			KeyboardKey::International8 => Scancode::International8,
+			KeyboardKey::International9 => Scancode::International9,
+			KeyboardKey::Lang1 => Scancode::Lang1,
+			KeyboardKey::Lang2 => Scancode::Lang2,
+			KeyboardKey::Lang3 => Scancode::Lang3,
+			KeyboardKey::Lang4 => Scancode::Lang4,
+			KeyboardKey::Lang5 => Scancode::Lang5,
+			KeyboardKey::Lang6 => Scancode::Lang6,
+			KeyboardKey::Lang7 => Scancode::Lang7,
+			KeyboardKey::Lang8 => Scancode::Lang8,
+			KeyboardKey::Lang9 => Scancode::Lang9,
+			KeyboardKey::AltErase => Scancode::AltErase,
+			KeyboardKey::SysReq => Scancode::SysReq,
+			KeyboardKey::Cancel => Scancode::Cancel,
+			KeyboardKey::Clear => Scancode::Clear,
+			KeyboardKey::Prior => Scancode::Prior,
+			KeyboardKey::Return2 => Scancode::Return2,
+			KeyboardKey::Separator => Scancode::Separator,
+			KeyboardKey::Out => Scancode::Out,
+			KeyboardKey::Oper => Scancode::Oper,
+			KeyboardKey::ClearAgain => Scancode::ClearAgain,
+			KeyboardKey::CrSel => Scancode::CrSel,
+			KeyboardKey::ExSel => Scancode::ExSel,
+			KeyboardKey::Kp00 => Scancode::Kp00,
+			KeyboardKey::Kp000 => Scancode::Kp000,
+			KeyboardKey::ThousandsSeparator => Scancode::ThousandsSeparator,
+			KeyboardKey::DecimalSeparator => Scancode::DecimalSeparator,
+			KeyboardKey::CurrencyUnit => Scancode::CurrencyUnit,
+			KeyboardKey::CurrencySubunit => Scancode::CurrencySubunit,
+			KeyboardKey::KpLeftParen => Scancode::KpLeftParen,
+			KeyboardKey::KpRightParen => Scancode::KpRightParen,
+			KeyboardKey::KpLeftBrace => Scancode::KpLeftBrace,
+			KeyboardKey::KpRightBrace => Scancode::KpRightBrace,
+			KeyboardKey::KpTab => Scancode::KpTab,
+			KeyboardKey::KpBackspace => Scancode::KpBackspace,
+			KeyboardKey::KpA => Scancode::KpA,
+			KeyboardKey::KpB => Scancode::KpB,
+			KeyboardKey::KpC => Scancode::KpC,
+			KeyboardKey::KpD => Scancode::KpD,
+			KeyboardKey::KpE => Scancode::KpE,
+			KeyboardKey::KpF => Scancode::KpF,
+			KeyboardKey::KpXor => Scancode::KpXor,
+			KeyboardKey::KpPower => Scancode::KpPower,
+			KeyboardKey::KpPercent => Scancode::KpPercent,
+			KeyboardKey::KpLess => Scancode::KpLess,
+			KeyboardKey::KpGreater => Scancode::KpGreater,
+			KeyboardKey::KpAmpersand => Scancode::KpAmpersand,
+			KeyboardKey::KpDblAmpersand => Scancode::KpDblAmpersand,
+			KeyboardKey::KpVerticalBar => Scancode::KpVerticalBar,
+			KeyboardKey::KpDblVerticalBar => Scancode::KpDblVerticalBar,
+			KeyboardKey::KpColon => Scancode::KpColon,
+			KeyboardKey::KpHash => Scancode::KpHash,
+			KeyboardKey::KpSpace => Scancode::KpSpace,
+			KeyboardKey::KpAt => Scancode::KpAt,
+			KeyboardKey::KpExclam => Scancode::KpExclam,
+			KeyboardKey::KpMemStore => Scancode::KpMemStore,
+			KeyboardKey::KpMemRecall => Scancode::KpMemRecall,
+			KeyboardKey::KpMemClear => Scancode::KpMemClear,
+			KeyboardKey::KpMemAdd => Scancode::KpMemAdd,
+			KeyboardKey::KpMemSubtract => Scancode::KpMemSubtract,
+			KeyboardKey::KpMemMultiply => Scancode::KpMemMultiply,
+			KeyboardKey::KpMemDivide => Scancode::KpMemDivide,
+			KeyboardKey::KpPlusMinus => Scancode::KpPlusMinus,
+			KeyboardKey::KpClear => Scancode::KpClear,
+			KeyboardKey::KpClearEntry => Scancode::KpClearEntry,
+			KeyboardKey::KpBinary => Scancode::KpBinary,
+			KeyboardKey::KpOctal => Scancode::KpOctal,
+			KeyboardKey::KpDecimal => Scancode::KpDecimal,
+			KeyboardKey::KpHexadecimal => Scancode::KpHexadecimal,
+			KeyboardKey::LCtrl => Scancode::LCtrl,
+			KeyboardKey::LShift => Scancode::LShift,
+			KeyboardKey::LAlt => Scancode::LAlt,
+			KeyboardKey::LGui => Scancode::LGui,
+			KeyboardKey::RCtrl => Scancode::RCtrl,
+			KeyboardKey::RShift => Scancode::RShift,
+			KeyboardKey::RAlt => Scancode::RAlt,
+			KeyboardKey::RGui => Scancode::RGui,
+			KeyboardKey::Mode => Scancode::Mode,
+			KeyboardKey::Sleep => Scancode::Sleep,
+			KeyboardKey::Wake => Scancode::Wake,
+			KeyboardKey::ChannelIncrement => Scancode::ChannelIncrement,
+			KeyboardKey::ChannelDecrement => Scancode::ChannelDecrement,
+			KeyboardKey::MediaPlay => Scancode::MediaPlay,
+			KeyboardKey::MediaPause => Scancode::MediaPause,
+			KeyboardKey::MediaRecord => Scancode::MediaRecord,
+			KeyboardKey::MediaFastForward => Scancode::MediaFastForward,
+			KeyboardKey::MediaRewind => Scancode::MediaRewind,
+			KeyboardKey::MediaNextTrack => Scancode::MediaNextTrack,
+			KeyboardKey::MediaPreviousTrack => Scancode::MediaPreviousTrack,
+			KeyboardKey::MediaStop => Scancode::MediaStop,
+			KeyboardKey::MediaEject => Scancode::MediaEject,
+			KeyboardKey::MediaPlayPause => Scancode::MediaPlayPause,
+			KeyboardKey::MediaSelect => Scancode::MediaSelect,
+			KeyboardKey::AcNew => Scancode::AcNew,
+			KeyboardKey::AcOpen => Scancode::AcOpen,
+			KeyboardKey::AcClose => Scancode::AcClose,
+			KeyboardKey::AcExit => Scancode::AcExit,
+			KeyboardKey::AcSave => Scancode::AcSave,
+			KeyboardKey::AcPrint => Scancode::AcPrint,
+			KeyboardKey::AcProperties => Scancode::AcProperties,
+			KeyboardKey::AcSearch => Scancode::AcSearch,
+			KeyboardKey::AcHome => Scancode::AcHome,
+			KeyboardKey::AcBack => Scancode::AcBack,
+			KeyboardKey::AcForward => Scancode::AcForward,
+			KeyboardKey::AcStop => Scancode::AcStop,
+			KeyboardKey::AcRefresh => Scancode::AcRefresh,
+			KeyboardKey::AcBookmarks => Scancode::AcBookmarks,
+		}
+	}
+
 	fn from_sdl(scancode: Scancode) -> Option<Self> {
 		match scancode {
 			Scancode::A => Some(KeyboardKey::A),
@@ -761,7 +1546,24 @@ impl KeyboardKey {
 	}
 }
 
+/// Modifier mask of key events, where left and right keys are not distinguished:
+///
+/// `1` Shift, `2` Ctrl, `4` Alt, `8` GUI, `16` Num Lock, `32` Caps Lock
+///
+/// The states are of the moment of the event, so the modifier key itself is included on its press.
+fn key_modifiers(keymod: Mod) -> u8 {
+	[
+		Mod::LSHIFTMOD | Mod::RSHIFTMOD,
+		Mod::LCTRLMOD | Mod::RCTRLMOD,
+		Mod::LALTMOD | Mod::RALTMOD,
+		Mod::LGUIMOD | Mod::RGUIMOD,
+		Mod::NUMMOD,
+		Mod::CAPSMOD,
+	].into_iter().enumerate().fold(0, |mask, (i, v)| if keymod.intersects(v) { mask | 1 << i } else { mask })
+}
+
 /// This list is made and filtered according to SDL 3 documentation of `SDL_MouseButtonFlags`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum MouseKey {
 	// Unknown is skipped.
 	Left,
@@ -772,6 +1574,17 @@ pub(crate) enum MouseKey {
 }
 
 impl MouseKey {
+	pub(crate) fn from_ordinal(ordinal: u8) -> Option<Self> {
+		match ordinal {
+			0 => Some(MouseKey::Left),
+			1 => Some(MouseKey::Middle),
+			2 => Some(MouseKey::Right),
+			3 => Some(MouseKey::X1),
+			4 => Some(MouseKey::X2),
+			_ => None,
+		}
+	}
+
 	fn from_sdl(mouse_button: MouseButton) -> Option<Self> {
 		match mouse_button {
 			MouseButton::Left => Some(MouseKey::Left),
@@ -0,0 +1,162 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Packing of multiple images into a single texture.
+//!
+//! Images are packed in shelves sorted by height, which works well for sprites of similar sizes.
+
+use crate::mui::rendering::{decode_image, read_image, track_texture_source, upload_texture, TextureOptions, TextureSource};
+use crate::mui::window::WindowHandle;
+use crate::FerriciaResult;
+use image::imageops::replace;
+use image::RgbaImage;
+
+/// Transparent gap between images to avoid bleeding by filtering and mipmaps.
+const PADDING: u32 = 1;
+
+pub(crate) struct TextureAtlasBuilder {
+	images: Vec<RgbaImage>,
+	/// `[u0, v0, u1, v1]` of each image by index of the last build, with (0, 0) as bottom-left
+	uvs: Vec<[f32; 4]>,
+}
+
+impl TextureAtlasBuilder {
+	pub(crate) fn new() -> Self {
+		Self { images: Vec::new(), uvs: Vec::new() }
+	}
+
+	/// Returns the index of the image.
+	pub(crate) fn add_path(&mut self, path: String) -> FerriciaResult<usize> {
+		self.images.push(read_image(path)?);
+		Ok(self.images.len() - 1)
+	}
+
+	/// Returns the index of the image.
+	pub(crate) fn add_bytes(&mut self, bytes: &[u8]) -> FerriciaResult<usize> {
+		self.images.push(decode_image(bytes)?);
+		Ok(self.images.len() - 1)
+	}
+
+	/// Uploads the packed texture and clears the images added.
+	///
+	/// The regions of images are available by [TextureAtlasBuilder::uvs] afterward.
+	pub(crate) fn build(&mut self, window_handle: &WindowHandle, options: &TextureOptions) -> FerriciaResult<u32> {
+		let images = std::mem::take(&mut self.images);
+		if images.is_empty() {
+			return Err("Texture atlas must not be empty".to_string().into());
+		}
+		let sizes = images.iter().map(|v| v.dimensions()).collect::<Vec<_>>();
+		let max_size = window_handle.gl_handle().max_texture_size() as u32;
+		let (positions, width, height) = layout(&sizes, max_size)?;
+
+		let mut atlas = RgbaImage::new(width, height);
+		let mut uvs = Vec::with_capacity(images.len());
+		for (img, (x, y)) in images.iter().zip(positions) {
+			replace(&mut atlas, img, x as _, y as _);
+			// The atlas is flipped vertically on upload.
+			uvs.push([
+				x as f32 / width as f32,
+				(height - y - img.height()) as f32 / height as f32,
+				(x + img.width()) as f32 / width as f32,
+				(height - y) as f32 / height as f32,
+			]);
+		}
+		self.uvs = uvs;
+		let texture = upload_texture(atlas.clone(), options);
+		track_texture_source(texture, TextureSource::Image(atlas), *options);
+		Ok(texture)
+	}
+
+	pub(crate) fn uvs(&self) -> &[[f32; 4]] {
+		&self.uvs
+	}
+}
+
+/// Top-left positions of images by index, and the width and height of the atlas
+type Layout = (Vec<(u32, u32)>, u32, u32);
+
+/// The size of the atlas is in powers of two, widened until at least as wide as high.
+fn layout(sizes: &[(u32, u32)], max_size: u32) -> FerriciaResult<Layout> {
+	let area = sizes.iter().map(|(w, h)| (w + PADDING) as u64 * (h + PADDING) as u64).sum::<u64>();
+	let widest = sizes.iter().map(|(w, _)| w + PADDING).max().unwrap_or(1);
+	let mut width = ((area as f64).sqrt().ceil() as u32).max(widest).next_power_of_two();
+	let (positions, height) = loop {
+		let (positions, height) = pack(sizes, width);
+		if height <= width || width >= max_size {
+			break (positions, height.next_power_of_two());
+		}
+		width *= 2;
+	};
+	if width > max_size || height > max_size {
+		return Err(format!("Texture atlas exceeds {max_size}x{max_size}").into());
+	}
+	Ok((positions, width, height))
+}
+
+/// Returns the top-left positions by index and the total height.
+fn pack(sizes: &[(u32, u32)], width: u32) -> (Vec<(u32, u32)>, u32) {
+	let mut order = (0..sizes.len()).collect::<Vec<_>>();
+	order.sort_by_key(|i| std::cmp::Reverse(sizes[*i].1));
+	let mut positions = vec![(0, 0); sizes.len()];
+	let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+	for i in order {
+		let (w, h) = (sizes[i].0 + PADDING, sizes[i].1 + PADDING);
+		if x + w > width {
+			x = 0;
+			y += shelf_height;
+			shelf_height = 0;
+		}
+		positions[i] = (x, y);
+		x += w;
+		shelf_height = shelf_height.max(h);
+	}
+	(positions, y + shelf_height)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn overlaps(a: ((u32, u32), (u32, u32)), b: ((u32, u32), (u32, u32))) -> bool {
+		let (((ax, ay), (aw, ah)), ((bx, by), (bw, bh))) = (a, b);
+		ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah
+	}
+
+	#[test]
+	fn packs_a_single_image_with_padding() {
+		let (positions, width, height) = layout(&[(16, 16)], 8192).unwrap();
+		assert_eq!(positions, [(0, 0)]);
+		assert_eq!((width, height), (32, 32));
+	}
+
+	#[test]
+	fn packs_images_apart_within_the_atlas() {
+		let sizes = [(16, 16), (32, 8), (8, 32), (16, 16), (64, 4), (5, 7), (16, 16), (30, 30)];
+		let (positions, width, height) = layout(&sizes, 8192).unwrap();
+		assert!(width.is_power_of_two() && height.is_power_of_two());
+		assert!(height <= width);
+		let rects = positions.iter().zip(sizes)
+			.map(|(&pos, (w, h))| (pos, (w + PADDING, h + PADDING)))
+			.collect::<Vec<_>>();
+		for (i, &((x, y), (w, h))) in rects.iter().enumerate() {
+			assert!(x + w <= width && y + h <= height);
+			assert!(rects[i + 1..].iter().all(|&other| !overlaps(rects[i], other)));
+		}
+	}
+
+	#[test]
+	fn widens_tall_layouts() {
+		let sizes = [(8, 100); 8];
+		let (_, width, height) = layout(&sizes, 8192).unwrap();
+		assert!(height <= width);
+	}
+
+	#[test]
+	fn rejects_layouts_beyond_the_maximum_size() {
+		assert!(layout(&[(64, 64)], 64).is_err());
+		assert!(layout(&[(63, 63)], 64).is_ok());
+		assert!(layout(&[(40, 40); 4], 64).is_err());
+	}
+}
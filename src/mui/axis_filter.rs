@@ -0,0 +1,164 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Dead zones, saturation and response curves of analog axes, applied before reporting the motion.
+//!
+//! The magnitude of an axis is zero within the dead zone, full beyond the saturation, and rescaled
+//! linearly in between before raising to the exponent of the curve, keeping the sign. Responses are
+//! set per axis for all devices, by the axis of gamepads or by the index for raw joysticks.
+//!
+//! Axes without a response are reported as is. For those with one, motion not changing the filtered
+//! value is dropped, so that drifting sticks resting within the dead zone make no events.
+//! The action map is still given the raw values, as its bindings have their own dead zones.
+
+use crate::mui::GamepadAxis;
+use crate::FerriciaResult;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy)]
+pub(crate) struct AxisResponse {
+	/// Magnitude in `[0, 1)` below which the axis is at rest
+	dead_zone: f32,
+	/// Magnitude in `(dead_zone, 1]` beyond which the axis is fully pushed
+	saturation: f32,
+	/// `1` for linear, with higher values for finer control near the center
+	exponent: f32,
+}
+
+impl AxisResponse {
+	pub(crate) fn new(dead_zone: f32, saturation: f32, exponent: f32) -> FerriciaResult<Self> {
+		if !(0.0 <= dead_zone && dead_zone < saturation && saturation <= 1.0) {
+			return Err(format!("Invalid dead zone {dead_zone} and saturation {saturation}").into());
+		}
+		if exponent.is_nan() || exponent <= 0.0 {
+			return Err(format!("Invalid response exponent: {exponent}").into());
+		}
+		Ok(Self { dead_zone, saturation, exponent })
+	}
+
+	fn apply(&self, value: i16) -> i16 {
+		let value = (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+		let magnitude = ((value.abs() - self.dead_zone) / (self.saturation - self.dead_zone)).clamp(0.0, 1.0);
+		(magnitude.powf(self.exponent).copysign(value) * i16::MAX as f32).round() as i16
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum AxisKey {
+	Gamepad(GamepadAxis),
+	Joystick(u8),
+}
+
+#[derive(Default)]
+pub(super) struct AxisFilter {
+	responses: HashMap<AxisKey, AxisResponse>,
+	/// Last reported values of the filtered axes by device
+	last: HashMap<(u32, AxisKey), i16>,
+}
+
+impl AxisFilter {
+	/// `None` removes the response, reporting the axis as is.
+	pub(super) fn set_gamepad_response(&mut self, axis: GamepadAxis, response: Option<AxisResponse>) {
+		self.set_response(AxisKey::Gamepad(axis), response);
+	}
+
+	pub(super) fn set_joystick_response(&mut self, axis: u8, response: Option<AxisResponse>) {
+		self.set_response(AxisKey::Joystick(axis), response);
+	}
+
+	fn set_response(&mut self, key: AxisKey, response: Option<AxisResponse>) {
+		match response {
+			Some(v) => self.responses.insert(key, v),
+			None => self.responses.remove(&key),
+		};
+		self.last.retain(|(_, k), _| *k != key);
+	}
+
+	pub(super) fn clear(&mut self) {
+		self.responses.clear();
+		self.last.clear();
+	}
+
+	/// Returns the value to report; `None` if unchanged since the last report.
+	pub(super) fn gamepad_axis(&mut self, which: u32, axis: GamepadAxis, value: i16) -> Option<i16> {
+		self.filter(which, AxisKey::Gamepad(axis), value)
+	}
+
+	pub(super) fn joystick_axis(&mut self, which: u32, axis: u8, value: i16) -> Option<i16> {
+		self.filter(which, AxisKey::Joystick(axis), value)
+	}
+
+	fn filter(&mut self, which: u32, key: AxisKey, value: i16) -> Option<i16> {
+		let Some(response) = self.responses.get(&key) else { return Some(value) };
+		let value = response.apply(value);
+		(self.last.insert((which, key), value) != Some(value)).then_some(value)
+	}
+
+	/// Forgets the last values of the device.
+	pub(super) fn device_removed(&mut self, which: u32) {
+		self.last.retain(|(w, _), _| *w != which);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MAX: i16 = i16::MAX;
+
+	fn at(magnitude: f32) -> i16 {
+		(magnitude * MAX as f32).round() as i16
+	}
+
+	#[test]
+	fn rejects_invalid_responses() {
+		assert!(AxisResponse::new(0.5, 0.4, 1.0).is_err());
+		assert!(AxisResponse::new(-0.1, 0.9, 1.0).is_err());
+		assert!(AxisResponse::new(0.1, 1.1, 1.0).is_err());
+		assert!(AxisResponse::new(0.1, 0.9, 0.0).is_err());
+		assert!(AxisResponse::new(0.1, 0.9, f32::NAN).is_err());
+		assert!(AxisResponse::new(0.1, 0.9, 2.0).is_ok());
+	}
+
+	#[test]
+	fn applies_dead_zones_saturation_and_curves() {
+		let linear = AxisResponse::new(0.2, 0.8, 1.0).unwrap();
+		assert_eq!(linear.apply(at(0.1)), 0);
+		assert_eq!(linear.apply(at(0.9)), MAX);
+		assert_eq!(linear.apply(i16::MIN), -MAX);
+		assert!((linear.apply(at(0.5)) - at(0.5)).abs() <= 1);
+		assert!((linear.apply(at(-0.5)) + at(0.5)).abs() <= 1);
+		let curved = AxisResponse::new(0.2, 0.8, 2.0).unwrap();
+		assert!((curved.apply(at(0.5)) - at(0.25)).abs() <= 1);
+	}
+
+	#[test]
+	fn drops_unchanged_filtered_values() {
+		let mut filter = AxisFilter::default();
+		filter.set_gamepad_response(GamepadAxis::LeftX, Some(AxisResponse::new(0.2, 1.0, 1.0).unwrap()));
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, 1000), Some(0));
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, 2000), None);
+		// Tracked per device
+		assert_eq!(filter.gamepad_axis(1, GamepadAxis::LeftX, 1000), Some(0));
+		filter.device_removed(0);
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, 1000), Some(0));
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, MAX), Some(MAX));
+	}
+
+	#[test]
+	fn reports_axes_without_responses_as_is() {
+		let mut filter = AxisFilter::default();
+		filter.set_joystick_response(0, Some(AxisResponse::new(0.2, 1.0, 1.0).unwrap()));
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, 1000), Some(1000));
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, 1000), Some(1000));
+		assert_eq!(filter.joystick_axis(0, 1, 1000), Some(1000));
+		assert_eq!(filter.joystick_axis(0, 0, 1000), Some(0));
+		filter.set_joystick_response(0, None);
+		assert_eq!(filter.joystick_axis(0, 0, 1000), Some(1000));
+		filter.set_gamepad_response(GamepadAxis::LeftX, Some(AxisResponse::new(0.2, 1.0, 1.0).unwrap()));
+		filter.clear();
+		assert_eq!(filter.gamepad_axis(0, GamepadAxis::LeftX, 1000), Some(1000));
+	}
+}
@@ -0,0 +1,173 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Rendering backend of the canvas, programs and primitives of [rendering](crate::mui::rendering).
+//!
+//! Those only reach the graphics API through [RenderBackend], of which [Backend] is the one in use,
+//! so that another API may be added without changing the Java API. OpenGL of [ogl](crate::mui::ogl)
+//! is the only backend; compute shaders, compressed textures, offscreen targets and timer queries
+//! still use it directly.
+//!
+//! Objects are identified by the names of the backend, and the state belongs to the current context
+//! of the thread, so the functions take no receiver.
+
+use crate::mui::rendering::TextureOptions;
+use nalgebra_glm::{TMat4, TVec2, TVec4};
+
+pub(super) use crate::mui::ogl::{GlBackend, NumType, Number, ShaderType, VertexAttrVariant};
+
+/// The backend in use
+pub(super) type Backend = GlBackend;
+
+/// Handle of the context of the backend in use, for querying the capabilities
+pub(super) type Context = <Backend as RenderBackend>::Context;
+
+/// How vertices are assembled into primitives by draws.
+#[derive(Clone, Copy)]
+pub(super) enum Topology {
+	Lines,
+	Points,
+	Triangles,
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum BufferTarget {
+	Vertex,
+	/// Element indices, kept in the vertex array bound at the time
+	Index,
+}
+
+/// Expected frequency of updates of the data store of a buffer
+#[derive(Clone, Copy)]
+pub(super) enum BufferUsage {
+	Static,
+	Dynamic,
+}
+
+/// Counts and estimated sizes of the live objects.
+///
+/// Sizes are estimated from the uploaded data, as the memory actually used by the driver
+/// cannot be queried portably.
+pub(crate) struct RenderStats {
+	pub(crate) textures: usize,
+	pub(crate) texture_bytes: usize,
+	pub(crate) buffers: usize,
+	pub(crate) buffer_bytes: usize,
+	pub(crate) vaos: usize,
+	pub(crate) programs: usize,
+}
+
+pub(super) trait RenderBackend {
+	type Context;
+
+	fn gen_buf_obj() -> u32;
+
+	fn gen_buf_objs<const N: usize>() -> [u32; N];
+
+	/// Replaces the data store of the buffer.
+	fn buf_obj_with_data<T: Number>(target: BufferTarget, buffer: u32, data: &[T], usage: BufferUsage);
+
+	/// `offset` is in bytes.
+	fn update_buf_obj<T: Number>(target: BufferTarget, buffer: u32, offset: usize, data: &[T]);
+
+	/// Names which are zero are ignored.
+	fn delete_buf_objs(buffers: &[u32]);
+
+	/// Creates a uniform buffer of `size` bytes bound to the binding point.
+	fn gen_uniform_buf_obj(size: usize, binding: u32) -> u32;
+
+	/// `offset` is in bytes.
+	fn update_uniform_buf_obj<T: Number>(buffer: u32, offset: usize, data: &[T]);
+
+	/// Creates a vertex array, which stays bound for the following buffers and attributes.
+	fn with_new_vert_arr() -> u32;
+
+	/// Zero is ignored.
+	fn delete_vert_arr_obj(vao: u32);
+
+	fn use_vao(vao: u32);
+
+	/// Sources the attribute from the vertex buffer bound at the time; `stride_len` and `offset_len`
+	/// are in the number of values.
+	fn vert_attr_arr(i: u32, vec_size: usize, kind: NumType, stride_len: usize, offset_len: usize);
+
+	/// Sets a constant value of the attribute, used when it is not sourced from a buffer.
+	fn vert_attr(i: u32, data: VertexAttrVariant);
+
+	fn draw_arrays(mode: Topology, count: u32);
+
+	/// The indices are of `u32` from the start of the index buffer.
+	fn draw_elements(mode: Topology, count: u32);
+
+	fn clear_canvas();
+
+	fn set_clear_color(color: (f32, f32, f32, f32));
+
+	/// Maps the canvas coordinates to the region `(x, y, width, height)` in pixels of the window.
+	fn set_viewport(rect: (i32, i32, u32, u32));
+
+	/// Sets the viewport and clips all the following draws and clears to the region.
+	fn use_viewport_scissor(rect: (i32, i32, u32, u32));
+
+	fn disable_scissor();
+
+	fn gen_texture() -> u32;
+
+	/// Replaces the whole content of the texture with RGBA pixels in rows from the bottom.
+	fn fill_texture_2d(texture: u32, size: (u32, u32), data: &[u8], options: &TextureOptions);
+
+	/// Replaces the whole content of the texture array with RGBA pixels of the layers in order.
+	fn fill_texture_2d_array(texture: u32, size: (u32, u32), layers: u32, data: &[u8], options: &TextureOptions);
+
+	/// Creates a single-channel unsigned integer texture, sampled only by `texelFetch`.
+	fn gen_uint_texture(size: (u32, u32), data: &[u32]) -> u32;
+
+	/// Creates an RGBA float texture, sampled only by `texelFetch`.
+	fn gen_vec_4_texture(size: (u32, u32), data: &[f32]) -> u32;
+
+	fn update_uint_texel(texture: u32, x: u32, y: u32, value: u32);
+
+	fn delete_texture(texture: u32);
+
+	fn use_texture_2d(texture: u32);
+
+	fn use_texture_2d_array(texture: u32);
+
+	/// Binds the texture to the texture unit, such as for a sampler other than the first one.
+	fn use_texture_2d_at(unit: u32, texture: u32);
+
+	/// `name` identifies the source in errors; the shader is deleted if the compilation fails.
+	fn compile_shader(src: String, kind: ShaderType, name: &str) -> Result<u32, String>;
+
+	fn delete_shader(shader: u32);
+
+	/// Links the shaders, which are deleted afterward, with the attributes at the locations in order.
+	fn new_shader_program(shaders: &[u32], attributes: &[&str]) -> Result<u32, String>;
+
+	/// Zero is ignored.
+	fn delete_program(program: u32);
+
+	fn use_program(program: u32);
+
+	/// `u32::MAX` if the program has no such active uniform.
+	fn get_uniform_location(program: u32, name: &str) -> u32;
+
+	/// Returns whether the program declares the block.
+	fn bind_uniform_block(program: u32, name: &str, binding: u32) -> bool;
+
+	fn use_uniform_mat_4(i: u32, mat: &TMat4<f32>);
+
+	fn use_uniform_vec_4(i: u32, vec: &TVec4<f32>);
+
+	fn use_uniform_vec_2(i: u32, vec: &TVec2<f32>);
+
+	fn use_uniform_uint(i: u32, value: u32);
+
+	fn use_uniform_float(i: u32, value: f32);
+
+	fn use_uniform_int(i: u32, value: i32);
+
+	fn render_stats() -> RenderStats;
+}
@@ -0,0 +1,58 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Draw commands recorded over a frame and replayed at once on the GL thread.
+//!
+//! Recording only stores the handles of each draw without touching GL, so the JNI calls of a frame
+//! are cheap, and recording may move to another thread than rendering later. Handles are resolved
+//! on replay, so a handle dropped after being recorded fails the replay instead of dangling.
+//!
+//! Replaying skips the state changes that consecutive draws share, such as binding the same VAO
+//! or texture again, in addition to the program tracking of the canvas.
+
+pub(crate) enum RenderCommand {
+	Clear,
+	/// Draws the drawable set with the program; `texture` is `None` for geometries.
+	DrawGui { drawable: i64, program: i64, texture: Option<u32> },
+	BeginViewport(usize),
+	EndViewport,
+}
+
+/// Commands of the frame being recorded, in the order of recording.
+#[derive(Default)]
+pub(crate) struct CommandList {
+	commands: Vec<RenderCommand>,
+}
+
+impl CommandList {
+	pub(crate) fn push(&mut self, command: RenderCommand) {
+		self.commands.push(command);
+	}
+
+	/// Takes the recorded commands, leaving the list empty for the next frame.
+	pub(crate) fn take(&mut self) -> Vec<RenderCommand> {
+		std::mem::take(&mut self.commands)
+	}
+}
+
+/// Bindings made by previous draws of the same replay or batch.
+#[derive(Default)]
+pub(crate) struct ReplayState {
+	vao: Option<u32>,
+	/// Texture with the program binding it, as programs bind to different targets
+	texture: Option<(u32, u32)>,
+}
+
+impl ReplayState {
+	/// Returns whether the VAO needs binding, recording it as bound.
+	pub(crate) fn bind_vao(&mut self, vao: u32) -> bool {
+		self.vao.replace(vao) != Some(vao)
+	}
+
+	/// Returns whether the texture needs binding for the program, recording it as bound.
+	pub(crate) fn bind_texture(&mut self, program: u32, texture: u32) -> bool {
+		self.texture.replace((program, texture)) != Some((program, texture))
+	}
+}
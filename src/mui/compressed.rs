@@ -0,0 +1,292 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Loading of block-compressed textures from DDS and KTX2 containers.
+//!
+//! The blocks are uploaded as is when the format is supported by the GPU, with all the mipmap
+//! levels in the container. Otherwise, only the base level is decoded on the CPU, which is
+//! available for the S3TC formats but not BC7, as BPTC is core since GL 4.2 anyway.
+//!
+//! Blocks cannot be flipped cheaply, so unlike other images, the first row of the data is the
+//! bottom of the texture. Compressed textures should thus be exported with a bottom-left origin,
+//! such as by `toktx --lower_left_maps_to_s0t0`. sRGB formats are treated as linear ones,
+//! consistent with the other images.
+
+use crate::mui::ogl::{set_texture_params, track_texture, GLHandle};
+use crate::mui::rendering::{upload_texture, TextureOptions};
+use crate::FerriciaResult;
+use gl::{BindTexture, CompressedTexImage2D, GenTextures, TexParameteri, COMPRESSED_RGBA_BPTC_UNORM, TEXTURE_2D, TEXTURE_MAX_LEVEL};
+use image::imageops::flip_vertical_in_place;
+use image::{Rgba, RgbaImage};
+use std::mem::MaybeUninit;
+
+// From GL_EXT_texture_compression_s3tc, which is not included in the core bindings.
+const COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3_EXT: u32 = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n'];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BlockFormat {
+	/// DXT1, with 1-bit alpha
+	Bc1,
+	/// DXT3, with explicit 4-bit alpha
+	Bc2,
+	/// DXT5, with interpolated alpha
+	Bc3,
+	Bc7,
+}
+
+impl BlockFormat {
+	/// Size of each 4x4 block in bytes
+	fn block_size(self) -> usize {
+		match self {
+			BlockFormat::Bc1 => 8,
+			_ => 16,
+		}
+	}
+
+	fn gl_format(self) -> u32 {
+		match self {
+			BlockFormat::Bc1 => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+			BlockFormat::Bc2 => COMPRESSED_RGBA_S3TC_DXT3_EXT,
+			BlockFormat::Bc3 => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+			BlockFormat::Bc7 => COMPRESSED_RGBA_BPTC_UNORM,
+		}
+	}
+
+	fn supported(self, gl_handle: &GLHandle) -> bool {
+		match self {
+			BlockFormat::Bc7 => gl_handle.bptc_supported(),
+			_ => gl_handle.s3tc_supported(),
+		}
+	}
+
+	fn level_size(self, width: u32, height: u32) -> usize {
+		width.div_ceil(4) as usize * height.div_ceil(4) as usize * self.block_size()
+	}
+}
+
+struct CompressedImage<'a> {
+	format: BlockFormat,
+	width: u32,
+	height: u32,
+	/// Data of mipmap levels, starting from the base level
+	levels: Vec<&'a [u8]>,
+}
+
+impl CompressedImage<'_> {
+	fn level_dimensions(&self, level: usize) -> (u32, u32) {
+		((self.width >> level).max(1), (self.height >> level).max(1))
+	}
+}
+
+fn truncated() -> String {
+	"Compressed texture is truncated".to_string()
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+	bytes.get(offset..offset + 4)
+		.map(|v| u32::from_le_bytes(v.try_into().expect("should be 4 bytes")))
+		.ok_or_else(truncated)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, String> {
+	bytes.get(offset..offset + 8)
+		.map(|v| u64::from_le_bytes(v.try_into().expect("should be 8 bytes")))
+		.ok_or_else(truncated)
+}
+
+fn check_dimensions(width: u32, height: u32, levels: u32) -> Result<(), String> {
+	if width == 0 || height == 0 {
+		return Err("Compressed texture must not be empty".to_string());
+	}
+	if levels > u32::BITS - width.max(height).leading_zeros() {
+		return Err(format!("Too many mipmap levels: {levels}"));
+	}
+	Ok(())
+}
+
+/// Only the first surface is loaded for texture arrays and cube maps.
+fn parse_dds(bytes: &[u8]) -> Result<CompressedImage<'_>, String> {
+	let height = read_u32(bytes, 12)?;
+	let width = read_u32(bytes, 16)?;
+	let level_count = read_u32(bytes, 28)?.max(1);
+	check_dimensions(width, height, level_count)?;
+	let (format, mut offset) = match bytes.get(84..88) {
+		Some(b"DXT1") => (BlockFormat::Bc1, 128),
+		Some(b"DXT3") => (BlockFormat::Bc2, 128),
+		Some(b"DXT5") => (BlockFormat::Bc3, 128),
+		Some(b"DX10") => (match read_u32(bytes, 128)? {
+			71 | 72 => BlockFormat::Bc1,
+			74 | 75 => BlockFormat::Bc2,
+			77 | 78 => BlockFormat::Bc3,
+			98 | 99 => BlockFormat::Bc7,
+			v => return Err(format!("Unsupported DXGI format: {v}")),
+		}, 148),
+		_ => return Err("Unsupported DDS pixel format".to_string()),
+	};
+	let mut image = CompressedImage { format, width, height, levels: Vec::with_capacity(level_count as _) };
+	for level in 0..level_count as usize {
+		let (w, h) = image.level_dimensions(level);
+		let size = format.level_size(w, h);
+		image.levels.push(bytes.get(offset..offset + size).ok_or_else(truncated)?);
+		offset += size;
+	}
+	Ok(image)
+}
+
+/// Only the first layer or face is loaded for texture arrays and cube maps.
+fn parse_ktx2(bytes: &[u8]) -> Result<CompressedImage<'_>, String> {
+	let format = match read_u32(bytes, 12)? {
+		131..=134 => BlockFormat::Bc1,
+		135 | 136 => BlockFormat::Bc2,
+		137 | 138 => BlockFormat::Bc3,
+		145 | 146 => BlockFormat::Bc7,
+		v => return Err(format!("Unsupported Vulkan format: {v}")),
+	};
+	let width = read_u32(bytes, 20)?;
+	let height = read_u32(bytes, 24)?;
+	if read_u32(bytes, 28)? > 1 {
+		return Err("3D textures are not supported".to_string());
+	}
+	let level_count = read_u32(bytes, 40)?.max(1);
+	if read_u32(bytes, 44)? != 0 {
+		return Err("Supercompressed KTX2 textures are not supported".to_string());
+	}
+	check_dimensions(width, height, level_count)?;
+	let mut image = CompressedImage { format, width, height, levels: Vec::with_capacity(level_count as _) };
+	for level in 0..level_count as usize {
+		let offset = read_u64(bytes, 80 + level * 24)? as usize;
+		let (w, h) = image.level_dimensions(level);
+		let size = format.level_size(w, h);
+		image.levels.push(bytes.get(offset..offset + size).ok_or_else(truncated)?);
+	}
+	Ok(image)
+}
+
+/// Decodes the base level of the S3TC formats; `None` for the other formats.
+fn decode_s3tc(image: &CompressedImage) -> Option<RgbaImage> {
+	if image.format == BlockFormat::Bc7 {
+		return None;
+	}
+	let block_size = image.format.block_size();
+	let blocks_x = image.width.div_ceil(4);
+	let mut img = RgbaImage::new(image.width, image.height);
+	for (i, block) in image.levels[0].chunks_exact(block_size).enumerate() {
+		let mut pixels = [[0; 4]; 16];
+		decode_color_block(&block[block_size - 8..], image.format == BlockFormat::Bc1, &mut pixels);
+		match image.format {
+			BlockFormat::Bc2 => {
+				let bits = u64::from_le_bytes(block[..8].try_into().expect("should be 8 bytes"));
+				for (j, p) in pixels.iter_mut().enumerate() {
+					p[3] = ((bits >> (j * 4)) & 0xF) as u8 * 17;
+				}
+			}
+			BlockFormat::Bc3 => decode_alpha_block(&block[..8], &mut pixels),
+			_ => {}
+		}
+		let (x, y) = (i as u32 % blocks_x * 4, i as u32 / blocks_x * 4);
+		for (j, p) in pixels.iter().enumerate() {
+			let (px, py) = (x + j as u32 % 4, y + j as u32 / 4);
+			if px < image.width && py < image.height {
+				img.put_pixel(px, py, Rgba(*p));
+			}
+		}
+	}
+	Some(img)
+}
+
+/// The 3-color mode with transparent black is only available for BC1.
+fn decode_color_block(block: &[u8], bc1: bool, pixels: &mut [[u8; 4]; 16]) {
+	let c0 = u16::from_le_bytes([block[0], block[1]]);
+	let c1 = u16::from_le_bytes([block[2], block[3]]);
+	let rgb = |c: u16| {
+		let (r, g, b) = ((c >> 11) & 0x1F, (c >> 5) & 0x3F, c & 0x1F);
+		[r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2]
+	};
+	let (a, b) = (rgb(c0), rgb(c1));
+	let mix = |wa: u16, wb: u16| {
+		let f = |i: usize| ((a[i] * wa + b[i] * wb) / (wa + wb)) as u8;
+		[f(0), f(1), f(2), 255]
+	};
+	let palette = if c0 > c1 || !bc1 {
+		[mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+	} else {
+		[mix(1, 0), mix(0, 1), mix(1, 1), [0; 4]]
+	};
+	let indices = u32::from_le_bytes(block[4..8].try_into().expect("should be 4 bytes"));
+	for (i, p) in pixels.iter_mut().enumerate() {
+		*p = palette[((indices >> (i * 2)) & 3) as usize];
+	}
+}
+
+fn decode_alpha_block(block: &[u8], pixels: &mut [[u8; 4]; 16]) {
+	let (a0, a1) = (block[0] as u32, block[1] as u32);
+	let mut palette = [a0 as u8, a1 as u8, 0, 0, 0, 0, 0, 255];
+	if a0 > a1 {
+		for i in 1..7 {
+			palette[i as usize + 1] = ((a0 * (7 - i) + a1 * i) / 7) as u8;
+		}
+	} else {
+		for i in 1..5 {
+			palette[i as usize + 1] = ((a0 * (5 - i) + a1 * i) / 5) as u8;
+		}
+	}
+	let bits = block[2..8].iter().rev().fold(0u64, |acc, v| acc << 8 | *v as u64);
+	for (i, p) in pixels.iter_mut().enumerate() {
+		p[3] = palette[((bits >> (i * 3)) & 7) as usize];
+	}
+}
+
+/// Returns the new texture; the container is detected from the content.
+pub(super) fn upload_compressed_texture(
+	gl_handle: &GLHandle,
+	bytes: &[u8],
+	options: &TextureOptions,
+) -> FerriciaResult<u32> {
+	let image = if bytes.starts_with(b"DDS ") {
+		parse_dds(bytes)?
+	} else if bytes.starts_with(&KTX2_IDENTIFIER) {
+		parse_ktx2(bytes)?
+	} else {
+		return Err("Unknown compressed texture container".to_string().into());
+	};
+
+	if !image.format.supported(gl_handle) {
+		let mut img = decode_s3tc(&image)
+			.ok_or_else(|| format!("{:?} textures are not supported by the GPU", image.format))?;
+		// Cancels the flip on upload to keep the same orientation as uploading the blocks.
+		flip_vertical_in_place(&mut img);
+		return Ok(upload_texture(img, options));
+	}
+
+	let mut id = MaybeUninit::uninit();
+	unsafe { GenTextures(1, id.as_mut_ptr()); }
+	let id = unsafe { id.assume_init() };
+	unsafe { BindTexture(TEXTURE_2D, id); }
+	// Mipmaps cannot be generated from the blocks, so only the levels in the container are used.
+	set_texture_params(TEXTURE_2D, &TextureOptions { mipmaps: options.mipmaps && image.levels.len() > 1, ..*options });
+	let max_level = options.levels.1.min(image.levels.len() as u32 - 1);
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MAX_LEVEL, max_level as _); }
+	for (level, data) in image.levels.iter().enumerate() {
+		let (width, height) = image.level_dimensions(level);
+		unsafe {
+			CompressedTexImage2D(
+				TEXTURE_2D,
+				level as _,
+				image.format.gl_format(),
+				width as _,
+				height as _,
+				0,
+				data.len() as _,
+				data.as_ptr() as *const _
+			);
+		}
+	}
+	track_texture(id, image.levels.iter().map(|v| v.len()).sum(), false);
+	Ok(id)
+}
@@ -0,0 +1,153 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Compute shaders on capable hardware, such as for lighting or particle simulation.
+//!
+//! Compute shaders and Shader Storage Buffer Objects require GL 4.3, or the extensions
+//! `GL_ARB_compute_shader` and `GL_ARB_shader_storage_buffer_object`, so callers should keep
+//! a CPU fallback and check [WindowHandle::compute_supported] first.
+//!
+//! Data is exchanged through [StorageBuffer]s bound to the binding points declared in shaders.
+//! Each dispatch is followed by a full memory barrier, so the results are visible to any
+//! following draw or read without further synchronization.
+
+use crate::mui::ogl::{bind_storage_buf_obj, delete_buf_objs, delete_program, dispatch_compute, gen_storage_buf_obj, read_buf_obj, update_buf_obj, use_program, ShaderType};
+use crate::mui::rendering::{link_stages, read_shader, CustomUniforms, ProgramSources, UniformValue, SOURCE_NAME};
+use crate::mui::window::WindowHandle;
+use crate::FerriciaResult;
+use gl::SHADER_STORAGE_BUFFER;
+
+fn check_supported(window_handle: &WindowHandle) -> FerriciaResult<()> {
+	if window_handle.compute_supported() {
+		Ok(())
+	} else {
+		Err("Compute shaders require GL 4.3".to_string().into())
+	}
+}
+
+pub(crate) struct ComputeProgram {
+	id: u32,
+	uniforms: CustomUniforms,
+	sources: ProgramSources,
+	/// Maximum numbers of work groups of a dispatch in each dimension
+	max_groups: [u32; 3],
+}
+
+impl ComputeProgram {
+	/// The shader is read from the file path.
+	pub(crate) fn new(window_handle: &WindowHandle, path: String) -> FerriciaResult<Self> {
+		check_supported(window_handle)?;
+		Self::link(window_handle, read_shader(&path)?, &path)
+	}
+
+	/// The shader is compiled from the GLSL source, such as one bundled in the game.
+	pub(crate) fn from_source(window_handle: &WindowHandle, src: String) -> FerriciaResult<Self> {
+		check_supported(window_handle)?;
+		Self::link(window_handle, src, SOURCE_NAME)
+	}
+
+	fn link(window_handle: &WindowHandle, src: String, name: &str) -> FerriciaResult<Self> {
+		let (id, sources) = link_stages([(ShaderType::Compute, src, name)], &[])?;
+		let max_groups = window_handle.gl_handle().max_compute_work_groups().map(|v| v as _);
+		Ok(Self { id, uniforms: CustomUniforms::default(), sources, max_groups })
+	}
+
+	/// Links the program again on a new context, such as after the previous one is lost,
+	/// keeping the custom uniforms.
+	pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+		// The program of the lost context must not be deleted, even if this fails.
+		self.id = 0;
+		self.id = self.sources.link()?;
+		self.uniforms.relocate(self.id);
+		Ok(())
+	}
+
+	pub(crate) fn set_uniform(&mut self, name: String, value: UniformValue) -> FerriciaResult<()> {
+		self.uniforms.set(self.id, name, value)
+	}
+
+	/// Runs the numbers of work groups in each dimension, with the custom uniforms uploaded.
+	pub(crate) fn dispatch(&self, groups: (u32, u32, u32)) -> FerriciaResult<()> {
+		if groups.0 == 0 || groups.1 == 0 || groups.2 == 0 {
+			return Err("Work groups must be positive in each dimension".to_string().into());
+		}
+		let [max_x, max_y, max_z] = self.max_groups;
+		if groups.0 > max_x || groups.1 > max_y || groups.2 > max_z {
+			return Err(format!("Work groups must not exceed {max_x}x{max_y}x{max_z}").into());
+		}
+		use_program(self.id);
+		self.uniforms.upload();
+		dispatch_compute(groups);
+		Ok(())
+	}
+}
+
+impl Drop for ComputeProgram {
+	fn drop(&mut self) {
+		delete_program(self.id);
+	}
+}
+
+/// A Shader Storage Buffer Object of floats, which may also be read by vertex shaders.
+pub(crate) struct StorageBuffer {
+	id: u32,
+	/// In floats
+	len: usize,
+	max_bindings: u32,
+}
+
+impl StorageBuffer {
+	pub(crate) fn new(window_handle: &WindowHandle, data: &[f32]) -> FerriciaResult<Self> {
+		check_supported(window_handle)?;
+		Ok(Self {
+			id: gen_storage_buf_obj(data),
+			len: data.len(),
+			max_bindings: window_handle.gl_handle().max_storage_bindings() as _,
+		})
+	}
+
+	fn check_range(&self, offset: usize, len: usize) -> FerriciaResult<()> {
+		if offset.checked_add(len).is_none_or(|v| v > self.len) {
+			return Err(format!("Range {offset}+{len} is out of the buffer of {}", self.len).into());
+		}
+		Ok(())
+	}
+
+	/// `offset` is in floats.
+	pub(crate) fn write(&self, offset: usize, data: &[f32]) -> FerriciaResult<()> {
+		self.check_range(offset, data.len())?;
+		update_buf_obj(SHADER_STORAGE_BUFFER, self.id, offset * size_of::<f32>(), data);
+		Ok(())
+	}
+
+	/// `offset` is in floats; this stalls until the GPU has finished writing.
+	pub(crate) fn read(&self, offset: usize, len: usize) -> FerriciaResult<Vec<f32>> {
+		self.check_range(offset, len)?;
+		let mut data = vec![0.0; len];
+		read_buf_obj(SHADER_STORAGE_BUFFER, self.id, offset * size_of::<f32>(), &mut data);
+		Ok(data)
+	}
+
+	/// Binds to the binding point, as declared by `layout(std430, binding = N) buffer`.
+	pub(crate) fn bind(&self, binding: u32) -> FerriciaResult<()> {
+		if binding >= self.max_bindings {
+			return Err(format!("Storage buffer binding {binding} is out of the {} binding points", self.max_bindings).into());
+		}
+		bind_storage_buf_obj(self.id, binding);
+		Ok(())
+	}
+
+	/// Creates the buffer again on a new context, such as after the previous one is lost;
+	/// the contents are lost along with the context, so the buffer is filled with zeros.
+	pub(crate) fn recreate(&mut self) {
+		self.id = gen_storage_buf_obj(&vec![0.0; self.len]);
+	}
+}
+
+impl Drop for StorageBuffer {
+	fn drop(&mut self) {
+		delete_buf_objs(&[self.id]);
+	}
+}
@@ -0,0 +1,76 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Mouse cursors of images or of the system, such as the hand over buttons and the I-beam over
+//! text fields.
+//!
+//! A cursor must be kept alive while set; dropping the active cursor restores the default one.
+
+use crate::mui::rendering::{decode_image, read_image};
+use crate::FerriciaResult;
+use image::RgbaImage;
+use sdl3::mouse::{Cursor, SystemCursor};
+use sdl3::pixels::{PixelFormat, PixelMasks};
+use sdl3::surface::Surface;
+
+pub(crate) struct CursorHandle {
+	cursor: Cursor,
+}
+
+impl CursorHandle {
+	/// `hotspot` is the pixel of the click, from the top-left corner of the image.
+	pub(crate) fn from_file(path: &str, hotspot: (i32, i32)) -> FerriciaResult<Self> {
+		Self::from_image(read_image(path)?, hotspot)
+	}
+
+	pub(crate) fn from_bytes(bytes: &[u8], hotspot: (i32, i32)) -> FerriciaResult<Self> {
+		Self::from_image(decode_image(bytes)?, hotspot)
+	}
+
+	fn from_image(mut img: RgbaImage, hotspot: (i32, i32)) -> FerriciaResult<Self> {
+		let (width, height) = img.dimensions();
+		if hotspot.0 < 0 || hotspot.1 < 0 || hotspot.0 as u32 >= width || hotspot.1 as u32 >= height {
+			return Err(format!("Hotspot {hotspot:?} is out of the cursor of {width}x{height}").into());
+		}
+		// Bytes in the order of RGBA, which is ABGR in words of little-endian platforms.
+		let format = PixelFormat::from_masks(PixelMasks {
+			bpp: 32,
+			rmask: u32::from_le_bytes([0xFF, 0, 0, 0]),
+			gmask: u32::from_le_bytes([0, 0xFF, 0, 0]),
+			bmask: u32::from_le_bytes([0, 0, 0xFF, 0]),
+			amask: u32::from_le_bytes([0, 0, 0, 0xFF]),
+		});
+		// The pixels are copied by SDL, so the surface is only borrowed for the creation.
+		let surface = Surface::from_data(&mut img, width, height, width * 4, format)?;
+		Ok(Self { cursor: Cursor::from_surface(surface, hotspot.0, hotspot.1)? })
+	}
+
+	/// `id` is the index of the system cursor, where `0` is the default arrow:
+	///
+	/// `arrow`, `I-beam`, `wait`, `crosshair`, `arrow with wait`, `NW-SE resize`, `NE-SW resize`,
+	/// `W-E resize`, `N-S resize`, `move`, `not allowed`, `hand`
+	pub(crate) fn system(id: i32) -> FerriciaResult<Self> {
+		let cursor = match id {
+			0 => SystemCursor::Arrow,
+			1 => SystemCursor::IBeam,
+			2 => SystemCursor::Wait,
+			3 => SystemCursor::Crosshair,
+			4 => SystemCursor::WaitArrow,
+			5 => SystemCursor::SizeNWSE,
+			6 => SystemCursor::SizeNESW,
+			7 => SystemCursor::SizeWE,
+			8 => SystemCursor::SizeNS,
+			9 => SystemCursor::SizeAll,
+			10 => SystemCursor::No,
+			11 => SystemCursor::Hand,
+			_ => return Err(format!("Unknown system cursor: {id}").into()),
+		};
+		Ok(Self { cursor: Cursor::from_system(cursor)? })
+	}
+
+	pub(crate) fn set(&self) {
+		self.cursor.set();
+	}
+}
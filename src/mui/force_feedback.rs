@@ -0,0 +1,179 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Force feedback of haptic devices, such as racing wheels and flight joysticks.
+//!
+//! Unlike the rumble of gamepads, effects are uploaded to the device once and then run by the
+//! device itself, so they keep playing without being ticked. Effects are made through raw SDL
+//! functions, as only rumble is ported to sdl3-rs.
+//!
+//! Effects belong to their device, and are destroyed along with it.
+
+use crate::FerriciaResult;
+use sdl3::sys::haptic::{
+	SDL_CloseHaptic, SDL_CreateHapticEffect, SDL_DestroyHapticEffect, SDL_GetHapticFeatures,
+	SDL_GetHapticNameForID, SDL_GetHaptics, SDL_HapticConstant, SDL_HapticDirection, SDL_HapticEffect,
+	SDL_HapticID, SDL_HapticPeriodic, SDL_OpenHaptic, SDL_RunHapticEffect, SDL_StopHapticEffect,
+	SDL_StopHapticEffects, SDL_Haptic, SDL_HAPTIC_CONSTANT, SDL_HAPTIC_INFINITY, SDL_HAPTIC_POLAR,
+	SDL_HAPTIC_SAWTOOTHDOWN, SDL_HAPTIC_SAWTOOTHUP, SDL_HAPTIC_SINE, SDL_HAPTIC_SQUARE, SDL_HAPTIC_TRIANGLE,
+};
+use sdl3::sys::stdinc::SDL_free;
+use std::ffi::CStr;
+use std::mem::zeroed;
+
+/// Returns the IDs of connected haptic devices; the haptic subsystem must be initialized.
+pub(super) fn haptic_devices() -> Vec<u32> {
+	let mut count = 0;
+	let ids = unsafe { SDL_GetHaptics(&mut count) };
+	if ids.is_null() {
+		return Vec::new();
+	}
+	let devices = unsafe { std::slice::from_raw_parts(ids, count as usize) }.iter().map(|v| v.0).collect();
+	unsafe { SDL_free(ids as *mut _) };
+	devices
+}
+
+pub(super) fn haptic_device_name(id: u32) -> Option<String> {
+	let name = unsafe { SDL_GetHapticNameForID(SDL_HapticID(id)) };
+	(!name.is_null()).then(|| unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned())
+}
+
+/// Waveforms of periodic effects
+pub(crate) enum Waveform {
+	Sine,
+	Square,
+	Triangle,
+	SawtoothUp,
+	SawtoothDown,
+}
+
+impl Waveform {
+	pub(crate) fn from_id(id: i32) -> FerriciaResult<Self> {
+		match id {
+			0 => Ok(Self::Sine),
+			1 => Ok(Self::Square),
+			2 => Ok(Self::Triangle),
+			3 => Ok(Self::SawtoothUp),
+			4 => Ok(Self::SawtoothDown),
+			_ => Err(format!("Unknown waveform: {id}").into()),
+		}
+	}
+
+	fn to_sdl(&self) -> u32 {
+		match self {
+			Waveform::Sine => SDL_HAPTIC_SINE,
+			Waveform::Square => SDL_HAPTIC_SQUARE,
+			Waveform::Triangle => SDL_HAPTIC_TRIANGLE,
+			Waveform::SawtoothUp => SDL_HAPTIC_SAWTOOTHUP,
+			Waveform::SawtoothDown => SDL_HAPTIC_SAWTOOTHDOWN,
+		}
+	}
+}
+
+pub(crate) struct ForceFeedbackDevice {
+	haptic: *mut SDL_Haptic,
+}
+
+impl ForceFeedbackDevice {
+	/// `id` is one of [haptic_devices].
+	pub(crate) fn open(id: u32) -> FerriciaResult<Self> {
+		let haptic = unsafe { SDL_OpenHaptic(SDL_HapticID(id)) };
+		if haptic.is_null() {
+			return Err(format!("Cannot open haptic device {id}: {}", sdl3::get_error()).into());
+		}
+		Ok(Self { haptic })
+	}
+
+	fn supports(&self, effect_type: u32) -> bool {
+		unsafe { SDL_GetHapticFeatures(self.haptic) } & effect_type != 0
+	}
+
+	fn create_effect(&mut self, effect: SDL_HapticEffect) -> FerriciaResult<i32> {
+		match unsafe { SDL_CreateHapticEffect(self.haptic, &effect) } {
+			-1 => Err(format!("Cannot create haptic effect: {}", sdl3::get_error()).into()),
+			v => Ok(v),
+		}
+	}
+
+	/// Pushes in the direction in degrees clockwise from north, where the player is facing,
+	/// with `level` in `[-1, 1]`.
+	pub(crate) fn new_constant_effect(&mut self, direction: f32, level: f32, duration_ms: u32) -> FerriciaResult<i32> {
+		if !self.supports(SDL_HAPTIC_CONSTANT) {
+			return Err("Haptic device does not support constant effects".to_string().into());
+		}
+		self.create_effect(SDL_HapticEffect {
+			constant: SDL_HapticConstant {
+				r#type: SDL_HAPTIC_CONSTANT as _,
+				direction: polar_direction(direction),
+				length: duration_ms,
+				level: (level.clamp(-1.0, 1.0) * i16::MAX as f32) as i16,
+				..unsafe { zeroed() }
+			},
+		})
+	}
+
+	/// Oscillates in the direction in degrees clockwise from north, with `magnitude` in `[0, 1]`.
+	pub(crate) fn new_periodic_effect(
+		&mut self,
+		waveform: Waveform,
+		direction: f32,
+		period_ms: u16,
+		magnitude: f32,
+		duration_ms: u32,
+	) -> FerriciaResult<i32> {
+		let effect_type = waveform.to_sdl();
+		if !self.supports(effect_type) {
+			return Err("Haptic device does not support the waveform".to_string().into());
+		}
+		self.create_effect(SDL_HapticEffect {
+			periodic: SDL_HapticPeriodic {
+				r#type: effect_type as _,
+				direction: polar_direction(direction),
+				length: duration_ms,
+				period: period_ms,
+				magnitude: (magnitude.clamp(0.0, 1.0) * i16::MAX as f32) as i16,
+				..unsafe { zeroed() }
+			},
+		})
+	}
+
+	/// `iterations` of `0` repeats the effect until stopped.
+	pub(crate) fn run_effect(&mut self, effect: i32, iterations: u32) -> FerriciaResult<()> {
+		let iterations = if iterations == 0 { SDL_HAPTIC_INFINITY } else { iterations };
+		if !unsafe { SDL_RunHapticEffect(self.haptic, effect, iterations) } {
+			return Err(format!("Cannot run haptic effect {effect}: {}", sdl3::get_error()).into());
+		}
+		Ok(())
+	}
+
+	pub(crate) fn stop_effect(&mut self, effect: i32) -> FerriciaResult<()> {
+		if !unsafe { SDL_StopHapticEffect(self.haptic, effect) } {
+			return Err(format!("Cannot stop haptic effect {effect}: {}", sdl3::get_error()).into());
+		}
+		Ok(())
+	}
+
+	pub(crate) fn stop_all(&mut self) {
+		unsafe { SDL_StopHapticEffects(self.haptic) };
+	}
+
+	pub(crate) fn destroy_effect(&mut self, effect: i32) {
+		unsafe { SDL_DestroyHapticEffect(self.haptic, effect) };
+	}
+}
+
+impl Drop for ForceFeedbackDevice {
+	fn drop(&mut self) {
+		unsafe { SDL_CloseHaptic(self.haptic) };
+	}
+}
+
+/// Hundredths of degrees, as used by SDL
+fn polar_direction(degrees: f32) -> SDL_HapticDirection {
+	SDL_HapticDirection {
+		r#type: SDL_HAPTIC_POLAR as _,
+		dir: [(degrees.rem_euclid(360.0) * 100.0) as i32, 0, 0],
+	}
+}
@@ -0,0 +1,51 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Identity and power of connected gamepads, such as for choosing button glyphs by the layout
+//! of the gamepad and warning about low batteries.
+//!
+//! These are queried through raw SDL functions from the opened gamepads, as they are not yet ported
+//! to sdl3-rs. Types, connection and power states are reported to Java by their values in SDL.
+
+use crate::mui::slots::gamepad_guid;
+use crate::FerriciaResult;
+use sdl3::sys::gamepad::{SDL_GetGamepadConnectionState, SDL_GetGamepadFromID, SDL_GetGamepadName, SDL_GetGamepadPowerInfo, SDL_GetGamepadType};
+use sdl3::sys::joystick::SDL_JoystickID;
+use std::ffi::CStr;
+
+pub(crate) struct GamepadInfo {
+	pub(crate) name: String,
+	pub(crate) guid: String,
+	/// `SDL_GamepadType`, such as `3` for Xbox One and `6` for PS5; `0` if unknown
+	pub(crate) kind: i32,
+	/// `SDL_JoystickConnectionState`; `1` for wired and `2` for wireless, or `0` if unknown
+	pub(crate) connection: i32,
+	/// `SDL_PowerState`; `1` on battery, `2` without battery, `3` charging and `4` charged
+	pub(crate) power: i32,
+	/// Battery level in percent; `-1` if unknown
+	pub(crate) battery: i32,
+}
+
+/// The gamepad must be opened.
+pub(super) fn gamepad_info(which: u32) -> FerriciaResult<GamepadInfo> {
+	let gamepad = unsafe { SDL_GetGamepadFromID(SDL_JoystickID(which)) };
+	if gamepad.is_null() {
+		return Err(format!("Gamepad {which} is not opened: {}", sdl3::get_error()).into());
+	}
+	let name = unsafe { SDL_GetGamepadName(gamepad) };
+	let mut battery = -1;
+	let power = unsafe { SDL_GetGamepadPowerInfo(gamepad, &mut battery) };
+	Ok(GamepadInfo {
+		name: match name.is_null() {
+			true => String::new(),
+			false => unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned(),
+		},
+		guid: gamepad_guid(which),
+		kind: unsafe { SDL_GetGamepadType(gamepad) }.0 as _,
+		connection: unsafe { SDL_GetGamepadConnectionState(gamepad) }.0 as _,
+		power: power.0 as _,
+		battery,
+	})
+}
@@ -0,0 +1,168 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Simple gesture recognition on top of raw gamepad touchpad events.
+//!
+//! Touchpad coordinates are normalized in `[0, 1]` with origin at top-left, so all the
+//! thresholds here are fractions of the touchpad dimensions.
+
+use crate::mui::MuiEvent;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Maximum duration between finger down and up to be counted as a tap.
+const TAP_DURATION: Duration = Duration::from_millis(250);
+/// Maximum movement of a finger to be still counted as a tap.
+const TAP_SLOP: f32 = 0.03;
+/// Minimum movement of a finger to start dragging.
+const DRAG_THRESHOLD: f32 = 0.03;
+/// Minimum average movement of two fingers to be counted as a swipe.
+const SWIPE_DISTANCE: f32 = 0.2;
+
+struct Finger {
+	start: (f32, f32),
+	last: (f32, f32),
+	since: Instant,
+}
+
+#[derive(Default)]
+struct TouchpadState {
+	fingers: HashMap<i32, Finger>,
+	/// Maximum number of fingers down at once since the first finger down.
+	max_fingers: usize,
+	dragging: bool,
+	/// Displacements of lifted fingers during a multi-finger gesture.
+	lifted: Vec<(f32, f32)>,
+}
+
+/// Recognizes taps, drags and two-finger swipes per touchpad of each gamepad.
+#[derive(Default)]
+pub(super) struct GestureRecognizer {
+	touchpads: HashMap<(u32, i32), TouchpadState>,
+}
+
+impl GestureRecognizer {
+	pub(super) fn finger_down(&mut self, which: u32, touchpad: i32, finger: i32, x: f32, y: f32) {
+		let state = self.touchpads.entry((which, touchpad)).or_default();
+		state.fingers.insert(finger, Finger { start: (x, y), last: (x, y), since: Instant::now() });
+		state.max_fingers = state.max_fingers.max(state.fingers.len());
+	}
+
+	pub(super) fn finger_motion(
+		&mut self,
+		which: u32,
+		touchpad: i32,
+		finger: i32,
+		x: f32,
+		y: f32,
+	) -> Option<MuiEvent> {
+		let state = self.touchpads.get_mut(&(which, touchpad))?;
+		let single = state.max_fingers == 1;
+		let f = state.fingers.get_mut(&finger)?;
+		let delta = (x - f.last.0, y - f.last.1);
+		f.last = (x, y);
+		if single && (state.dragging || distance(f.start, f.last) > DRAG_THRESHOLD) {
+			state.dragging = true;
+			Some(MuiEvent::GamepadTouchpadDrag(which, touchpad, delta.0, delta.1))
+		} else {
+			None
+		}
+	}
+
+	pub(super) fn finger_up(&mut self, which: u32, touchpad: i32, finger: i32, x: f32, y: f32) -> Option<MuiEvent> {
+		let state = self.touchpads.get_mut(&(which, touchpad))?;
+		let f = state.fingers.remove(&finger)?;
+		let event = if state.max_fingers == 1 {
+			(!state.dragging && f.since.elapsed() <= TAP_DURATION && distance(f.start, (x, y)) <= TAP_SLOP)
+				.then_some(MuiEvent::GamepadTouchpadTap(which, touchpad, x, y))
+		} else {
+			state.lifted.push((x - f.start.0, y - f.start.1));
+			if state.fingers.is_empty() && state.max_fingers == 2 {
+				swipe(&state.lifted).map(|(dx, dy)| MuiEvent::GamepadTouchpadSwipe(which, touchpad, dx, dy))
+			} else {
+				None
+			}
+		};
+		if state.fingers.is_empty() {
+			self.touchpads.remove(&(which, touchpad));
+		}
+		event
+	}
+
+	/// Discards all the states of a removed gamepad.
+	pub(super) fn remove_gamepad(&mut self, which: u32) {
+		self.touchpads.retain(|(id, _), _| *id != which);
+	}
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+	(b.0 - a.0).hypot(b.1 - a.1)
+}
+
+/// Both fingers must move in roughly the same direction.
+fn swipe(displacements: &[(f32, f32)]) -> Option<(f32, f32)> {
+	let [a, b] = displacements else { return None };
+	if a.0 * b.0 + a.1 * b.1 <= 0.0 {
+		return None;
+	}
+	let avg = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+	(distance((0.0, 0.0), avg) >= SWIPE_DISTANCE).then_some(avg)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn recognizes_taps() {
+		let mut gestures = GestureRecognizer::default();
+		gestures.finger_down(0, 0, 1, 0.5, 0.5);
+		assert!(gestures.finger_motion(0, 0, 1, 0.51, 0.5).is_none());
+		let event = gestures.finger_up(0, 0, 1, 0.51, 0.5);
+		assert!(matches!(event, Some(MuiEvent::GamepadTouchpadTap(0, 0, x, _)) if x == 0.51));
+	}
+
+	#[test]
+	fn drags_beyond_the_threshold() {
+		let mut gestures = GestureRecognizer::default();
+		gestures.finger_down(0, 0, 1, 0.5, 0.5);
+		assert!(gestures.finger_motion(0, 0, 1, 0.52, 0.5).is_none());
+		let event = gestures.finger_motion(0, 0, 1, 0.6, 0.5);
+		assert!(matches!(event, Some(MuiEvent::GamepadTouchpadDrag(0, 0, dx, dy)) if (dx - 0.08).abs() < 1e-6 && dy == 0.0));
+		// Dragging continues below the threshold, and ends without a tap.
+		assert!(gestures.finger_motion(0, 0, 1, 0.61, 0.5).is_some());
+		assert!(gestures.finger_up(0, 0, 1, 0.61, 0.5).is_none());
+	}
+
+	#[test]
+	fn recognizes_two_finger_swipes() {
+		let mut gestures = GestureRecognizer::default();
+		gestures.finger_down(0, 0, 1, 0.2, 0.4);
+		gestures.finger_down(0, 0, 2, 0.2, 0.6);
+		assert!(gestures.finger_motion(0, 0, 1, 0.4, 0.4).is_none());
+		assert!(gestures.finger_up(0, 0, 1, 0.5, 0.4).is_none());
+		let event = gestures.finger_up(0, 0, 2, 0.5, 0.6);
+		assert!(matches!(event, Some(MuiEvent::GamepadTouchpadSwipe(0, 0, dx, dy)) if (dx - 0.3).abs() < 1e-6 && dy.abs() < 1e-6));
+	}
+
+	#[test]
+	fn ignores_fingers_moving_apart() {
+		let mut gestures = GestureRecognizer::default();
+		gestures.finger_down(0, 0, 1, 0.4, 0.5);
+		gestures.finger_down(0, 0, 2, 0.6, 0.5);
+		assert!(gestures.finger_up(0, 0, 1, 0.1, 0.5).is_none());
+		assert!(gestures.finger_up(0, 0, 2, 0.9, 0.5).is_none());
+	}
+
+	#[test]
+	fn discards_removed_gamepads() {
+		let mut gestures = GestureRecognizer::default();
+		gestures.finger_down(0, 0, 1, 0.5, 0.5);
+		gestures.finger_down(1, 0, 1, 0.5, 0.5);
+		gestures.remove_gamepad(0);
+		assert!(gestures.finger_up(0, 0, 1, 0.5, 0.5).is_none());
+		assert!(gestures.finger_up(1, 0, 1, 0.5, 0.5).is_some());
+	}
+}
@@ -0,0 +1,282 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Preprocessing of GLSL sources for the detected GL capabilities.
+//!
+//! `#version` is injected with the detected GLSL version unless the source declares one, in which
+//! case the declared version is kept as the requirement. Features are defined as macros after it,
+//! so that one shader source tree may serve GL 2.0 through 4.x with `#ifdef` and `__VERSION__`:
+//!
+//! - `HAS_UBO` if the shared uniform block is available, enabling its extension for GLSL < 1.40
+//! - `HAS_TEXTURE_ARRAY` if texture arrays may be sampled
+//!
+//! With OpenGL ES, sources without a version are compiled as GLSL ES 1.00, of which the syntax is
+//! that of GLSL 1.20 with `__VERSION__` being `100`, and declared versions must be of ES, such as
+//! `300 es`. The default float precision of fragment shaders is declared right after the version,
+//! as `highp` where available. As `#extension` must precede it, these directives are moved right
+//! after the version, so they take effect regardless of the conditions around them.
+//!
+//! `#include "path"` is resolved relative to the including file. Each file is included only once,
+//! so headers need no include guards and cyclic includes are harmless.
+//!
+//! `#line` is emitted after the injected lines and around each include, so that compile errors
+//! refer to the lines in the files. Files are numbered as source strings in the order of inclusion,
+//! from `0` for the processed file.
+
+use crate::mui::ogl::GLHandle;
+use crate::FerriciaResult;
+use std::collections::HashSet;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+pub(super) struct ShaderPreprocessor {
+	/// Detected GLSL version, such as `330`
+	version: u32,
+	ubo: bool,
+	texture_array: bool,
+	es: bool,
+}
+
+impl ShaderPreprocessor {
+	pub(super) fn new(gl_handle: &GLHandle) -> Self {
+		let glsl = gl_handle.glsl_version();
+		// Minor versions are two digits, but some drivers report `4.6` for `4.60`.
+		let minor = if glsl.minor < 10 { glsl.minor * 10 } else { glsl.minor };
+		Self {
+			version: (glsl.major * 100 + minor) as u32,
+			ubo: gl_handle.ubo_supported(),
+			texture_array: gl_handle.texture_array_supported(),
+			es: gl_handle.is_es(),
+		}
+	}
+
+	pub(super) fn process_file(&self, path: impl AsRef<Path>) -> FerriciaResult<String> {
+		let mut included = HashSet::new();
+		let mut sources = Sources::default();
+		self.include(path.as_ref(), &mut included, &mut sources)?;
+		self.finish(sources)
+	}
+
+	/// Sources without a file, such as those bundled in the game, cannot include other files.
+	pub(super) fn process_source(&self, src: &str) -> FerriciaResult<String> {
+		let mut sources = Sources::default();
+		sources.resume(1, 0);
+		for line in src.lines() {
+			match parse_directive(line) {
+				Some(Directive::Version(v)) => {
+					sources.set_version(v)?;
+					sources.push_line("");
+				}
+				Some(Directive::Extension) => sources.push_extension(line),
+				Some(Directive::Include(_)) =>
+					return Err("Includes are only supported in shader files".to_string().into()),
+				None => sources.push_line(line),
+			}
+		}
+		self.finish(sources)
+	}
+
+	fn include(&self, path: &Path, included: &mut HashSet<PathBuf>, sources: &mut Sources) -> FerriciaResult<()> {
+		let path = path.canonicalize()
+			.map_err(|e| format!("Cannot resolve shader {}: {e}", path.display()))?;
+		if !included.insert(path.clone()) {
+			return Ok(());
+		}
+		let file = included.len() - 1;
+		let src = read_to_string(&path)
+			.map_err(|e| format!("Cannot read shader {}: {e}", path.display()))?;
+		sources.resume(1, file);
+		for (i, line) in src.lines().enumerate() {
+			match parse_directive(line) {
+				Some(Directive::Version(v)) => {
+					sources.set_version(v)?;
+					sources.push_line("");
+				}
+				Some(Directive::Extension) => sources.push_extension(line),
+				Some(Directive::Include(v)) => {
+					let target = path.parent().expect("should be a file").join(v);
+					self.include(&target, included, sources)?;
+					sources.resume(i + 2, file);
+				}
+				None => sources.push_line(line),
+			}
+		}
+		Ok(())
+	}
+
+	fn finish(&self, sources: Sources) -> FerriciaResult<String> {
+		let (version, number) = match sources.version {
+			Some(v) => {
+				let number = v.split_whitespace().next()
+					.and_then(|v| v.parse::<u32>().ok())
+					.ok_or_else(|| format!("Invalid GLSL version: {v}"))?;
+				if self.es && number != 100 && !v.ends_with(" es") {
+					return Err(format!("Shader requires desktop GLSL {v}, but the context is OpenGL ES").into());
+				}
+				if number > self.version {
+					return Err(format!("Shader requires GLSL {number}, but only {} is supported", self.version).into());
+				}
+				(v, number)
+			}
+			None if self.es => ("100".to_string(), 100),
+			None => (self.version.to_string(), self.version),
+		};
+		let mut out = format!("#version {version}\n");
+		if self.ubo && number < 140 {
+			out.push_str("#extension GL_ARB_uniform_buffer_object : require\n");
+		}
+		out.push_str(&sources.extensions);
+		if self.es {
+			// Fragment shaders have no default float precision; the macro is defined in both stages.
+			out.push_str("#ifdef GL_FRAGMENT_PRECISION_HIGH\nprecision highp float;\n#else\nprecision mediump float;\n#endif\n");
+		}
+		if self.ubo {
+			out.push_str("#define HAS_UBO\n");
+		}
+		// Samplers of arrays are only available since GLSL 1.30.
+		if self.texture_array && number >= 130 {
+			out.push_str("#define HAS_TEXTURE_ARRAY\n");
+		}
+		// Before GLSL 3.30 and GLSL ES 3.00, the line following `#line N` is numbered `N + 1`.
+		let line_base = if number >= 330 || self.es && number >= 300 { 0 } else { 1 };
+		for line in sources.body {
+			match line {
+				Line::Source(v) => out.push_str(&v),
+				Line::Resume(line, file) => out.push_str(&format!("#line {} {file}", line - line_base)),
+			}
+			out.push('\n');
+		}
+		Ok(out)
+	}
+}
+
+/// Collected from the sources, and put together by [ShaderPreprocessor::finish]
+#[derive(Default)]
+struct Sources {
+	body: Vec<Line>,
+	version: Option<String>,
+	/// `#extension` directives, which are put before the body
+	extensions: String,
+}
+
+impl Sources {
+	/// Files may repeat the same version, such as in headers, but not conflicting ones.
+	fn set_version(&mut self, new: &str) -> FerriciaResult<()> {
+		match &self.version {
+			Some(v) if v != new => Err(format!("Conflicting GLSL versions: {v} and {new}").into()),
+			Some(_) => Ok(()),
+			None => {
+				self.version = Some(new.to_string());
+				Ok(())
+			}
+		}
+	}
+
+	/// The line is kept empty, so the following lines keep their numbers.
+	fn push_extension(&mut self, line: &str) {
+		self.extensions.push_str(line.trim());
+		self.extensions.push('\n');
+		self.push_line("");
+	}
+
+	fn push_line(&mut self, line: &str) {
+		self.body.push(Line::Source(line.to_string()));
+	}
+
+	fn resume(&mut self, line: usize, file: usize) {
+		self.body.push(Line::Resume(line, file));
+	}
+}
+
+enum Line {
+	Source(String),
+	/// Continues from the line number, counted from `1`, of the file by its source string number
+	Resume(usize, usize),
+}
+
+enum Directive<'a> {
+	/// The number with the optional profile
+	Version(&'a str),
+	Extension,
+	Include(&'a str),
+}
+
+fn parse_directive(line: &str) -> Option<Directive<'_>> {
+	let line = line.trim_start().strip_prefix('#')?.trim_start();
+	if let Some(v) = line.strip_prefix("version") {
+		return Some(Directive::Version(v.trim()));
+	}
+	if line.starts_with("extension") {
+		return Some(Directive::Extension);
+	}
+	let v = line.strip_prefix("include")?.trim();
+	v.strip_prefix('"').and_then(|v| v.strip_suffix('"')).map(Directive::Include)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn es2() -> ShaderPreprocessor {
+		ShaderPreprocessor { version: 100, ubo: false, texture_array: false, es: true }
+	}
+
+	#[test]
+	fn hoists_extensions_above_precision() {
+		let out = es2().process_source("#extension GL_OES_standard_derivatives : enable\nvoid main() {}\n").unwrap();
+		let extension = out.find("#extension GL_OES_standard_derivatives").unwrap();
+		assert!(out.starts_with("#version 100\n"));
+		assert!(extension < out.find("precision").unwrap());
+		assert_eq!(out.matches("#extension").count(), 1);
+	}
+
+	fn gl33() -> ShaderPreprocessor {
+		ShaderPreprocessor { version: 330, ubo: true, texture_array: true, es: false }
+	}
+
+	/// Returns the line following the `#line` directive.
+	fn numbered_line<'a>(out: &'a str, directive: &str) -> &'a str {
+		out.lines().skip_while(|v| *v != directive).nth(1).unwrap()
+	}
+
+	#[test]
+	fn injects_header() {
+		let out = gl33().process_source("void main() {}\n").unwrap();
+		assert!(out.starts_with("#version 330\n#define HAS_UBO\n#define HAS_TEXTURE_ARRAY\n"));
+		let out = es2().process_source("void main() {}\n").unwrap();
+		assert!(!out.contains("HAS_UBO") && !out.contains("HAS_TEXTURE_ARRAY"));
+	}
+
+	#[test]
+	fn checks_versions() {
+		assert!(gl33().process_source("#version 150\n").unwrap().starts_with("#version 150\n"));
+		assert!(gl33().process_source("#version 450\n").is_err());
+		assert!(gl33().process_source("#version 150\n#version 330\n").is_err());
+		assert!(es2().process_source("#version 120\n").is_err());
+	}
+
+	#[test]
+	fn numbers_lines_after_header() {
+		let out = gl33().process_source("#version 330\nuniform float a;\n").unwrap();
+		assert_eq!(numbered_line(&out, "#line 1 0"), "");
+		// The line following `#line N` is numbered `N + 1` in GLSL ES 1.00.
+		let out = es2().process_source("uniform float a;\n").unwrap();
+		assert_eq!(numbered_line(&out, "#line 0 0"), "uniform float a;");
+	}
+
+	#[test]
+	fn numbers_lines_around_includes() {
+		let dir = std::env::temp_dir().join(format!("ferricia-glsl-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join("common.glsl"), "#include \"main.glsl\"\nfloat f() { return 1.0; }\n").unwrap();
+		std::fs::write(dir.join("main.glsl"), "#version 330\n#include \"common.glsl\"\nvoid main() {}\n").unwrap();
+		let out = gl33().process_file(dir.join("main.glsl")).unwrap();
+		std::fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(out.matches("float f()").count(), 1);
+		assert_eq!(out.matches("void main()").count(), 1);
+		assert_eq!(numbered_line(&out, "#line 2 1"), "float f() { return 1.0; }");
+		assert_eq!(numbered_line(&out, "#line 3 0"), "void main() {}");
+	}
+}
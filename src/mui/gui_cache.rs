@@ -0,0 +1,76 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Caching of mostly-static GUI subtrees into textures.
+//!
+//! The subtree is only drawn into the cache when it is dirty; otherwise the cached texture
+//! is drawn alone with a [SpriteMesh](crate::mui::rendering::SpriteMesh) covering the canvas.
+
+use crate::mui::ogl::{clear_canvas, use_framebuffer};
+use crate::mui::render_graph::RenderTarget;
+use crate::FerriciaResult;
+
+pub(crate) struct GuiCache {
+	target: Option<RenderTarget>,
+	size: (u32, u32),
+	dirty: bool,
+}
+
+impl GuiCache {
+	pub(crate) fn new() -> Self {
+		Self { target: None, size: (0, 0), dirty: true }
+	}
+
+	/// The subtree will be drawn again at the next [GuiCache::begin].
+	pub(crate) fn mark_dirty(&mut self) {
+		self.dirty = true;
+	}
+
+	/// Binds and clears the cache if it is dirty or the canvas has been resized.
+	///
+	/// Returns whether the subtree must be drawn, followed by [GuiCache::end].
+	pub(crate) fn begin(&mut self, size: (u32, u32)) -> FerriciaResult<bool> {
+		self.resize(size)?;
+		if !self.dirty {
+			return Ok(false);
+		}
+		use_framebuffer(self.target.as_ref().expect("should be created").fbo);
+		clear_canvas();
+		Ok(true)
+	}
+
+	/// Creates the cache for the canvas size, unless already created at the size;
+	/// the subtree will be drawn again if recreated.
+	pub(crate) fn resize(&mut self, size: (u32, u32)) -> FerriciaResult<()> {
+		if self.target.is_none() || self.size != size {
+			self.target = None; // Frees the previous target first
+			self.target = Some(RenderTarget::new(size)?);
+			self.size = size;
+			self.dirty = true;
+		}
+		Ok(())
+	}
+
+	/// Creates the cache again on a new context, such as after the previous one is lost;
+	/// the subtree will be drawn again.
+	pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+		if let Some(v) = &mut self.target {
+			v.recreate(self.size)?;
+		}
+		self.dirty = true;
+		Ok(())
+	}
+
+	/// Restores drawing to the canvas and marks the cache clean.
+	pub(crate) fn end(&mut self) {
+		use_framebuffer(0);
+		self.dirty = false;
+	}
+
+	/// Zero if nothing has been cached yet.
+	pub(crate) fn texture(&self) -> u32 {
+		self.target.as_ref().map_or(0, |v| v.texture)
+	}
+}
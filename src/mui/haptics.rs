@@ -0,0 +1,163 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Rumble patterns executed natively across frames.
+//!
+//! A pattern is an envelope of keyframes, linearly interpolated in between.
+//! The scheduler is ticked on every event poll, so each rumble request lasts a little longer than
+//! the usual frame time to keep the motors running until the next tick.
+
+use crate::{FerriciaError, FerriciaResult};
+use sdl3::gamepad::Gamepad;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Duration of each rumble request made by a tick.
+const TICK_RUMBLE_MS: u32 = 100;
+
+#[derive(Clone, Copy)]
+pub(crate) struct RumbleKeyframe {
+	at: Duration,
+	/// Intensity of the low frequency (left) motor in `[0, 1]`
+	low: f32,
+	/// Intensity of the high frequency (right) motor in `[0, 1]`
+	high: f32,
+}
+
+impl RumbleKeyframe {
+	pub(crate) const fn new(at_ms: u64, low: f32, high: f32) -> Self {
+		Self { at: Duration::from_millis(at_ms), low, high }
+	}
+}
+
+/// Keyframes must be sorted by time; the last keyframe marks the end of the pattern.
+pub(crate) struct RumblePattern {
+	keyframes: Vec<RumbleKeyframe>,
+}
+
+impl RumblePattern {
+	pub(crate) fn new(keyframes: Vec<RumbleKeyframe>) -> FerriciaResult<Self> {
+		if keyframes.is_empty() {
+			return Err("Rumble pattern must not be empty".to_string().into());
+		}
+		if keyframes.windows(2).any(|w| w[0].at > w[1].at) {
+			return Err("Rumble keyframes must be sorted by time".to_string().into());
+		}
+		Ok(Self { keyframes })
+	}
+
+	fn duration(&self) -> Duration {
+		self.keyframes.last().expect("should not be empty").at
+	}
+
+	/// Returns `(low, high)` at the time.
+	fn sample(&self, t: Duration) -> (f32, f32) {
+		match self.keyframes.iter().position(|k| k.at > t) {
+			None => {
+				let last = self.keyframes.last().expect("should not be empty");
+				(last.low, last.high)
+			}
+			Some(0) => (self.keyframes[0].low, self.keyframes[0].high),
+			Some(i) => {
+				let (a, b) = (&self.keyframes[i - 1], &self.keyframes[i]);
+				let f = (t - a.at).as_secs_f32() / (b.at - a.at).as_secs_f32();
+				(a.low + (b.low - a.low) * f, a.high + (b.high - a.high) * f)
+			}
+		}
+	}
+}
+
+fn builtin_patterns() -> HashMap<String, RumblePattern> {
+	let patterns = [
+		("heartbeat", vec![
+			RumbleKeyframe::new(0, 0.8, 0.2),
+			RumbleKeyframe::new(80, 0.0, 0.0),
+			RumbleKeyframe::new(200, 0.0, 0.0),
+			RumbleKeyframe::new(260, 0.6, 0.1),
+			RumbleKeyframe::new(340, 0.0, 0.0),
+			RumbleKeyframe::new(900, 0.0, 0.0),
+		]),
+		("explosion", vec![
+			RumbleKeyframe::new(0, 1.0, 1.0),
+			RumbleKeyframe::new(150, 1.0, 0.6),
+			RumbleKeyframe::new(700, 0.3, 0.0),
+			RumbleKeyframe::new(1200, 0.0, 0.0),
+		]),
+		("damage_tick", vec![
+			RumbleKeyframe::new(0, 0.3, 0.7),
+			RumbleKeyframe::new(60, 0.3, 0.7),
+			RumbleKeyframe::new(120, 0.0, 0.0),
+		]),
+	];
+	patterns.into_iter()
+		.map(|(name, keyframes)| (name.to_string(), RumblePattern { keyframes }))
+		.collect()
+}
+
+struct Playback {
+	pattern: String,
+	started: Instant,
+	intensity: f32,
+}
+
+pub(super) struct HapticsScheduler {
+	patterns: HashMap<String, RumblePattern>,
+	/// Playing patterns by gamepad ID
+	playing: HashMap<u32, Playback>,
+}
+
+impl HapticsScheduler {
+	pub(super) fn new() -> Self {
+		Self {
+			patterns: builtin_patterns(),
+			playing: HashMap::new(),
+		}
+	}
+
+	/// Existing patterns with the same name are replaced, including the built-in ones.
+	pub(super) fn register(&mut self, name: String, pattern: RumblePattern) {
+		self.patterns.insert(name, pattern);
+	}
+
+	/// Intensity scales the whole envelope and is clamped in `[0, 1]`.
+	pub(super) fn play(&mut self, gamepad: u32, name: &str, intensity: f32) -> FerriciaResult<()> {
+		if !self.patterns.contains_key(name) {
+			return Err(FerriciaError(format!("Unknown rumble pattern: {name}")));
+		}
+		self.playing.insert(gamepad, Playback {
+			pattern: name.to_string(),
+			started: Instant::now(),
+			intensity: intensity.clamp(0.0, 1.0),
+		});
+		Ok(())
+	}
+
+	pub(super) fn stop(&mut self, gamepad: u32, gamepads: &mut HashMap<u32, Gamepad>) {
+		if self.playing.remove(&gamepad).is_none() {
+			return;
+		}
+		if let Some(v) = gamepads.get_mut(&gamepad) {
+			let _ = v.set_rumble(0, 0, 0);
+		}
+	}
+
+	pub(super) fn tick(&mut self, gamepads: &mut HashMap<u32, Gamepad>) {
+		let patterns = &self.patterns;
+		self.playing.retain(|id, playback| {
+			let Some(gamepad) = gamepads.get_mut(id) else { return false };
+			let pattern = &patterns[&playback.pattern];
+			let t = playback.started.elapsed();
+			if t >= pattern.duration() {
+				let _ = gamepad.set_rumble(0, 0, 0);
+				return false;
+			}
+			let (low, high) = pattern.sample(t);
+			let scale = |v: f32| (v * playback.intensity).clamp(0.0, 1.0) * u16::MAX as f32;
+			// Failures are ignored, as rumble is not supported by all gamepads.
+			let _ = gamepad.set_rumble(scale(low) as u16, scale(high) as u16, TICK_RUMBLE_MS);
+			true
+		});
+	}
+}
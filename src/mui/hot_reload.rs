@@ -0,0 +1,155 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Hot-reloading of textures and shader programs on file changes, for development only.
+//!
+//! Parent directories are watched instead of the files, since many editors save by replacing
+//! the file, which would otherwise drop the watch. Changes are only applied on [HotReloader::poll],
+//! which must be called on the thread owning the OpenGL context.
+
+use crate::mui::rendering::{fill_texture, read_image, ReloadProgram, TextureOptions};
+use crate::events::post_event;
+use crate::util::{HandleRegistry, Tagged, TaggedId};
+use crate::FerriciaResult;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+struct WatchedProgram {
+	handle: TaggedId,
+	vsh: PathBuf,
+	fsh: PathBuf,
+	/// `None` if the handle has been dropped
+	reload: fn(TaggedId, String, String) -> Option<FerriciaResult<()>>,
+}
+
+pub(crate) struct HotReloader {
+	watcher: RecommendedWatcher,
+	events: Receiver<notify::Result<Event>>,
+	directories: HashSet<PathBuf>,
+	textures: HashMap<PathBuf, Vec<(u32, TextureOptions)>>,
+	programs: Vec<WatchedProgram>,
+}
+
+impl HotReloader {
+	pub(crate) fn new() -> FerriciaResult<Self> {
+		let (tx, rx) = channel();
+		let watcher = notify::recommended_watcher(tx)
+			.map_err(|e| format!("Cannot create file watcher: {e}"))?;
+		Ok(Self {
+			watcher,
+			events: rx,
+			directories: HashSet::new(),
+			textures: HashMap::new(),
+			programs: Vec::new(),
+		})
+	}
+
+	/// The texture is reloaded with the same options as it was loaded.
+	pub(crate) fn watch_texture(&mut self, path: String, texture: u32, options: TextureOptions) -> FerriciaResult<()> {
+		let path = self.watch(path)?;
+		self.textures.entry(path).or_default().push((texture, options));
+		Ok(())
+	}
+
+	/// Replaces the watched textures by pairs of the previous and the new textures, such as after
+	/// they are uploaded again on a new context; those failed to upload, as zero, are no longer watched.
+	pub(crate) fn remap_textures(&mut self, textures: &[(u32, u32)]) {
+		let textures = textures.iter().copied().collect::<HashMap<_, _>>();
+		for watched in self.textures.values_mut() {
+			watched.retain_mut(|(texture, _)| match textures.get(texture) {
+				Some(0) => false,
+				Some(v) => {
+					*texture = *v;
+					true
+				}
+				None => true,
+			});
+		}
+	}
+
+	/// The program is recompiled when either shader file changes.
+	pub(crate) fn watch_program<T: ReloadProgram + Tagged>(
+		&mut self,
+		handle: TaggedId,
+		vsh: String,
+		fsh: String,
+	) -> FerriciaResult<()> {
+		let vsh = self.watch(vsh)?;
+		let fsh = self.watch(fsh)?;
+		self.programs.push(WatchedProgram { handle, vsh, fsh, reload: reload_program::<T> });
+		Ok(())
+	}
+
+	fn watch(&mut self, path: String) -> FerriciaResult<PathBuf> {
+		let path = Path::new(&path).canonicalize()
+			.map_err(|e| format!("Cannot resolve {path}: {e}"))?;
+		let dir = path.parent().expect("file should have a parent").to_path_buf();
+		if !self.directories.contains(&dir) {
+			self.watcher.watch(&dir, RecursiveMode::NonRecursive)
+				.map_err(|e| format!("Cannot watch {}: {e}", dir.display()))?;
+			self.directories.insert(dir);
+		}
+		Ok(path)
+	}
+
+	/// Reloads everything affected by the changes since the last poll.
+	///
+	/// Returns the changed files that have been reloaded. Programs of dropped handles are
+	/// no longer watched. On failure, the previous texture or program is kept, and the
+	/// remaining changes are discarded until the file is changed again.
+	pub(crate) fn poll(&mut self) -> FerriciaResult<Vec<String>> {
+		let mut changed = HashSet::new();
+		for event in self.events.try_iter() {
+			let event = event.map_err(|e| format!("File watcher failed: {e}"))?;
+			if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+				changed.extend(event.paths);
+			}
+		}
+
+		let mut reloaded = Vec::new();
+		for path in changed {
+			let mut hit = false;
+			if let Some(textures) = self.textures.get(&path) {
+				let img = read_image(&path)?;
+				for (texture, options) in textures {
+					fill_texture(*texture, img.clone(), options);
+				}
+				hit = true;
+			}
+			let mut result = Ok(());
+			self.programs.retain(|v| {
+				if result.is_err() || (v.vsh != path && v.fsh != path) {
+					return true;
+				}
+				hit = true;
+				let vsh = v.vsh.to_string_lossy().into_owned();
+				let fsh = v.fsh.to_string_lossy().into_owned();
+				match (v.reload)(v.handle, vsh, fsh) {
+					None => false,
+					Some(Ok(())) => true,
+					Some(Err(e)) => {
+						result = Err(e);
+						true
+					}
+				}
+			});
+			result?;
+			if hit {
+				let path = path.to_string_lossy().into_owned();
+				post_event("hot-reload", format!("Reloaded {path}"));
+				reloaded.push(path);
+			}
+		}
+		Ok(reloaded)
+	}
+}
+
+fn reload_program<T: ReloadProgram + Tagged>(handle: TaggedId, vsh: String, fsh: String) -> Option<FerriciaResult<()>> {
+	// The tag always matches, so this only fails if the handle has been dropped.
+	let ptr = HandleRegistry::lock().get::<T>(handle).ok()?;
+	Some(unsafe { &mut *ptr }.reload(vsh, fsh))
+}
@@ -0,0 +1,274 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Bindings of actions to keyboard keys, mouse buttons and gamepad buttons and axes.
+//!
+//! A key binding targets either the physical position of a key (scancode), unaffected by the
+//! keyboard layout, or the symbol of a key (keycode) under the current layout. Movement is usually
+//! bound physically, so that WASD stays in place on AZERTY or Dvorak, while mnemonic shortcuts, such
+//! as `T` for chat, should follow the layout.
+//!
+//! Actions are released by the source that pressed them, so switching the layout while holding a
+//! key never leaves an action stuck. Gamepad bindings apply to all gamepads, and an axis presses
+//! its actions when pushed beyond the dead zone of the binding in its direction.
+//!
+//! Each action has an analog value in `[0, 1]`, which is `1` when pressed by a button and is rescaled
+//! from the dead zone when pressed by an axis, so that movement may be polled per frame instead of
+//! following the raw events.
+
+use crate::mui::{GamepadAxis, GamepadButton, KeyboardKey, MouseKey};
+use crate::FerriciaResult;
+use sdl3::keyboard::{Keycode, Scancode};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Binding {
+	Physical(Scancode),
+	Layout(Keycode),
+	Mouse(MouseKey),
+	GamepadButton(GamepadButton),
+	/// Axis with the direction, where `true` is positive, and the dead zone
+	GamepadAxis(GamepadAxis, bool, f32),
+}
+
+/// Inputs pressing actions
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Source {
+	Key(Scancode),
+	Mouse(MouseKey),
+	GamepadButton(u32, GamepadButton),
+	/// Axis with the direction, as both directions may press different actions
+	GamepadAxis(u32, GamepadAxis, bool),
+}
+
+#[derive(Default)]
+pub(super) struct ActionMap {
+	/// Bindings by action; an action may be bound to multiple inputs.
+	bindings: HashMap<String, Vec<Binding>>,
+	/// Actions pressed by each held source, with their analog values
+	pressed: HashMap<Source, Vec<(String, f32)>>,
+}
+
+impl ActionMap {
+	pub(super) fn bind_physical(&mut self, action: String, key: KeyboardKey) {
+		self.bind(action, Binding::Physical(key.to_sdl()));
+	}
+
+	/// `symbol` is the name of the key, such as `T` or `Return`.
+	pub(super) fn bind_layout(&mut self, action: String, symbol: &str) -> FerriciaResult<()> {
+		let keycode = Keycode::from_name(symbol).ok_or_else(|| format!("Unknown key symbol: {symbol}"))?;
+		self.bind(action, Binding::Layout(keycode));
+		Ok(())
+	}
+
+	pub(super) fn bind_mouse(&mut self, action: String, key: MouseKey) {
+		self.bind(action, Binding::Mouse(key));
+	}
+
+	pub(super) fn bind_gamepad_button(&mut self, action: String, button: GamepadButton) {
+		self.bind(action, Binding::GamepadButton(button));
+	}
+
+	/// `dead_zone` is in `[0, 1)`; an existing binding of the same axis and direction is replaced.
+	pub(super) fn bind_gamepad_axis(&mut self, action: String, axis: GamepadAxis, positive: bool, dead_zone: f32) -> FerriciaResult<()> {
+		if !(0.0..1.0).contains(&dead_zone) {
+			return Err(format!("Invalid dead zone: {dead_zone}").into());
+		}
+		let bindings = self.bindings.entry(action).or_default();
+		bindings.retain(|v| !matches!(v, Binding::GamepadAxis(a, p, _) if *a == axis && *p == positive));
+		bindings.push(Binding::GamepadAxis(axis, positive, dead_zone));
+		Ok(())
+	}
+
+	fn bind(&mut self, action: String, binding: Binding) {
+		let bindings = self.bindings.entry(action).or_default();
+		if !bindings.contains(&binding) {
+			bindings.push(binding);
+		}
+	}
+
+	/// Removes all the bindings of the action; it is not released if being pressed.
+	pub(super) fn unbind(&mut self, action: &str) {
+		self.bindings.remove(action);
+	}
+
+	/// Returns the newly pressed actions, excluding those already held by another source.
+	pub(super) fn key_down(&mut self, scancode: Scancode, keycode: Option<Keycode>) -> Vec<String> {
+		self.press(Source::Key(scancode), |b| match b {
+			Binding::Physical(v) => *v == scancode,
+			Binding::Layout(v) => Some(*v) == keycode,
+			_ => false,
+		})
+	}
+
+	/// Returns the released actions, excluding those still held by another source.
+	pub(super) fn key_up(&mut self, scancode: Scancode) -> Vec<String> {
+		self.release(Source::Key(scancode))
+	}
+
+	pub(super) fn mouse_down(&mut self, key: MouseKey) -> Vec<String> {
+		self.press(Source::Mouse(key), |b| *b == Binding::Mouse(key))
+	}
+
+	pub(super) fn mouse_up(&mut self, key: MouseKey) -> Vec<String> {
+		self.release(Source::Mouse(key))
+	}
+
+	pub(super) fn gamepad_button_down(&mut self, which: u32, button: GamepadButton) -> Vec<String> {
+		self.press(Source::GamepadButton(which, button), |b| *b == Binding::GamepadButton(button))
+	}
+
+	pub(super) fn gamepad_button_up(&mut self, which: u32, button: GamepadButton) -> Vec<String> {
+		self.release(Source::GamepadButton(which, button))
+	}
+
+	/// Returns the newly pressed and the released actions, updating the analog values of the held.
+	pub(super) fn gamepad_axis(&mut self, which: u32, axis: GamepadAxis, value: i16) -> (Vec<String>, Vec<String>) {
+		let value = (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+		let mut pressed = Vec::new();
+		let mut released = Vec::new();
+		for positive in [true, false] {
+			let source = Source::GamepadAxis(which, axis, positive);
+			let magnitude = if positive { value } else { -value };
+			let actions = self.bindings.iter()
+				.filter_map(|(k, v)| v.iter()
+					.filter_map(|b| match b {
+						Binding::GamepadAxis(a, p, dead_zone) if *a == axis && *p == positive && magnitude > *dead_zone =>
+							Some((magnitude - dead_zone) / (1.0 - dead_zone)),
+						_ => None,
+					})
+					.reduce(f32::max)
+					.map(|v| (k.clone(), v)))
+				.collect::<Vec<_>>();
+			let previous = self.pressed.remove(&source).unwrap_or_default();
+			pressed.extend(actions.iter()
+				.filter(|(k, _)| !previous.iter().any(|(v, _)| v == k) && !self.is_held(k))
+				.map(|(k, _)| k.clone()));
+			if !actions.is_empty() {
+				self.pressed.insert(source, actions);
+			}
+			released.extend(previous.into_iter().map(|(k, _)| k).filter(|k| !self.is_held(k)));
+		}
+		(pressed, released)
+	}
+
+	/// Releases the actions held by the gamepad.
+	pub(super) fn gamepad_removed(&mut self, which: u32) -> Vec<String> {
+		let sources = self.pressed.keys()
+			.filter(|v| matches!(v, Source::GamepadButton(w, _) | Source::GamepadAxis(w, ..) if *w == which))
+			.copied()
+			.collect::<Vec<_>>();
+		sources.into_iter().flat_map(|v| self.release(v)).collect()
+	}
+
+	fn press(&mut self, source: Source, matches: impl Fn(&Binding) -> bool) -> Vec<String> {
+		let actions = self.bindings.iter()
+			.filter(|(_, v)| v.iter().any(&matches))
+			.map(|(k, _)| k.clone())
+			.collect::<Vec<_>>();
+		let pressed = actions.iter().filter(|k| !self.is_held(k)).cloned().collect();
+		if !actions.is_empty() {
+			self.pressed.insert(source, actions.into_iter().map(|k| (k, 1.0)).collect());
+		}
+		pressed
+	}
+
+	fn release(&mut self, source: Source) -> Vec<String> {
+		let actions = self.pressed.remove(&source).unwrap_or_default();
+		actions.into_iter().map(|(k, _)| k).filter(|k| !self.is_held(k)).collect()
+	}
+
+	pub(super) fn is_held(&self, action: &str) -> bool {
+		self.pressed.values().any(|v| v.iter().any(|(k, _)| k == action))
+	}
+
+	/// The highest analog value among the sources holding the action; `0` if not held.
+	pub(super) fn value(&self, action: &str) -> f32 {
+		self.pressed.values().flatten().filter(|(k, _)| k == action).map(|(_, v)| *v).fold(0.0, f32::max)
+	}
+}
+
+/// Returns the symbol of the physical key under the current layout, such as for displaying
+/// physical bindings.
+pub(super) fn layout_symbol(key: KeyboardKey) -> Option<String> {
+	Keycode::from_scancode(key.to_sdl()).map(|v| v.name()).filter(|v| !v.is_empty())
+}
+
+/// Returns the SDL keycode of the physical key under the current layout, which is the Unicode code
+/// point of the symbol for printable keys, such as `z` for [KeyboardKey::W] on AZERTY.
+pub(super) fn layout_keycode(key: KeyboardKey) -> Option<u32> {
+	Keycode::from_scancode(key.to_sdl()).map(|v| v.to_ll())
+}
+
+/// Returns the physical key producing the symbol under the current layout.
+pub(super) fn physical_key(symbol: &str) -> Option<KeyboardKey> {
+	Keycode::from_name(symbol).and_then(Scancode::from_keycode).and_then(KeyboardKey::from_sdl)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn matches_physical_and_layout_bindings() {
+		let mut actions = ActionMap::default();
+		actions.bind_physical("forward".to_string(), KeyboardKey::W);
+		actions.bind_layout("chat".to_string(), "T").unwrap();
+		// `W` on AZERTY produces `Z`, while the physical binding stays in place.
+		assert_eq!(actions.key_down(Scancode::W, Keycode::from_name("Z")), ["forward"]);
+		assert_eq!(actions.key_down(Scancode::Y, Keycode::from_name("T")), ["chat"]);
+		assert!(actions.key_down(Scancode::T, Keycode::from_name("Y")).is_empty());
+		assert_eq!(actions.key_up(Scancode::Y), ["chat"]);
+		assert_eq!(actions.key_up(Scancode::W), ["forward"]);
+		assert!(actions.key_up(Scancode::T).is_empty());
+	}
+
+	#[test]
+	fn holds_actions_until_released_by_all_sources() {
+		let mut actions = ActionMap::default();
+		actions.bind_physical("jump".to_string(), KeyboardKey::Space);
+		actions.bind_mouse("jump".to_string(), MouseKey::Left);
+		assert_eq!(actions.key_down(Scancode::Space, None), ["jump"]);
+		assert!(actions.mouse_down(MouseKey::Left).is_empty());
+		assert!(actions.key_up(Scancode::Space).is_empty());
+		assert!(actions.is_held("jump"));
+		assert_eq!(actions.mouse_up(MouseKey::Left), ["jump"]);
+		assert!(!actions.is_held("jump"));
+	}
+
+	#[test]
+	fn presses_by_axes_beyond_dead_zones() {
+		let mut actions = ActionMap::default();
+		actions.bind_gamepad_axis("right".to_string(), GamepadAxis::LeftX, true, 0.2).unwrap();
+		actions.bind_gamepad_axis("left".to_string(), GamepadAxis::LeftX, false, 0.2).unwrap();
+		let at = |v: f32| (v * i16::MAX as f32) as i16;
+		assert_eq!(actions.gamepad_axis(0, GamepadAxis::LeftX, at(0.1)), (vec![], vec![]));
+		assert_eq!(actions.value("right"), 0.0);
+		assert_eq!(actions.gamepad_axis(0, GamepadAxis::LeftX, at(0.6)), (vec!["right".to_string()], vec![]));
+		assert!((actions.value("right") - 0.5).abs() < 0.01);
+		assert_eq!(actions.gamepad_axis(0, GamepadAxis::LeftX, i16::MAX), (vec![], vec![]));
+		assert_eq!(actions.value("right"), 1.0);
+		let flipped = (vec!["left".to_string()], vec!["right".to_string()]);
+		assert_eq!(actions.gamepad_axis(0, GamepadAxis::LeftX, -i16::MAX), flipped);
+		assert_eq!(actions.gamepad_axis(0, GamepadAxis::LeftX, 0), (vec![], vec!["left".to_string()]));
+	}
+
+	#[test]
+	fn releases_actions_of_removed_gamepads() {
+		let mut actions = ActionMap::default();
+		actions.bind_gamepad_button("jump".to_string(), GamepadButton::South);
+		assert_eq!(actions.gamepad_button_down(1, GamepadButton::South), ["jump"]);
+		assert!(actions.gamepad_removed(2).is_empty());
+		assert_eq!(actions.gamepad_removed(1), ["jump"]);
+		assert!(!actions.is_held("jump"));
+	}
+
+	#[test]
+	fn rejects_invalid_bindings() {
+		let mut actions = ActionMap::default();
+		assert!(actions.bind_gamepad_axis("right".to_string(), GamepadAxis::LeftX, true, 1.0).is_err());
+		assert!(actions.bind_layout("chat".to_string(), "Not A Key").is_err());
+	}
+}
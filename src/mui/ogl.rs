@@ -31,26 +31,60 @@
 //! This feature is introduced in OpenGL 3.1, but existed as the `GL_ARB_uniform_buffer_object` extension.
 //! For versions prior to 3.1, the extension is required to simplify the amount of work;
 //! otherwise, regular uniforms are used instead.
+//!
+//! **OpenGL ES** 2.0 and later is supported as a separate path for ARM devices. Shaders are compiled
+//! as GLSL ES 1.00 unless they declare an ES version, so the features depending on newer GLSL are
+//! not used with ES, and ES 2.0 additionally requires `GL_OES_vertex_array_object` and
+//! `GL_OES_element_index_uint` for VAOs and 32-bit indices.
+//!
+//! GL errors are silent unless queried, so they are reported as engine events from two sources:
+//! the `GL_KHR_debug` message callback where available (core since 4.3), and in debug builds,
+//! `glGetError` checked after the helpers of this module, naming the helper that raised them.
 
+use crate::events::post_event;
+use crate::mui::backend::{BufferTarget, BufferUsage, RenderBackend, RenderStats, Topology};
+use crate::mui::rendering::{TextureFilter, TextureOptions, TextureWrap};
 use getset::Getters;
-use gl::types::{GLenum, GLubyte, GLuint};
-use gl::{ActiveTexture, AttachShader, BindBuffer, BindTexture, BindVertexArray, BlendFunc, BufferData, Clear, ClearColor, CompileShader, CreateProgram, CreateShader, DeleteShader, DisableVertexAttribArray, DrawArrays, DrawElements, Enable, EnableVertexAttribArray, GenBuffers, GenVertexArrays, GetIntegerv, GetShaderInfoLog, GetShaderiv, GetString, GetStringi, GetUniformLocation, LinkProgram, ShaderSource, UniformMatrix4fv, UseProgram, VertexAttrib1d, VertexAttrib1f, VertexAttrib1s, VertexAttrib2d, VertexAttrib2f, VertexAttrib2s, VertexAttrib3d, VertexAttrib3f, VertexAttrib3s, VertexAttrib4Nub, VertexAttrib4d, VertexAttrib4f, VertexAttrib4s, VertexAttribI1i, VertexAttribI1ui, VertexAttribI2i, VertexAttribI2ui, VertexAttribI3i, VertexAttribI3ui, VertexAttribI4i, VertexAttribI4ui, VertexAttribPointer, Viewport, ARRAY_BUFFER, BLEND, BYTE, COLOR_BUFFER_BIT, COMPILE_STATUS, COMPUTE_SHADER, DOUBLE, EXTENSIONS, FALSE, FLOAT, FRAGMENT_SHADER, GEOMETRY_SHADER, INT, NUM_EXTENSIONS, ONE_MINUS_SRC_ALPHA, RENDERER, SHADING_LANGUAGE_VERSION, SHORT, SRC_ALPHA, TESS_CONTROL_SHADER, TESS_EVALUATION_SHADER, TEXTURE0, TEXTURE_2D, UNSIGNED_BYTE, UNSIGNED_INT, UNSIGNED_SHORT, VENDOR, VERSION, VERTEX_SHADER};
+use gl::types::{GLchar, GLenum, GLint, GLsizei, GLubyte, GLuint};
+use gl::{ActiveTexture, AttachShader, BindAttribLocation, BindBuffer, BindBufferBase, BindFramebuffer, BindTexture, BeginQuery, BindVertexArray, BlendFunc, BufferData, BufferSubData, CheckFramebufferStatus, Clear, ClearBufferuiv, ClearColor, CompileShader, CreateProgram, CreateShader, DebugMessageCallback, DeleteBuffers, DeleteFramebuffers, DeleteProgram, DeleteQueries, DeleteShader, DeleteTextures, DeleteVertexArrays, Disable, DisableVertexAttribArray, DispatchCompute, DrawArrays, DrawElements, Enable, EndQuery, EnableVertexAttribArray, FramebufferTexture2D, GenBuffers, GenFramebuffers, GenQueries, GenTextures, GenerateMipmap, GenVertexArrays, GetFloatv, GetIntegeri_v, GetIntegerv, GetProgramInfoLog, GetProgramiv, GetQueryObjectui64v, GetQueryObjectuiv, GetShaderInfoLog, GetShaderiv, GetString, GetBufferSubData, GetError, GetStringi, GetUniformBlockIndex, GetUniformLocation, LinkProgram, MemoryBarrier, ReadPixels, Scissor, ShaderSource, TexImage2D, TexImage3D, TexParameterf, TexParameteri, TexSubImage2D, Uniform1f, Uniform1i, Uniform1ui, Uniform2fv, Uniform4fv, UniformBlockBinding, UniformMatrix4fv, UseProgram, VertexAttrib1d, VertexAttrib1f, VertexAttrib1s, VertexAttrib2d, VertexAttrib2f, VertexAttrib2s, VertexAttrib3d, VertexAttrib3f, VertexAttrib3s, VertexAttrib4Nub, VertexAttrib4d, VertexAttrib4f, VertexAttrib4s, VertexAttribI1i, VertexAttribI1ui, VertexAttribI2i, VertexAttribI2ui, VertexAttribI3i, VertexAttribI3ui, VertexAttribI4i, VertexAttribI4ui, VertexAttribPointer, Viewport, ALL_BARRIER_BITS, ARRAY_BUFFER, BLEND, BYTE, CLAMP_TO_EDGE, COLOR, COLOR_ATTACHMENT0, COLOR_BUFFER_BIT, COMPILE_STATUS, COMPUTE_SHADER, CONTEXT_CORE_PROFILE_BIT, CONTEXT_PROFILE_MASK, DEBUG_OUTPUT, DEBUG_OUTPUT_SYNCHRONOUS, DEBUG_SEVERITY_HIGH, DEBUG_SEVERITY_LOW, DEBUG_SEVERITY_MEDIUM, DEBUG_SEVERITY_NOTIFICATION, DEBUG_TYPE_DEPRECATED_BEHAVIOR, DEBUG_TYPE_ERROR, DEBUG_TYPE_PERFORMANCE, DEBUG_TYPE_PORTABILITY, DEBUG_TYPE_UNDEFINED_BEHAVIOR, DOUBLE, DYNAMIC_DRAW, ELEMENT_ARRAY_BUFFER, EXTENSIONS, FALSE, FLOAT, FRAGMENT_SHADER, FRAMEBUFFER, FRAMEBUFFER_COMPLETE, GEOMETRY_SHADER, INFO_LOG_LENGTH, INT, INVALID_ENUM, INVALID_FRAMEBUFFER_OPERATION, INVALID_INDEX, INVALID_OPERATION, INVALID_VALUE, LINEAR, LINEAR_MIPMAP_LINEAR, LINES, LINK_STATUS, MAX_COMBINED_TEXTURE_IMAGE_UNITS, MAX_COMPUTE_WORK_GROUP_COUNT, MAX_SHADER_STORAGE_BUFFER_BINDINGS, MAX_TEXTURE_SIZE, MIRRORED_REPEAT, NEAREST, NEAREST_MIPMAP_LINEAR, NO_ERROR, NUM_EXTENSIONS, ONE_MINUS_SRC_ALPHA, OUT_OF_MEMORY, POINTS, QUERY_RESULT, QUERY_RESULT_AVAILABLE, R32UI, READ_FRAMEBUFFER, RED_INTEGER, RENDERER, REPEAT, RGBA, RGBA32F, SCISSOR_TEST, SHADER_STORAGE_BUFFER, SHADING_LANGUAGE_VERSION, SHORT, SRC_ALPHA, STACK_OVERFLOW, STACK_UNDERFLOW, STATIC_DRAW, TESS_CONTROL_SHADER, TESS_EVALUATION_SHADER, TEXTURE0, TEXTURE_2D, TEXTURE_2D_ARRAY, TEXTURE_BASE_LEVEL, TEXTURE_MAG_FILTER, TEXTURE_MAX_LEVEL, TEXTURE_MIN_FILTER, TEXTURE_WRAP_S, TEXTURE_WRAP_T, TIME_ELAPSED, TRIANGLES, UNIFORM_BUFFER, UNSIGNED_BYTE, UNSIGNED_INT, UNSIGNED_SHORT, VENDOR, VERSION, VERTEX_SHADER};
 use num_traits::{Bounded, Num};
 use regex::Regex;
 use sdl3::video::GLContext;
 use semver::Version;
 use std::cmp::Ordering;
-use std::collections::HashSet;
-use std::ffi::{c_char, CStr, CString};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{c_char, c_void, CStr, CString};
 use std::mem::MaybeUninit;
-use std::ptr::{null, null_mut};
-use std::sync::LazyLock;
-use nalgebra_glm::TMat4;
+use std::ptr::null;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering as AtomicOrdering};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use nalgebra_glm::{TMat4, TVec2, TVec4};
 use sdl3::pixels::Color;
 
 const VER_2_0: Version = Version::new(2, 0, 0);
 const VER_3_0: Version = Version::new(3, 0, 0);
 const VER_3_1: Version = Version::new(3, 1, 0);
+const VER_3_2: Version = Version::new(3, 2, 0);
+const VER_3_3: Version = Version::new(3, 3, 0);
+const VER_4_2: Version = Version::new(4, 2, 0);
+const VER_4_3: Version = Version::new(4, 3, 0);
+const VER_4_6: Version = Version::new(4, 6, 0);
+
+// From GL_EXT_texture_filter_anisotropic, which is core only since GL 4.6 with the same values.
+const TEXTURE_MAX_ANISOTROPY_EXT: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: GLenum = 0x84FF;
+
+/// Maximum degree of anisotropic filtering as bits of `f32`; `1.0` if not supported.
+///
+/// Mirrored from [GLHandle] so that textures may be created without access to the handle.
+static MAX_ANISOTROPY: AtomicU32 = AtomicU32::new(0x3F80_0000);
+
+/// Mirrored from [GLHandle] so that programs may bind uniform blocks without access to the handle.
+static UBO_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// The program last bound by [use_program], reset to zero once deleted, so that it is unbound before
+/// being deleted.
+static BOUND_PROGRAM: AtomicU32 = AtomicU32::new(0);
 
 /// As long as this is never mutated after creation, this **should** be *thread-safe*.
 #[derive(Getters)]
@@ -68,6 +102,20 @@ pub(super) struct GLHandle {
 	full_glsl_version: String,
 	#[get = "pub"]
 	glsl_version: Version,
+	/// Whether the context is OpenGL ES, of which the versions are of ES rather than desktop GL.
+	es: bool,
+	/// Whether the context is of the core profile, without the deprecated functionalities
+	core_profile: bool,
+	/// Maximum degree of anisotropic filtering; `1.0` if not supported.
+	max_anisotropy: f32,
+	/// In pixels of each side
+	max_texture_size: i32,
+	/// Combined across all shader stages
+	max_texture_units: i32,
+	/// Maximum numbers of work groups of a dispatch in each dimension; zeros without compute shaders.
+	max_compute_work_groups: [i32; 3],
+	/// Zero without compute shaders
+	max_storage_bindings: i32,
 	extensions: HashSet<String>,
 	features: HashSet<GLFeature>,
 }
@@ -79,6 +127,20 @@ unsafe impl Sync for GLHandle {}
 #[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 enum GLFeature {
 	Ubo,
+	/// BC1 to BC3 (DXT1, DXT3, DXT5) compressed textures
+	S3tc,
+	/// BC7 compressed textures
+	Bptc,
+	TextureArray,
+	/// Unnormalized integer textures, sampled by `texelFetch`
+	IntegerTexture,
+	GeometryShader,
+	/// Compute shaders with Shader Storage Buffer Objects
+	Compute,
+	/// Instanced draws with per-instance vertex attributes
+	Instancing,
+	/// Queries of the GPU time elapsed
+	TimerQuery,
 }
 
 /// Supposed to be **immutable**.
@@ -87,24 +149,44 @@ impl GLHandle {
 	pub(crate) fn new(gl_context: GLContext) -> Result<Self, String> {
 		let full_gl_version = get_string(VERSION);
 		let full_glsl_version = get_string(SHADING_LANGUAGE_VERSION);
+		let gl_version = parse_version(&full_gl_version);
 		let mut instance = Self {
 			gl_context,
 			vendor: get_string(VENDOR),
 			renderer: get_string(RENDERER),
-			gl_version: parse_version(&full_gl_version),
+			// Indexed queries of extensions are only available since both GL 3.0 and ES 3.0.
+			extensions: get_extensions(gl_version.cmp(&VER_3_0) != Ordering::Less),
+			gl_version,
+			es: full_gl_version.starts_with("OpenGL ES"),
+			core_profile: false,
 			full_gl_version,
 			glsl_version: parse_version(&full_glsl_version),
 			full_glsl_version,
-			extensions: get_extensions(),
+			max_anisotropy: 1.0,
+			max_texture_size: get_integer(MAX_TEXTURE_SIZE),
+			max_texture_units: get_integer(MAX_COMBINED_TEXTURE_IMAGE_UNITS),
+			max_compute_work_groups: [0; 3],
+			max_storage_bindings: 0,
 			features: HashSet::new(),
 		};
 		instance.check_requirements()?;
+		if instance.compute_supported() {
+			instance.max_compute_work_groups = [0, 1, 2].map(|i| get_integer_indexed(MAX_COMPUTE_WORK_GROUP_COUNT, i));
+			instance.max_storage_bindings = get_integer(MAX_SHADER_STORAGE_BUFFER_BINDINGS);
+		}
+		if instance.gl_version.cmp(&VER_4_3) != Ordering::Less // >= 4.3
+			|| instance.extensions.contains("GL_KHR_debug") {
+			enable_debug_output();
+		}
 		setup();
 		Ok(instance)
 	}
 
-	/// Since mobile platforms are not supported, OpenGL ES and OES extensions are not relevant.
 	fn check_requirements(&mut self) -> Result<(), String> {
+		if self.es {
+			return self.check_es_requirements();
+		}
+
 		if self.gl_version.cmp(&VER_2_0) == Ordering::Less { // < 2.0
 			return Err(format!("GL {} not supported", self.gl_version));
 		}
@@ -113,6 +195,10 @@ impl GLHandle {
 			if !self.extensions.contains("GL_ARB_vertex_array_object") {
 				return Err(format!("GL_ARB_vertex_array_object not found with GL {}", self.gl_version));
 			}
+		} else {
+			// Sampling from arrays requires GLSL 1.30 as well, so the extension is not considered.
+			self.features.insert(GLFeature::TextureArray);
+			self.features.insert(GLFeature::IntegerTexture);
 		}
 
 		if self.gl_version.cmp(&VER_3_1) == Ordering::Less { // < 3.1
@@ -122,6 +208,92 @@ impl GLHandle {
 		} else {
 			self.features.insert(GLFeature::Ubo);
 		}
+		UBO_SUPPORTED.store(self.ubo_supported(), AtomicOrdering::Relaxed);
+
+		// The extensions of geometry shaders differ from the core in GLSL, so they are not considered.
+		if self.gl_version.cmp(&VER_3_2) != Ordering::Less { // >= 3.2
+			self.core_profile = get_integer(CONTEXT_PROFILE_MASK) as GLenum & CONTEXT_CORE_PROFILE_BIT != 0;
+			self.features.insert(GLFeature::GeometryShader);
+		}
+
+		if self.gl_version.cmp(&VER_3_3) == Ordering::Less { // < 3.3
+			if self.extensions.contains("GL_ARB_draw_instanced")
+				&& self.extensions.contains("GL_ARB_instanced_arrays") {
+				self.features.insert(GLFeature::Instancing);
+			}
+		} else {
+			self.features.insert(GLFeature::Instancing);
+		}
+
+		if self.gl_version.cmp(&VER_3_3) != Ordering::Less // >= 3.3
+			|| self.extensions.contains("GL_ARB_timer_query") {
+			self.features.insert(GLFeature::TimerQuery);
+		}
+
+		if self.gl_version.cmp(&VER_4_3) == Ordering::Less { // < 4.3
+			if self.extensions.contains("GL_ARB_compute_shader")
+				&& self.extensions.contains("GL_ARB_shader_storage_buffer_object") {
+				self.features.insert(GLFeature::Compute);
+			}
+		} else {
+			self.features.insert(GLFeature::Compute);
+		}
+
+		// S3TC has never been promoted to core due to patents, though supported by all desktop drivers.
+		if self.extensions.contains("GL_EXT_texture_compression_s3tc") {
+			self.features.insert(GLFeature::S3tc);
+		}
+
+		if self.gl_version.cmp(&VER_4_2) == Ordering::Less { // < 4.2
+			if self.extensions.contains("GL_ARB_texture_compression_bptc") {
+				self.features.insert(GLFeature::Bptc);
+			}
+		} else {
+			self.features.insert(GLFeature::Bptc);
+		}
+
+		if self.gl_version.cmp(&VER_4_6) != Ordering::Less // >= 4.6
+			|| self.extensions.contains("GL_EXT_texture_filter_anisotropic")
+			|| self.extensions.contains("GL_ARB_texture_filter_anisotropic") {
+			let mut data = MaybeUninit::uninit();
+			unsafe { GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY_EXT, data.as_mut_ptr()); }
+			self.max_anisotropy = unsafe { data.assume_init() };
+		}
+		MAX_ANISOTROPY.store(self.max_anisotropy.to_bits(), AtomicOrdering::Relaxed);
+
+		Ok(())
+	}
+
+	/// Only features without requirements on GLSL are considered, as shaders target GLSL ES 1.00.
+	fn check_es_requirements(&mut self) -> Result<(), String> {
+		if self.gl_version.cmp(&VER_3_0) == Ordering::Less { // < ES 3.0
+			if !self.extensions.contains("GL_OES_vertex_array_object") {
+				return Err(format!("GL_OES_vertex_array_object not found with GL ES {}", self.gl_version));
+			}
+			if !self.extensions.contains("GL_OES_element_index_uint") {
+				return Err(format!("GL_OES_element_index_uint not found with GL ES {}", self.gl_version));
+			}
+		}
+		UBO_SUPPORTED.store(false, AtomicOrdering::Relaxed);
+
+		if self.gl_version.cmp(&VER_3_0) != Ordering::Less // >= ES 3.0
+			|| self.extensions.contains("GL_EXT_instanced_arrays") {
+			self.features.insert(GLFeature::Instancing);
+		}
+
+		if self.extensions.contains("GL_EXT_texture_compression_s3tc") {
+			self.features.insert(GLFeature::S3tc);
+		}
+		if self.extensions.contains("GL_EXT_texture_compression_bptc") {
+			self.features.insert(GLFeature::Bptc);
+		}
+
+		if self.extensions.contains("GL_EXT_texture_filter_anisotropic") {
+			let mut data = MaybeUninit::uninit();
+			unsafe { GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY_EXT, data.as_mut_ptr()); }
+			self.max_anisotropy = unsafe { data.assume_init() };
+		}
+		MAX_ANISOTROPY.store(self.max_anisotropy.to_bits(), AtomicOrdering::Relaxed);
 
 		Ok(())
 	}
@@ -130,9 +302,79 @@ impl GLHandle {
 		unsafe { Viewport(0, 0, width as i32, height as i32) }
 	}
 
+	pub(super) fn is_es(&self) -> bool {
+		self.es
+	}
+
+	/// Profiles are only distinguished since GL 3.2.
+	pub(super) fn is_core_profile(&self) -> bool {
+		self.core_profile
+	}
+
+	/// Whether the VAO functions are only available with the `OES` suffix, as in ES 2.0.
+	pub(super) fn oes_vertex_array(&self) -> bool {
+		self.es && self.gl_version.cmp(&VER_3_0) == Ordering::Less
+	}
+
 	pub(super) fn ubo_supported(&self) -> bool {
 		self.features.contains(&GLFeature::Ubo)
 	}
+
+	pub(super) fn s3tc_supported(&self) -> bool {
+		self.features.contains(&GLFeature::S3tc)
+	}
+
+	pub(super) fn bptc_supported(&self) -> bool {
+		self.features.contains(&GLFeature::Bptc)
+	}
+
+	pub(super) fn texture_array_supported(&self) -> bool {
+		self.features.contains(&GLFeature::TextureArray)
+	}
+
+	pub(super) fn integer_texture_supported(&self) -> bool {
+		self.features.contains(&GLFeature::IntegerTexture)
+	}
+
+	pub(super) fn geometry_shader_supported(&self) -> bool {
+		self.features.contains(&GLFeature::GeometryShader)
+	}
+
+	pub(super) fn compute_supported(&self) -> bool {
+		self.features.contains(&GLFeature::Compute)
+	}
+
+	pub(super) fn max_anisotropy(&self) -> f32 {
+		self.max_anisotropy
+	}
+
+	pub(super) fn timer_query_supported(&self) -> bool {
+		self.features.contains(&GLFeature::TimerQuery)
+	}
+
+	pub(super) fn instancing_supported(&self) -> bool {
+		self.features.contains(&GLFeature::Instancing)
+	}
+
+	pub(super) fn max_texture_size(&self) -> i32 {
+		self.max_texture_size
+	}
+
+	pub(super) fn max_texture_units(&self) -> i32 {
+		self.max_texture_units
+	}
+
+	pub(super) fn max_compute_work_groups(&self) -> [i32; 3] {
+		self.max_compute_work_groups
+	}
+
+	pub(super) fn max_storage_bindings(&self) -> i32 {
+		self.max_storage_bindings
+	}
+
+	pub(super) fn extensions(&self) -> &HashSet<String> {
+		&self.extensions
+	}
 }
 
 fn setup() {
@@ -140,14 +382,93 @@ fn setup() {
 	unsafe { BlendFunc(SRC_ALPHA, ONE_MINUS_SRC_ALPHA); }
 }
 
+/// Messages are delivered on the calling thread in debug builds, so that they follow the failing
+/// call; release builds let the driver deliver them asynchronously instead.
+fn enable_debug_output() {
+	if !DebugMessageCallback::is_loaded() {
+		return;
+	}
+	unsafe { Enable(DEBUG_OUTPUT); }
+	if cfg!(debug_assertions) {
+		unsafe { Enable(DEBUG_OUTPUT_SYNCHRONOUS); }
+	}
+	unsafe { DebugMessageCallback(Some(debug_message_callback), null()); }
+}
+
+/// Notifications are dropped, as some drivers report every buffer allocation.
+extern "system" fn debug_message_callback(
+	_source: GLenum,
+	kind: GLenum,
+	id: GLuint,
+	severity: GLenum,
+	_length: GLsizei,
+	message: *const GLchar,
+	_user_param: *mut c_void,
+) {
+	let severity = match severity {
+		DEBUG_SEVERITY_HIGH => "high",
+		DEBUG_SEVERITY_MEDIUM => "medium",
+		DEBUG_SEVERITY_LOW => "low",
+		DEBUG_SEVERITY_NOTIFICATION => return,
+		_ => "unknown",
+	};
+	let kind = match kind {
+		DEBUG_TYPE_ERROR => "error",
+		DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+		DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+		DEBUG_TYPE_PORTABILITY => "portability",
+		DEBUG_TYPE_PERFORMANCE => "performance",
+		_ => "other",
+	};
+	let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+	post_event("gl", format!("{kind} ({severity}, {id:#x}): {message}"));
+}
+
+/// Reports the GL errors raised since the last check, attributed to the helper, in debug builds only,
+/// as `glGetError` may stall the pipeline.
+#[inline]
+fn check_error(helper: &str) {
+	if !cfg!(debug_assertions) {
+		return;
+	}
+	// Bounded, since a lost context may report the same error indefinitely.
+	for _ in 0..8 {
+		let error = match unsafe { GetError() } {
+			NO_ERROR => break,
+			INVALID_ENUM => "GL_INVALID_ENUM",
+			INVALID_VALUE => "GL_INVALID_VALUE",
+			INVALID_OPERATION => "GL_INVALID_OPERATION",
+			INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+			OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+			STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+			STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+			_ => "unknown error",
+		};
+		post_event("gl", format!("{error} in {helper}"));
+	}
+}
+
 fn get_string(name: GLenum) -> String {
 	unsafe { str_from_gl(GetString(name)).to_string() }
 }
 
-fn get_extensions() -> HashSet<String> {
+fn get_integer(name: GLenum) -> GLint {
 	let mut data = MaybeUninit::uninit();
-	unsafe { GetIntegerv(NUM_EXTENSIONS, data.as_mut_ptr()); }
-	let num = unsafe { data.assume_init() } as u32;
+	unsafe { GetIntegerv(name, data.as_mut_ptr()); }
+	unsafe { data.assume_init() }
+}
+
+fn get_integer_indexed(name: GLenum, index: u32) -> GLint {
+	let mut data = MaybeUninit::uninit();
+	unsafe { GetIntegeri_v(name, index, data.as_mut_ptr()); }
+	unsafe { data.assume_init() }
+}
+
+fn get_extensions(indexed: bool) -> HashSet<String> {
+	if !indexed {
+		return get_string(EXTENSIONS).split_whitespace().map(str::to_string).collect();
+	}
+	let num = get_integer(NUM_EXTENSIONS) as u32;
 	let mut data = HashSet::with_capacity(num as usize);
 	for i in 0..num {
 		data.insert(str_from_gl(unsafe { GetStringi(EXTENSIONS, i as GLuint) }).to_string());
@@ -155,9 +476,11 @@ fn get_extensions() -> HashSet<String> {
 	data
 }
 
-static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d+)\.(\d+)").expect("invalid regex"));
+static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d+)\.(\d+)").expect("invalid regex"));
 
 /// Only parses the first two parts (major, minor) of the version string.
+///
+/// Versions of ES are prefixed, such as `OpenGL ES 3.2` and `OpenGL ES GLSL ES 3.20`.
 fn parse_version(version_str: &str) -> Version {
 	let caps = VERSION_REGEX.captures(version_str).expect("invalid version string");
 	Version::new(caps[1].parse().unwrap(), caps[2].parse().unwrap(), 0)
@@ -168,31 +491,84 @@ fn str_from_gl(string: *const GLubyte) -> &'static str {
 }
 
 pub(crate) fn clear_canvas() {
-	unsafe { Clear(COLOR_BUFFER_BIT) }
+	unsafe { Clear(COLOR_BUFFER_BIT); }
+	check_error("clear_canvas");
 }
 
 pub(crate) fn set_clear_color(color: (f32, f32, f32, f32)) {
-	unsafe { ClearColor(color.0, color.1, color.2, color.3) }
+	unsafe { ClearColor(color.0, color.1, color.2, color.3); }
+	check_error("set_clear_color");
+}
+
+/// Live GL objects created through this module, tracked for debugging.
+#[derive(Default)]
+struct ObjectStats {
+	/// Estimated bytes by texture
+	textures: HashMap<u32, usize>,
+	/// Bytes of the data store by buffer
+	buffers: HashMap<u32, usize>,
+	vaos: usize,
+	programs: usize,
+}
+
+static OBJECT_STATS: LazyLock<Mutex<ObjectStats>> = LazyLock::new(Default::default);
+
+fn object_stats() -> MutexGuard<'static, ObjectStats> {
+	OBJECT_STATS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(super) fn render_stats() -> RenderStats {
+	let stats = object_stats();
+	RenderStats {
+		textures: stats.textures.len(),
+		texture_bytes: stats.textures.values().sum(),
+		buffers: stats.buffers.len(),
+		buffer_bytes: stats.buffers.values().sum(),
+		vaos: stats.vaos,
+		programs: stats.programs,
+	}
+}
+
+/// Forgets all the objects, such as after the context is lost, of which the names are no longer valid.
+///
+/// The names of a lost context must not be deleted, as they may be reused by the new context;
+/// the helpers of this module ignore zero names, so owners may zero them before dropping.
+pub(super) fn forget_objects() {
+	*object_stats() = ObjectStats::default();
+	BOUND_PROGRAM.store(0, AtomicOrdering::Relaxed);
+}
+
+/// Records the size of the texture after its storage is (re)specified.
+///
+/// A full mipmap chain adds a third of the base level.
+pub(super) fn track_texture(texture: u32, bytes: usize, mipmaps: bool) {
+	let bytes = if mipmaps { bytes + bytes / 3 } else { bytes };
+	object_stats().textures.insert(texture, bytes);
 }
 
 /// Generate a single Buffer Object.
 pub(super) fn gen_buf_obj() -> u32 {
 	let mut bo = MaybeUninit::uninit();
 	unsafe { GenBuffers(1, bo.as_mut_ptr()); }
-	unsafe { bo.assume_init() }
+	let bo = unsafe { bo.assume_init() };
+	object_stats().buffers.insert(bo, 0);
+	bo
 }
 
 /// Generate multiple Buffer Objects at once for optimization.
 pub(super) fn gen_buf_objs<const N: usize>() -> [u32; N] {
 	let mut bos = MaybeUninit::uninit();
 	unsafe { GenBuffers(N as _, bos.as_mut_ptr() as *mut _); }
-	unsafe { bos.assume_init() }
+	let bos: [u32; N] = unsafe { bos.assume_init() };
+	object_stats().buffers.extend(bos.map(|v| (v, 0)));
+	bos
 }
 
 /// Generate a single Vertex Array Object.
 pub(super) fn gen_vert_arr_obj() -> u32 {
 	let mut vao = MaybeUninit::uninit();
 	unsafe { GenVertexArrays(1, vao.as_mut_ptr()); }
+	object_stats().vaos += 1;
 	unsafe { vao.assume_init() }
 }
 
@@ -200,9 +576,268 @@ pub(super) fn gen_vert_arr_obj() -> u32 {
 pub(super) fn gen_vert_arr_objs<const N: usize>() -> [u32; N] {
 	let mut vaos = MaybeUninit::uninit();
 	unsafe { GenVertexArrays(N as _, vaos.as_mut_ptr() as *mut _); }
+	object_stats().vaos += N;
 	unsafe { vaos.assume_init() }
 }
 
+/// Delete Buffer Objects; names which are zero or not of any buffer are silently ignored.
+pub(super) fn delete_buf_objs(bos: &[u32]) {
+	unsafe { DeleteBuffers(bos.len() as _, bos.as_ptr()); }
+	let mut stats = object_stats();
+	bos.iter().for_each(|v| { stats.buffers.remove(v); });
+	check_error("delete_buf_objs");
+}
+
+/// Delete a single Vertex Array Object; zero is ignored.
+pub(super) fn delete_vert_arr_obj(vao: u32) {
+	if vao == 0 {
+		return;
+	}
+	unsafe { DeleteVertexArrays(1, &vao); }
+	let mut stats = object_stats();
+	stats.vaos = stats.vaos.saturating_sub(1);
+	check_error("delete_vert_arr_obj");
+}
+
+/// Maps the canvas coordinates to the region `(x, y, width, height)` in pixels of the window.
+pub(super) fn set_viewport(rect: (i32, i32, u32, u32)) {
+	unsafe { Viewport(rect.0, rect.1, rect.2 as _, rect.3 as _); }
+	check_error("set_viewport");
+}
+
+/// Sets the viewport and clips all the following draws and clears to the region.
+pub(super) fn use_viewport_scissor(rect: (i32, i32, u32, u32)) {
+	set_viewport(rect);
+	unsafe { Enable(SCISSOR_TEST); }
+	unsafe { Scissor(rect.0, rect.1, rect.2 as _, rect.3 as _); }
+	check_error("use_viewport_scissor");
+}
+
+pub(super) fn disable_scissor() {
+	unsafe { Disable(SCISSOR_TEST); }
+	check_error("disable_scissor");
+}
+
+/// Sets the degree of anisotropic filtering of the bound texture, clamped to the supported maximum.
+///
+/// This is ignored if anisotropic filtering is not supported.
+pub(super) fn set_texture_anisotropy(target: GLenum, degree: f32) {
+	let max = f32::from_bits(MAX_ANISOTROPY.load(AtomicOrdering::Relaxed));
+	if max > 1.0 {
+		unsafe { TexParameterf(target, TEXTURE_MAX_ANISOTROPY_EXT, degree.clamp(1.0, max)); }
+	}
+	check_error("set_texture_anisotropy");
+}
+
+/// Sets the range of mipmap levels of the bound texture used for sampling.
+pub(super) fn set_texture_levels(target: GLenum, base: u32, max: u32) {
+	unsafe { TexParameteri(target, TEXTURE_BASE_LEVEL, base as _); }
+	unsafe { TexParameteri(target, TEXTURE_MAX_LEVEL, max as _); }
+	check_error("set_texture_levels");
+}
+
+/// Delete a single Texture Object.
+pub(super) fn delete_texture(texture: u32) {
+	unsafe { DeleteTextures(1, &texture); }
+	object_stats().textures.remove(&texture);
+	check_error("delete_texture");
+}
+
+/// Generate a single Texture Object without any storage.
+pub(super) fn gen_texture() -> u32 {
+	let mut texture = MaybeUninit::uninit();
+	unsafe { GenTextures(1, texture.as_mut_ptr()); }
+	unsafe { texture.assume_init() }
+}
+
+/// Replaces the whole content of the texture with RGBA pixels in rows from the bottom.
+pub(super) fn fill_texture_2d(texture: u32, size: (u32, u32), data: &[u8], options: &TextureOptions) {
+	unsafe { BindTexture(TEXTURE_2D, texture); }
+	set_texture_params(TEXTURE_2D, options);
+	unsafe {
+		TexImage2D(
+			TEXTURE_2D,
+			0,
+			RGBA as _,
+			size.0 as _,
+			size.1 as _,
+			0,
+			RGBA,
+			UNSIGNED_BYTE,
+			data.as_ptr() as *const _
+		);
+	}
+	track_texture(texture, data.len(), options.mipmaps);
+	if options.mipmaps {
+		unsafe { GenerateMipmap(TEXTURE_2D) }
+	}
+	check_error("fill_texture_2d");
+}
+
+/// Replaces the whole content of the texture array with RGBA pixels of the layers in order.
+pub(super) fn fill_texture_2d_array(texture: u32, size: (u32, u32), layers: u32, data: &[u8], options: &TextureOptions) {
+	unsafe { BindTexture(TEXTURE_2D_ARRAY, texture); }
+	set_texture_params(TEXTURE_2D_ARRAY, options);
+	unsafe {
+		TexImage3D(
+			TEXTURE_2D_ARRAY,
+			0,
+			RGBA as _,
+			size.0 as _,
+			size.1 as _,
+			layers as _,
+			0,
+			RGBA,
+			UNSIGNED_BYTE,
+			data.as_ptr() as *const _
+		);
+	}
+	track_texture(texture, data.len(), options.mipmaps);
+	if options.mipmaps {
+		unsafe { GenerateMipmap(TEXTURE_2D_ARRAY) }
+	}
+	check_error("fill_texture_2d_array");
+}
+
+/// Sets the sampling parameters of the texture bound to the target.
+pub(super) fn set_texture_params(target: GLenum, options: &TextureOptions) {
+	let wrap = match options.wrap {
+		TextureWrap::Clamp => CLAMP_TO_EDGE,
+		TextureWrap::Repeat => REPEAT,
+		TextureWrap::Mirror => MIRRORED_REPEAT,
+	};
+	let (min_filter, mag_filter) = match (options.filter, options.mipmaps) {
+		(TextureFilter::Nearest, true) => (NEAREST_MIPMAP_LINEAR, NEAREST),
+		(TextureFilter::Nearest, false) => (NEAREST, NEAREST),
+		(TextureFilter::Linear, true) => (LINEAR_MIPMAP_LINEAR, LINEAR),
+		(TextureFilter::Linear, false) => (LINEAR, LINEAR),
+	};
+	unsafe { TexParameteri(target, TEXTURE_WRAP_S, wrap as _); }
+	unsafe { TexParameteri(target, TEXTURE_WRAP_T, wrap as _); }
+	unsafe { TexParameteri(target, TEXTURE_MIN_FILTER, min_filter as _); }
+	unsafe { TexParameteri(target, TEXTURE_MAG_FILTER, mag_filter as _); }
+	set_texture_levels(target, options.levels.0, options.levels.1);
+	if options.anisotropy > 1.0 {
+		set_texture_anisotropy(target, options.anisotropy);
+	}
+}
+
+/// Generate a Framebuffer Object with an empty RGBA texture as the only color attachment.
+///
+/// Returns `(fbo, texture)`; the default framebuffer is bound afterward.
+pub(super) fn gen_framebuffer_with_texture(size: (u32, u32)) -> Result<(u32, u32), String> {
+	gen_framebuffer(size, RGBA, RGBA, UNSIGNED_BYTE)
+}
+
+/// Generate a Framebuffer Object with an empty single-channel unsigned integer texture,
+/// such as for object IDs.
+///
+/// Returns `(fbo, texture)`; the default framebuffer is bound afterward.
+pub(super) fn gen_framebuffer_with_uint_texture(size: (u32, u32)) -> Result<(u32, u32), String> {
+	gen_framebuffer(size, R32UI, RED_INTEGER, UNSIGNED_INT)
+}
+
+fn gen_framebuffer(size: (u32, u32), internal_format: GLenum, format: GLenum, kind: GLenum) -> Result<(u32, u32), String> {
+	let mut texture = MaybeUninit::uninit();
+	unsafe { GenTextures(1, texture.as_mut_ptr()); }
+	let texture = unsafe { texture.assume_init() };
+	unsafe { BindTexture(TEXTURE_2D, texture); }
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_WRAP_S, CLAMP_TO_EDGE as _); }
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_WRAP_T, CLAMP_TO_EDGE as _); }
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MIN_FILTER, NEAREST as _); }
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MAG_FILTER, NEAREST as _); }
+	unsafe { TexImage2D(TEXTURE_2D, 0, internal_format as _, size.0 as _, size.1 as _, 0, format, kind, null()); }
+	// Both RGBA and R32UI take 4 bytes per pixel.
+	track_texture(texture, size.0 as usize * size.1 as usize * 4, false);
+	let mut fbo = MaybeUninit::uninit();
+	unsafe { GenFramebuffers(1, fbo.as_mut_ptr()); }
+	let fbo = unsafe { fbo.assume_init() };
+	unsafe { BindFramebuffer(FRAMEBUFFER, fbo); }
+	unsafe { FramebufferTexture2D(FRAMEBUFFER, COLOR_ATTACHMENT0, TEXTURE_2D, texture, 0); }
+	let status = unsafe { CheckFramebufferStatus(FRAMEBUFFER) };
+	unsafe { BindFramebuffer(FRAMEBUFFER, 0); }
+	if status != FRAMEBUFFER_COMPLETE {
+		delete_framebuffer(fbo);
+		delete_texture(texture);
+		return Err(format!("Incomplete framebuffer: {status:#x}"));
+	}
+	check_error("gen_framebuffer");
+	Ok((fbo, texture))
+}
+
+/// Clears the single-channel unsigned integer color attachment of the bound framebuffer.
+pub(super) fn clear_framebuffer_uint(value: u32) {
+	let data = [value, 0, 0, 0];
+	unsafe { ClearBufferuiv(COLOR, 0, data.as_ptr()); }
+	check_error("clear_framebuffer_uint");
+}
+
+/// Reads a pixel of the single-channel unsigned integer color attachment of the framebuffer.
+///
+/// Coordinates have the origin at the bottom-left.
+pub(super) fn read_pixel_uint(fbo: u32, x: i32, y: i32) -> u32 {
+	let mut value = 0;
+	unsafe { BindFramebuffer(READ_FRAMEBUFFER, fbo); }
+	unsafe { ReadPixels(x, y, 1, 1, RED_INTEGER, UNSIGNED_INT, &mut value as *mut u32 as *mut _); }
+	unsafe { BindFramebuffer(READ_FRAMEBUFFER, 0); }
+	check_error("read_pixel_uint");
+	value
+}
+
+/// Generate a single-channel unsigned integer texture of `size.0 * size.1` texels,
+/// sampled only by `texelFetch`, such as for IDs.
+pub(super) fn gen_uint_texture(size: (u32, u32), data: &[u32]) -> u32 {
+	gen_data_texture(size, R32UI, RED_INTEGER, UNSIGNED_INT, data)
+}
+
+/// Generate an RGBA float texture of `size.0 * size.1` texels, sampled only by `texelFetch`,
+/// such as for lookup tables.
+pub(super) fn gen_vec_4_texture(size: (u32, u32), data: &[f32]) -> u32 {
+	gen_data_texture(size, RGBA32F, RGBA, FLOAT, data)
+}
+
+fn gen_data_texture<T: Number>(size: (u32, u32), internal_format: GLenum, format: GLenum, kind: GLenum, data: &[T]) -> u32 {
+	let mut texture = MaybeUninit::uninit();
+	unsafe { GenTextures(1, texture.as_mut_ptr()); }
+	let texture = unsafe { texture.assume_init() };
+	unsafe { BindTexture(TEXTURE_2D, texture); }
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MIN_FILTER, NEAREST as _); }
+	unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MAG_FILTER, NEAREST as _); }
+	unsafe { TexImage2D(TEXTURE_2D, 0, internal_format as _, size.0 as _, size.1 as _, 0, format, kind, data.as_ptr() as _); }
+	track_texture(texture, size_of_val(data), false);
+	check_error("gen_data_texture");
+	texture
+}
+
+/// Updates a texel of a texture by [gen_uint_texture].
+pub(super) fn update_uint_texel(texture: u32, x: u32, y: u32, value: u32) {
+	unsafe { BindTexture(TEXTURE_2D, texture); }
+	unsafe { TexSubImage2D(TEXTURE_2D, 0, x as _, y as _, 1, 1, RED_INTEGER, UNSIGNED_INT, &value as *const u32 as *const _); }
+	check_error("update_uint_texel");
+}
+
+/// Binds the texture to the texture unit, such as for a sampler other than the first one.
+///
+/// Unit zero is left active afterward, as expected by the other texture calls.
+pub(super) fn use_texture_2d_at(unit: u32, texture: u32) {
+	unsafe { ActiveTexture(TEXTURE0 + unit) }
+	unsafe { BindTexture(TEXTURE_2D, texture); }
+	unsafe { ActiveTexture(TEXTURE0); }
+	check_error("use_texture_2d_at");
+}
+
+/// Zero binds the default framebuffer of the window.
+pub(super) fn use_framebuffer(fbo: u32) {
+	unsafe { BindFramebuffer(FRAMEBUFFER, fbo); }
+	check_error("use_framebuffer");
+}
+
+/// Delete a single Framebuffer Object; the attachments are not deleted.
+pub(super) fn delete_framebuffer(fbo: u32) {
+	unsafe { DeleteFramebuffers(1, &fbo); }
+	check_error("delete_framebuffer");
+}
+
 pub(super) trait Number : Num + Bounded {}
 
 impl<T: Num + Bounded> Number for T {}
@@ -252,6 +887,113 @@ impl NumType {
 pub(super) fn buf_obj_with_data<T: Number>(target: GLenum, buffer: u32, data: &[T], usage: GLenum) {
 	unsafe { BindBuffer(target, buffer); }
 	unsafe { BufferData(target, size_of_val(data) as _, data.as_ptr() as _, usage); }
+	object_stats().buffers.insert(buffer, size_of_val(data));
+	check_error("buf_obj_with_data");
+}
+
+/// Replaces a part of the data of the buffer; `offset` is in bytes.
+pub(super) fn update_buf_obj<T: Number>(target: GLenum, buffer: u32, offset: usize, data: &[T]) {
+	unsafe { BindBuffer(target, buffer); }
+	unsafe { BufferSubData(target, offset as _, size_of_val(data) as _, data.as_ptr() as _); }
+	check_error("update_buf_obj");
+}
+
+/// Creates a Uniform Buffer Object of the size in bytes, bound to the binding point.
+pub(super) fn gen_uniform_buf_obj(size: usize, binding: u32) -> u32 {
+	let bo = gen_buf_obj();
+	unsafe { BindBuffer(UNIFORM_BUFFER, bo); }
+	unsafe { BufferData(UNIFORM_BUFFER, size as _, null(), DYNAMIC_DRAW); }
+	unsafe { BindBufferBase(UNIFORM_BUFFER, binding, bo); }
+	object_stats().buffers.insert(bo, size);
+	check_error("gen_uniform_buf_obj");
+	bo
+}
+
+/// `offset` is in bytes.
+pub(super) fn update_uniform_buf_obj<T: Number>(buffer: u32, offset: usize, data: &[T]) {
+	unsafe { BindBuffer(UNIFORM_BUFFER, buffer); }
+	unsafe { BufferSubData(UNIFORM_BUFFER, offset as _, size_of_val(data) as _, data.as_ptr() as _); }
+	check_error("update_uniform_buf_obj");
+}
+
+/// Creates a Shader Storage Buffer Object with the initial data.
+pub(super) fn gen_storage_buf_obj<T: Number>(data: &[T]) -> u32 {
+	let bo = gen_buf_obj();
+	buf_obj_with_data(SHADER_STORAGE_BUFFER, bo, data, DYNAMIC_DRAW);
+	bo
+}
+
+/// Reads a part of the data of the buffer into `data`; `offset` is in bytes.
+pub(super) fn read_buf_obj<T: Number>(target: GLenum, buffer: u32, offset: usize, data: &mut [T]) {
+	unsafe { BindBuffer(target, buffer); }
+	unsafe { GetBufferSubData(target, offset as _, size_of_val(data) as _, data.as_mut_ptr() as _); }
+	check_error("read_buf_obj");
+}
+
+/// Binds the Shader Storage Buffer Object to the binding point declared in compute shaders.
+pub(super) fn bind_storage_buf_obj(buffer: u32, binding: u32) {
+	unsafe { BindBufferBase(SHADER_STORAGE_BUFFER, binding, buffer); }
+	check_error("bind_storage_buf_obj");
+}
+
+/// After `use_program`; all the writes are made visible to the following commands.
+pub(super) fn dispatch_compute(groups: (u32, u32, u32)) {
+	unsafe { DispatchCompute(groups.0, groups.1, groups.2); }
+	unsafe { MemoryBarrier(ALL_BARRIER_BITS); }
+	check_error("dispatch_compute");
+}
+
+pub(super) fn gen_queries<const N: usize>() -> [u32; N] {
+	let mut queries = MaybeUninit::uninit();
+	unsafe { GenQueries(N as _, queries.as_mut_ptr() as *mut _); }
+	check_error("gen_queries");
+	unsafe { queries.assume_init() }
+}
+
+pub(super) fn delete_queries(queries: &[u32]) {
+	unsafe { DeleteQueries(queries.len() as _, queries.as_ptr()); }
+	check_error("delete_queries");
+}
+
+/// Only one query of the elapsed time may be active at a time.
+pub(super) fn begin_time_query(query: u32) {
+	unsafe { BeginQuery(TIME_ELAPSED, query); }
+	check_error("begin_time_query");
+}
+
+pub(super) fn end_time_query() {
+	unsafe { EndQuery(TIME_ELAPSED); }
+	check_error("end_time_query");
+}
+
+/// Returns the result in nanoseconds if available, without waiting for the GPU.
+pub(super) fn poll_time_query(query: u32) -> Option<u64> {
+	let mut available = MaybeUninit::uninit();
+	unsafe { GetQueryObjectuiv(query, QUERY_RESULT_AVAILABLE, available.as_mut_ptr()); }
+	if unsafe { available.assume_init() } == 0 {
+		check_error("poll_time_query");
+		return None;
+	}
+	let mut result = MaybeUninit::uninit();
+	unsafe { GetQueryObjectui64v(query, QUERY_RESULT, result.as_mut_ptr()); }
+	check_error("poll_time_query");
+	Some(unsafe { result.assume_init() })
+}
+
+/// Binds the uniform block of the name to the binding point, returning whether the program
+/// declares the block; always `false` without UBO support.
+pub(super) fn bind_uniform_block(program: u32, name: &str, binding: u32) -> bool {
+	if !UBO_SUPPORTED.load(AtomicOrdering::Relaxed) {
+		return false;
+	}
+	let name = str_to_c(name);
+	let index = unsafe { GetUniformBlockIndex(program, name.as_ptr()) };
+	if index == INVALID_INDEX {
+		return false;
+	}
+	unsafe { UniformBlockBinding(program, index, binding); }
+	check_error("bind_uniform_block");
+	true
 }
 
 /// Defines an array of Vertex Attribute. Normalized is not applied.
@@ -267,6 +1009,7 @@ pub(super) fn vert_attr_arr(i: u32, vec_size: usize, kind: NumType, stride_len:
 			(offset_len * kind.size()) as _,
 		);
 	}
+	check_error("vert_attr_arr");
 }
 
 pub(super) enum VertexAttrVariant {
@@ -367,11 +1110,13 @@ impl VertexAttrVariant {
 pub(super) fn vert_attr(i: u32, data: VertexAttrVariant) {
 	unsafe { DisableVertexAttribArray(i); }
 	data.invoke_gl(i);
+	check_error("vert_attr");
 }
 
 pub(super) fn with_new_vert_arr() -> u32 {
 	let vao = gen_vert_arr_obj();
 	unsafe { BindVertexArray(vao); }
+	check_error("with_new_vert_arr");
 	vao
 }
 
@@ -381,6 +1126,7 @@ pub(super) fn str_to_c(str: impl AsRef<str>) -> CString {
 	CString::new(str).expect("Cannot create CString")
 }
 
+#[derive(Clone, Copy)]
 pub(super) enum ShaderType {
 	Vertex,
 	Fragment,
@@ -403,10 +1149,23 @@ impl ShaderType {
 			}
 		}
 	}
+
+	fn stage_name(&self) -> &'static str {
+		match self {
+			ShaderType::Vertex => "vertex",
+			ShaderType::Fragment => "fragment",
+			ShaderType::Compute => "compute",
+			ShaderType::Geometry => "geometry",
+			ShaderType::TessControl => "tessellation control",
+			ShaderType::TessEvaluation => "tessellation evaluation",
+		}
+	}
 }
 
-/// `src` should not contain any `\0` char.
-pub(super) fn compile_shader(src: String, kind: ShaderType) -> Result<u32, String> {
+/// `src` should not contain any `\0` char; `name` identifies the source in errors, such as its path.
+///
+/// The shader is deleted if the compilation fails.
+pub(super) fn compile_shader(src: String, kind: ShaderType, name: &str) -> Result<u32, String> {
 	let shader = kind.invoke_gl();
 	let src = str_to_c(src);
 	unsafe { ShaderSource(shader, 1, &src.as_ptr(), null()); }
@@ -414,20 +1173,68 @@ pub(super) fn compile_shader(src: String, kind: ShaderType) -> Result<u32, Strin
 	let mut status = MaybeUninit::uninit();
 	unsafe { GetShaderiv(shader, COMPILE_STATUS, status.as_mut_ptr()); }
 	if unsafe { status.assume_init() } == FALSE as i32 {
-		let out = CString::default().into_raw();
-		unsafe { GetShaderInfoLog(shader, COMPILE_STATUS as _, null_mut(), out); }
-		let out = unsafe { CString::from_raw(out) };
-		return Err(out.to_str().expect("Invalid UTF-8 CString").to_string());
+		let log = info_log(shader, GetShaderiv, GetShaderInfoLog);
+		unsafe { DeleteShader(shader); }
+		return Err(format!("Cannot compile {} shader {name}:\n{log}", kind.stage_name()));
 	}
+	check_error("compile_shader");
 	Ok(shader)
 }
 
-pub(super) fn new_shader_program<const N: usize>(shaders: [u32; N]) -> u32 {
+pub(super) fn delete_shader(shader: u32) {
+	unsafe { DeleteShader(shader); }
+}
+
+/// The shaders are deleted regardless of the result, and so is the program if the linking fails.
+/// `attributes` are bound to the locations of their indices, for shaders without `layout` qualifiers.
+pub(super) fn new_shader_program(shaders: &[u32], attributes: &[&str]) -> Result<u32, String> {
 	let program = unsafe { CreateProgram() };
 	shaders.iter().for_each(|s| unsafe { AttachShader(program, *s) });
+	for (i, name) in attributes.iter().enumerate() {
+		unsafe { BindAttribLocation(program, i as _, str_to_c(name).as_ptr()); }
+	}
 	unsafe { LinkProgram(program); }
-	shaders.into_iter().for_each(|s| unsafe { DeleteShader(s) });
-	program
+	shaders.iter().for_each(|s| unsafe { DeleteShader(*s) });
+	let mut status = MaybeUninit::uninit();
+	unsafe { GetProgramiv(program, LINK_STATUS, status.as_mut_ptr()); }
+	if unsafe { status.assume_init() } == FALSE as i32 {
+		let log = info_log(program, GetProgramiv, GetProgramInfoLog);
+		unsafe { DeleteProgram(program); }
+		return Err(format!("Cannot link program:\n{log}"));
+	}
+	object_stats().programs += 1;
+	check_error("new_shader_program");
+	Ok(program)
+}
+
+/// Returns the whole info log of the shader or program, using the respective GL functions.
+fn info_log(
+	object: u32,
+	get_iv: unsafe fn(GLuint, GLenum, *mut GLint),
+	get_log: unsafe fn(GLuint, GLsizei, *mut GLsizei, *mut GLchar),
+) -> String {
+	let mut len = MaybeUninit::uninit();
+	unsafe { get_iv(object, INFO_LOG_LENGTH, len.as_mut_ptr()); }
+	let len = unsafe { len.assume_init() }.max(1);
+	let mut buf = vec![0u8; len as usize];
+	let mut written = MaybeUninit::uninit();
+	unsafe { get_log(object, len, written.as_mut_ptr(), buf.as_mut_ptr() as *mut GLchar); }
+	buf.truncate(unsafe { written.assume_init() }.max(0) as usize);
+	String::from_utf8_lossy(&buf).trim_end().to_string()
+}
+
+/// The program is unbound first if it is being used; zero is ignored.
+pub(super) fn delete_program(program: u32) {
+	if program == 0 {
+		return;
+	}
+	if BOUND_PROGRAM.compare_exchange(program, 0, AtomicOrdering::Relaxed, AtomicOrdering::Relaxed).is_ok() {
+		unsafe { UseProgram(0); }
+	}
+	unsafe { DeleteProgram(program); }
+	let mut stats = object_stats();
+	stats.programs = stats.programs.saturating_sub(1);
+	check_error("delete_program");
 }
 
 pub(super) fn get_uniform_location(program: u32, name: &str) -> u32 {
@@ -437,26 +1244,272 @@ pub(super) fn get_uniform_location(program: u32, name: &str) -> u32 {
 
 pub(super) fn use_program(program: u32) {
 	unsafe { UseProgram(program); }
+	BOUND_PROGRAM.store(program, AtomicOrdering::Relaxed);
+	check_error("use_program");
 }
 
 /// After `use_program`
 pub(super) fn use_texture_2d(texture: u32) {
 	unsafe { ActiveTexture(TEXTURE0) }
 	unsafe { BindTexture(TEXTURE_2D, texture); }
+	check_error("use_texture_2d");
+}
+
+/// After `use_program`
+pub(super) fn use_texture_2d_array(texture: u32) {
+	unsafe { ActiveTexture(TEXTURE0) }
+	unsafe { BindTexture(TEXTURE_2D_ARRAY, texture); }
+	check_error("use_texture_2d_array");
 }
 
 pub(super) fn use_vao(vao: u32) {
 	unsafe { BindVertexArray(vao); }
+	check_error("use_vao");
 }
 
 pub(super) fn use_uniform_mat_4(i: u32, mat: &TMat4<f32>) {
 	unsafe { UniformMatrix4fv(i as _, 1, FALSE, mat.as_ptr()); }
+	check_error("use_uniform_mat_4");
+}
+
+pub(super) fn use_uniform_vec_4(i: u32, vec: &TVec4<f32>) {
+	unsafe { Uniform4fv(i as _, 1, vec.as_ptr()); }
+	check_error("use_uniform_vec_4");
+}
+
+pub(super) fn use_uniform_uint(i: u32, value: u32) {
+	unsafe { Uniform1ui(i as _, value); }
+	check_error("use_uniform_uint");
+}
+
+pub(super) fn use_uniform_float(i: u32, value: f32) {
+	unsafe { Uniform1f(i as _, value); }
+	check_error("use_uniform_float");
+}
+
+pub(super) fn use_uniform_int(i: u32, value: i32) {
+	unsafe { Uniform1i(i as _, value); }
+	check_error("use_uniform_int");
+}
+
+pub(super) fn use_uniform_vec_2(i: u32, vec: &TVec2<f32>) {
+	unsafe { Uniform2fv(i as _, 1, vec.as_ptr()); }
+	check_error("use_uniform_vec_2");
 }
 
 pub(super) fn draw_arrays(mode: GLenum, count: u32) {
-	unsafe { DrawArrays(mode, 0, count as _) }
+	unsafe { DrawArrays(mode, 0, count as _); }
+	check_error("draw_arrays");
 }
 
 pub(super) fn draw_elements(mode: GLenum, count: u32) {
-	unsafe { DrawElements(mode, count as _, UNSIGNED_INT, 0 as _) }
+	unsafe { DrawElements(mode, count as _, UNSIGNED_INT, 0 as _); }
+	check_error("draw_elements");
+}
+
+impl Topology {
+	fn gl_mode(self) -> GLenum {
+		match self {
+			Topology::Lines => LINES,
+			Topology::Points => POINTS,
+			Topology::Triangles => TRIANGLES,
+		}
+	}
+}
+
+impl BufferTarget {
+	fn gl_target(self) -> GLenum {
+		match self {
+			BufferTarget::Vertex => ARRAY_BUFFER,
+			BufferTarget::Index => ELEMENT_ARRAY_BUFFER,
+		}
+	}
+}
+
+impl BufferUsage {
+	fn gl_usage(self) -> GLenum {
+		match self {
+			BufferUsage::Static => STATIC_DRAW,
+			BufferUsage::Dynamic => DYNAMIC_DRAW,
+		}
+	}
+}
+
+/// The OpenGL backend, through the helpers of this module.
+pub(super) struct GlBackend;
+
+impl RenderBackend for GlBackend {
+	type Context = GLHandle;
+
+	fn gen_buf_obj() -> u32 {
+		gen_buf_obj()
+	}
+
+	fn gen_buf_objs<const N: usize>() -> [u32; N] {
+		gen_buf_objs()
+	}
+
+	fn buf_obj_with_data<T: Number>(target: BufferTarget, buffer: u32, data: &[T], usage: BufferUsage) {
+		buf_obj_with_data(target.gl_target(), buffer, data, usage.gl_usage());
+	}
+
+	fn update_buf_obj<T: Number>(target: BufferTarget, buffer: u32, offset: usize, data: &[T]) {
+		update_buf_obj(target.gl_target(), buffer, offset, data);
+	}
+
+	fn delete_buf_objs(buffers: &[u32]) {
+		delete_buf_objs(buffers);
+	}
+
+	fn gen_uniform_buf_obj(size: usize, binding: u32) -> u32 {
+		gen_uniform_buf_obj(size, binding)
+	}
+
+	fn update_uniform_buf_obj<T: Number>(buffer: u32, offset: usize, data: &[T]) {
+		update_uniform_buf_obj(buffer, offset, data);
+	}
+
+	fn with_new_vert_arr() -> u32 {
+		with_new_vert_arr()
+	}
+
+	fn delete_vert_arr_obj(vao: u32) {
+		delete_vert_arr_obj(vao);
+	}
+
+	fn use_vao(vao: u32) {
+		use_vao(vao);
+	}
+
+	fn vert_attr_arr(i: u32, vec_size: usize, kind: NumType, stride_len: usize, offset_len: usize) {
+		vert_attr_arr(i, vec_size, kind, stride_len, offset_len);
+	}
+
+	fn vert_attr(i: u32, data: VertexAttrVariant) {
+		vert_attr(i, data);
+	}
+
+	fn draw_arrays(mode: Topology, count: u32) {
+		draw_arrays(mode.gl_mode(), count);
+	}
+
+	fn draw_elements(mode: Topology, count: u32) {
+		draw_elements(mode.gl_mode(), count);
+	}
+
+	fn clear_canvas() {
+		clear_canvas();
+	}
+
+	fn set_clear_color(color: (f32, f32, f32, f32)) {
+		set_clear_color(color);
+	}
+
+	fn set_viewport(rect: (i32, i32, u32, u32)) {
+		set_viewport(rect);
+	}
+
+	fn use_viewport_scissor(rect: (i32, i32, u32, u32)) {
+		use_viewport_scissor(rect);
+	}
+
+	fn disable_scissor() {
+		disable_scissor();
+	}
+
+	fn gen_texture() -> u32 {
+		gen_texture()
+	}
+
+	fn fill_texture_2d(texture: u32, size: (u32, u32), data: &[u8], options: &TextureOptions) {
+		fill_texture_2d(texture, size, data, options);
+	}
+
+	fn fill_texture_2d_array(texture: u32, size: (u32, u32), layers: u32, data: &[u8], options: &TextureOptions) {
+		fill_texture_2d_array(texture, size, layers, data, options);
+	}
+
+	fn gen_uint_texture(size: (u32, u32), data: &[u32]) -> u32 {
+		gen_uint_texture(size, data)
+	}
+
+	fn gen_vec_4_texture(size: (u32, u32), data: &[f32]) -> u32 {
+		gen_vec_4_texture(size, data)
+	}
+
+	fn update_uint_texel(texture: u32, x: u32, y: u32, value: u32) {
+		update_uint_texel(texture, x, y, value);
+	}
+
+	fn delete_texture(texture: u32) {
+		delete_texture(texture);
+	}
+
+	fn use_texture_2d(texture: u32) {
+		use_texture_2d(texture);
+	}
+
+	fn use_texture_2d_array(texture: u32) {
+		use_texture_2d_array(texture);
+	}
+
+	fn use_texture_2d_at(unit: u32, texture: u32) {
+		use_texture_2d_at(unit, texture);
+	}
+
+	fn compile_shader(src: String, kind: ShaderType, name: &str) -> Result<u32, String> {
+		compile_shader(src, kind, name)
+	}
+
+	fn delete_shader(shader: u32) {
+		delete_shader(shader);
+	}
+
+	fn new_shader_program(shaders: &[u32], attributes: &[&str]) -> Result<u32, String> {
+		new_shader_program(shaders, attributes)
+	}
+
+	fn delete_program(program: u32) {
+		delete_program(program);
+	}
+
+	fn use_program(program: u32) {
+		use_program(program);
+	}
+
+	fn get_uniform_location(program: u32, name: &str) -> u32 {
+		get_uniform_location(program, name)
+	}
+
+	fn bind_uniform_block(program: u32, name: &str, binding: u32) -> bool {
+		bind_uniform_block(program, name, binding)
+	}
+
+	fn use_uniform_mat_4(i: u32, mat: &TMat4<f32>) {
+		use_uniform_mat_4(i, mat);
+	}
+
+	fn use_uniform_vec_4(i: u32, vec: &TVec4<f32>) {
+		use_uniform_vec_4(i, vec);
+	}
+
+	fn use_uniform_vec_2(i: u32, vec: &TVec2<f32>) {
+		use_uniform_vec_2(i, vec);
+	}
+
+	fn use_uniform_uint(i: u32, value: u32) {
+		use_uniform_uint(i, value);
+	}
+
+	fn use_uniform_float(i: u32, value: f32) {
+		use_uniform_float(i, value);
+	}
+
+	fn use_uniform_int(i: u32, value: i32) {
+		use_uniform_int(i, value);
+	}
+
+	fn render_stats() -> RenderStats {
+		render_stats()
+	}
 }
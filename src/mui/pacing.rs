@@ -0,0 +1,112 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Frame limiting with steady frame pacing.
+//!
+//! Sleeping alone overshoots by up to the scheduler granularity of the OS, which is visible as
+//! jitter, so the pacer sleeps until shortly before the deadline and spins for the rest.
+//! Deadlines advance by whole frame times rather than from the end of each wait, so that an
+//! overshoot is absorbed by the next frame instead of drifting the frame rate.
+
+use crate::FerriciaResult;
+use std::hint::spin_loop;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+pub(super) struct FramePacer {
+	/// `None` if unlimited
+	frame_time: Option<Duration>,
+	/// Time before the deadline to spin instead of sleeping
+	spin_threshold: Duration,
+	deadline: Option<Instant>,
+}
+
+impl FramePacer {
+	pub(super) fn new() -> Self {
+		Self { frame_time: None, spin_threshold: Duration::ZERO, deadline: None }
+	}
+
+	/// `fps` of `0` removes the limit; a zero `spin_threshold` only sleeps, saving power.
+	pub(super) fn set_limit(&mut self, fps: u32, spin_threshold: Duration) -> FerriciaResult<()> {
+		let frame_time = (fps > 0).then(|| Duration::from_secs(1) / fps);
+		if frame_time.is_some_and(|v| spin_threshold > v) {
+			return Err("Spin threshold must not exceed the frame time".to_string().into());
+		}
+		self.frame_time = frame_time;
+		self.spin_threshold = spin_threshold;
+		self.deadline = None;
+		Ok(())
+	}
+
+	/// Waits until the end of the current frame; called once per frame, such as before swapping.
+	pub(super) fn wait(&mut self) {
+		let Some(frame_time) = self.frame_time else {
+			return;
+		};
+		let now = Instant::now();
+		let Some(deadline) = self.deadline else {
+			// The first frame only starts the schedule.
+			self.deadline = Some(now + frame_time);
+			return;
+		};
+		if now < deadline {
+			let remaining = deadline - now;
+			if remaining > self.spin_threshold {
+				sleep(remaining - self.spin_threshold);
+			}
+			while Instant::now() < deadline {
+				spin_loop();
+			}
+		}
+		// Frames later than a whole frame time restart the schedule, rather than rushing
+		// the following frames to catch up.
+		let next = deadline + frame_time;
+		self.deadline = Some(if next < now { now + frame_time } else { next });
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rejects_spin_thresholds_beyond_the_frame_time() {
+		let mut pacer = FramePacer::new();
+		assert!(pacer.set_limit(100, Duration::from_millis(20)).is_err());
+		assert!(pacer.set_limit(100, Duration::from_millis(2)).is_ok());
+		assert!(pacer.set_limit(0, Duration::from_secs(1)).is_ok());
+	}
+
+	#[test]
+	fn never_waits_without_limits() {
+		let mut pacer = FramePacer::new();
+		pacer.wait();
+		pacer.wait();
+		assert!(pacer.deadline.is_none());
+	}
+
+	#[test]
+	fn paces_frames_by_whole_frame_times() {
+		let mut pacer = FramePacer::new();
+		pacer.set_limit(100, Duration::from_millis(1)).unwrap();
+		pacer.wait(); // Starts the schedule
+		let start = Instant::now();
+		pacer.wait();
+		pacer.wait();
+		assert!(start.elapsed() >= Duration::from_millis(19));
+	}
+
+	#[test]
+	fn restarts_the_schedule_after_late_frames() {
+		let mut pacer = FramePacer::new();
+		pacer.set_limit(100, Duration::ZERO).unwrap();
+		pacer.wait();
+		sleep(Duration::from_millis(35));
+		pacer.wait();
+		let deadline = pacer.deadline.unwrap();
+		let now = Instant::now();
+		assert!(deadline > now && deadline - now <= Duration::from_millis(10));
+	}
+}
@@ -0,0 +1,80 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Pixel-accurate picking by reading back IDs drawn into an offscreen target.
+//!
+//! Drawables are drawn again with a [PickProgram](crate::mui::rendering::PickProgram)
+//! between [PickBuffer::begin] and [PickBuffer::end], only when picking is needed.
+
+use crate::mui::ogl::{clear_framebuffer_uint, delete_framebuffer, delete_texture, gen_framebuffer_with_uint_texture, read_pixel_uint, use_framebuffer};
+use crate::FerriciaResult;
+
+pub(crate) struct PickBuffer {
+	/// `(fbo, texture)`
+	target: Option<(u32, u32)>,
+	size: (u32, u32),
+}
+
+impl PickBuffer {
+	pub(crate) fn new() -> Self {
+		Self { target: None, size: (0, 0) }
+	}
+
+	/// Binds and clears the buffer, which is recreated if the canvas has been resized.
+	pub(crate) fn begin(&mut self, size: (u32, u32)) -> FerriciaResult<()> {
+		self.resize(size)?;
+		use_framebuffer(self.target.expect("should be created").0);
+		clear_framebuffer_uint(0);
+		Ok(())
+	}
+
+	/// Creates the buffer for the canvas size, unless already created at the size.
+	pub(crate) fn resize(&mut self, size: (u32, u32)) -> FerriciaResult<()> {
+		if self.target.is_none() || self.size != size {
+			self.release();
+			self.target = Some(gen_framebuffer_with_uint_texture(size)?);
+			self.size = size;
+		}
+		Ok(())
+	}
+
+	/// Creates the buffer again on a new context, such as after the previous one is lost,
+	/// if it has been created.
+	pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+		// The objects of the lost context must not be deleted.
+		if self.target.take().is_some() {
+			self.resize(self.size)?;
+		}
+		Ok(())
+	}
+
+	/// Restores drawing to the canvas.
+	pub(crate) fn end(&self) {
+		use_framebuffer(0);
+	}
+
+	/// Returns the ID drawn at the canvas coordinates with the origin at the bottom-left,
+	/// or zero if nothing has been drawn there.
+	pub(crate) fn pick(&self, x: i32, y: i32) -> u32 {
+		match self.target {
+			Some((fbo, _)) if x >= 0 && y >= 0 && (x as u32) < self.size.0 && (y as u32) < self.size.1 =>
+				read_pixel_uint(fbo, x, y),
+			_ => 0,
+		}
+	}
+
+	fn release(&mut self) {
+		if let Some((fbo, texture)) = self.target.take() {
+			delete_framebuffer(fbo);
+			delete_texture(texture);
+		}
+	}
+}
+
+impl Drop for PickBuffer {
+	fn drop(&mut self) {
+		self.release();
+	}
+}
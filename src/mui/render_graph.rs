@@ -0,0 +1,285 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Render graph ordering passes by their declared render targets.
+//!
+//! Passes only declare the names of the targets they read and write; the draws of each pass are
+//! still issued by the caller between [RenderGraph::begin_pass] and [RenderGraph::end_pass].
+//! All the targets have the size of the canvas, so a physical target is reused by a later pass
+//! as soon as all the readers of its previous content have finished.
+
+use crate::mui::ogl::{clear_canvas, delete_framebuffer, delete_texture, gen_framebuffer_with_texture, use_framebuffer};
+use crate::FerriciaResult;
+use std::collections::HashMap;
+
+/// A framebuffer with a texture as the color attachment.
+pub(super) struct RenderTarget {
+	pub(super) fbo: u32,
+	pub(super) texture: u32,
+}
+
+impl RenderTarget {
+	pub(super) fn new(size: (u32, u32)) -> FerriciaResult<Self> {
+		let (fbo, texture) = gen_framebuffer_with_texture(size)?;
+		Ok(Self { fbo, texture })
+	}
+
+	/// Creates the objects again on a new context, such as after the previous one is lost,
+	/// of which the objects must not be deleted even if this fails.
+	pub(super) fn recreate(&mut self, size: (u32, u32)) -> FerriciaResult<()> {
+		(self.fbo, self.texture) = (0, 0);
+		(self.fbo, self.texture) = gen_framebuffer_with_texture(size)?;
+		Ok(())
+	}
+}
+
+impl Drop for RenderTarget {
+	fn drop(&mut self) {
+		delete_framebuffer(self.fbo);
+		delete_texture(self.texture);
+	}
+}
+
+struct RenderPass {
+	name: String,
+	inputs: Vec<String>,
+	/// Draws to the canvas if `None`.
+	output: Option<String>,
+}
+
+pub(crate) struct RenderGraph {
+	passes: Vec<RenderPass>,
+	/// Indices of passes in execution order; empty if not compiled.
+	order: Vec<usize>,
+	targets: Vec<RenderTarget>,
+	/// Physical target index by target name
+	bindings: HashMap<String, usize>,
+	size: (u32, u32),
+}
+
+impl RenderGraph {
+	pub(crate) fn new() -> Self {
+		Self {
+			passes: Vec::new(),
+			order: Vec::new(),
+			targets: Vec::new(),
+			bindings: HashMap::new(),
+			size: (0, 0),
+		}
+	}
+
+	/// Returns the index of the pass; this invalidates the compiled graph.
+	pub(crate) fn add_pass(&mut self, name: String, inputs: Vec<String>, output: Option<String>) -> usize {
+		self.passes.push(RenderPass { name, inputs, output });
+		self.order.clear();
+		self.passes.len() - 1
+	}
+
+	/// Orders the passes and allocates the targets for the canvas size.
+	///
+	/// Passes independent of each other keep the order of declaration.
+	/// Returns the indices of passes in execution order.
+	pub(crate) fn compile(&mut self, size: (u32, u32)) -> FerriciaResult<&[usize]> {
+		let allocated = self.schedule()?;
+		if self.size != size {
+			self.targets.clear();
+			self.size = size;
+		}
+		self.targets.truncate(allocated);
+		while self.targets.len() < allocated {
+			self.targets.push(RenderTarget::new(size)?);
+		}
+		Ok(&self.order)
+	}
+
+	/// Creates the targets again on a new context, such as after the previous one is lost.
+	pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+		let size = self.size;
+		self.targets.iter_mut().try_for_each(|v| v.recreate(size))
+	}
+
+	/// Orders the passes and binds the targets to physical ones, without allocating them.
+	///
+	/// Returns the number of physical targets.
+	fn schedule(&mut self) -> FerriciaResult<usize> {
+		self.order.clear();
+		self.bindings.clear();
+		let mut producers = HashMap::new();
+		for (i, pass) in self.passes.iter().enumerate() {
+			if let Some(output) = &pass.output && let Some(j) = producers.insert(output.as_str(), i) {
+				return Err(format!(
+					"Render target {output} is written by both {} and {}",
+					self.passes[j].name, pass.name,
+				).into());
+			}
+		}
+
+		let mut deps = Vec::with_capacity(self.passes.len());
+		for pass in &self.passes {
+			let mut v = Vec::with_capacity(pass.inputs.len());
+			for input in &pass.inputs {
+				match producers.get(input.as_str()) {
+					Some(i) => v.push(*i),
+					None => return Err(format!("Render target {input} of {} is never written", pass.name).into()),
+				}
+			}
+			deps.push(v);
+		}
+
+		let mut done = vec![false; self.passes.len()];
+		while self.order.len() < self.passes.len() {
+			let Some(i) = (0..self.passes.len())
+				.find(|i| !done[*i] && deps[*i].iter().all(|d| done[*d])) else {
+				self.order.clear();
+				return Err("Render graph contains a cycle".to_string().into());
+			};
+			done[i] = true;
+			self.order.push(i);
+		}
+
+		// Position in order of the last pass reading each target
+		let mut last_reads = HashMap::new();
+		for (pos, i) in self.order.iter().enumerate() {
+			for input in &self.passes[*i].inputs {
+				last_reads.insert(input.as_str(), pos);
+			}
+		}
+
+		let mut free = Vec::new();
+		let mut allocated = 0;
+		for (pos, i) in self.order.iter().enumerate() {
+			let pass = &self.passes[*i];
+			if let Some(output) = &pass.output {
+				let target = match free.pop() {
+					Some(v) => v,
+					None => {
+						allocated += 1;
+						allocated - 1
+					}
+				};
+				self.bindings.insert(output.clone(), target);
+			}
+			// Freed after the output is allocated so that a pass never writes to its own input.
+			for input in &pass.inputs {
+				if last_reads.get(input.as_str()) == Some(&pos) {
+					free.push(self.bindings[input]);
+				}
+			}
+		}
+		Ok(allocated)
+	}
+
+	/// Reallocates the targets for the canvas size, keeping the compiled order.
+	///
+	/// The graph must be compiled again if this fails.
+	pub(crate) fn resize(&mut self, size: (u32, u32)) -> FerriciaResult<()> {
+		if self.size == size {
+			return Ok(());
+		}
+		let count = self.targets.len();
+		self.targets.clear(); // Frees the previous targets first
+		self.size = size;
+		for _ in 0..count {
+			match RenderTarget::new(size) {
+				Ok(v) => self.targets.push(v),
+				Err(err) => {
+					self.order.clear();
+					return Err(err);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Binds and clears the output target of the pass.
+	///
+	/// Returns the textures of the inputs in the order of declaration.
+	pub(crate) fn begin_pass(&self, pass: usize) -> FerriciaResult<Vec<u32>> {
+		if self.order.is_empty() {
+			return Err("Render graph is not compiled".to_string().into());
+		}
+		let pass = self.passes.get(pass).ok_or("Invalid render pass".to_string())?;
+		match &pass.output {
+			Some(output) => {
+				use_framebuffer(self.targets[self.bindings[output]].fbo);
+				clear_canvas();
+			}
+			None => use_framebuffer(0),
+		}
+		Ok(pass.inputs.iter().map(|v| self.targets[self.bindings[v]].texture).collect())
+	}
+
+	/// Restores drawing to the canvas.
+	pub(crate) fn end_pass(&self) {
+		use_framebuffer(0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Inputs of each pass are separated by spaces.
+	fn new_graph(passes: &[(&str, &str, Option<&str>)]) -> RenderGraph {
+		let mut graph = RenderGraph::new();
+		for (name, inputs, output) in passes {
+			let inputs = inputs.split_whitespace().map(str::to_string).collect();
+			graph.add_pass(name.to_string(), inputs, output.map(str::to_string));
+		}
+		graph
+	}
+
+	#[test]
+	fn orders_passes_after_their_inputs() {
+		let mut graph = new_graph(&[
+			("post", "scene", None),
+			("scene", "", Some("scene")),
+			("ui", "", None),
+		]);
+		assert_eq!(graph.schedule().unwrap(), 1);
+		assert_eq!(graph.order, [1, 0, 2]);
+	}
+
+	#[test]
+	fn reuses_targets_after_their_last_reads() {
+		let mut graph = new_graph(&[
+			("scene", "", Some("a")),
+			("bloom", "a", Some("b")),
+			("blur", "b", Some("c")),
+			("compose", "a c", None),
+		]);
+		assert_eq!(graph.schedule().unwrap(), 3);
+		let mut graph = new_graph(&[
+			("scene", "", Some("a")),
+			("bloom", "a", Some("b")),
+			("blur", "b", Some("c")),
+			("compose", "c", None),
+		]);
+		assert_eq!(graph.schedule().unwrap(), 2);
+		// A pass never writes to its own input.
+		assert_ne!(graph.bindings["a"], graph.bindings["b"]);
+		assert_ne!(graph.bindings["b"], graph.bindings["c"]);
+		assert_eq!(graph.bindings["a"], graph.bindings["c"]);
+	}
+
+	#[test]
+	fn rejects_invalid_graphs() {
+		let mut graph = new_graph(&[("a", "", Some("x")), ("b", "", Some("x"))]);
+		assert!(graph.schedule().is_err());
+		let mut graph = new_graph(&[("a", "missing", None)]);
+		assert!(graph.schedule().is_err());
+		let mut graph = new_graph(&[("a", "y", Some("x")), ("b", "x", Some("y"))]);
+		assert!(graph.schedule().is_err());
+		assert!(graph.order.is_empty());
+	}
+
+	#[test]
+	fn invalidates_the_order_by_new_passes() {
+		let mut graph = new_graph(&[("scene", "", None)]);
+		graph.schedule().unwrap();
+		graph.add_pass("ui".to_string(), Vec::new(), None);
+		assert!(graph.begin_pass(0).is_err());
+	}
+}
@@ -5,33 +5,58 @@
 
 #![allow(private_interfaces)]
 
-use crate::mui::ogl::{buf_obj_with_data, compile_shader, draw_arrays, draw_elements, gen_buf_obj, gen_buf_objs, get_uniform_location, new_shader_program, use_program, use_texture_2d, use_uniform_mat_4, use_vao, vert_attr, vert_attr_arr, with_new_vert_arr, GLHandle, NumType, ShaderType, VertexAttrVariant};
+use crate::events::post_event;
+use crate::mui::backend::{Backend, BufferTarget, BufferUsage, Context, NumType, RenderBackend, ShaderType, Topology, VertexAttrVariant};
+use crate::mui::commands::{CommandList, RenderCommand, ReplayState};
+use crate::mui::compressed::upload_compressed_texture;
+use crate::mui::shadows::ShadowBatch;
+use crate::mui::tilemap::{TileMap, PALETTE_UNIT, TILES_UNIT};
+use crate::mui::timing::{FrameTimer, FrameTimings};
+use crate::mui::viewport::Viewport;
 use crate::mui::window::WindowHandle;
 use crate::FerriciaResult;
-use gl::{BindTexture, GenTextures, GenerateMipmap, TexImage2D, TexParameteri, ARRAY_BUFFER, CLAMP_TO_EDGE, ELEMENT_ARRAY_BUFFER, LINES, NEAREST, NEAREST_MIPMAP_LINEAR, RGBA, STATIC_DRAW, TEXTURE_2D, TEXTURE_MAG_FILTER, TEXTURE_MIN_FILTER, TEXTURE_WRAP_S, TEXTURE_WRAP_T, TRIANGLES, UNSIGNED_BYTE};
+use getset::Getters;
 use image::imageops::flip_vertical_in_place;
-use image::ImageReader;
-use nalgebra_glm::{identity, ortho, scaling, translation, vec2, vec2_to_vec3, vec3, TMat4, TVec2};
+use image::{load_from_memory, ImageReader, RgbaImage};
+use nalgebra_glm::{identity, ortho, scaling, translation, vec2, vec2_to_vec3, vec3, vec4, TMat4, TVec2, TVec4};
 use ordermap::OrderSet;
 use sdl3::pixels::Color;
 use std::borrow::Cow;
-use std::cell::Cell;
-use std::fs::read_to_string;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs::{read, read_to_string};
 use std::hash::{Hash, Hasher};
-use std::mem::MaybeUninit;
+use std::path::Path;
 use std::ptr;
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 static IDENT_MAT_4: LazyLock<TMat4<f32>> = LazyLock::new(identity);
 
+#[derive(Getters)]
 pub(crate) struct CanvasHandle {
 	/// Size of Canvas in pixels
+	#[get = "pub(crate)"]
 	size: (u32, u32),
 	ortho_proj_mat: TMat4<f32>,
-	// drawable_sets: HashMap<OpaqueId, DrawableSet>,
+	camera: Option<Camera>,
+	/// Evaluated from `camera`; `None` if no camera is set.
+	view_mat: Option<TMat4<f32>>,
+	viewports: Vec<Viewport>,
+	/// Index of the viewport being drawn, if any.
+	active_viewport: Option<usize>,
+	/// Pixels per unit of draws, so that GUI metrics stay in logical pixels on high density displays
+	gui_scale: f32,
+	/// Whether `gui_scale` follows the display scale of the window
+	#[get = "pub(crate)"]
+	auto_gui_scale: bool,
 	used_program: Cell<u32>,
+	/// `None` if UBOs are not supported
+	uniform_block: Option<UniformBlock>,
+	commands: CommandList,
+	timer: FrameTimer,
 	/// DO NOT MUTATE
-	gl_handle: Arc<GLHandle>,
+	gl_handle: Arc<Context>,
 }
 
 impl CanvasHandle {
@@ -40,9 +65,18 @@ impl CanvasHandle {
 		let gl_handle = window_handle.gl_handle().clone();
 		Self {
 			ortho_proj_mat: ortho_proj_mat(size),
+			camera: None,
+			view_mat: None,
+			viewports: Vec::new(),
+			active_viewport: None,
+			gui_scale: 1.0,
+			auto_gui_scale: false,
 			size,
-			gl_handle,
 			used_program: Cell::new(0),
+			uniform_block: gl_handle.ubo_supported().then(UniformBlock::new),
+			commands: CommandList::default(),
+			timer: FrameTimer::new(gl_handle.timer_query_supported()),
+			gl_handle,
 			// drawable_sets: HashMap::new(),
 		}
 	}
@@ -56,65 +90,507 @@ impl CanvasHandle {
 	// 	self.drawable_sets.get(&id).expect("should exist")
 	// }
 
-	pub(crate) fn load_image(&self, path: String) -> u32 {
-		let mut img = ImageReader::open(path)
-			.expect("Cannot open image")
-			.decode()
-			.expect("Cannot decode image")
-			.into_rgba8();
-		// Image coordinates have a difference direction as OpenGL texture coordinates.
-		flip_vertical_in_place(&mut img);
-		let mut id = MaybeUninit::uninit();
-		unsafe { GenTextures(1, id.as_mut_ptr()); }
-		let id = unsafe { id.assume_init() };
-		unsafe { BindTexture(TEXTURE_2D, id); }
-		unsafe { TexParameteri(TEXTURE_2D, TEXTURE_WRAP_S, CLAMP_TO_EDGE as _); }
-		unsafe { TexParameteri(TEXTURE_2D, TEXTURE_WRAP_T, CLAMP_TO_EDGE as _); }
-		unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MIN_FILTER, NEAREST_MIPMAP_LINEAR as _); }
-		unsafe { TexParameteri(TEXTURE_2D, TEXTURE_MAG_FILTER, NEAREST as _); }
-		unsafe {
-			TexImage2D(
-				TEXTURE_2D,
-				0,
-				RGBA as _,
-				img.width() as _,
-				img.height() as _,
-				0,
-				RGBA,
-				UNSIGNED_BYTE,
-				img.as_ptr() as *const _
-			);
-		}
-		unsafe { GenerateMipmap(TEXTURE_2D) }
-		id
+	pub(crate) fn load_image(&self, path: String, options: &TextureOptions) -> FerriciaResult<u32> {
+		self.load_texture(TextureSource::File(path), options)
+	}
+
+	/// The bytes are kept to upload the texture again on a new context.
+	pub(crate) fn load_image_from_bytes(&self, bytes: &[u8], options: &TextureOptions) -> FerriciaResult<u32> {
+		self.load_texture(TextureSource::Bytes(bytes.into()), options)
+	}
+
+	/// Loads a DDS or KTX2 texture of BC1, BC2, BC3 or BC7 blocks.
+	pub(crate) fn load_compressed_image(&self, path: String, options: &TextureOptions) -> FerriciaResult<u32> {
+		self.load_texture(TextureSource::Compressed(path), options)
+	}
+
+	/// Loads the images as the layers of a texture array, such as the frames of an animation.
+	///
+	/// This requires GL 3.0.
+	pub(crate) fn load_texture_array(&self, paths: Vec<String>, options: &TextureOptions) -> FerriciaResult<u32> {
+		self.load_texture(TextureSource::Array(paths), options)
+	}
+
+	fn load_texture(&self, source: TextureSource, options: &TextureOptions) -> FerriciaResult<u32> {
+		let texture = source.upload(&self.gl_handle, options)?;
+		track_texture_source(texture, source, *options);
+		Ok(texture)
+	}
+
+	/// Returns `1.0` if anisotropic filtering is not supported.
+	pub(crate) fn max_anisotropy(&self) -> f32 {
+		self.gl_handle.max_anisotropy()
+	}
+
+	/// The texture must not be used by any following draw.
+	pub(crate) fn delete_texture(&self, texture: u32) {
+		texture_sources().remove(&texture);
+		Backend::delete_texture(texture);
 	}
 
 	pub(crate) fn refresh_canvas_size(&mut self, width: u32, height: u32) {
 		self.size = (width, height);
-		self.ortho_proj_mat = ortho_proj_mat(self.size);
+		if self.active_viewport.take().is_some() {
+			Backend::disable_scissor();
+		}
+		self.refresh_canvas_mats();
+	}
+
+	/// Sets the pixels per unit of the following draws, such as the display scale of the window,
+	/// so that the canvas is laid out in logical pixels; this stops following the window.
+	pub(crate) fn set_gui_scale(&mut self, scale: f32) -> FerriciaResult<()> {
+		self.auto_gui_scale = false;
+		self.apply_gui_scale(scale)
+	}
+
+	/// Whether the GUI scale follows the display scale of the window, applied by the window.
+	pub(crate) fn set_auto_gui_scale(&mut self, enabled: bool) {
+		self.auto_gui_scale = enabled;
+	}
+
+	pub(super) fn apply_gui_scale(&mut self, scale: f32) -> FerriciaResult<()> {
+		if !(scale > 0.0) {
+			return Err(format!("Invalid GUI scale: {scale}").into());
+		}
+		self.gui_scale = scale;
+		match self.active_viewport {
+			Some(i) => self.begin_viewport(i)?,
+			None => self.refresh_canvas_mats(),
+		}
+		Ok(())
+	}
+
+	/// Size in units of draws from the size in pixels
+	fn logical_size(&self, size: (u32, u32)) -> (u32, u32) {
+		let scale = |v: u32| (v as f32 / self.gui_scale).round() as u32;
+		(scale(size.0), scale(size.1))
+	}
+
+	fn refresh_canvas_mats(&mut self) {
+		let size = self.logical_size(self.size);
+		self.ortho_proj_mat = ortho_proj_mat(size);
+		self.view_mat = self.camera.as_ref().map(|c| c.view_mat(size));
+	}
+
+	/// Sets a pan/zoom camera applied to all the following draws until it is reset.
+	pub(crate) fn set_camera(&mut self, camera: Option<Camera>) {
+		self.view_mat = camera.as_ref().map(|c| c.view_mat(self.logical_size(self.size)));
+		self.camera = camera;
+	}
+
+	/// Replaces the viewport layout, keeping the cameras of the remaining viewports.
+	pub(crate) fn set_viewports(&mut self, rects: Vec<[f32; 4]>) -> FerriciaResult<()> {
+		let mut viewports = rects.into_iter().map(Viewport::new).collect::<FerriciaResult<Vec<_>>>()?;
+		for (new, old) in viewports.iter_mut().zip(self.viewports.drain(..)) {
+			new.camera = old.camera;
+		}
+		self.viewports = viewports;
+		self.end_viewport();
+		Ok(())
+	}
+
+	pub(crate) fn set_viewport_camera(&mut self, index: usize, camera: Camera) -> FerriciaResult<()> {
+		let viewport = self.viewports.get_mut(index).ok_or(format!("Invalid viewport: {index}"))?;
+		viewport.camera = camera;
+		if self.active_viewport == Some(index) {
+			self.begin_viewport(index)?;
+		}
+		Ok(())
+	}
+
+	/// Restricts the following draws to the viewport with its camera, instead of the canvas camera.
+	///
+	/// The draws are laid out as if the canvas had the size of the viewport.
+	pub(crate) fn begin_viewport(&mut self, index: usize) -> FerriciaResult<()> {
+		let viewport = self.viewports.get(index).ok_or(format!("Invalid viewport: {index}"))?;
+		let rect = viewport.pixel_rect(self.size);
+		let size = self.logical_size((rect.2, rect.3));
+		Backend::use_viewport_scissor(rect);
+		self.ortho_proj_mat = ortho_proj_mat(size);
+		self.view_mat = Some(viewport.camera.view_mat(size));
+		self.active_viewport = Some(index);
+		Ok(())
+	}
+
+	/// Creates the GL objects again on the new context of the window, such as after the previous one
+	/// is lost; pending commands and frame timings are discarded.
+	pub(crate) fn recreate(&mut self, window_handle: &WindowHandle) -> FerriciaResult<()> {
+		self.gl_handle = window_handle.gl_handle().clone();
+		self.used_program.set(0);
+		if let Some(v) = &mut self.uniform_block {
+			// The buffer of the lost context must not be deleted.
+			v.ubo = 0;
+		}
+		self.uniform_block = self.gl_handle.ubo_supported().then(UniformBlock::new);
+		self.timer.reset(self.gl_handle.timer_query_supported());
+		self.commands.take();
+		match self.active_viewport {
+			Some(i) => self.begin_viewport(i),
+			None => Ok(()),
+		}
+	}
+
+	/// Restores drawing to the whole canvas.
+	pub(crate) fn end_viewport(&mut self) {
+		if self.active_viewport.take().is_none() {
+			return;
+		}
+		Backend::disable_scissor();
+		Backend::set_viewport((0, 0, self.size.0, self.size.1));
+		self.refresh_canvas_mats();
+	}
+
+	/// Returns the world positions of the viewport cameras, to be used as the audio listeners.
+	pub(crate) fn viewport_listeners(&self) -> Vec<(f32, f32)> {
+		self.viewports.iter().map(|v| (v.camera.pos.x, v.camera.pos.y)).collect()
+	}
+
+	/// Logical size of the active viewport, or the canvas otherwise.
+	fn draw_size(&self) -> (u32, u32) {
+		match self.active_viewport {
+			Some(i) => {
+				let rect = self.viewports[i].pixel_rect(self.size);
+				self.logical_size((rect.2, rect.3))
+			}
+			None => self.logical_size(self.size),
+		}
 	}
 
 	pub(crate) fn draw_gui(&self, set: &DrawableSet, program: &impl GuiProgram, texture: Option<u32>) {
+		self.use_gui_program(program);
+		if let Some(v) = texture {
+			program.bind_texture(v);
+		}
+		self.draw_set(set, program);
+	}
+
+	/// Draws with the textures bound to the named samplers, such as `diffuse`, `lightmap` and `lut`.
+	///
+	/// Samplers are assigned to texture units in order, so the first texture is at unit zero,
+	/// the same as the single texture of [CanvasHandle::draw_gui]. GL 2.0 only guarantees two
+	/// units for fragment shaders, while GL 3.0 guarantees 16.
+	pub(crate) fn draw_gui_textures(
+		&self,
+		window_handle: &WindowHandle,
+		set: &DrawableSet,
+		program: &impl GuiProgram,
+		textures: &[(String, u32)],
+	) -> FerriciaResult<()> {
+		let max_units = window_handle.gl_handle().max_texture_units();
+		if textures.len() > max_units as usize {
+			return Err(format!("Expected at most {max_units} textures but got {}", textures.len()).into());
+		}
+		let locations = textures.iter()
+			.map(|(name, _)| program.sampler_location(name))
+			.collect::<FerriciaResult<Vec<_>>>()?;
+		self.use_gui_program(program);
+		for (unit, (location, (_, texture))) in locations.into_iter().zip(textures).enumerate() {
+			Backend::use_uniform_int(location, unit as _);
+			Backend::use_texture_2d_at(unit as _, *texture);
+		}
+		self.draw_set(set, program);
+		Ok(())
+	}
+
+	/// Draws as [CanvasHandle::draw_gui], skipping the bindings already made in the replay.
+	pub(crate) fn replay_gui(
+		&self,
+		set: &DrawableSet,
+		program: &impl GuiProgram,
+		texture: Option<u32>,
+		state: &mut ReplayState,
+	) {
+		self.use_gui_program(program);
+		if let Some(v) = texture && state.bind_texture(program.id(), v) {
+			program.bind_texture(v);
+		}
+		if state.bind_vao(set.prim.vao()) {
+			set.prim.apply_vao();
+		}
+		self.draw_set_bound(set, program);
+	}
+
+	/// Starts timing a frame, reading the available GPU times of previous frames.
+	pub(crate) fn begin_frame(&mut self) {
+		self.timer.begin_frame();
+	}
+
+	pub(crate) fn end_frame(&mut self) -> FerriciaResult<()> {
+		self.timer.end_frame()
+	}
+
+	/// Times the GPU work of the following draws as the named pass, if supported.
+	pub(crate) fn begin_timed_pass(&mut self, name: &str) -> FerriciaResult<()> {
+		self.timer.begin_pass(name)
+	}
+
+	pub(crate) fn end_timed_pass(&mut self) -> FerriciaResult<()> {
+		self.timer.end_pass()
+	}
+
+	pub(crate) fn frame_timings(&self) -> FrameTimings {
+		self.timer.timings()
+	}
+
+	/// Records a command for the frame, to be replayed by `Mui.renderFrame`.
+	pub(crate) fn record(&mut self, command: RenderCommand) {
+		self.commands.push(command);
+	}
+
+	/// Takes the commands recorded for the frame.
+	pub(crate) fn take_commands(&mut self) -> Vec<RenderCommand> {
+		self.commands.take()
+	}
+
+	/// Makes the next draw bind its program again, such as after a program is deleted or replaced,
+	/// as its name may be reused, or after another program is bound outside the canvas.
+	pub(crate) fn forget_used_program(&self) {
+		self.used_program.set(0);
+	}
+
+	fn use_gui_program(&self, program: &impl GuiProgram) {
 		if self.used_program.get() != program.id() {
 			program.apply();
 			self.used_program.set(program.id());
+		} else {
+			program.refresh();
 		}
+	}
 
-		if let Some(v) = texture {
-			use_texture_2d(v);
-		}
-
+	fn draw_set(&self, set: &DrawableSet, program: &impl GuiProgram) {
 		set.prim.apply_vao();
-		let context = DrawingContext { window_size: &self.size };
-		program.uniform(&self.ortho_proj_mat, set, context);
+		self.draw_set_bound(set, program);
+	}
+
+	/// After the VAO of the set is bound
+	fn draw_set_bound(&self, set: &DrawableSet, program: &impl GuiProgram) {
+		let size = self.draw_size();
+		let context = DrawingContext { window_size: &size, time: Instant::now() };
+		let view = self.view_mat.as_ref().unwrap_or(&IDENT_MAT_4);
+		if let Some(v) = &self.uniform_block {
+			v.upload(&self.ortho_proj_mat, view);
+		}
+		program.uniform(&self.ortho_proj_mat, view, set, context);
 		set.prim.draw();
 	}
 }
 
-pub(crate) use crate::mui::ogl::{clear_canvas, set_clear_color};
+pub(crate) use crate::mui::backend::RenderStats;
+
+pub(crate) fn clear_canvas() {
+	Backend::clear_canvas();
+}
+
+pub(crate) fn set_clear_color(color: (f32, f32, f32, f32)) {
+	Backend::set_clear_color(color);
+}
+
+pub(crate) fn render_stats() -> RenderStats {
+	Backend::render_stats()
+}
+
+/// The format is detected from the content, falling back to the file extension.
+///
+/// PNG is always supported, while BMP, TGA, WebP and the other formats are only supported with the respective features.
+pub(super) fn read_image(path: impl AsRef<Path>) -> FerriciaResult<RgbaImage> {
+	let path = path.as_ref();
+	let img = ImageReader::open(path)
+		.and_then(|v| v.with_guessed_format())
+		.map_err(|e| format!("Cannot open image {}: {e}", path.display()))?
+		.decode()
+		.map_err(|e| format!("Cannot decode image {}: {e}", path.display()))?;
+	Ok(img.into_rgba8())
+}
+
+/// The format is detected from the content; see [read_image] for the supported formats.
+pub(super) fn decode_image(bytes: &[u8]) -> FerriciaResult<RgbaImage> {
+	let img = load_from_memory(bytes).map_err(|e| format!("Cannot decode image: {e}"))?;
+	Ok(img.into_rgba8())
+}
+
+/// Sampling parameters of a texture on creation.
+#[derive(Clone, Copy)]
+pub(crate) struct TextureOptions {
+	pub(crate) filter: TextureFilter,
+	pub(crate) wrap: TextureWrap,
+	pub(crate) mipmaps: bool,
+	/// Degree of anisotropic filtering, where `1.0` disables it
+	pub(crate) anisotropy: f32,
+	/// Range of mipmap levels used for sampling, such as to skip the finest levels for low quality
+	pub(crate) levels: (u32, u32),
+}
+
+impl Default for TextureOptions {
+	/// Crisp pixel art without any tiling.
+	fn default() -> Self {
+		Self {
+			filter: TextureFilter::Nearest,
+			wrap: TextureWrap::Clamp,
+			mipmaps: true,
+			anisotropy: 1.0,
+			levels: (0, 1000),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum TextureFilter {
+	Nearest,
+	Linear,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum TextureWrap {
+	Clamp,
+	Repeat,
+	Mirror,
+}
+
+/// Where a texture is loaded from, kept to upload it again on a new context.
+pub(super) enum TextureSource {
+	File(String),
+	Bytes(Box<[u8]>),
+	/// DDS or KTX2 file
+	Compressed(String),
+	/// Files of the layers in order
+	Array(Vec<String>),
+	Image(RgbaImage),
+}
+
+impl TextureSource {
+	/// Returns the new texture.
+	fn upload(&self, gl_handle: &Context, options: &TextureOptions) -> FerriciaResult<u32> {
+		match self {
+			Self::File(path) => Ok(upload_texture(read_image(path)?, options)),
+			Self::Bytes(bytes) => Ok(upload_texture(decode_image(bytes)?, options)),
+			Self::Compressed(path) => {
+				let bytes = read(path).map_err(|e| format!("Cannot open texture {path}: {e}"))?;
+				upload_compressed_texture(gl_handle, &bytes, options)
+					.map_err(|e| format!("Cannot load texture {path}: {}", e.0).into())
+			}
+			Self::Array(paths) => {
+				if !gl_handle.texture_array_supported() {
+					return Err("Texture arrays are not supported".to_string().into());
+				}
+				let images = paths.iter().map(read_image).collect::<FerriciaResult<Vec<_>>>()?;
+				upload_texture_array(images, options)
+			}
+			Self::Image(img) => Ok(upload_texture(img.clone(), options)),
+		}
+	}
+}
+
+/// Sources and options of the textures loaded by the engine, by texture
+static TEXTURE_SOURCES: LazyLock<Mutex<HashMap<u32, (TextureSource, TextureOptions)>>> = LazyLock::new(Default::default);
+
+fn texture_sources() -> MutexGuard<'static, HashMap<u32, (TextureSource, TextureOptions)>> {
+	TEXTURE_SOURCES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Records the source of a new texture, so that it is uploaded again by [recreate_textures].
+pub(super) fn track_texture_source(texture: u32, source: TextureSource, options: TextureOptions) {
+	texture_sources().insert(texture, (source, options));
+}
+
+/// Returns the number of layers of the loaded texture array.
+pub(crate) fn texture_array_layers(texture: u32) -> FerriciaResult<u32> {
+	match texture_sources().get(&texture) {
+		Some((TextureSource::Array(paths), _)) => Ok(paths.len() as _),
+		_ => Err(format!("Texture {texture} is not a loaded texture array").into()),
+	}
+}
+
+/// Uploads all the loaded textures again on the new context of the window, such as after the previous
+/// one is lost, returning pairs of the previous and the new textures.
+///
+/// Textures failed to upload are returned with zero, while the errors are posted as `gl` engine events.
+pub(crate) fn recreate_textures(window_handle: &WindowHandle) -> Vec<(u32, u32)> {
+	let mut sources = texture_sources();
+	// The new names may be the same as the previous ones of other textures.
+	let previous = std::mem::take(&mut *sources);
+	let mut textures = Vec::with_capacity(previous.len());
+	for (texture, (source, options)) in previous {
+		match source.upload(window_handle.gl_handle(), &options) {
+			Ok(v) => {
+				sources.insert(v, (source, options));
+				textures.push((texture, v));
+			}
+			Err(e) => {
+				post_event("gl", format!("Cannot recreate texture {texture}: {}", e.0));
+				textures.push((texture, 0));
+			}
+		}
+	}
+	textures
+}
+
+/// Returns the new texture.
+pub(super) fn upload_texture(img: RgbaImage, options: &TextureOptions) -> u32 {
+	let id = Backend::gen_texture();
+	fill_texture(id, img, options);
+	id
+}
+
+/// Replaces the whole content of an existing texture, keeping its name valid.
+pub(super) fn fill_texture(id: u32, mut img: RgbaImage, options: &TextureOptions) {
+	// Image coordinates have a difference direction as OpenGL texture coordinates.
+	flip_vertical_in_place(&mut img);
+	Backend::fill_texture_2d(id, img.dimensions(), &img, options);
+}
+
+/// Returns the new texture array with the images as the layers in order.
+///
+/// All the images must have the same size.
+pub(super) fn upload_texture_array(mut images: Vec<RgbaImage>, options: &TextureOptions) -> FerriciaResult<u32> {
+	let Some(first) = images.first() else {
+		return Err("Texture array must not be empty".to_string().into());
+	};
+	let size = first.dimensions();
+	if let Some(img) = images.iter().find(|v| v.dimensions() != size) {
+		return Err(format!("Texture array layers must be {}x{}, got {}x{}", size.0, size.1, img.width(), img.height()).into());
+	}
+	let layers = images.len();
+	let mut data = Vec::with_capacity(images.iter().map(|v| v.len()).sum());
+	for img in &mut images {
+		// Image coordinates have a difference direction as OpenGL texture coordinates.
+		flip_vertical_in_place(img);
+		data.extend_from_slice(img);
+	}
+	let id = Backend::gen_texture();
+	Backend::fill_texture_2d_array(id, size, layers as _, &data, options);
+	Ok(id)
+}
+
+/// A simple 2D camera for world rendering.
+///
+/// The position is the world coordinate shown at the center of the canvas,
+/// while the zoom scales the world around that center.
+pub(crate) struct Camera {
+	pos: TVec2<f32>,
+	zoom: f32,
+}
+
+impl Camera {
+	/// `zoom` must be finite and positive.
+	pub(crate) fn new(x: f32, y: f32, zoom: f32) -> FerriciaResult<Self> {
+		if !zoom.is_finite() || zoom <= 0.0 {
+			return Err(format!("Invalid camera zoom: {zoom}").into());
+		}
+		Ok(Self { pos: vec2(x, y), zoom })
+	}
+
+	fn view_mat(&self, size: (u32, u32)) -> TMat4<f32> {
+		let center = vec3(size.0 as f32 / 2.0, size.1 as f32 / 2.0, 0.0);
+		translation(&center)
+			* scaling(&vec3(self.zoom, self.zoom, 1.0))
+			* translation(&vec3(-self.pos.x, -self.pos.y, 0.0))
+	}
+}
 
 struct DrawingContext<'a> {
 	window_size: &'a (u32, u32),
+	/// Time of the draw, used by animated transforms and filters.
+	time: Instant,
 }
 
 /// Usage: `unsafe { UniformMatrix4fv(0, 1, FALSE, ortho.as_ptr()) }`
@@ -125,8 +601,186 @@ fn ortho_proj_mat(size: (u32, u32)) -> TMat4<f32> {
 	ortho::<f32>(0., width as _, 0., height as _, -1., 1.)
 }
 
-fn compile_shader_from(kind: ShaderType, path: String) -> FerriciaResult<u32> {
-	Ok(compile_shader(read_to_string(path).expect("Cannot read the file"), kind)?)
+/// Name of shaders compiled from sources in errors
+pub(super) const SOURCE_NAME: &str = "(source)";
+
+/// Shaders embedded in the crate, written against GLSL 1.10 and preprocessed for the detected version
+const DEFAULT_GEO_SHADERS: [&str; 2] = [include_str!("shaders/geo.vsh"), include_str!("shaders/geo.fsh")];
+const DEFAULT_TEX_SHADERS: [&str; 2] = [include_str!("shaders/tex.vsh"), include_str!("shaders/tex.fsh")];
+const DEFAULT_TILE_MAP_SHADERS: [&str; 2] = [include_str!("shaders/tilemap.vsh"), include_str!("shaders/tilemap.fsh")];
+const DEFAULT_SHADOW_SHADERS: [&str; 2] = [include_str!("shaders/shadow.vsh"), include_str!("shaders/shadow.fsh")];
+/// Vertex, geometry and fragment shaders, requiring GLSL 1.50
+const DEFAULT_WIDE_LINE_SHADERS: [&str; 3] = [include_str!("shaders/wide.vsh"), include_str!("shaders/wide_lines.gsh"), include_str!("shaders/geo.fsh")];
+const DEFAULT_WIDE_POINT_SHADERS: [&str; 3] = [include_str!("shaders/wide.vsh"), include_str!("shaders/wide_points.gsh"), include_str!("shaders/geo.fsh")];
+/// Attribute locations of the embedded shaders, which cannot use `layout` qualifiers in GLSL 1.10
+const DEFAULT_GEO_ATTRIBUTES: [&str; 2] = ["position", "color"];
+const DEFAULT_TEX_ATTRIBUTES: [&str; 2] = ["position", "tex_coord"];
+const DEFAULT_TILE_MAP_ATTRIBUTES: [&str; 2] = ["position", "tile_coord"];
+const DEFAULT_SHADOW_ATTRIBUTES: [&str; 3] = ["position", "offset", "opacity"];
+
+/// Preprocesses and links the embedded shaders; `name` identifies the set in errors.
+fn link_default_program(
+	window_handle: &WindowHandle,
+	[vsh, fsh]: [&str; 2],
+	attributes: &'static [&'static str],
+	name: &str,
+) -> FerriciaResult<(u32, ProgramSources)> {
+	let vsh = window_handle.preprocess_shader_source(vsh)?;
+	let fsh = window_handle.preprocess_shader_source(fsh)?;
+	link_program(vsh, fsh, [&format!("(default) {name}.vsh"), &format!("(default) {name}.fsh")], attributes)
+}
+
+/// Fails if geometry shaders are not supported by the context.
+fn check_geometry_shader(window_handle: &WindowHandle) -> FerriciaResult<()> {
+	if window_handle.geometry_shader_supported() {
+		Ok(())
+	} else {
+		Err("Geometry shaders require GL 3.2".to_string().into())
+	}
+}
+
+/// The same as [link_default_program] but with a geometry shader between the stages.
+fn link_default_geometry_program(
+	window_handle: &WindowHandle,
+	[vsh, gsh, fsh]: [&str; 3],
+	name: &str,
+) -> FerriciaResult<(u32, ProgramSources)> {
+	check_geometry_shader(window_handle)?;
+	link_stages([
+		(ShaderType::Vertex, window_handle.preprocess_shader_source(vsh)?, &format!("(default) {name}.vsh")),
+		(ShaderType::Geometry, window_handle.preprocess_shader_source(gsh)?, &format!("(default) {name}.gsh")),
+		(ShaderType::Fragment, window_handle.preprocess_shader_source(fsh)?, &format!("(default) {name}.fsh")),
+	], &DEFAULT_GEO_ATTRIBUTES)
+}
+
+/// `filter` is reserved since GLSL 1.30, so `filter_matrix` is accepted as well.
+fn filter_location(program: u32) -> u32 {
+	match Backend::get_uniform_location(program, "filter") {
+		u32::MAX => Backend::get_uniform_location(program, "filter_matrix"),
+		location => location,
+	}
+}
+
+pub(super) fn read_shader(path: &str) -> FerriciaResult<String> {
+	read_to_string(path).map_err(|e| format!("Cannot read shader {path}: {e}").into())
+}
+
+fn compile_shader_source(kind: ShaderType, src: String, name: &str) -> FerriciaResult<u32> {
+	if src.contains('\0') {
+		return Err(format!("Shader {name} contains a NUL character").into());
+	}
+	Ok(Backend::compile_shader(src, kind, name)?)
+}
+
+/// Compiles and links the vertex and fragment shaders; `names` identify the sources in errors.
+fn link_program(
+	vsh: String,
+	fsh: String,
+	names: [&str; 2],
+	attributes: &'static [&'static str],
+) -> FerriciaResult<(u32, ProgramSources)> {
+	link_stages([(ShaderType::Vertex, vsh, names[0]), (ShaderType::Fragment, fsh, names[1])], attributes)
+}
+
+/// Compiles and links the shaders of the stages with the names of their sources,
+/// returning the program along with the sources.
+pub(super) fn link_stages<const N: usize>(
+	stages: [(ShaderType, String, &str); N],
+	attributes: &'static [&'static str],
+) -> FerriciaResult<(u32, ProgramSources)> {
+	let sources = ProgramSources {
+		stages: stages.into_iter().map(|(kind, src, name)| (kind, src, name.to_string())).collect(),
+		attributes,
+	};
+	Ok((sources.link()?, sources))
+}
+
+/// Shader sources of a linked program, kept to link it again on a new context.
+#[derive(Default)]
+pub(super) struct ProgramSources {
+	/// Source and name of each stage
+	stages: Vec<(ShaderType, String, String)>,
+	attributes: &'static [&'static str],
+}
+
+impl ProgramSources {
+	pub(super) fn link(&self) -> FerriciaResult<u32> {
+		let mut shaders = Vec::with_capacity(self.stages.len());
+		for (kind, src, name) in &self.stages {
+			match compile_shader_source(*kind, src.clone(), name) {
+				Ok(v) => shaders.push(v),
+				Err(e) => {
+					shaders.iter().for_each(|v| Backend::delete_shader(*v));
+					return Err(e);
+				}
+			}
+		}
+		Ok(Backend::new_shader_program(&shaders, self.attributes)?)
+	}
+}
+
+/// Name of the uniform block shared by all the programs, declared in shaders as
+/// `layout(std140) uniform Matrices { mat4 projection; mat4 view; };`
+const MATRICES_BLOCK: &str = "Matrices";
+const MATRICES_BINDING: u32 = 0;
+
+/// UBO of the projection and view matrices, uploaded only when they change rather than on every draw.
+///
+/// Only one canvas is expected per GL context, as the UBO is bound to a fixed binding point.
+struct UniformBlock {
+	ubo: u32,
+	/// The last uploaded projection and view matrices
+	uploaded: Cell<Option<(TMat4<f32>, TMat4<f32>)>>,
+}
+
+impl UniformBlock {
+	fn new() -> Self {
+		Self {
+			ubo: Backend::gen_uniform_buf_obj(2 * size_of::<TMat4<f32>>(), MATRICES_BINDING),
+			uploaded: Cell::new(None),
+		}
+	}
+
+	fn upload(&self, proj: &TMat4<f32>, view: &TMat4<f32>) {
+		if self.uploaded.get() == Some((*proj, *view)) {
+			return;
+		}
+		Backend::update_uniform_buf_obj(self.ubo, 0, proj.as_slice());
+		Backend::update_uniform_buf_obj(self.ubo, size_of::<TMat4<f32>>(), view.as_slice());
+		self.uploaded.set(Some((*proj, *view)));
+	}
+}
+
+impl Drop for UniformBlock {
+	fn drop(&mut self) {
+		Backend::delete_buf_objs(&[self.ubo]);
+	}
+}
+
+/// Projection and view matrices of a program, set by plain uniforms only when the program
+/// does not declare the shared block or UBOs are not supported.
+struct MatrixUniforms {
+	projection_pos: u32,
+	view_pos: u32,
+	/// Whether the shared block is bound
+	block: bool,
+}
+
+impl MatrixUniforms {
+	fn new(program: u32) -> Self {
+		Self {
+			projection_pos: Backend::get_uniform_location(program, "projection"),
+			view_pos: Backend::get_uniform_location(program, "view"),
+			block: Backend::bind_uniform_block(program, MATRICES_BLOCK, MATRICES_BINDING),
+		}
+	}
+
+	fn apply(&self, proj: &TMat4<f32>, view: &TMat4<f32>) {
+		if !self.block {
+			Backend::use_uniform_mat_4(self.projection_pos, proj);
+			Backend::use_uniform_mat_4(self.view_pos, view);
+		}
+	}
 }
 
 pub(crate) trait GuiProgram {
@@ -134,28 +788,312 @@ pub(crate) trait GuiProgram {
 
 	fn apply(&self);
 
-	fn uniform(&self, proj: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext);
+	/// Uploads the uniforms set since the program is applied, while it is still in use.
+	#[inline]
+	fn refresh(&self) {}
+
+	fn uniform(&self, proj: &TMat4<f32>, view: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext);
+
+	/// Binds the texture to be sampled by the following draw.
+	#[inline]
+	fn bind_texture(&self, texture: u32) {
+		Backend::use_texture_2d(texture);
+	}
+
+	/// Returns the location of the sampler uniform for [CanvasHandle::draw_gui_textures].
+	fn sampler_location(&self, name: &str) -> FerriciaResult<u32> {
+		Err(format!("Program does not support named samplers: {name}").into())
+	}
+}
+
+/// Recompiles the program from the shader files in place, keeping the handle valid.
+#[cfg(feature = "hot-reload")]
+pub(crate) trait ReloadProgram {
+	fn reload(&mut self, vsh: String, fsh: String) -> FerriciaResult<()>;
 }
 
+#[cfg(feature = "hot-reload")]
+macro_rules! impl_reload_program {
+	($($t:ty),*) => {
+		$(
+			impl ReloadProgram for $t {
+				/// The current program is kept if the compilation fails.
+				fn reload(&mut self, vsh: String, fsh: String) -> FerriciaResult<()> {
+					// The old program is deleted on drop.
+					*self = Self::new(vsh, fsh)?;
+					Ok(())
+				}
+			}
+		)*
+	};
+	(uniforms: $($t:ty),*) => {
+		$(
+			impl ReloadProgram for $t {
+				/// The current program is kept if the compilation fails.
+				///
+				/// Custom uniforms are carried over, except those removed from the shaders.
+				fn reload(&mut self, vsh: String, fsh: String) -> FerriciaResult<()> {
+					let mut new = self.recompile(vsh, fsh)?;
+					new.uniforms = std::mem::take(&mut self.uniforms);
+					new.uniforms.relocate(new.id);
+					// The old program is deleted on drop.
+					*self = new;
+					Ok(())
+				}
+			}
+		)*
+	};
+}
+
+#[cfg(feature = "hot-reload")]
+impl_reload_program!(uniforms: GeoProgram, TexProgram);
+#[cfg(feature = "hot-reload")]
+impl_reload_program!(ArrayTexProgram, PickProgram, TileMapProgram);
+
+macro_rules! impl_drop_program {
+	($($t:ty),*) => {
+		$(
+			impl Drop for $t {
+				fn drop(&mut self) {
+					Backend::delete_program(self.id);
+				}
+			}
+		)*
+	};
+}
+
+impl_drop_program!(GeoProgram, TexProgram, ArrayTexProgram, TileMapProgram, PickProgram);
+
+macro_rules! impl_recreate_program {
+	($($t:ty),*) => {
+		$(
+			impl $t {
+				/// Links the program again on a new context, such as after the previous one is lost.
+				pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+					// The program of the lost context must not be deleted, even if this fails.
+					self.id = 0;
+					*self = Self::with_program((self.sources.link()?, std::mem::take(&mut self.sources)));
+					Ok(())
+				}
+			}
+		)*
+	};
+}
+
+impl_recreate_program!(ArrayTexProgram, TileMapProgram, PickProgram);
+
+/// Value of a custom uniform, uploaded with the matching `glUniform*` call.
+#[derive(Clone, Copy)]
+pub(crate) enum UniformValue {
+	Float(f32),
+	Vec2(TVec2<f32>),
+	Vec4(TVec4<f32>),
+	Int(i32),
+	Mat4(TMat4<f32>),
+}
+
+/// Uniforms set by name from gameplay, such as time or wave amplitude.
+///
+/// Values are uploaded whenever the program is applied, and those set while the program stays
+/// in use are uploaded before the next draw, so they may be set at any time.
+/// The built-in uniforms of the program take precedence over those of the same names.
+#[derive(Default)]
+pub(crate) struct CustomUniforms {
+	/// Cached location and value by name
+	values: HashMap<String, (u32, UniformValue)>,
+	/// Cached locations of samplers by name, which are assigned per draw
+	samplers: RefCell<HashMap<String, u32>>,
+	/// Whether any value is set since the last upload
+	dirty: Cell<bool>,
+}
+
+impl CustomUniforms {
+	pub(super) fn set(&mut self, program: u32, name: String, value: UniformValue) -> FerriciaResult<()> {
+		let location = match self.values.get(&name) {
+			Some((location, _)) => *location,
+			None => {
+				if name.contains('\0') {
+					return Err("Uniform name contains a NUL character".to_string().into());
+				}
+				match Backend::get_uniform_location(program, &name) {
+					u32::MAX => return Err(format!("Unknown uniform: {name}").into()),
+					location => location,
+				}
+			}
+		};
+		self.values.insert(name, (location, value));
+		self.dirty.set(true);
+		Ok(())
+	}
+
+	fn sampler_location(&self, program: u32, name: &str) -> FerriciaResult<u32> {
+		if let Some(v) = self.samplers.borrow().get(name) {
+			return Ok(*v);
+		}
+		if name.contains('\0') {
+			return Err("Sampler name contains a NUL character".to_string().into());
+		}
+		match Backend::get_uniform_location(program, name) {
+			u32::MAX => Err(format!("Unknown sampler: {name}").into()),
+			location => {
+				self.samplers.borrow_mut().insert(name.to_string(), location);
+				Ok(location)
+			}
+		}
+	}
+
+	/// Looks up the locations again after the program is replaced.
+	pub(super) fn relocate(&mut self, program: u32) {
+		self.samplers.get_mut().clear();
+		self.values.retain(|name, (location, _)| {
+			*location = Backend::get_uniform_location(program, name);
+			*location != u32::MAX
+		});
+	}
+
+	/// After `use_program`
+	pub(super) fn upload(&self) {
+		self.dirty.set(false);
+		for (location, value) in self.values.values() {
+			match value {
+				UniformValue::Float(v) => Backend::use_uniform_float(*location, *v),
+				UniformValue::Vec2(v) => Backend::use_uniform_vec_2(*location, v),
+				UniformValue::Vec4(v) => Backend::use_uniform_vec_4(*location, v),
+				UniformValue::Int(v) => Backend::use_uniform_int(*location, *v),
+				UniformValue::Mat4(v) => Backend::use_uniform_mat_4(*location, v),
+			}
+		}
+	}
+
+	/// Uploads the values if any is set since the last upload, while the program is in use.
+	fn upload_changed(&self) {
+		if self.dirty.get() {
+			self.upload();
+		}
+	}
+}
+
+/// Draws geometries, optionally expanded by a geometry shader, such as for wide lines.
+///
+/// The size of the drawing area in pixels is set to the `viewport_size` uniform if declared,
+/// for shaders expanding primitives in screen space.
 pub(crate) struct GeoProgram {
 	id: u32,
 	model_pos: u32,
-	projection_pos: u32,
+	matrices: MatrixUniforms,
 	filter_pos: u32,
+	filter_offset_pos: u32,
+	viewport_size_pos: u32,
+	uniforms: CustomUniforms,
+	sources: ProgramSources,
+	/// Path of the geometry shader, kept for reloading
+	#[cfg(feature = "hot-reload")]
+	gsh: Option<String>,
 }
 
 impl GeoProgram {
+	/// Shaders are read from the file paths.
 	pub(crate) fn new(vsh: String, fsh: String) -> FerriciaResult<Self> {
-		let id = new_shader_program([
-			compile_shader_from(ShaderType::Vertex, vsh)?,
-			compile_shader_from(ShaderType::Fragment, fsh)?,
-		]);
-		Ok(Self {
-			model_pos: get_uniform_location(id, "model"),
-			projection_pos: get_uniform_location(id, "projection"),
-			filter_pos: get_uniform_location(id, "filter"),
+		Ok(Self::with_program(link_program(read_shader(&vsh)?, read_shader(&fsh)?, [&vsh, &fsh], &[])?))
+	}
+
+	/// Shaders are compiled from the GLSL sources, such as those bundled in the game.
+	pub(crate) fn from_source(vsh: String, fsh: String) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_program(vsh, fsh, [SOURCE_NAME; 2], &[])?))
+	}
+
+	/// Shaders are embedded in the crate, so no files have to be located.
+	pub(crate) fn default_program(window_handle: &WindowHandle) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_default_program(window_handle, DEFAULT_GEO_SHADERS, &DEFAULT_GEO_ATTRIBUTES, "geo")?))
+	}
+
+	/// Draws a [ShadowBatch] with the shaders embedded in the crate.
+	pub(crate) fn default_shadow_program(window_handle: &WindowHandle) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_default_program(window_handle, DEFAULT_SHADOW_SHADERS, &DEFAULT_SHADOW_ATTRIBUTES, "shadow")?))
+	}
+
+	/// Shaders are read from the file paths, with the geometry shader requiring GL 3.2.
+	pub(crate) fn with_geometry_shader(window_handle: &WindowHandle, vsh: String, gsh: String, fsh: String) -> FerriciaResult<Self> {
+		check_geometry_shader(window_handle)?;
+		Self::link_geometry_shader(vsh, gsh, fsh)
+	}
+
+	/// Support of geometry shaders must be checked first.
+	fn link_geometry_shader(vsh: String, gsh: String, fsh: String) -> FerriciaResult<Self> {
+		#[allow(unused_mut)]
+		let mut program = Self::with_program(link_stages([
+			(ShaderType::Vertex, read_shader(&vsh)?, &vsh),
+			(ShaderType::Geometry, read_shader(&gsh)?, &gsh),
+			(ShaderType::Fragment, read_shader(&fsh)?, &fsh),
+		], &[])?);
+		#[cfg(feature = "hot-reload")]
+		{
+			program.gsh = Some(gsh);
+		}
+		Ok(program)
+	}
+
+	/// Expands lines into quads of the custom uniform `line_width` in pixels, `1.0` by default.
+	///
+	/// Requires GL 3.2; see [WindowHandle::geometry_shader_supported].
+	pub(crate) fn default_wide_line_program(window_handle: &WindowHandle) -> FerriciaResult<Self> {
+		let mut program = Self::with_program(link_default_geometry_program(window_handle, DEFAULT_WIDE_LINE_SHADERS, "wide_lines")?);
+		program.set_uniform("line_width".to_string(), UniformValue::Float(1.0))?;
+		Ok(program)
+	}
+
+	/// Expands points into squares of the custom uniform `point_size` in pixels, `1.0` by default,
+	/// such as for particles.
+	///
+	/// Requires GL 3.2; see [WindowHandle::geometry_shader_supported].
+	pub(crate) fn default_wide_point_program(window_handle: &WindowHandle) -> FerriciaResult<Self> {
+		let mut program = Self::with_program(link_default_geometry_program(window_handle, DEFAULT_WIDE_POINT_SHADERS, "wide_points")?);
+		program.set_uniform("point_size".to_string(), UniformValue::Float(1.0))?;
+		Ok(program)
+	}
+
+	fn with_program((id, sources): (u32, ProgramSources)) -> Self {
+		Self {
+			model_pos: Backend::get_uniform_location(id, "model"),
+			matrices: MatrixUniforms::new(id),
+			filter_pos: filter_location(id),
+			filter_offset_pos: Backend::get_uniform_location(id, "filter_offset"),
+			viewport_size_pos: Backend::get_uniform_location(id, "viewport_size"),
+			uniforms: CustomUniforms::default(),
+			sources,
+			#[cfg(feature = "hot-reload")]
+			gsh: None,
 			id,
-		})
+		}
+	}
+
+	/// Links the program again on a new context, such as after the previous one is lost,
+	/// keeping the custom uniforms.
+	pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+		// The program of the lost context must not be deleted, even if this fails.
+		self.id = 0;
+		let mut new = Self::with_program((self.sources.link()?, std::mem::take(&mut self.sources)));
+		new.uniforms = std::mem::take(&mut self.uniforms);
+		new.uniforms.relocate(new.id);
+		#[cfg(feature = "hot-reload")]
+		{
+			new.gsh = self.gsh.take();
+		}
+		*self = new;
+		Ok(())
+	}
+
+	/// Recompiles with the same geometry shader, if any, which is supported as it has been linked.
+	#[cfg(feature = "hot-reload")]
+	fn recompile(&self, vsh: String, fsh: String) -> FerriciaResult<Self> {
+		match &self.gsh {
+			Some(gsh) => Self::link_geometry_shader(vsh, gsh.clone(), fsh),
+			None => Self::new(vsh, fsh),
+		}
+	}
+
+	pub(crate) fn set_uniform(&mut self, name: String, value: UniformValue) -> FerriciaResult<()> {
+		self.uniforms.set(self.id, name, value)
 	}
 }
 
@@ -166,37 +1104,88 @@ impl GuiProgram for GeoProgram {
 
 	#[inline]
 	fn apply(&self) {
-		use_program(self.id);
+		Backend::use_program(self.id);
+		self.uniforms.upload();
 	}
 
-	fn uniform(&self, proj: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
-		use_uniform_mat_4(self.projection_pos, proj);
+	#[inline]
+	fn refresh(&self) {
+		self.uniforms.upload_changed();
+	}
+
+	fn uniform(&self, proj: &TMat4<f32>, view: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
+		self.matrices.apply(proj, view);
 		let model = set.eval_model_mat(&drawing_context);
-		use_uniform_mat_4(self.model_pos, model.as_ref());
-		let filter = set.eval_filter_mat(&drawing_context);
-		use_uniform_mat_4(self.filter_pos, filter.as_ref());
+		Backend::use_uniform_mat_4(self.model_pos, model.as_ref());
+		let (filter, filter_offset) = set.eval_filter(&drawing_context);
+		Backend::use_uniform_mat_4(self.filter_pos, filter.as_ref());
+		Backend::use_uniform_vec_4(self.filter_offset_pos, &filter_offset);
+		let (width, height) = *drawing_context.window_size;
+		Backend::use_uniform_vec_2(self.viewport_size_pos, &vec2(width as _, height as _));
+	}
+
+	fn sampler_location(&self, name: &str) -> FerriciaResult<u32> {
+		self.uniforms.sampler_location(self.id, name)
 	}
 }
 
 pub(crate) struct TexProgram {
 	id: u32,
 	model_pos: u32,
-	projection_pos: u32,
+	matrices: MatrixUniforms,
 	filter_pos: u32,
+	filter_offset_pos: u32,
+	uniforms: CustomUniforms,
+	sources: ProgramSources,
 }
 
 impl TexProgram {
+	/// Shaders are read from the file paths.
 	pub(crate) fn new(vsh: String, fsh: String) -> FerriciaResult<Self> {
-		let id = new_shader_program([
-			compile_shader_from(ShaderType::Vertex, vsh)?,
-			compile_shader_from(ShaderType::Fragment, fsh)?,
-		]);
-		Ok(Self {
-			model_pos: get_uniform_location(id, "model"),
-			projection_pos: get_uniform_location(id, "projection"),
-			filter_pos: get_uniform_location(id, "filter"),
+		Ok(Self::with_program(link_program(read_shader(&vsh)?, read_shader(&fsh)?, [&vsh, &fsh], &[])?))
+	}
+
+	/// Shaders are compiled from the GLSL sources, such as those bundled in the game.
+	pub(crate) fn from_source(vsh: String, fsh: String) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_program(vsh, fsh, [SOURCE_NAME; 2], &[])?))
+	}
+
+	/// Shaders are embedded in the crate, so no files have to be located.
+	pub(crate) fn default_program(window_handle: &WindowHandle) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_default_program(window_handle, DEFAULT_TEX_SHADERS, &DEFAULT_TEX_ATTRIBUTES, "tex")?))
+	}
+
+	fn with_program((id, sources): (u32, ProgramSources)) -> Self {
+		Self {
+			model_pos: Backend::get_uniform_location(id, "model"),
+			matrices: MatrixUniforms::new(id),
+			filter_pos: filter_location(id),
+			filter_offset_pos: Backend::get_uniform_location(id, "filter_offset"),
+			uniforms: CustomUniforms::default(),
+			sources,
 			id,
-		})
+		}
+	}
+
+	/// Links the program again on a new context, such as after the previous one is lost,
+	/// keeping the custom uniforms.
+	pub(crate) fn recreate(&mut self) -> FerriciaResult<()> {
+		// The program of the lost context must not be deleted, even if this fails.
+		self.id = 0;
+		let mut new = Self::with_program((self.sources.link()?, std::mem::take(&mut self.sources)));
+		new.uniforms = std::mem::take(&mut self.uniforms);
+		new.uniforms.relocate(new.id);
+		*self = new;
+		Ok(())
+	}
+
+	#[cfg(feature = "hot-reload")]
+	fn recompile(&self, vsh: String, fsh: String) -> FerriciaResult<Self> {
+		Self::new(vsh, fsh)
+	}
+
+	pub(crate) fn set_uniform(&mut self, name: String, value: UniformValue) -> FerriciaResult<()> {
+		self.uniforms.set(self.id, name, value)
 	}
 }
 
@@ -207,15 +1196,200 @@ impl GuiProgram for TexProgram {
 
 	#[inline]
 	fn apply(&self) {
-		use_program(self.id);
+		Backend::use_program(self.id);
+		self.uniforms.upload();
+	}
+
+	#[inline]
+	fn refresh(&self) {
+		self.uniforms.upload_changed();
+	}
+
+	fn uniform(&self, proj: &TMat4<f32>, view: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
+		self.matrices.apply(proj, view);
+		let model = set.eval_model_mat(&drawing_context);
+		Backend::use_uniform_mat_4(self.model_pos, model.as_ref());
+		let (filter, filter_offset) = set.eval_filter(&drawing_context);
+		Backend::use_uniform_mat_4(self.filter_pos, filter.as_ref());
+		Backend::use_uniform_vec_4(self.filter_offset_pos, &filter_offset);
+	}
+
+	fn sampler_location(&self, name: &str) -> FerriciaResult<u32> {
+		self.uniforms.sampler_location(self.id, name)
+	}
+}
+
+/// Samples a layer of a texture array, selected by [RenderPrimitive::texture_layer].
+///
+/// The same as [TexProgram] but with the `layer` uniform as `uint`, so the shaders require GLSL 1.30.
+pub(crate) struct ArrayTexProgram {
+	id: u32,
+	model_pos: u32,
+	matrices: MatrixUniforms,
+	filter_pos: u32,
+	filter_offset_pos: u32,
+	layer_pos: u32,
+	sources: ProgramSources,
+}
+
+impl ArrayTexProgram {
+	pub(crate) fn new(vsh: String, fsh: String) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_program(read_shader(&vsh)?, read_shader(&fsh)?, [&vsh, &fsh], &[])?))
+	}
+
+	fn with_program((id, sources): (u32, ProgramSources)) -> Self {
+		Self {
+			model_pos: Backend::get_uniform_location(id, "model"),
+			matrices: MatrixUniforms::new(id),
+			filter_pos: filter_location(id),
+			filter_offset_pos: Backend::get_uniform_location(id, "filter_offset"),
+			layer_pos: Backend::get_uniform_location(id, "layer"),
+			sources,
+			id,
+		}
+	}
+}
+
+impl GuiProgram for ArrayTexProgram {
+	fn id(&self) -> u32 {
+		self.id
+	}
+
+	#[inline]
+	fn apply(&self) {
+		Backend::use_program(self.id);
 	}
 
-	fn uniform(&self, proj: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
-		use_uniform_mat_4(self.projection_pos, proj);
+	fn uniform(&self, proj: &TMat4<f32>, view: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
+		self.matrices.apply(proj, view);
 		let model = set.eval_model_mat(&drawing_context);
-		use_uniform_mat_4(self.model_pos, model.as_ref());
-		let filter = set.eval_filter_mat(&drawing_context);
-		use_uniform_mat_4(self.filter_pos, filter.as_ref());
+		Backend::use_uniform_mat_4(self.model_pos, model.as_ref());
+		let (filter, filter_offset) = set.eval_filter(&drawing_context);
+		Backend::use_uniform_mat_4(self.filter_pos, filter.as_ref());
+		Backend::use_uniform_vec_4(self.filter_offset_pos, &filter_offset);
+		Backend::use_uniform_uint(self.layer_pos, set.prim.texture_layer().map_or(0, |(v, _)| v.get()));
+	}
+
+	#[inline]
+	fn bind_texture(&self, texture: u32) {
+		Backend::use_texture_2d_array(texture);
+	}
+}
+
+/// Draws an indexed [TileMap], resolving the atlas region of each tile per fragment.
+///
+/// The atlas is bound to unit zero, while the `tiles` and `palette` samplers are assigned
+/// to the units bound by the map; the shaders require GLSL 1.30.
+pub(crate) struct TileMapProgram {
+	id: u32,
+	model_pos: u32,
+	matrices: MatrixUniforms,
+	filter_pos: u32,
+	filter_offset_pos: u32,
+	tiles_pos: u32,
+	palette_pos: u32,
+	sources: ProgramSources,
+}
+
+impl TileMapProgram {
+	/// Shaders are read from the file paths.
+	pub(crate) fn new(vsh: String, fsh: String) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_program(read_shader(&vsh)?, read_shader(&fsh)?, [&vsh, &fsh], &[])?))
+	}
+
+	/// Shaders are embedded in the crate, so no files have to be located.
+	pub(crate) fn default_program(window_handle: &WindowHandle) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_default_program(window_handle, DEFAULT_TILE_MAP_SHADERS, &DEFAULT_TILE_MAP_ATTRIBUTES, "tilemap")?))
+	}
+
+	fn with_program((id, sources): (u32, ProgramSources)) -> Self {
+		Self {
+			model_pos: Backend::get_uniform_location(id, "model"),
+			matrices: MatrixUniforms::new(id),
+			filter_pos: filter_location(id),
+			filter_offset_pos: Backend::get_uniform_location(id, "filter_offset"),
+			tiles_pos: Backend::get_uniform_location(id, "tiles"),
+			palette_pos: Backend::get_uniform_location(id, "palette"),
+			sources,
+			id,
+		}
+	}
+}
+
+impl GuiProgram for TileMapProgram {
+	fn id(&self) -> u32 {
+		self.id
+	}
+
+	#[inline]
+	fn apply(&self) {
+		Backend::use_program(self.id);
+		Backend::use_uniform_int(self.tiles_pos, TILES_UNIT as _);
+		Backend::use_uniform_int(self.palette_pos, PALETTE_UNIT as _);
+	}
+
+	fn uniform(&self, proj: &TMat4<f32>, view: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
+		self.matrices.apply(proj, view);
+		let model = set.eval_model_mat(&drawing_context);
+		Backend::use_uniform_mat_4(self.model_pos, model.as_ref());
+		let (filter, filter_offset) = set.eval_filter(&drawing_context);
+		Backend::use_uniform_mat_4(self.filter_pos, filter.as_ref());
+		Backend::use_uniform_vec_4(self.filter_offset_pos, &filter_offset);
+	}
+}
+
+/// Draws the ID of each drawable into a [PickBuffer](crate::mui::picking::PickBuffer).
+///
+/// The fragment shader should output the `pick_id` uniform as `uint`, and may discard
+/// transparent texels for pixel-accurate picking of irregular sprites.
+/// Filters are not applied.
+pub(crate) struct PickProgram {
+	id: u32,
+	model_pos: u32,
+	matrices: MatrixUniforms,
+	pick_id_pos: u32,
+	/// ID of the following draw
+	pick_id: Cell<u32>,
+	sources: ProgramSources,
+}
+
+impl PickProgram {
+	pub(crate) fn new(vsh: String, fsh: String) -> FerriciaResult<Self> {
+		Ok(Self::with_program(link_program(read_shader(&vsh)?, read_shader(&fsh)?, [&vsh, &fsh], &[])?))
+	}
+
+	fn with_program((id, sources): (u32, ProgramSources)) -> Self {
+		Self {
+			model_pos: Backend::get_uniform_location(id, "model"),
+			matrices: MatrixUniforms::new(id),
+			pick_id_pos: Backend::get_uniform_location(id, "pick_id"),
+			pick_id: Cell::new(0),
+			sources,
+			id,
+		}
+	}
+
+	/// Zero is reserved for nothing picked.
+	pub(crate) fn set_pick_id(&self, pick_id: u32) {
+		self.pick_id.set(pick_id);
+	}
+}
+
+impl GuiProgram for PickProgram {
+	fn id(&self) -> u32 {
+		self.id
+	}
+
+	#[inline]
+	fn apply(&self) {
+		Backend::use_program(self.id);
+	}
+
+	fn uniform(&self, proj: &TMat4<f32>, view: &TMat4<f32>, set: &DrawableSet, drawing_context: DrawingContext) {
+		self.matrices.apply(proj, view);
+		let model = set.eval_model_mat(&drawing_context);
+		Backend::use_uniform_mat_4(self.model_pos, model.as_ref());
+		Backend::use_uniform_uint(self.pick_id_pos, self.pick_id.get());
 	}
 }
 
@@ -233,63 +1407,103 @@ impl GuiProgram for TexProgram {
 // 		}
 // 	}
 // }
-//
-// /// A sequence of links to `DrawableSet` that are drawn by this instance.
-// pub(crate) struct DrawingLinks<'a> {
-// 	id: OpaqueId,
-// 	links: HashMap<OpaqueId, DrawingNode<'a>>,
-// }
-//
-// impl DrawingLinks<'_> {
-// 	fn new() -> Self {
-// 		static COUNTER: AtomicUsize = AtomicUsize::new(0);
-// 		Self {
-// 			id: OpaqueId::new(&COUNTER),
-// 			links: HashMap::new(),
-// 		}
-// 	}
-//
-// 	fn add_link(node: DrawingNode) {
-//
-// 	}
-// }
-//
-// enum DrawingNode<'a> {
-// 	Links(&'a DrawingLinks<'a>),
-// 	Set(&'a DrawableSet),
-// }
-//
-// impl<'a> DrawingNode<'a> {
-// 	fn id(&self) -> OpaqueId {
-// 		match self {
-// 			DrawingNode::Links(v) => v.id,
-// 			DrawingNode::Set(v) => v.id,
-// 		}
-// 	}
-// }
+
+/// A node of the transform hierarchy.
+///
+/// The model matrix of a node is applied after its own transforms are applied, so children
+/// are transformed by the whole model matrix chain of their ancestors.
+/// A node without any drawable may be used to group [DrawableSet]s, such as a GUI window
+/// with all its child components.
+///
+/// Parents keep the addresses of their children, so that both are detached when either is dropped.
+/// Nodes must not be moved while attached, which holds for those behind handles.
+pub(crate) struct SceneNode<'a> {
+	parent: Cell<Option<&'a SceneNode<'a>>>,
+	children: RefCell<Vec<*const SceneNode<'a>>>,
+	models: OrderSet<&'a dyn PrimModelTransform>,
+}
+
+impl<'a> SceneNode<'a> {
+	pub(crate) fn new() -> Self {
+		Self {
+			parent: Cell::new(None),
+			children: RefCell::new(Vec::new()),
+			models: OrderSet::new(),
+		}
+	}
+
+	pub(crate) fn add_model_transform<'b: 'a>(&mut self, transform: &'b dyn PrimModelTransform) {
+		self.models.insert(transform);
+	}
+
+	pub(crate) fn remove_model_transform<'b: 'a>(&mut self, transform: &'b dyn PrimModelTransform) {
+		self.models.remove(&transform);
+	}
+
+	/// Fails if the node would become its own ancestor.
+	pub(crate) fn set_parent(&mut self, parent: Option<&'a SceneNode<'a>>) -> FerriciaResult<()> {
+		let mut node = parent;
+		while let Some(v) = node {
+			if ptr::addr_eq(v, self as &Self) {
+				return Err("Scene node cannot be attached to its descendant".to_string().into());
+			}
+			node = v.parent.get();
+		}
+		self.attach(parent);
+		Ok(())
+	}
+
+	fn attach(&self, parent: Option<&'a SceneNode<'a>>) {
+		if let Some(v) = self.parent.replace(parent) {
+			v.children.borrow_mut().retain(|c| !ptr::eq(*c, self));
+		}
+		if let Some(v) = parent {
+			v.children.borrow_mut().push(self);
+		}
+	}
+
+	fn eval_model_mat(&self, drawing_context: &DrawingContext) -> Cow<TMat4<f32>> {
+		let local = if self.models.is_empty() {
+			Cow::Borrowed(&*IDENT_MAT_4)
+		} else {
+			let mut it = self.models.iter();
+			let first = it.next().unwrap();
+			Cow::Owned(it.fold(first.model_matrix(drawing_context), |m1, m2| m2.model_matrix(drawing_context) * m1))
+		};
+		match self.parent.get() {
+			None => local,
+			Some(parent) => Cow::Owned(parent.eval_model_mat(drawing_context).as_ref() * local.as_ref()),
+		}
+	}
+}
+
+impl Drop for SceneNode<'_> {
+	fn drop(&mut self) {
+		self.attach(None);
+		for child in self.children.get_mut().drain(..) {
+			// Children detach themselves from this node when dropped, so they are still alive.
+			unsafe { &*child }.parent.set(None);
+		}
+	}
+}
 
 /// A set of data that is completely drawable for an instance with all the information available.
 ///
 /// The functions of model and filter additions and removals are made generalized using
 /// experimental features which may not have guarantees.
 /// See [Rust RFC 2580](https://rust-lang.github.io/rfcs/2580-ptr-meta.html) for details.
-// #[derive(Getters)]
 pub(crate) struct DrawableSet<'a> {
-	// #[getset(get = "pub")]
-	// id: OpaqueId,
 	prim: Box<dyn RenderPrimitive>,
-	models: OrderSet<&'a dyn PrimModelTransform>,
+	node: SceneNode<'a>,
 	filters: OrderSet<&'a dyn PrimColorFilter>,
 	// _pin: PhantomPinned,
 }
 
 impl<'a> DrawableSet<'a> {
 	pub(crate) fn new(prim: impl RenderPrimitive + 'static) -> Self {
-		// static COUNTER: AtomicUsize = AtomicUsize::new(0);
 		Self {
-			// id: OpaqueId::new(&COUNTER),
 			prim: Box::new(prim),
-			models: OrderSet::new(),
+			node: SceneNode::new(),
 			filters: OrderSet::new(),
 			// _pin: PhantomPinned,
 		}
@@ -301,11 +1515,17 @@ impl<'a> DrawableSet<'a> {
 	}
 
 	pub(crate) fn add_model_transform<'b: 'a>(&mut self, transform: &'b dyn PrimModelTransform) {
-		self.models.insert(transform);
+		self.node.add_model_transform(transform);
 	}
 
 	pub(crate) fn remove_model_transform<'b: 'a>(&mut self, transform: &'b dyn PrimModelTransform) {
-		self.models.remove(&transform);
+		self.node.remove_model_transform(transform);
+	}
+
+	/// Attaches to the parent node, or detaches if `None`.
+	pub(crate) fn set_parent(&mut self, parent: Option<&'a SceneNode<'a>>) {
+		// A set cannot be a parent of any node, so no cycle may be formed.
+		self.node.attach(parent);
 	}
 
 	pub(crate) fn add_filter_transform<'b: 'a>(&mut self, filter: &'b dyn PrimColorFilter) {
@@ -316,23 +1536,47 @@ impl<'a> DrawableSet<'a> {
 		self.filters.remove(&filter);
 	}
 
+	/// Selects the layer of the texture array for a [LayeredSpriteMesh].
+	pub(crate) fn set_texture_layer(&self, layer: u32) -> FerriciaResult<()> {
+		let (cell, layers) = self.prim.texture_layer().ok_or("Drawable does not sample a texture array".to_string())?;
+		check_texture_layer(layer, layers)?;
+		cell.set(layer);
+		Ok(())
+	}
+
+	pub(crate) fn tile_map(&self) -> FerriciaResult<&TileMap> {
+		Ok(self.prim.tile_map().ok_or("Drawable is not a tile map".to_string())?)
+	}
+
+	pub(crate) fn shadow_batch(&self) -> FerriciaResult<&ShadowBatch> {
+		Ok(self.prim.shadow_batch().ok_or("Drawable is not a shadow batch".to_string())?)
+	}
+
+	/// Uploads the primitive again on a new context, such as after the previous one is lost.
+	pub(crate) fn recreate(&mut self) {
+		self.prim.recreate();
+	}
+
 	fn eval_model_mat(&self, drawing_context: &DrawingContext) -> Cow<TMat4<f32>> {
-		if self.models.is_empty() {
-			Cow::Borrowed(&*IDENT_MAT_4)
-		} else {
-			let mut it = self.models.iter();
-			let first = it.next().unwrap();
-			Cow::Owned(it.fold(first.model_matrix(drawing_context), |m1, m2| m2.model_matrix(drawing_context) * m1))
-		}
+		self.node.eval_model_mat(drawing_context)
 	}
 
-	fn eval_filter_mat(&self, drawing_context: &DrawingContext) -> Cow<TMat4<f32>> {
+	/// Returns the composed filter matrix and additive offset, applied as `filter * color + offset`.
+	///
+	/// Each filter is applied to the result of the previous one, so the previous offset
+	/// is also transformed by the matrix of the next filter.
+	fn eval_filter(&self, drawing_context: &DrawingContext) -> (Cow<TMat4<f32>>, TVec4<f32>) {
 		if self.filters.is_empty() {
-			Cow::Borrowed(&*IDENT_MAT_4)
+			(Cow::Borrowed(&*IDENT_MAT_4), TVec4::zeros())
 		} else {
 			let mut it = self.filters.iter();
 			let first = it.next().unwrap();
-			Cow::Owned(it.fold(first.filter_matrix(drawing_context), |m1, m2| m2.filter_matrix(drawing_context) * m1))
+			let init = (first.filter_matrix(drawing_context), first.filter_offset(drawing_context));
+			let (mat, offset) = it.fold(init, |(m1, o1), f| {
+				let m2 = f.filter_matrix(drawing_context);
+				(m2 * m1, m2 * o1 + f.filter_offset(drawing_context))
+			});
+			(Cow::Owned(mat), offset)
 		}
 	}
 }
@@ -342,10 +1586,28 @@ pub(crate) trait RenderPrimitive {
 
 	#[inline]
 	fn apply_vao(&self) {
-		use_vao(self.vao());
+		Backend::use_vao(self.vao());
 	}
 
 	fn draw(&self);
+
+	/// Uploads the data again on a new context, such as after the previous one is lost,
+	/// of which the objects must not be deleted.
+	fn recreate(&mut self);
+
+	/// Layer of the texture array to sample and the number of layers of the array,
+	/// if the primitive samples a texture array.
+	fn texture_layer(&self) -> Option<(&Cell<u32>, u32)> {
+		None
+	}
+
+	fn tile_map(&self) -> Option<&TileMap> {
+		None
+	}
+
+	fn shadow_batch(&self) -> Option<&ShadowBatch> {
+		None
+	}
 }
 
 /// All `Geom`s take coordinates as screen coordinates.
@@ -357,21 +1619,34 @@ trait Geom : RenderPrimitive {
 pub(crate) struct SimpleLineGeom {
 	vao: u32,
 	vbo: u32,
+	points: [(f32, f32); 2],
 	color: Color,
 }
 
 impl SimpleLineGeom {
 	const NUM_VERTICES: u32 = 2;
 	pub(crate) fn new(points: [(f32, f32); 2], color: Color) -> Self {
-		let vao = with_new_vert_arr();
-		let vbo = gen_buf_obj();
+		let (vao, vbo) = Self::upload(points);
+		Self { vao, vbo, points, color } // Note: Binding to the VAO remains
+	}
+
+	fn upload(points: [(f32, f32); 2]) -> (u32, u32) {
+		let vao = Backend::with_new_vert_arr();
+		let vbo = Backend::gen_buf_obj();
 		let vertices = [
 			points[0].0, points[0].1,
 			points[1].0, points[1].1,
 		];
-		buf_obj_with_data(ARRAY_BUFFER, vbo, &vertices, STATIC_DRAW);
-		vert_attr_arr(0, 2, NumType::Float, 2, 0); // Position
-		Self { vao, vbo, color } // Note: Binding to the VAO remains
+		Backend::buf_obj_with_data(BufferTarget::Vertex, vbo, &vertices, BufferUsage::Static);
+		Backend::vert_attr_arr(0, 2, NumType::Float, 2, 0); // Position
+		(vao, vbo)
+	}
+}
+
+impl Drop for SimpleLineGeom {
+	fn drop(&mut self) {
+		Backend::delete_vert_arr_obj(self.vao);
+		Backend::delete_buf_objs(&[self.vbo]);
 	}
 }
 
@@ -381,17 +1656,71 @@ impl RenderPrimitive for SimpleLineGeom {
 	}
 
 	fn draw(&self) {
-		vert_attr(1, VertexAttrVariant::UbyteNorm4.call(self.color.rgba())); // Color
-		draw_arrays(LINES, Self::NUM_VERTICES);
+		Backend::vert_attr(1, VertexAttrVariant::UbyteNorm4.call(self.color.rgba())); // Color
+		Backend::draw_arrays(Topology::Lines, Self::NUM_VERTICES);
+	}
+
+	fn recreate(&mut self) {
+		(self.vao, self.vbo) = Self::upload(self.points);
 	}
 }
 
 impl Geom for SimpleLineGeom {}
 
+/// Points of one pixel, or of any size by [GeoProgram::default_wide_point_program].
+pub(crate) struct SimplePointsGeom {
+	vao: u32,
+	vbo: u32,
+	num_vertices: u32,
+	vertices: Vec<f32>,
+	color: Color,
+}
+
+impl SimplePointsGeom {
+	pub(crate) fn new(points: &[(f32, f32)], color: Color) -> Self {
+		let vertices = points.iter().flat_map(|v| [v.0, v.1]).collect::<Vec<_>>();
+		let (vao, vbo) = Self::upload(&vertices);
+		Self { vao, vbo, num_vertices: points.len() as _, vertices, color } // Note: Binding to the VAO remains
+	}
+
+	fn upload(vertices: &[f32]) -> (u32, u32) {
+		let vao = Backend::with_new_vert_arr();
+		let vbo = Backend::gen_buf_obj();
+		Backend::buf_obj_with_data(BufferTarget::Vertex, vbo, vertices, BufferUsage::Static);
+		Backend::vert_attr_arr(0, 2, NumType::Float, 2, 0); // Position
+		(vao, vbo)
+	}
+}
+
+impl Drop for SimplePointsGeom {
+	fn drop(&mut self) {
+		Backend::delete_vert_arr_obj(self.vao);
+		Backend::delete_buf_objs(&[self.vbo]);
+	}
+}
+
+impl RenderPrimitive for SimplePointsGeom {
+	fn vao(&self) -> u32 {
+		self.vao
+	}
+
+	fn draw(&self) {
+		Backend::vert_attr(1, VertexAttrVariant::UbyteNorm4.call(self.color.rgba())); // Color
+		Backend::draw_arrays(Topology::Points, self.num_vertices);
+	}
+
+	fn recreate(&mut self) {
+		(self.vao, self.vbo) = Self::upload(&self.vertices);
+	}
+}
+
+impl Geom for SimplePointsGeom {}
+
 pub(crate) struct SimpleRectGeom {
 	vao: u32,
 	vbo: u32,
 	ebo: u32,
+	points: [f32; 4],
 	color: Color,
 }
 
@@ -405,8 +1734,13 @@ impl SimpleRectGeom {
 
 	/// `[x0, y0, x1, y1]`; (0, 0) as bottom-left
 	pub(crate) fn new(points: [f32; 4], color: Color) -> Self {
-		let vao = with_new_vert_arr();
-		let [vbo, ebo] = gen_buf_objs();
+		let (vao, vbo, ebo) = Self::upload(points);
+		Self { vao, vbo, ebo, points, color } // Note: Binding to the VAO remains
+	}
+
+	fn upload(points: [f32; 4]) -> (u32, u32, u32) {
+		let vao = Backend::with_new_vert_arr();
+		let [vbo, ebo] = Backend::gen_buf_objs();
 		let vertices = [
 			// positions
 			points[0], points[3], // top-left
@@ -414,10 +1748,17 @@ impl SimpleRectGeom {
 			points[2], points[1], // bottom-right
 			points[2], points[3], // top-right
 		];
-		buf_obj_with_data(ARRAY_BUFFER, vbo, &vertices, STATIC_DRAW);
-		buf_obj_with_data(ELEMENT_ARRAY_BUFFER, ebo, &Self::INDICES, STATIC_DRAW);
-		vert_attr_arr(0, 2, NumType::Float, 2, 0); // Position
-		Self { vao, vbo, ebo, color } // Note: Binding to the VAO remains
+		Backend::buf_obj_with_data(BufferTarget::Vertex, vbo, &vertices, BufferUsage::Static);
+		Backend::buf_obj_with_data(BufferTarget::Index, ebo, &Self::INDICES, BufferUsage::Static);
+		Backend::vert_attr_arr(0, 2, NumType::Float, 2, 0); // Position
+		(vao, vbo, ebo)
+	}
+}
+
+impl Drop for SimpleRectGeom {
+	fn drop(&mut self) {
+		Backend::delete_vert_arr_obj(self.vao);
+		Backend::delete_buf_objs(&[self.vbo, self.ebo]);
 	}
 }
 
@@ -427,8 +1768,12 @@ impl RenderPrimitive for SimpleRectGeom {
 	}
 
 	fn draw(&self) {
-		vert_attr(1, VertexAttrVariant::UbyteNorm4.call(self.color.rgba())); // Color
-		draw_elements(TRIANGLES, Self::NUM_ELEMENTS);
+		Backend::vert_attr(1, VertexAttrVariant::UbyteNorm4.call(self.color.rgba())); // Color
+		Backend::draw_elements(Topology::Triangles, Self::NUM_ELEMENTS);
+	}
+
+	fn recreate(&mut self) {
+		(self.vao, self.vbo, self.ebo) = Self::upload(self.points);
 	}
 }
 
@@ -443,6 +1788,8 @@ pub(crate) struct SpriteMesh {
 	vao: u32,
 	vbo: u32,
 	ebo: u32,
+	points: [u32; 4],
+	uv: [f32; 4],
 }
 
 impl SpriteMesh {
@@ -455,32 +1802,98 @@ impl SpriteMesh {
 
 	/// `[x0, y0, x1, y1]`; (0, 0) as bottom-left
 	pub(crate) fn new(points: [u32; 4]) -> Self {
-		let vao = with_new_vert_arr();
-		let [vbo, ebo] = gen_buf_objs();
+		Self::with_uv(points, [0.0, 0.0, 1.0, 1.0])
+	}
+
+	/// Samples only a region of the texture, such as a sprite in a texture atlas.
+	///
+	/// `uv` is `[u0, v0, u1, v1]` with (0, 0) as bottom-left, the same as `points`.
+	pub(crate) fn with_uv(points: [u32; 4], uv: [f32; 4]) -> Self {
+		let (vao, vbo, ebo) = Self::upload(points, uv);
+		Self { vao, vbo, ebo, points, uv } // Note: Binding to the VAO remains
+	}
+
+	fn upload(points: [u32; 4], uv: [f32; 4]) -> (u32, u32, u32) {
+		let vao = Backend::with_new_vert_arr();
+		let [vbo, ebo] = Backend::gen_buf_objs();
 		let vertices: [f32; 16] = [
 			// positions                    // tex coords
-			points[0] as _, points[3] as _, 0.0, 1.0, // top-left
-			points[0] as _, points[1] as _, 0.0, 0.0, // bottom-left
-			points[2] as _, points[1] as _, 1.0, 0.0, // bottom-right
-			points[2] as _, points[3] as _, 1.0, 1.0, // top-right
+			points[0] as _, points[3] as _, uv[0], uv[3], // top-left
+			points[0] as _, points[1] as _, uv[0], uv[1], // bottom-left
+			points[2] as _, points[1] as _, uv[2], uv[1], // bottom-right
+			points[2] as _, points[3] as _, uv[2], uv[3], // top-right
 		];
-		buf_obj_with_data(ARRAY_BUFFER, vbo, &vertices, STATIC_DRAW);
-		buf_obj_with_data(ELEMENT_ARRAY_BUFFER, ebo, &Self::INDICES, STATIC_DRAW);
-		vert_attr_arr(0, 2, NumType::Float, 4, 0); // Position
-		vert_attr_arr(1, 2, NumType::Float, 4, 2); // Texture coord
-		Self { vao, vbo, ebo } // Note: Binding to the VAO remains
+		Backend::buf_obj_with_data(BufferTarget::Vertex, vbo, &vertices, BufferUsage::Static);
+		Backend::buf_obj_with_data(BufferTarget::Index, ebo, &Self::INDICES, BufferUsage::Static);
+		Backend::vert_attr_arr(0, 2, NumType::Float, 4, 0); // Position
+		Backend::vert_attr_arr(1, 2, NumType::Float, 4, 2); // Texture coord
+		(vao, vbo, ebo)
 	}
 }
 
 impl Mesh for SpriteMesh {}
 
+impl Drop for SpriteMesh {
+	fn drop(&mut self) {
+		Backend::delete_vert_arr_obj(self.vao);
+		Backend::delete_buf_objs(&[self.vbo, self.ebo]);
+	}
+}
+
 impl RenderPrimitive for SpriteMesh {
 	fn vao(&self) -> u32 {
 		self.vao
 	}
 
 	fn draw(&self) {
-		draw_elements(TRIANGLES, Self::NUM_ELEMENTS);
+		Backend::draw_elements(Topology::Triangles, Self::NUM_ELEMENTS);
+	}
+
+	fn recreate(&mut self) {
+		(self.vao, self.vbo, self.ebo) = Self::upload(self.points, self.uv);
+	}
+}
+
+/// A [SpriteMesh] sampling a layer of a texture array, such as a frame of an animation.
+pub(crate) struct LayeredSpriteMesh {
+	mesh: SpriteMesh,
+	layer: Cell<u32>,
+	/// Number of layers of the texture array to sample
+	layers: u32,
+}
+
+impl LayeredSpriteMesh {
+	/// `[x0, y0, x1, y1]`; (0, 0) as bottom-left
+	pub(crate) fn new(points: [u32; 4], layers: u32, layer: u32) -> FerriciaResult<Self> {
+		check_texture_layer(layer, layers)?;
+		Ok(Self { mesh: SpriteMesh::new(points), layer: Cell::new(layer), layers })
+	}
+}
+
+fn check_texture_layer(layer: u32, layers: u32) -> FerriciaResult<()> {
+	if layer >= layers {
+		return Err(format!("Texture layer {layer} is out of the {layers} layers").into());
+	}
+	Ok(())
+}
+
+impl Mesh for LayeredSpriteMesh {}
+
+impl RenderPrimitive for LayeredSpriteMesh {
+	fn vao(&self) -> u32 {
+		self.mesh.vao
+	}
+
+	fn draw(&self) {
+		self.mesh.draw();
+	}
+
+	fn recreate(&mut self) {
+		self.mesh.recreate();
+	}
+
+	fn texture_layer(&self) -> Option<(&Cell<u32>, u32)> {
+		Some((&self.layer, self.layers))
 	}
 }
 
@@ -609,8 +2022,119 @@ impl PrimModelTransform for SimpleTranslation {
 	}
 }
 
+/// Scaling around an arbitrary anchor point in screen coordinates, such as the center of a sprite,
+/// instead of the origin of the window as in [SmartScaling].
+///
+/// The matrix is evaluated as `T(anchor) * S(factor) * T(-anchor)`.
+pub(crate) struct PivotScaling {
+	anchor: TVec2<f32>,
+	factor: TVec2<f32>,
+}
+
+impl PivotScaling {
+	pub(crate) fn new(anchor: (f32, f32), factor: (f32, f32)) -> Self {
+		Self { anchor: vec2(anchor.0, anchor.1), factor: vec2(factor.0, factor.1) }
+	}
+
+	pub(crate) fn set_anchor(&mut self, anchor: TVec2<f32>) {
+		self.anchor = anchor;
+	}
+
+	pub(crate) fn set_factor(&mut self, factor: TVec2<f32>) {
+		self.factor = factor;
+	}
+}
+
+impl PrimModelTransform for PivotScaling {
+	fn model_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let anchor = vec2_to_vec3(&self.anchor);
+		translation(&anchor) * scaling(&vec3(self.factor.x, self.factor.y, 1.0)) * translation(&-anchor)
+	}
+}
+
+/// Flips and skews around an anchor, such as the center of a sprite for its facing direction.
+///
+/// Skew factors are shears, so that `x' = x + skew.x * y` and `y' = y + skew.y * x`
+/// relative to the anchor; flips are applied before skewing.
+pub(crate) struct FlipSkew {
+	anchor: TVec2<f32>,
+	flip: (bool, bool),
+	skew: TVec2<f32>,
+}
+
+impl FlipSkew {
+	pub(crate) fn new(anchor: (f32, f32)) -> Self {
+		Self { anchor: vec2(anchor.0, anchor.1), flip: (false, false), skew: vec2(0.0, 0.0) }
+	}
+
+	pub(crate) fn set_anchor(&mut self, anchor: TVec2<f32>) {
+		self.anchor = anchor;
+	}
+
+	pub(crate) fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+		self.flip = (horizontal, vertical);
+	}
+
+	pub(crate) fn set_skew(&mut self, skew: TVec2<f32>) {
+		self.skew = skew;
+	}
+}
+
+impl PrimModelTransform for FlipSkew {
+	fn model_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let anchor = vec2_to_vec3(&self.anchor);
+		let mut shear = *IDENT_MAT_4;
+		shear.m12 = self.skew.x;
+		shear.m21 = self.skew.y;
+		let flip = vec3(if self.flip.0 { -1.0 } else { 1.0 }, if self.flip.1 { -1.0 } else { 1.0 }, 1.0);
+		translation(&anchor) * shear * scaling(&flip) * translation(&-anchor)
+	}
+}
+
+/// General 2D affine transformation supplied as a full matrix.
+///
+/// The matrix is given in the same layout as `java.awt.geom.AffineTransform#getMatrix`:
+/// `[m00, m10, m01, m11, m02, m12]`, so that `x' = m00 * x + m01 * y + m02`
+/// and `y' = m10 * x + m11 * y + m12`.
+pub(crate) struct AffineTransform {
+	mat: TMat4<f32>,
+}
+
+impl AffineTransform {
+	pub(crate) fn new(matrix: [f32; 6]) -> Self {
+		Self { mat: affine_mat(matrix) }
+	}
+
+	pub(crate) fn set_matrix(&mut self, matrix: [f32; 6]) {
+		self.mat = affine_mat(matrix);
+	}
+}
+
+fn affine_mat(matrix: [f32; 6]) -> TMat4<f32> {
+	let [m00, m10, m01, m11, m02, m12] = matrix;
+	let mut mat = *IDENT_MAT_4;
+	mat.m11 = m00;
+	mat.m21 = m10;
+	mat.m12 = m01;
+	mat.m22 = m11;
+	mat.m14 = m02;
+	mat.m24 = m12;
+	mat
+}
+
+impl PrimModelTransform for AffineTransform {
+	fn model_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		self.mat
+	}
+}
+
 pub(crate) trait PrimColorFilter {
 	fn filter_matrix(&self, drawing_context: &DrawingContext) -> TMat4<f32>;
+
+	/// Additive RGBA offset applied after the filter matrix.
+	fn filter_offset(&self, _drawing_context: &DrawingContext) -> TVec4<f32> {
+		TVec4::zeros()
+	}
 }
 
 impl Hash for &dyn PrimColorFilter {
@@ -627,24 +2151,350 @@ impl PartialEq for &dyn PrimColorFilter {
 
 impl Eq for &dyn PrimColorFilter {}
 
+/// The alpha may be animated natively with [AlphaFilter::fade_to], evaluated at each draw.
 pub(crate) struct AlphaFilter {
 	alpha: f32,
+	fade: Option<AlphaFade>,
+}
+
+struct AlphaFade {
+	to: f32,
+	start: Instant,
+	duration: Duration,
 }
 
 impl AlphaFilter {
 	pub(crate) fn new(alpha: f32) -> Self {
-		Self { alpha }
+		Self { alpha, fade: None }
 	}
-	
+
+	/// This cancels the running fade.
 	pub(crate) fn set_alpha(&mut self, alpha: f32) {
 		self.alpha = alpha;
+		self.fade = None;
+	}
+
+	/// Fades linearly from the current alpha to the target.
+	pub(crate) fn fade_to(&mut self, alpha: f32, duration: Duration) {
+		let now = Instant::now();
+		self.alpha = self.alpha_at(now);
+		self.fade = Some(AlphaFade { to: alpha, start: now, duration });
+	}
+
+	/// Keeps the alpha at the current point of the running fade.
+	pub(crate) fn stop_fade(&mut self) {
+		self.alpha = self.alpha_at(Instant::now());
+		self.fade = None;
+	}
+
+	fn alpha_at(&self, time: Instant) -> f32 {
+		match &self.fade {
+			None => self.alpha,
+			Some(fade) => {
+				let elapsed = time.saturating_duration_since(fade.start);
+				if elapsed >= fade.duration {
+					fade.to
+				} else {
+					let f = elapsed.as_secs_f32() / fade.duration.as_secs_f32();
+					self.alpha + (fade.to - self.alpha) * f
+				}
+			}
+		}
 	}
 }
 
 impl PrimColorFilter for AlphaFilter {
+	fn filter_matrix(&self, drawing_context: &DrawingContext) -> TMat4<f32> {
+		let mut mat = *IDENT_MAT_4;
+		mat.m44 = self.alpha_at(drawing_context.time);
+		mat
+	}
+}
+
+/// Multiplies the RGB channels by the tint color, blended with the original color by `strength`.
+pub(crate) struct TintFilter {
+	color: (f32, f32, f32),
+	strength: f32,
+}
+
+impl TintFilter {
+	pub(crate) fn new(color: (f32, f32, f32), strength: f32) -> Self {
+		Self { color, strength }
+	}
+
+	pub(crate) fn set_color(&mut self, color: (f32, f32, f32)) {
+		self.color = color;
+	}
+
+	pub(crate) fn set_strength(&mut self, strength: f32) {
+		self.strength = strength;
+	}
+}
+
+impl PrimColorFilter for TintFilter {
+	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let mut mat = *IDENT_MAT_4;
+		mat.m11 = 1.0 + (self.color.0 - 1.0) * self.strength;
+		mat.m22 = 1.0 + (self.color.1 - 1.0) * self.strength;
+		mat.m33 = 1.0 + (self.color.2 - 1.0) * self.strength;
+		mat
+	}
+}
+
+/// Desaturates the RGB channels by the relative luminance (BT.709), blended by `amount`.
+pub(crate) struct GrayscaleFilter {
+	amount: f32,
+}
+
+impl GrayscaleFilter {
+	const LUMA: (f32, f32, f32) = (0.2126, 0.7152, 0.0722);
+
+	pub(crate) fn new(amount: f32) -> Self {
+		Self { amount }
+	}
+
+	pub(crate) fn set_amount(&mut self, amount: f32) {
+		self.amount = amount;
+	}
+}
+
+impl PrimColorFilter for GrayscaleFilter {
+	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let mut mat = *IDENT_MAT_4;
+		let (r, g, b) = Self::LUMA;
+		let (a, k) = (self.amount, 1.0 - self.amount);
+		mat.m11 = k + a * r;
+		mat.m12 = a * g;
+		mat.m13 = a * b;
+		mat.m21 = a * r;
+		mat.m22 = k + a * g;
+		mat.m23 = a * b;
+		mat.m31 = a * r;
+		mat.m32 = a * g;
+		mat.m33 = k + a * b;
+		mat
+	}
+}
+
+/// Rotates the hue of the RGB channels in degrees, preserving the luminance approximately.
+///
+/// The matrix is the same as `hueRotate` of `feColorMatrix` in SVG.
+pub(crate) struct HueRotateFilter {
+	degrees: f32,
+}
+
+impl HueRotateFilter {
+	pub(crate) fn new(degrees: f32) -> Self {
+		Self { degrees }
+	}
+
+	pub(crate) fn set_degrees(&mut self, degrees: f32) {
+		self.degrees = degrees;
+	}
+}
+
+impl PrimColorFilter for HueRotateFilter {
+	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let (sin, cos) = self.degrees.to_radians().sin_cos();
+		let mut mat = *IDENT_MAT_4;
+		mat.m11 = 0.213 + cos * 0.787 - sin * 0.213;
+		mat.m12 = 0.715 - cos * 0.715 - sin * 0.715;
+		mat.m13 = 0.072 - cos * 0.072 + sin * 0.928;
+		mat.m21 = 0.213 - cos * 0.213 + sin * 0.143;
+		mat.m22 = 0.715 + cos * 0.285 + sin * 0.140;
+		mat.m23 = 0.072 - cos * 0.072 - sin * 0.283;
+		mat.m31 = 0.213 - cos * 0.213 - sin * 0.787;
+		mat.m32 = 0.715 - cos * 0.715 + sin * 0.715;
+		mat.m33 = 0.072 + cos * 0.928 + sin * 0.072;
+		mat
+	}
+}
+
+/// Adds `brightness` to the RGB channels; `0` keeps the original color.
+pub(crate) struct BrightnessFilter {
+	brightness: f32,
+}
+
+impl BrightnessFilter {
+	pub(crate) fn new(brightness: f32) -> Self {
+		Self { brightness }
+	}
+
+	pub(crate) fn set_brightness(&mut self, brightness: f32) {
+		self.brightness = brightness;
+	}
+}
+
+impl PrimColorFilter for BrightnessFilter {
+	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		*IDENT_MAT_4
+	}
+
+	fn filter_offset(&self, _drawing_context: &DrawingContext) -> TVec4<f32> {
+		vec4(self.brightness, self.brightness, self.brightness, 0.0)
+	}
+}
+
+/// Scales the RGB channels around the middle gray by `contrast`; `1` keeps the original color.
+pub(crate) struct ContrastFilter {
+	contrast: f32,
+}
+
+impl ContrastFilter {
+	pub(crate) fn new(contrast: f32) -> Self {
+		Self { contrast }
+	}
+
+	pub(crate) fn set_contrast(&mut self, contrast: f32) {
+		self.contrast = contrast;
+	}
+}
+
+impl PrimColorFilter for ContrastFilter {
+	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let mut mat = *IDENT_MAT_4;
+		mat.m11 = self.contrast;
+		mat.m22 = self.contrast;
+		mat.m33 = self.contrast;
+		mat
+	}
+
+	fn filter_offset(&self, _drawing_context: &DrawingContext) -> TVec4<f32> {
+		let v = 0.5 * (1.0 - self.contrast);
+		vec4(v, v, v, 0.0)
+	}
+}
+
+/// Scales the saturation of the RGB channels by the relative luminance (BT.709).
+///
+/// `0` results in grayscale, `1` keeps the original color, and values above `1` oversaturate.
+pub(crate) struct SaturationFilter {
+	saturation: f32,
+}
+
+impl SaturationFilter {
+	pub(crate) fn new(saturation: f32) -> Self {
+		Self { saturation }
+	}
+
+	pub(crate) fn set_saturation(&mut self, saturation: f32) {
+		self.saturation = saturation;
+	}
+}
+
+impl PrimColorFilter for SaturationFilter {
+	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
+		let mut mat = *IDENT_MAT_4;
+		let (r, g, b) = GrayscaleFilter::LUMA;
+		let (s, k) = (self.saturation, 1.0 - self.saturation);
+		mat.m11 = s + k * r;
+		mat.m12 = k * g;
+		mat.m13 = k * b;
+		mat.m21 = k * r;
+		mat.m22 = s + k * g;
+		mat.m23 = k * b;
+		mat.m31 = k * r;
+		mat.m32 = k * g;
+		mat.m33 = s + k * b;
+		mat
+	}
+}
+
+/// Interpolates linearly between two filter matrices, either animated by time or set manually.
+pub(crate) struct LerpColorFilter {
+	from: TMat4<f32>,
+	to: TMat4<f32>,
+	progress: f32,
+	animation: Option<LerpAnimation>,
+}
+
+struct LerpAnimation {
+	start: Instant,
+	duration: Duration,
+	mode: LerpMode,
+}
+
+pub(crate) enum LerpMode {
+	/// From `from` to `to` once, and stays at `to`.
+	Once,
+	/// From `from` to `to` and back once within twice the duration, useful for flashes.
+	Bounce,
+	/// Back and forth repeatedly.
+	PingPong,
+}
+
+impl LerpColorFilter {
+	/// Matrices are in column-major order.
+	pub(crate) fn new(from: TMat4<f32>, to: TMat4<f32>) -> Self {
+		Self { from, to, progress: 0.0, animation: None }
+	}
+
+	/// This stops the running animation.
+	pub(crate) fn set_progress(&mut self, progress: f32) {
+		self.progress = progress.clamp(0.0, 1.0);
+		self.animation = None;
+	}
+
+	pub(crate) fn start(&mut self, duration: Duration, mode: LerpMode) {
+		self.animation = Some(LerpAnimation { start: Instant::now(), duration, mode });
+	}
+
+	fn progress_at(&self, time: Instant) -> f32 {
+		let Some(animation) = &self.animation else { return self.progress };
+		if animation.duration.is_zero() {
+			return match animation.mode {
+				LerpMode::Once => 1.0,
+				LerpMode::Bounce | LerpMode::PingPong => 0.0,
+			};
+		}
+		let t = time.saturating_duration_since(animation.start).as_secs_f32() / animation.duration.as_secs_f32();
+		match animation.mode {
+			LerpMode::Once => t.min(1.0),
+			LerpMode::Bounce => if t >= 2.0 { 0.0 } else { 1.0 - (t - 1.0).abs() },
+			LerpMode::PingPong => 1.0 - (t % 2.0 - 1.0).abs(),
+		}
+	}
+}
+
+impl PrimColorFilter for LerpColorFilter {
+	fn filter_matrix(&self, drawing_context: &DrawingContext) -> TMat4<f32> {
+		let t = self.progress_at(drawing_context.time);
+		self.from * (1.0 - t) + self.to * t
+	}
+}
+
+/// Blends the RGB channels towards a flat color by `strength`, such as a white flash on hit.
+///
+/// This cannot be expressed by a matrix alone, so the flash color is supplied as the offset.
+pub(crate) struct FlashFilter {
+	color: (f32, f32, f32),
+	strength: f32,
+}
+
+impl FlashFilter {
+	pub(crate) fn new(color: (f32, f32, f32), strength: f32) -> Self {
+		Self { color, strength }
+	}
+
+	pub(crate) fn set_color(&mut self, color: (f32, f32, f32)) {
+		self.color = color;
+	}
+
+	pub(crate) fn set_strength(&mut self, strength: f32) {
+		self.strength = strength;
+	}
+}
+
+impl PrimColorFilter for FlashFilter {
 	fn filter_matrix(&self, _drawing_context: &DrawingContext) -> TMat4<f32> {
 		let mut mat = *IDENT_MAT_4;
-		mat.m44 = self.alpha;
+		mat.m11 = 1.0 - self.strength;
+		mat.m22 = 1.0 - self.strength;
+		mat.m33 = 1.0 - self.strength;
 		mat
 	}
+
+	fn filter_offset(&self, _drawing_context: &DrawingContext) -> TVec4<f32> {
+		vec4(self.color.0, self.color.1, self.color.2, 0.0) * self.strength
+	}
 }
@@ -0,0 +1,168 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Blob shadows of entities, drawn in a single batch.
+//!
+//! Each shadow is a soft ellipse under an entity, growing and fading as the entity rises,
+//! so that Java does not have to draw an extra sprite per entity. The batch is drawn with
+//! the shadow program of [GeoProgram::default_shadow_program](crate::mui::rendering::GeoProgram::default_shadow_program),
+//! or any [GeoProgram](crate::mui::rendering::GeoProgram) with the same attributes.
+//!
+//! Vertices are rebuilt on the next draw after any change, which is cheap for the few hundred
+//! shadows expected on screen.
+
+use crate::mui::backend::{Backend, BufferTarget, BufferUsage, NumType, RenderBackend, Topology};
+use crate::mui::rendering::RenderPrimitive;
+use crate::FerriciaResult;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Vertical radius over horizontal radius, as seen from the top-down view
+const ASPECT: f32 = 0.5;
+/// Floats of a vertex: position, offset and opacity
+const VERTEX_LEN: usize = 5;
+
+#[derive(Clone, Copy)]
+struct Shadow {
+	/// Ground position under the entity
+	pos: (f32, f32),
+	/// Width on the ground
+	width: f32,
+	/// Height of the entity above the ground
+	height: f32,
+}
+
+pub(crate) struct ShadowBatch {
+	vao: u32,
+	vbo: u32,
+	ebo: u32,
+	/// Opacity of shadows of entities on the ground
+	opacity: f32,
+	/// Height at which shadows are twice as large and half as opaque
+	falloff: f32,
+	shadows: RefCell<HashMap<u32, Shadow>>,
+	next_id: Cell<u32>,
+	/// Elements of the last upload; `None` if changed since then
+	num_elements: Cell<Option<u32>>,
+}
+
+impl ShadowBatch {
+	pub(crate) fn new(opacity: f32, falloff: f32) -> FerriciaResult<Self> {
+		if falloff <= 0.0 {
+			return Err("Shadow falloff must be positive".to_string().into());
+		}
+		let (vao, vbo, ebo) = Self::gen_objects();
+		Ok(Self { // Note: Binding to the VAO remains
+			vao,
+			vbo,
+			ebo,
+			opacity: opacity.clamp(0.0, 1.0),
+			falloff,
+			shadows: RefCell::new(HashMap::new()),
+			next_id: Cell::new(0),
+			num_elements: Cell::new(None),
+		})
+	}
+
+	fn gen_objects() -> (u32, u32, u32) {
+		let vao = Backend::with_new_vert_arr();
+		let [vbo, ebo] = Backend::gen_buf_objs();
+		Backend::buf_obj_with_data::<f32>(BufferTarget::Vertex, vbo, &[], BufferUsage::Dynamic);
+		Backend::buf_obj_with_data::<u32>(BufferTarget::Index, ebo, &[], BufferUsage::Dynamic);
+		Backend::vert_attr_arr(0, 2, NumType::Float, VERTEX_LEN, 0); // Position
+		Backend::vert_attr_arr(1, 2, NumType::Float, VERTEX_LEN, 2); // Offset
+		Backend::vert_attr_arr(2, 1, NumType::Float, VERTEX_LEN, 4); // Opacity
+		(vao, vbo, ebo)
+	}
+
+	/// Returns the ID of the shadow; `pos` is the ground position under the entity.
+	pub(crate) fn add(&self, pos: (f32, f32), width: f32, height: f32) -> u32 {
+		let id = self.next_id.get();
+		self.next_id.set(id.wrapping_add(1));
+		self.shadows.borrow_mut().insert(id, Shadow { pos, width, height });
+		self.num_elements.set(None);
+		id
+	}
+
+	pub(crate) fn update(&self, id: u32, pos: (f32, f32), height: f32) -> FerriciaResult<()> {
+		let mut shadows = self.shadows.borrow_mut();
+		let shadow = shadows.get_mut(&id).ok_or_else(|| format!("Unknown shadow: {id}"))?;
+		shadow.pos = pos;
+		shadow.height = height;
+		self.num_elements.set(None);
+		Ok(())
+	}
+
+	/// Returns whether the shadow existed.
+	pub(crate) fn remove(&self, id: u32) -> bool {
+		let removed = self.shadows.borrow_mut().remove(&id).is_some();
+		if removed {
+			self.num_elements.set(None);
+		}
+		removed
+	}
+
+	fn upload(&self) -> u32 {
+		let shadows = self.shadows.borrow();
+		let mut vertices = Vec::with_capacity(shadows.len() * 4 * VERTEX_LEN);
+		let mut indices = Vec::with_capacity(shadows.len() * 6);
+		for (i, shadow) in shadows.values().enumerate() {
+			let scale = 1.0 + shadow.height.max(0.0) / self.falloff;
+			let rx = shadow.width / 2.0 * scale;
+			let ry = rx * ASPECT;
+			let opacity = self.opacity / scale;
+			let (x, y) = shadow.pos;
+			vertices.extend([
+				x - rx, y + ry, -1.0, 1.0, opacity, // top-left
+				x - rx, y - ry, -1.0, -1.0, opacity, // bottom-left
+				x + rx, y - ry, 1.0, -1.0, opacity, // bottom-right
+				x + rx, y + ry, 1.0, 1.0, opacity, // top-right
+			]);
+			let base = i as u32 * 4;
+			indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+		}
+		Backend::buf_obj_with_data(BufferTarget::Vertex, self.vbo, &vertices, BufferUsage::Dynamic);
+		// The VAO is bound by the draw, so the EBO binding is kept in it.
+		Backend::buf_obj_with_data(BufferTarget::Index, self.ebo, &indices, BufferUsage::Dynamic);
+		indices.len() as _
+	}
+}
+
+impl Drop for ShadowBatch {
+	fn drop(&mut self) {
+		Backend::delete_vert_arr_obj(self.vao);
+		Backend::delete_buf_objs(&[self.vbo, self.ebo]);
+	}
+}
+
+impl RenderPrimitive for ShadowBatch {
+	fn vao(&self) -> u32 {
+		self.vao
+	}
+
+	fn draw(&self) {
+		let num_elements = match self.num_elements.get() {
+			Some(v) => v,
+			None => {
+				let v = self.upload();
+				self.num_elements.set(Some(v));
+				v
+			}
+		};
+		if num_elements > 0 {
+			Backend::draw_elements(Topology::Triangles, num_elements);
+		}
+	}
+
+	fn recreate(&mut self) {
+		(self.vao, self.vbo, self.ebo) = Self::gen_objects();
+		// Uploaded again on the next draw
+		self.num_elements.set(None);
+	}
+
+	fn shadow_batch(&self) -> Option<&ShadowBatch> {
+		Some(self)
+	}
+}
@@ -0,0 +1,130 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Assignment of connected gamepads to player slots for local multiplayer.
+//!
+//! Each slot remembers the GUID of its last gamepad, so a reconnected gamepad returns to its slot.
+//! GUIDs identify the model rather than the device, so identical gamepads are told apart only by
+//! the order of reconnection. Slot events are queued and reported at the end of each poll.
+
+use crate::mui::MuiEvent;
+use crate::FerriciaResult;
+use sdl3::sys::guid::SDL_GUIDToString;
+use sdl3::sys::joystick::{SDL_GetJoystickGUIDForID, SDL_JoystickID};
+use std::ffi::{c_char, CStr};
+
+#[derive(Default)]
+struct PlayerSlot {
+	gamepad: Option<u32>,
+	/// GUID of the last assigned gamepad, kept after disconnection.
+	guid: Option<String>,
+}
+
+#[derive(Default)]
+pub(super) struct PlayerSlots {
+	slots: Vec<PlayerSlot>,
+	events: Vec<MuiEvent>,
+}
+
+impl PlayerSlots {
+	/// Gamepads of removed slots are released; new slots are filled by unassigned gamepads
+	/// in the order of connection.
+	pub(super) fn set_count(&mut self, count: usize, connected: impl Iterator<Item = u32>) {
+		while self.slots.len() > count {
+			let slot = self.slots.pop().expect("should not be empty");
+			if slot.gamepad.is_some() {
+				self.events.push(MuiEvent::PlayerSlotDisconnected(self.slots.len() as _));
+			}
+		}
+		self.slots.resize_with(count, PlayerSlot::default);
+		// Joystick IDs increase with each connection in SDL, unlike the order of iterating a map.
+		let mut connected = connected.collect::<Vec<_>>();
+		connected.sort_unstable();
+		for which in connected {
+			if !self.slots.iter().any(|v| v.gamepad == Some(which)) {
+				self.gamepad_added(which);
+			}
+		}
+	}
+
+	/// Prefers the slot of the same GUID, then an unused slot, and then a slot remembering
+	/// another gamepad. Nothing is assigned if all the slots are occupied.
+	pub(super) fn gamepad_added(&mut self, which: u32) {
+		let guid = gamepad_guid(which);
+		let Some((i, slot)) = self.slots.iter_mut().enumerate()
+			.filter(|(_, v)| v.gamepad.is_none())
+			.min_by_key(|(_, v)| match &v.guid {
+				Some(g) if *g == guid => 0,
+				None => 1,
+				Some(_) => 2,
+			}) else { return };
+		slot.gamepad = Some(which);
+		slot.guid = Some(guid);
+		self.events.push(MuiEvent::PlayerSlotAssigned(i as _, which));
+	}
+
+	/// The slot keeps the GUID for reconnection.
+	pub(super) fn gamepad_removed(&mut self, which: u32) {
+		if let Some(i) = self.slots.iter().position(|v| v.gamepad == Some(which)) {
+			self.slots[i].gamepad = None;
+			self.events.push(MuiEvent::PlayerSlotDisconnected(i as _));
+		}
+	}
+
+	/// Swaps with the slot having the gamepad if any, such as for choosing slots in a lobby.
+	pub(super) fn assign(&mut self, slot: usize, which: u32) -> FerriciaResult<()> {
+		if slot >= self.slots.len() {
+			return Err(format!("Invalid player slot: {slot}").into());
+		}
+		if let Some(i) = self.slots.iter().position(|v| v.gamepad == Some(which)) {
+			if i == slot {
+				return Ok(());
+			}
+			self.slots.swap(i, slot);
+			self.events.push(match self.slots[i].gamepad {
+				Some(v) => MuiEvent::PlayerSlotAssigned(i as _, v),
+				None => MuiEvent::PlayerSlotDisconnected(i as _),
+			});
+		} else {
+			if self.slots[slot].gamepad.is_some() {
+				return Err(format!("Player slot {slot} is occupied").into());
+			}
+			self.slots[slot].gamepad = Some(which);
+			self.slots[slot].guid = Some(gamepad_guid(which));
+		}
+		self.events.push(MuiEvent::PlayerSlotAssigned(slot as _, which));
+		Ok(())
+	}
+
+	pub(super) fn drain_events(&mut self) -> impl Iterator<Item = MuiEvent> + '_ {
+		self.events.drain(..)
+	}
+
+	/// Returns the gamepad of the slot.
+	pub(super) fn gamepad(&self, slot: usize) -> Option<u32> {
+		self.slots.get(slot).and_then(|v| v.gamepad)
+	}
+
+	/// Returns the GUIDs of slots by index, to be persisted.
+	pub(super) fn export(&self) -> Vec<Option<String>> {
+		self.slots.iter().map(|v| v.guid.clone()).collect()
+	}
+
+	/// Restores the GUIDs of slots without changing the current assignments.
+	pub(super) fn import(&mut self, guids: Vec<Option<String>>) {
+		for (slot, guid) in self.slots.iter_mut().zip(guids) {
+			if slot.gamepad.is_none() {
+				slot.guid = guid;
+			}
+		}
+	}
+}
+
+pub(super) fn gamepad_guid(which: u32) -> String {
+	let guid = unsafe { SDL_GetJoystickGUIDForID(SDL_JoystickID(which)) };
+	let mut buf = [0 as c_char; 33];
+	unsafe { SDL_GUIDToString(guid, buf.as_mut_ptr(), buf.len() as _) };
+	unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned()
+}
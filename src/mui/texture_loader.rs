@@ -0,0 +1,138 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Decoding of images on background threads.
+//!
+//! Only the decoding is done by the workers, as GL calls must be made on the render thread;
+//! the decoded images are uploaded by [TextureLoader::poll], which should be called every frame.
+//! Failures are posted as `texture-loader` engine events, with the ID of the request.
+
+use crate::events::post_event;
+use crate::mui::rendering::{read_image, track_texture_source, upload_texture, TextureOptions, TextureSource};
+use crate::FerriciaResult;
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{Builder, JoinHandle};
+
+struct LoadJob {
+	request: u32,
+	path: String,
+}
+
+struct LoadResult {
+	request: u32,
+	image: FerriciaResult<RgbaImage>,
+}
+
+pub(crate) struct TextureLoader {
+	/// `None` only when dropping, to stop the workers.
+	jobs: Option<Sender<LoadJob>>,
+	results: Receiver<LoadResult>,
+	workers: Vec<JoinHandle<()>>,
+	/// Set when dropping, so that the queued jobs are skipped.
+	stopped: Arc<AtomicBool>,
+	/// Paths and options of pending requests
+	pending: HashMap<u32, (String, TextureOptions)>,
+	next_request: u32,
+}
+
+impl TextureLoader {
+	pub(crate) fn new(threads: usize) -> FerriciaResult<Self> {
+		if threads == 0 {
+			return Err("Texture loader must have at least one thread".to_string().into());
+		}
+		let (jobs, job_receiver) = channel::<LoadJob>();
+		let (result_sender, results) = channel();
+		let job_receiver = Arc::new(Mutex::new(job_receiver));
+		let stopped = Arc::new(AtomicBool::new(false));
+		let mut workers = Vec::with_capacity(threads);
+		for i in 0..threads {
+			let job_receiver = job_receiver.clone();
+			let result_sender = result_sender.clone();
+			let stopped = stopped.clone();
+			let worker = Builder::new()
+				.name(format!("Texture Loader {i}"))
+				.spawn(move || loop {
+					// The lock is released before decoding, so that the other workers may take jobs.
+					let job = job_receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+					let Ok(job) = job else { break }; // Loader dropped
+					if stopped.load(Ordering::Relaxed) {
+						break;
+					}
+					let image = read_image(&job.path);
+					if result_sender.send(LoadResult { request: job.request, image }).is_err() {
+						break;
+					}
+				})
+				.map_err(|e| format!("Cannot spawn texture loader thread: {e}"))?;
+			workers.push(worker);
+		}
+		Ok(Self {
+			jobs: Some(jobs),
+			results,
+			workers,
+			stopped,
+			pending: HashMap::new(),
+			next_request: 0,
+		})
+	}
+
+	/// Returns the ID of the request, reported by [TextureLoader::poll] when loaded.
+	pub(crate) fn request(&mut self, path: String, options: TextureOptions) -> u32 {
+		let request = self.next_request;
+		self.next_request = self.next_request.wrapping_add(1);
+		self.pending.insert(request, (path.clone(), options));
+		self.jobs.as_ref()
+			.expect("should exist until dropped")
+			.send(LoadJob { request, path })
+			.expect("workers should be running");
+		request
+	}
+
+	/// Uploads the decoded images on the current thread, returning pairs of request and texture.
+	///
+	/// Failed requests are returned with `None`, while their messages are posted as engine events.
+	/// Fails if the workers have all stopped, such as after panicking, so that no more requests can be loaded.
+	pub(crate) fn poll(&mut self) -> FerriciaResult<Vec<(u32, Option<u32>)>> {
+		let mut loaded = Vec::new();
+		loop {
+			match self.results.try_recv() {
+				Ok(result) => {
+					let (path, options) = self.pending.remove(&result.request).expect("should be pending");
+					match result.image {
+						Ok(img) => {
+							let texture = upload_texture(img, &options);
+							track_texture_source(texture, TextureSource::File(path), options);
+							loaded.push((result.request, Some(texture)));
+						}
+						Err(e) => {
+							post_event("texture-loader", format!("Request {} failed: {}", result.request, e.0));
+							loaded.push((result.request, None));
+						}
+					}
+				}
+				Err(TryRecvError::Empty) => break,
+				Err(TryRecvError::Disconnected) => {
+					return Err("Texture loader workers stopped unexpectedly".to_string().into());
+				}
+			}
+		}
+		Ok(loaded)
+	}
+}
+
+impl Drop for TextureLoader {
+	/// Pending requests are discarded after the workers finish their current jobs.
+	fn drop(&mut self) {
+		self.stopped.store(true, Ordering::Relaxed);
+		self.jobs = None;
+		for worker in self.workers.drain(..) {
+			let _ = worker.join();
+		}
+	}
+}
@@ -0,0 +1,223 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Grids of tiles drawn as a single primitive.
+//!
+//! Tiles are IDs mapped by a palette to `[u0, v0, u1, v1]` regions of the atlas, such as those of
+//! a [TextureAtlasBuilder](crate::mui::atlas::TextureAtlasBuilder). IDs of empty regions are not drawn.
+//!
+//! With integer textures (GL 3.0), the map is **indexed**: the IDs are uploaded as a texture and
+//! the atlas regions are resolved per fragment by a
+//! [TileMapProgram](crate::mui::rendering::TileMapProgram), so editing a tile updates one texel.
+//! Otherwise, the map is **meshed** with a quad per tile for a
+//! [TexProgram](crate::mui::rendering::TexProgram), and editing a tile updates its four vertices.
+
+use crate::mui::backend::{Backend, BufferTarget, BufferUsage, NumType, RenderBackend, Topology};
+use crate::mui::rendering::{RenderPrimitive, SpriteMesh};
+use crate::mui::window::WindowHandle;
+use crate::FerriciaResult;
+use std::cell::RefCell;
+
+/// Texture unit of the tile IDs, after the atlas at unit zero
+pub(super) const TILES_UNIT: u32 = 1;
+pub(super) const PALETTE_UNIT: u32 = 2;
+/// Width of the palette texture in regions, within the minimum texture size of GL 3.0
+const PALETTE_WIDTH: usize = 1024;
+/// Floats of the vertices of a tile in the meshed path
+const TILE_VERTICES_LEN: usize = 16;
+
+pub(crate) struct TileMap {
+	grid: TileGrid,
+	path: TileMapPath,
+	/// Kept to upload the map again on a new context
+	tiles: RefCell<Vec<u32>>,
+}
+
+struct TileGrid {
+	/// In tiles
+	size: (u32, u32),
+	/// In pixels
+	tile_size: u32,
+	palette: Vec<[f32; 4]>,
+}
+
+enum TileMapPath {
+	Indexed {
+		/// With tile coordinates as texture coordinates
+		quad: SpriteMesh,
+		tiles: u32,
+		palette: u32,
+	},
+	Meshed {
+		vao: u32,
+		vbo: u32,
+		ebo: u32,
+		num_elements: u32,
+	},
+}
+
+impl TileMap {
+	/// `tiles` are the IDs in rows from the bottom, and `size` is in tiles.
+	pub(crate) fn new(
+		window_handle: &WindowHandle,
+		size: (u32, u32),
+		tile_size: u32,
+		tiles: Vec<u32>,
+		palette: Vec<[f32; 4]>,
+	) -> FerriciaResult<Self> {
+		if size.0 == 0 || size.1 == 0 || tile_size == 0 {
+			return Err("Tile map must not be empty".to_string().into());
+		}
+		let count = size.0 as usize * size.1 as usize;
+		if tiles.len() != count {
+			return Err(format!("Expected {count} tiles but got {}", tiles.len()).into());
+		}
+		if let Some(v) = tiles.iter().find(|v| **v as usize >= palette.len()) {
+			return Err(format!("Tile ID {v} is not in the palette").into());
+		}
+		let grid = TileGrid { size, tile_size, palette };
+		let path = if window_handle.gl_handle().integer_texture_supported() {
+			grid.new_indexed(&tiles)?
+		} else {
+			grid.new_meshed(&tiles)
+		};
+		Ok(Self { grid, path, tiles: RefCell::new(tiles) })
+	}
+
+	/// Whether the map requires a [TileMapProgram](crate::mui::rendering::TileMapProgram)
+	/// rather than a [TexProgram](crate::mui::rendering::TexProgram).
+	pub(crate) fn is_indexed(&self) -> bool {
+		matches!(self.path, TileMapPath::Indexed { .. })
+	}
+
+	/// `(x, y)` is in tiles with (0, 0) as bottom-left.
+	pub(crate) fn set_tile(&self, x: u32, y: u32, id: u32) -> FerriciaResult<()> {
+		let grid = &self.grid;
+		if x >= grid.size.0 || y >= grid.size.1 {
+			return Err(format!("Tile ({x}, {y}) is out of the map").into());
+		}
+		if id as usize >= grid.palette.len() {
+			return Err(format!("Tile ID {id} is not in the palette").into());
+		}
+		match &self.path {
+			TileMapPath::Indexed { tiles, .. } => Backend::update_uint_texel(*tiles, x, y, id),
+			TileMapPath::Meshed { vbo, .. } => {
+				let offset = (y * grid.size.0 + x) as usize * TILE_VERTICES_LEN * size_of::<f32>();
+				Backend::update_buf_obj(BufferTarget::Vertex, *vbo, offset, &grid.tile_vertices(x, y, id));
+			}
+		}
+		self.tiles.borrow_mut()[(y * grid.size.0 + x) as usize] = id;
+		Ok(())
+	}
+}
+
+impl TileGrid {
+	fn new_indexed(&self, tiles: &[u32]) -> FerriciaResult<TileMapPath> {
+		let pixel_width = self.size.0.checked_mul(self.tile_size);
+		let pixel_height = self.size.1.checked_mul(self.tile_size);
+		let (Some(pixel_width), Some(pixel_height)) = (pixel_width, pixel_height) else {
+			return Err(format!("Tile map of {:?} tiles in {} pixels is too large", self.size, self.tile_size).into());
+		};
+		let quad = SpriteMesh::with_uv([0, 0, pixel_width, pixel_height], [0.0, 0.0, self.size.0 as _, self.size.1 as _]);
+		Ok(TileMapPath::Indexed {
+			quad,
+			tiles: Backend::gen_uint_texture(self.size, tiles),
+			palette: self.gen_palette_texture(),
+		})
+	}
+
+	fn gen_palette_texture(&self) -> u32 {
+		let width = self.palette.len().clamp(1, PALETTE_WIDTH);
+		let height = self.palette.len().div_ceil(PALETTE_WIDTH).max(1);
+		let mut regions = self.palette.as_flattened().to_vec();
+		regions.resize(width * height * 4, 0.0);
+		Backend::gen_vec_4_texture((width as _, height as _), &regions)
+	}
+
+	fn new_meshed(&self, tiles: &[u32]) -> TileMapPath {
+		let vao = Backend::with_new_vert_arr();
+		let [vbo, ebo] = Backend::gen_buf_objs();
+		let mut vertices = Vec::with_capacity(tiles.len() * TILE_VERTICES_LEN);
+		let mut indices = Vec::with_capacity(tiles.len() * 6);
+		for (i, id) in tiles.iter().enumerate() {
+			let i = i as u32;
+			vertices.extend(self.tile_vertices(i % self.size.0, i / self.size.0, *id));
+			let base = i * 4;
+			indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+		}
+		Backend::buf_obj_with_data(BufferTarget::Vertex, vbo, &vertices, BufferUsage::Dynamic);
+		Backend::buf_obj_with_data(BufferTarget::Index, ebo, &indices, BufferUsage::Static);
+		Backend::vert_attr_arr(0, 2, NumType::Float, 4, 0); // Position
+		Backend::vert_attr_arr(1, 2, NumType::Float, 4, 2); // Texture coord
+		TileMapPath::Meshed { vao, vbo, ebo, num_elements: indices.len() as _ } // Note: Binding to the VAO remains
+	}
+
+	/// The same layout as [SpriteMesh]; all collapsed to a point for empty regions.
+	fn tile_vertices(&self, x: u32, y: u32, id: u32) -> [f32; TILE_VERTICES_LEN] {
+		let [u0, v0, u1, v1] = self.palette[id as usize];
+		if u0 == u1 {
+			return [0.0; TILE_VERTICES_LEN];
+		}
+		let (x0, y0) = ((x * self.tile_size) as f32, (y * self.tile_size) as f32);
+		let (x1, y1) = (x0 + self.tile_size as f32, y0 + self.tile_size as f32);
+		[
+			x0, y1, u0, v1, // top-left
+			x0, y0, u0, v0, // bottom-left
+			x1, y0, u1, v0, // bottom-right
+			x1, y1, u1, v1, // top-right
+		]
+	}
+}
+
+impl Drop for TileMap {
+	fn drop(&mut self) {
+		match &self.path {
+			TileMapPath::Indexed { tiles, palette, .. } => {
+				Backend::delete_texture(*tiles);
+				Backend::delete_texture(*palette);
+			}
+			TileMapPath::Meshed { vao, vbo, ebo, .. } => {
+				Backend::delete_vert_arr_obj(*vao);
+				Backend::delete_buf_objs(&[*vbo, *ebo]);
+			}
+		}
+	}
+}
+
+impl RenderPrimitive for TileMap {
+	fn vao(&self) -> u32 {
+		match &self.path {
+			TileMapPath::Indexed { quad, .. } => quad.vao(),
+			TileMapPath::Meshed { vao, .. } => *vao,
+		}
+	}
+
+	fn draw(&self) {
+		match &self.path {
+			TileMapPath::Indexed { quad, tiles, palette } => {
+				Backend::use_texture_2d_at(TILES_UNIT, *tiles);
+				Backend::use_texture_2d_at(PALETTE_UNIT, *palette);
+				quad.draw();
+			}
+			TileMapPath::Meshed { num_elements, .. } => Backend::draw_elements(Topology::Triangles, *num_elements),
+		}
+	}
+
+	fn recreate(&mut self) {
+		let tiles = self.tiles.borrow();
+		if let TileMapPath::Indexed { quad, tiles: tiles_texture, palette } = &mut self.path {
+			quad.recreate();
+			*tiles_texture = Backend::gen_uint_texture(self.grid.size, &tiles);
+			*palette = self.grid.gen_palette_texture();
+		} else {
+			// The objects of the lost context are overwritten without being deleted.
+			self.path = self.grid.new_meshed(&tiles);
+		}
+	}
+
+	fn tile_map(&self) -> Option<&TileMap> {
+		Some(self)
+	}
+}
@@ -0,0 +1,160 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Frame timings of a canvas, so that performance regressions can be tracked on players' machines.
+//!
+//! The CPU time of a frame is measured between its begin and end, along with the interval between
+//! the begins of frames. The GPU time of named passes is measured with timer queries, which require
+//! GL 3.3 or `GL_ARB_timer_query`; passes are not timed otherwise.
+//!
+//! Results of queries are read without waiting for the GPU, so the GPU times lag behind by a few
+//! frames. Timer queries cannot be nested, so timed passes must not overlap, and a pass timed more
+//! than once in a frame only keeps the last.
+
+use crate::mui::ogl::{begin_time_query, delete_queries, end_time_query, gen_queries, poll_time_query};
+use crate::FerriciaResult;
+use std::time::{Duration, Instant};
+
+/// Frames in flight of each pass, before the oldest query must be read
+const QUERY_FRAMES: usize = 3;
+
+struct TimedPass {
+	name: String,
+	/// Queries by frames in rotation
+	queries: [u32; QUERY_FRAMES],
+	/// Whether each query has been issued but not read
+	pending: [bool; QUERY_FRAMES],
+	/// The latest result; `None` until the first is available
+	gpu_time: Option<Duration>,
+}
+
+impl TimedPass {
+	fn new(name: String) -> Self {
+		Self { name, queries: gen_queries(), pending: [false; QUERY_FRAMES], gpu_time: None }
+	}
+
+	/// Reads the available results from the oldest, so that the newest available is kept.
+	///
+	/// `frame` is the one being begun, of which the slot holds the oldest query.
+	fn collect(&mut self, frame: usize) {
+		for i in 0..QUERY_FRAMES {
+			let slot = (frame + i) % QUERY_FRAMES;
+			if self.pending[slot] && let Some(v) = poll_time_query(self.queries[slot]) {
+				self.pending[slot] = false;
+				self.gpu_time = Some(Duration::from_nanos(v));
+			}
+		}
+	}
+}
+
+impl Drop for TimedPass {
+	fn drop(&mut self) {
+		delete_queries(&self.queries);
+	}
+}
+
+pub(crate) struct FrameTimings {
+	/// CPU time of the last frame
+	pub(crate) cpu_time: Duration,
+	/// Between the begins of the last two frames
+	pub(crate) interval: Duration,
+	/// GPU time of each pass, in the order of the first use; `None` if not yet available
+	pub(crate) passes: Vec<(String, Option<Duration>)>,
+}
+
+pub(super) struct FrameTimer {
+	/// Whether timer queries are supported
+	gpu: bool,
+	frame: usize,
+	frame_start: Option<Instant>,
+	cpu_time: Duration,
+	interval: Duration,
+	passes: Vec<TimedPass>,
+	/// Index of the pass being timed
+	active_pass: Option<usize>,
+}
+
+impl FrameTimer {
+	pub(super) fn new(gpu: bool) -> Self {
+		Self {
+			gpu,
+			frame: 0,
+			frame_start: None,
+			cpu_time: Duration::ZERO,
+			interval: Duration::ZERO,
+			passes: Vec::new(),
+			active_pass: None,
+		}
+	}
+
+	pub(super) fn begin_frame(&mut self) {
+		let now = Instant::now();
+		if let Some(v) = self.frame_start {
+			self.interval = now - v;
+		}
+		self.frame_start = Some(now);
+		self.frame = self.frame.wrapping_add(1);
+		let frame = self.frame;
+		self.passes.iter_mut().for_each(|v| v.collect(frame));
+	}
+
+	pub(super) fn end_frame(&mut self) -> FerriciaResult<()> {
+		if let Some(i) = self.active_pass {
+			return Err(format!("Timed pass {} is not ended", self.passes[i].name).into());
+		}
+		let start = self.frame_start.ok_or("Frame is not begun".to_string())?;
+		self.cpu_time = start.elapsed();
+		Ok(())
+	}
+
+	/// Does nothing if timer queries are not supported.
+	pub(super) fn begin_pass(&mut self, name: &str) -> FerriciaResult<()> {
+		if !self.gpu {
+			return Ok(());
+		}
+		if let Some(i) = self.active_pass {
+			return Err(format!("Timed pass {} is not ended before {name}", self.passes[i].name).into());
+		}
+		let index = match self.passes.iter().position(|v| v.name == name) {
+			Some(v) => v,
+			None => {
+				self.passes.push(TimedPass::new(name.to_string()));
+				self.passes.len() - 1
+			}
+		};
+		let pass = &mut self.passes[index];
+		let slot = self.frame % QUERY_FRAMES;
+		// The query of the same slot is reissued only if unavailable after all the frames in flight,
+		// in which case its result is lost.
+		pass.pending[slot] = false;
+		begin_time_query(pass.queries[slot]);
+		self.active_pass = Some(index);
+		Ok(())
+	}
+
+	pub(super) fn end_pass(&mut self) -> FerriciaResult<()> {
+		if !self.gpu {
+			return Ok(());
+		}
+		let index = self.active_pass.take().ok_or("No timed pass is begun".to_string())?;
+		end_time_query();
+		self.passes[index].pending[self.frame % QUERY_FRAMES] = true;
+		Ok(())
+	}
+
+	pub(super) fn timings(&self) -> FrameTimings {
+		FrameTimings {
+			cpu_time: self.cpu_time,
+			interval: self.interval,
+			passes: self.passes.iter().map(|v| (v.name.clone(), v.gpu_time)).collect(),
+		}
+	}
+
+	/// Forgets all the passes along with their queries of a lost context, which must not be deleted.
+	pub(super) fn reset(&mut self, gpu: bool) {
+		self.passes.iter_mut().for_each(|v| v.queries = [0; QUERY_FRAMES]);
+		*self = Self::new(gpu);
+	}
+}
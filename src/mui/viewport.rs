@@ -0,0 +1,58 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Viewport regions of the canvas for split-screen rendering.
+//!
+//! Regions are specified in fractions of the canvas from the bottom-left corner, consistent with
+//! the canvas coordinates, so that a layout survives resizing. Each viewport has its own camera,
+//! and the world layer is drawn once per viewport between [CanvasHandle::begin_viewport] and
+//! [CanvasHandle::end_viewport]; draws are clipped to the region by the scissor test.
+//!
+//! [CanvasHandle::begin_viewport]: crate::mui::rendering::CanvasHandle::begin_viewport
+//! [CanvasHandle::end_viewport]: crate::mui::rendering::CanvasHandle::end_viewport
+
+use crate::mui::rendering::Camera;
+use crate::FerriciaResult;
+
+pub(crate) struct Viewport {
+	/// `(x, y, width, height)` in fractions of the canvas
+	rect: [f32; 4],
+	pub(super) camera: Camera,
+}
+
+impl Viewport {
+	pub(crate) fn new(rect: [f32; 4]) -> FerriciaResult<Self> {
+		let [x, y, width, height] = rect;
+		if rect.iter().any(|v| v.is_nan())
+			|| width <= 0.0 || height <= 0.0 || x < 0.0 || y < 0.0 || x + width > 1.0 || y + height > 1.0 {
+			return Err(format!("Invalid viewport region: {rect:?}").into());
+		}
+		Ok(Self { rect, camera: Camera::new(0.0, 0.0, 1.0)? })
+	}
+
+	/// Returns `(x, y, width, height)` in pixels of the canvas.
+	///
+	/// Edges are rounded separately, so that adjacent viewports never overlap or leave a gap.
+	pub(super) fn pixel_rect(&self, size: (u32, u32)) -> (i32, i32, u32, u32) {
+		let [x, y, width, height] = self.rect;
+		let (w, h) = (size.0 as f32, size.1 as f32);
+		let (x0, y0) = ((x * w).round() as i32, (y * h).round() as i32);
+		let (x1, y1) = (((x + width) * w).round() as i32, ((y + height) * h).round() as i32);
+		(x0, y0, (x1 - x0).max(1) as u32, (y1 - y0).max(1) as u32)
+	}
+}
+
+/// Common layouts for local multiplayer of up to 4 players, with the first player at top-left.
+///
+/// Two players are split side by side, while the third player takes the whole bottom half.
+pub(crate) fn split_layout(count: u32) -> FerriciaResult<Vec<[f32; 4]>> {
+	Ok(match count {
+		1 => vec![[0.0, 0.0, 1.0, 1.0]],
+		2 => vec![[0.0, 0.0, 0.5, 1.0], [0.5, 0.0, 0.5, 1.0]],
+		3 => vec![[0.0, 0.5, 0.5, 0.5], [0.5, 0.5, 0.5, 0.5], [0.0, 0.0, 1.0, 0.5]],
+		4 => vec![[0.0, 0.5, 0.5, 0.5], [0.5, 0.5, 0.5, 0.5], [0.0, 0.0, 0.5, 0.5], [0.5, 0.0, 0.5, 0.5]],
+		_ => return Err(format!("Unsupported viewport count: {count}").into()),
+	})
+}
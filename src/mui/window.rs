@@ -2,14 +2,19 @@
  * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
  * SPDX-License-Identifier: LGPL-3.0-only
  */
-use crate::mui::ogl::GLHandle;
+use crate::mui::glsl::ShaderPreprocessor;
+use crate::mui::ogl::{forget_objects, GLHandle};
+use crate::mui::pacing::FramePacer;
 use crate::mui::SdlHandle;
 use crate::{FerriciaError, FerriciaResult};
 use gl::COLOR_BUFFER_BIT;
-use sdl3::video::{SwapInterval, Window, WindowBuildError};
+use sdl3::rect::Rect;
+use sdl3::sys::video::{SDL_GL_GetAttribute, SDL_GL_ResetAttributes, SDL_GL_SetAttribute, SDL_GL_FLOATBUFFERS};
+use sdl3::video::{DisplayMode, GLProfile, SwapInterval, Window, WindowBuildError};
 use std::ptr::null;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 use getset::Getters;
 use semver::Version;
 use crate::mui::rendering::CanvasHandle;
@@ -23,17 +28,145 @@ impl From<WindowBuildError> for FerriciaError {
 /// Handles top level functionalities of OpenGL
 #[derive(Getters)]
 pub(crate) struct WindowHandle {
+	#[get = "pub(super)"]
 	window: Window,
 	/// Must be internally immutable upon initialization.
 	#[get = "pub(super)"]
 	gl_handle: Arc<GLHandle>,
+	pacer: FramePacer,
+}
+
+/// Attributes of the GL context, requested before creating the window.
+///
+/// Drivers may grant more than requested, such as more depth bits, but creation fails if less is
+/// available, so callers should retry with fewer samples on failure.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct ContextConfig {
+	/// MSAA samples per pixel; `0` disables multisampling.
+	pub(crate) samples: u8,
+	pub(crate) depth_bits: u8,
+	pub(crate) stencil_bits: u8,
+	pub(crate) profile: ContextProfile,
+	/// `(major, minor)`; `None` for the default of the driver
+	pub(crate) version: Option<(u8, u8)>,
+	/// 16-bit floating point color buffers, so that colors beyond `1.0` are kept rather than clamped.
+	///
+	/// No HDR colorspace is selected, so whether the colors reach HDR displays is up to the platform.
+	pub(crate) float_buffers: bool,
+}
+
+impl Default for ContextConfig {
+	/// The defaults of SDL
+	fn default() -> Self {
+		Self { samples: 0, depth_bits: 16, stencil_bits: 0, profile: ContextProfile::Default, version: None, float_buffers: false }
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(i32)]
+pub(crate) enum ContextProfile {
+	/// Left to the driver, usually the compatibility profile
+	Default = 0,
+	/// Requires GL 3.2
+	Core,
+	Compatibility,
+	/// OpenGL ES, of which [ContextConfig::version] is the ES version
+	Es,
+}
+
+impl ContextProfile {
+	pub(crate) fn from_id(id: i32) -> FerriciaResult<Self> {
+		match id {
+			0 => Ok(Self::Default),
+			1 => Ok(Self::Core),
+			2 => Ok(Self::Compatibility),
+			3 => Ok(Self::Es),
+			_ => Err(format!("Unknown context profile: {id}").into()),
+		}
+	}
+}
+
+/// A report of the GL context, such as for warning about unsupported hardware before playing.
+pub(crate) struct GLCapabilities {
+	pub(crate) vendor: String,
+	pub(crate) renderer: String,
+	pub(crate) version: String,
+	/// In pixels of each side
+	pub(crate) max_texture_size: i32,
+	/// Combined across all shader stages
+	pub(crate) max_texture_units: i32,
+	pub(crate) ubo: bool,
+	pub(crate) instancing: bool,
+	/// Sorted
+	pub(crate) extensions: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FullscreenMode {
+	Windowed,
+	/// Covers the display at the desktop resolution without changing the display mode
+	Borderless,
+	/// Changes the display mode, of one in [SdlHandle::fullscreen_modes]
+	Exclusive(DisplayMode),
 }
 
 const MIN_WIDTH: u32 = 800;
 const MIN_HEIGHT: u32 = 480;
 
+/// Creates a GL context of the window and makes it current, with the functions loaded for it.
+fn load_gl(sdl_handle: &SdlHandle, window: &Window) -> FerriciaResult<GLHandle> {
+	let gl_context = window.gl_create_context()?;
+	window.gl_make_current(&gl_context)?;
+	let get_proc = |s: &str| sdl_handle.video.gl_get_proc_address(s).map_or(null::<fn()>(), |f| f as *const _) as *const _;
+	gl::load_with(get_proc);
+	let gl_handle = GLHandle::new(gl_context)?;
+	if gl_handle.oes_vertex_array() {
+		// The same functions are only provided by the extension in ES 2.0.
+		gl::GenVertexArrays::load_with(|_| get_proc("glGenVertexArraysOES"));
+		gl::BindVertexArray::load_with(|_| get_proc("glBindVertexArrayOES"));
+		gl::DeleteVertexArrays::load_with(|_| get_proc("glDeleteVertexArraysOES"));
+	}
+	Ok(gl_handle)
+}
+
 impl WindowHandle {
-	pub(crate) fn new(sdl_handle: &SdlHandle) -> FerriciaResult<Self> {
+	pub(crate) fn new(sdl_handle: &SdlHandle, config: &ContextConfig) -> FerriciaResult<Self> {
+		// Attributes persist across windows, so those not in the config are back to the defaults of SDL.
+		unsafe { SDL_GL_ResetAttributes() };
+		let gl_attr = sdl_handle.video.gl_attr();
+		gl_attr.set_multisample_buffers((config.samples > 0) as u8);
+		gl_attr.set_multisample_samples(config.samples);
+		gl_attr.set_depth_size(config.depth_bits);
+		gl_attr.set_stencil_size(config.stencil_bits);
+		match config.profile {
+			ContextProfile::Default => {}
+			ContextProfile::Core => {
+				if config.version.is_some_and(|v| v < (3, 2)) {
+					return Err("Core profile requires GL 3.2".to_string().into());
+				}
+				gl_attr.set_context_profile(GLProfile::Core);
+			}
+			ContextProfile::Compatibility => gl_attr.set_context_profile(GLProfile::Compatibility),
+			ContextProfile::Es => {
+				if config.version.is_some_and(|v| v < (2, 0)) {
+					return Err("OpenGL ES requires version 2.0".to_string().into());
+				}
+				gl_attr.set_context_profile(GLProfile::GLES);
+			}
+		}
+		if let Some((major, minor)) = config.version {
+			gl_attr.set_context_version(major, minor);
+		}
+		if config.float_buffers {
+			gl_attr.set_red_size(16);
+			gl_attr.set_green_size(16);
+			gl_attr.set_blue_size(16);
+			gl_attr.set_alpha_size(16);
+		}
+		// Not yet ported to sdl3-rs
+		if !unsafe { SDL_GL_SetAttribute(SDL_GL_FLOATBUFFERS, config.float_buffers as _) } {
+			return Err(format!("Cannot request floating point color buffers: {}", sdl3::get_error()).into());
+		}
 		let mut window = sdl_handle.video.window("TerraModulus", MIN_WIDTH, MIN_HEIGHT)
 			.opengl()
 			.hidden()
@@ -41,25 +174,123 @@ impl WindowHandle {
 			.resizable()
 			.build()?;
 		window.set_minimum_size(MIN_WIDTH, MIN_HEIGHT)?;
-		let gl_context = window.gl_create_context()?;
-		window.gl_make_current(&gl_context)?;
-		gl::load_with(|s| sdl_handle.video.gl_get_proc_address(s).map_or(null::<fn()>(), |f| f as *const _) as *const _);
-		let gl_handle = GLHandle::new(gl_context)?;
+		let gl_handle = load_gl(sdl_handle, &window)?;
 		gl_handle.gl_resize_viewport(MIN_WIDTH, MIN_HEIGHT);
 		Ok(Self {
 			gl_handle: Arc::new(gl_handle),
 			window,
+			pacer: FramePacer::new(),
 		})
 	}
 
+	/// Replaces the GL context with a new one of the same attributes, such as after the previous one
+	/// is lost by a reset of the graphics driver.
+	///
+	/// All the objects of the previous context are forgotten, so the owners must create them again,
+	/// without deleting the previous ones, before any further draw.
+	pub(crate) fn recreate_gl_context(&mut self, sdl_handle: &SdlHandle) -> FerriciaResult<()> {
+		self.gl_handle = Arc::new(load_gl(sdl_handle, &self.window)?);
+		forget_objects();
+		let (width, height) = self.window_size_in_pixels();
+		self.gl_handle.gl_resize_viewport(width, height);
+		Ok(())
+	}
+
 	pub(crate) fn show_window(&mut self) {
 		self.window.show();
 	}
 
+	/// In logical size; the size in pixels may differ on high density displays.
+	pub(crate) fn set_window_size(&mut self, width: u32, height: u32) -> FerriciaResult<()> {
+		Ok(self.window.set_size(width, height)?)
+	}
+
+	/// The size may not be less than the minimum size of the engine.
+	pub(crate) fn set_minimum_size(&mut self, width: u32, height: u32) -> FerriciaResult<()> {
+		if width < MIN_WIDTH || height < MIN_HEIGHT {
+			return Err(format!("Minimum size must be at least {MIN_WIDTH}x{MIN_HEIGHT}").into());
+		}
+		Ok(self.window.set_minimum_size(width, height)?)
+	}
+
+	/// `(0, 0)` removes the limit.
+	pub(crate) fn set_maximum_size(&mut self, width: u32, height: u32) -> FerriciaResult<()> {
+		Ok(self.window.set_maximum_size(width, height)?)
+	}
+
+	pub(crate) fn maximize_window(&mut self) {
+		self.window.maximize();
+	}
+
+	pub(crate) fn minimize_window(&mut self) {
+		self.window.minimize();
+	}
+
+	/// Restores from being maximized or minimized.
+	pub(crate) fn restore_window(&mut self) {
+		self.window.restore();
+	}
+
+	/// Confines the cursor to the window while focused, such as in fullscreen gameplay, so that it
+	/// does not slide onto other displays.
+	pub(crate) fn set_mouse_grab(&mut self, grabbed: bool) {
+		self.window.set_mouse_grab(grabbed);
+	}
+
+	/// Confines the cursor to the area in window coordinates while focused; `None` removes the area.
+	pub(crate) fn set_mouse_area(&mut self, area: Option<Rect>) -> FerriciaResult<()> {
+		Ok(self.window.set_mouse_rect(area)?)
+	}
+
+	/// The window is moved to the display of the mode for exclusive fullscreen.
+	pub(crate) fn set_fullscreen(&mut self, mode: FullscreenMode) -> FerriciaResult<()> {
+		match mode {
+			FullscreenMode::Windowed => self.window.set_fullscreen(false)?,
+			FullscreenMode::Borderless => {
+				// Without a display mode, fullscreen is borderless at the desktop resolution.
+				self.window.set_display_mode(None)?;
+				self.window.set_fullscreen(true)?;
+			}
+			FullscreenMode::Exclusive(mode) => {
+				self.window.set_display_mode(Some(mode))?;
+				self.window.set_fullscreen(true)?;
+			}
+		}
+		Ok(())
+	}
+
 	pub(crate) fn gl_resize_viewport(&self, canvas_handle: &mut CanvasHandle) {
 		let (width, height) = self.window.size_in_pixels();
 		self.gl_handle.gl_resize_viewport(width, height);
 		canvas_handle.refresh_canvas_size(width, height);
+		if *canvas_handle.auto_gui_scale() {
+			self.apply_display_scale(canvas_handle);
+		}
+	}
+
+	/// Scale of the content on the display, such as `2.0` on displays set to 200%.
+	///
+	/// Unlike [WindowHandle::pixel_density], this includes the scaling set by the user on the system.
+	pub(crate) fn display_scale(&self) -> f32 {
+		self.window.display_scale()
+	}
+
+	/// Pixels per logical unit of the window size, such as `2.0` on Retina displays.
+	pub(crate) fn pixel_density(&self) -> f32 {
+		self.window.pixel_density()
+	}
+
+	/// Makes the canvas follow the display scale on resizing, such as moving between displays.
+	pub(crate) fn set_auto_gui_scale(&self, canvas_handle: &mut CanvasHandle, enabled: bool) {
+		canvas_handle.set_auto_gui_scale(enabled);
+		if enabled {
+			self.apply_display_scale(canvas_handle);
+		}
+	}
+
+	fn apply_display_scale(&self, canvas_handle: &mut CanvasHandle) {
+		// SDL reports `0.0` on failure, in which case the last scale is kept.
+		let _ = canvas_handle.apply_gui_scale(self.display_scale());
 	}
 
 	pub(super) fn window_size_in_pixels(&self) -> (u32, u32) {
@@ -70,6 +301,16 @@ impl WindowHandle {
 		self.window.gl_swap_window();
 	}
 
+	/// See [FramePacer::set_limit]; the limit is independent of VSync, which may lower it further.
+	pub(crate) fn set_frame_limit(&mut self, fps: u32, spin_threshold: Duration) -> FerriciaResult<()> {
+		self.pacer.set_limit(fps, spin_threshold)
+	}
+
+	/// Waits for the frame limit, if any; should be called once per frame before swapping.
+	pub(crate) fn limit_frame(&mut self) {
+		self.pacer.wait();
+	}
+
 	fn set_icon(&self) {
 		todo!()
 	}
@@ -81,4 +322,65 @@ impl WindowHandle {
 	pub(crate) fn full_gl_version(&self) -> &str {
 		self.gl_handle.full_gl_version()
 	}
+
+	/// Whether programs with geometry shaders may be used, such as for wide lines.
+	pub(crate) fn geometry_shader_supported(&self) -> bool {
+		self.gl_handle.geometry_shader_supported()
+	}
+
+	/// Whether compute shaders and storage buffers may be used.
+	pub(crate) fn compute_supported(&self) -> bool {
+		self.gl_handle.compute_supported()
+	}
+
+	pub(crate) fn gl_capabilities(&self) -> GLCapabilities {
+		let mut extensions = self.gl_handle.extensions().iter().cloned().collect::<Vec<_>>();
+		extensions.sort_unstable();
+		GLCapabilities {
+			vendor: self.gl_handle.vendor().clone(),
+			renderer: self.gl_handle.renderer().clone(),
+			version: self.gl_handle.full_gl_version().clone(),
+			max_texture_size: self.gl_handle.max_texture_size(),
+			max_texture_units: self.gl_handle.max_texture_units(),
+			ubo: self.gl_handle.ubo_supported(),
+			instancing: self.gl_handle.instancing_supported(),
+			extensions,
+		}
+	}
+
+	/// The attributes actually obtained, which may differ from those requested.
+	pub(crate) fn context_config(&self, sdl_handle: &SdlHandle) -> ContextConfig {
+		let gl_attr = sdl_handle.video.gl_attr();
+		let version = self.gl_handle.gl_version();
+		// Queried from the context, as the attribute is only the requested profile
+		let profile = if self.gl_handle.is_es() {
+			ContextProfile::Es
+		} else if self.gl_handle.is_core_profile() {
+			ContextProfile::Core
+		} else if (version.major, version.minor) >= (3, 2) {
+			ContextProfile::Compatibility
+		} else {
+			ContextProfile::Default // Without profiles
+		};
+		let mut float_buffers = 0;
+		unsafe { SDL_GL_GetAttribute(SDL_GL_FLOATBUFFERS, &mut float_buffers); }
+		ContextConfig {
+			samples: gl_attr.multisample_samples(),
+			depth_bits: gl_attr.depth_size(),
+			stencil_bits: gl_attr.stencil_size(),
+			profile,
+			version: Some((version.major as _, version.minor as _)),
+			float_buffers: float_buffers != 0,
+		}
+	}
+
+	/// See [glsl](crate::mui::glsl) for the preprocessing.
+	pub(crate) fn preprocess_shader(&self, path: String) -> FerriciaResult<String> {
+		ShaderPreprocessor::new(&self.gl_handle).process_file(path)
+	}
+
+	/// The same as [WindowHandle::preprocess_shader] but `#include` is not supported.
+	pub(crate) fn preprocess_shader_source(&self, src: &str) -> FerriciaResult<String> {
+		ShaderPreprocessor::new(&self.gl_handle).process_source(src)
+	}
 }
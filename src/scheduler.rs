@@ -0,0 +1,203 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Timers shared by client and server loops.
+//!
+//! Timers are based either on ticks, advanced explicitly by the game loop, or on wall-clock time,
+//! so that a paused or lagging simulation delays only the tick-based ones. Nothing runs in the
+//! background; due timers are fired by [Scheduler::fire], which should be called every loop.
+//! Fired timers are posted as `timer` engine events, with messages of `{id} {count}`.
+//!
+//! Wakeups are coalesced: a repeating timer that missed several periods, such as after a lag
+//! spike, is reported once with the number of periods elapsed instead of firing in a burst.
+
+use crate::events::post_event;
+use crate::FerriciaResult;
+use std::collections::HashMap;
+use std::time::Instant;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimerBase {
+	Tick,
+	/// In milliseconds
+	WallClock,
+}
+
+struct Timer {
+	base: TimerBase,
+	/// In ticks or milliseconds since the creation of the scheduler
+	due: u64,
+	/// `None` for one-shot timers
+	period: Option<u64>,
+}
+
+pub(crate) struct Scheduler {
+	start: Instant,
+	tick: u64,
+	timers: HashMap<u32, Timer>,
+	next_id: u32,
+}
+
+impl Scheduler {
+	pub(crate) fn new() -> Self {
+		Self {
+			start: Instant::now(),
+			tick: 0,
+			timers: HashMap::new(),
+			next_id: 0,
+		}
+	}
+
+	fn now(&self, base: TimerBase) -> u64 {
+		match base {
+			TimerBase::Tick => self.tick,
+			TimerBase::WallClock => self.start.elapsed().as_millis() as u64,
+		}
+	}
+
+	/// Returns the ID of the timer, firing after `delay`, and then every `period` if specified.
+	pub(crate) fn schedule(&mut self, base: TimerBase, delay: u64, period: Option<u64>) -> FerriciaResult<u32> {
+		if period == Some(0) {
+			return Err("Timer period must be positive".to_string().into());
+		}
+		let id = self.next_id;
+		self.next_id = self.next_id.wrapping_add(1);
+		let due = self.now(base) + delay;
+		self.timers.insert(id, Timer { base, due, period });
+		Ok(id)
+	}
+
+	/// Returns whether the timer was pending; fired one-shot timers are no longer pending.
+	pub(crate) fn cancel(&mut self, id: u32) -> bool {
+		self.timers.remove(&id).is_some()
+	}
+
+	pub(crate) fn advance_ticks(&mut self, ticks: u64) {
+		self.tick += ticks;
+	}
+
+	/// Returns pairs of fired timer and number of periods elapsed, in the order of due time,
+	/// with tick-based timers first.
+	fn poll(&mut self) -> Vec<(u32, u32)> {
+		let (tick, millis) = (self.tick, self.now(TimerBase::WallClock));
+		let mut fired = Vec::new();
+		self.timers.retain(|id, timer| {
+			let now = match timer.base {
+				TimerBase::Tick => tick,
+				TimerBase::WallClock => millis,
+			};
+			if timer.due > now {
+				return true;
+			}
+			let due = timer.due;
+			match timer.period {
+				Some(period) => {
+					let count = (now - timer.due) / period + 1;
+					timer.due += count * period;
+					fired.push((timer.base == TimerBase::WallClock, due, *id, count as u32));
+					true
+				}
+				None => {
+					fired.push((timer.base == TimerBase::WallClock, due, *id, 1));
+					false
+				}
+			}
+		});
+		fired.sort_unstable();
+		fired.into_iter().map(|(_, _, id, count)| (id, count)).collect()
+	}
+
+	/// Posts the due timers as engine events, in the order of [Scheduler::poll].
+	pub(crate) fn fire(&mut self) {
+		for (id, count) in self.poll() {
+			post_event("timer", format!("{id} {count}"));
+		}
+	}
+
+	/// Returns the milliseconds until the next wall-clock timer is due, so that the loop may
+	/// sleep until then; tick-based timers are not counted.
+	pub(crate) fn next_wakeup(&self) -> Option<u64> {
+		let millis = self.now(TimerBase::WallClock);
+		self.timers.values()
+			.filter(|v| v.base == TimerBase::WallClock)
+			.map(|v| v.due.saturating_sub(millis))
+			.min()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fires_one_shot_timers_once() {
+		let mut scheduler = Scheduler::new();
+		let id = scheduler.schedule(TimerBase::Tick, 2, None).unwrap();
+		scheduler.advance_ticks(1);
+		assert_eq!(scheduler.poll(), []);
+		scheduler.advance_ticks(1);
+		assert_eq!(scheduler.poll(), [(id, 1)]);
+		scheduler.advance_ticks(5);
+		assert_eq!(scheduler.poll(), []);
+		assert!(!scheduler.cancel(id));
+	}
+
+	#[test]
+	fn coalesces_missed_periods() {
+		let mut scheduler = Scheduler::new();
+		let id = scheduler.schedule(TimerBase::Tick, 1, Some(3)).unwrap();
+		scheduler.advance_ticks(1);
+		assert_eq!(scheduler.poll(), [(id, 1)]);
+		scheduler.advance_ticks(9); // Due at 4, 7 and 10
+		assert_eq!(scheduler.poll(), [(id, 3)]);
+		scheduler.advance_ticks(2);
+		assert_eq!(scheduler.poll(), []);
+		scheduler.advance_ticks(1);
+		assert_eq!(scheduler.poll(), [(id, 1)]);
+	}
+
+	#[test]
+	fn cancels_pending_timers() {
+		let mut scheduler = Scheduler::new();
+		let id = scheduler.schedule(TimerBase::Tick, 1, Some(1)).unwrap();
+		assert!(scheduler.cancel(id));
+		scheduler.advance_ticks(3);
+		assert_eq!(scheduler.poll(), []);
+	}
+
+	#[test]
+	fn reports_in_the_order_of_due_time() {
+		let mut scheduler = Scheduler::new();
+		let wall_clock = scheduler.schedule(TimerBase::WallClock, 0, None).unwrap();
+		let late = scheduler.schedule(TimerBase::Tick, 3, None).unwrap();
+		let early = scheduler.schedule(TimerBase::Tick, 1, None).unwrap();
+		scheduler.advance_ticks(3);
+		assert_eq!(scheduler.poll(), [(early, 1), (late, 1), (wall_clock, 1)]);
+	}
+
+	#[test]
+	fn wakes_up_only_for_wall_clock_timers() {
+		let mut scheduler = Scheduler::new();
+		scheduler.schedule(TimerBase::Tick, 1, None).unwrap();
+		assert_eq!(scheduler.next_wakeup(), None);
+		scheduler.schedule(TimerBase::WallClock, 60_000, None).unwrap();
+		assert!(scheduler.next_wakeup().is_some_and(|v| v > 0 && v <= 60_000));
+	}
+
+	#[test]
+	fn posts_fired_timers_as_events() {
+		let mut scheduler = Scheduler::new();
+		let id = scheduler.schedule(TimerBase::Tick, 0, Some(2)).unwrap();
+		scheduler.advance_ticks(4);
+		scheduler.fire();
+		let events = crate::events::drain_events();
+		assert!(events.iter().any(|v| v.source == "timer" && v.message == format!("{id} 3")));
+	}
+
+	#[test]
+	fn rejects_zero_periods() {
+		assert!(Scheduler::new().schedule(TimerBase::Tick, 1, Some(0)).is_err());
+	}
+}
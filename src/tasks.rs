@@ -0,0 +1,73 @@
+/*
+ * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
+ * SPDX-License-Identifier: LGPL-3.0-only
+ */
+
+//! Work that must run on a specific thread, spread across frames within a time budget.
+//!
+//! Tasks may be queued from any thread, such as handle drops requested by Java cleaners, but
+//! only run when the owning loop pumps them, which is the render loop for anything touching GL.
+//! Each pump runs tasks in the order of queuing until the budget is spent, so a burst of work
+//! is spread over the following frames instead of causing a frame spike.
+//!
+//! A minimum number of tasks is run on every pump regardless of the budget, so that the queue
+//! keeps draining even when single tasks take longer than the whole budget.
+
+use crate::FerriciaResult;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+pub(crate) struct TaskPump {
+	queue: Mutex<VecDeque<Task>>,
+	budget: Duration,
+	/// Tasks run on every pump regardless of the budget
+	min_tasks: usize,
+}
+
+impl TaskPump {
+	pub(crate) fn new(budget: Duration, min_tasks: usize) -> FerriciaResult<Self> {
+		let mut pump = Self { queue: Mutex::new(VecDeque::new()), budget: Duration::ZERO, min_tasks: 0 };
+		pump.set_budget(budget, min_tasks)?;
+		Ok(pump)
+	}
+
+	pub(crate) fn set_budget(&mut self, budget: Duration, min_tasks: usize) -> FerriciaResult<()> {
+		if min_tasks == 0 {
+			return Err("Task pump must run at least one task per pump".to_string().into());
+		}
+		self.budget = budget;
+		self.min_tasks = min_tasks;
+		Ok(())
+	}
+
+	fn queue(&self) -> MutexGuard<'_, VecDeque<Task>> {
+		self.queue.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	pub(crate) fn push(&self, task: impl FnOnce() + Send + 'static) {
+		self.queue().push_back(Box::new(task));
+	}
+
+	pub(crate) fn pending(&self) -> usize {
+		self.queue().len()
+	}
+
+	/// Returns the number of tasks run.
+	pub(crate) fn pump(&self) -> usize {
+		let start = Instant::now();
+		let mut count = 0;
+		loop {
+			if count >= self.min_tasks && start.elapsed() >= self.budget {
+				break;
+			}
+			// The lock is released before running, so that tasks may queue more tasks.
+			let Some(task) = self.queue().pop_front() else { break };
+			task();
+			count += 1;
+		}
+		count
+	}
+}
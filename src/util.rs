@@ -2,19 +2,423 @@
  * SPDX-FileCopyrightText: 2025 TerraModulus Team and Contributors
  * SPDX-License-Identifier: LGPL-3.0-only
  */
-use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::FerriciaError;
+use std::fmt::{Display, Formatter};
+use std::sync::{Mutex, MutexGuard};
 
-/// Source: https://stackoverflow.com/a/72149089
+/// Type discriminants of all the handles exposed across FFI.
+///
+/// Zero is never used, so that a raw handle of `0` (`null` on the Java side) is always invalid.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
-#[repr(transparent)]
-pub struct OpaqueId(usize);
+#[repr(u16)]
+pub enum HandleTag {
+	SdlHandle = 1,
+	WindowHandle,
+	CanvasHandle,
+	DisplayHandle,
+	GeoProgram,
+	TexProgram,
+	ArrayTexProgram,
+	PickProgram,
+	DrawableSet,
+	SceneNode,
+	SmartScaling,
+	FullScaling,
+	SimpleTranslation,
+	AffineTransform,
+	PivotScaling,
+	FlipSkew,
+	AlphaFilter,
+	TintFilter,
+	GrayscaleFilter,
+	HueRotateFilter,
+	BrightnessFilter,
+	ContrastFilter,
+	SaturationFilter,
+	LerpColorFilter,
+	FlashFilter,
+	RenderGraph,
+	GuiCache,
+	TextureAtlasBuilder,
+	PickBuffer,
+	TextureLoader,
+	HotReloader,
+	ChatFilter,
+	Scheduler,
+	TileMapProgram,
+	ComputeProgram,
+	StorageBuffer,
+	StringTable,
+	TaskPump,
+	CursorHandle,
+	ForceFeedbackDevice,
+	ModelTransformRef,
+	ColorFilterRef,
+}
+
+impl HandleTag {
+	/// Must be kept in sync with the declaration.
+	const ALL: &'static [HandleTag] = &[
+		HandleTag::SdlHandle,
+		HandleTag::WindowHandle,
+		HandleTag::CanvasHandle,
+		HandleTag::DisplayHandle,
+		HandleTag::GeoProgram,
+		HandleTag::TexProgram,
+		HandleTag::ArrayTexProgram,
+		HandleTag::PickProgram,
+		HandleTag::DrawableSet,
+		HandleTag::SceneNode,
+		HandleTag::SmartScaling,
+		HandleTag::FullScaling,
+		HandleTag::SimpleTranslation,
+		HandleTag::AffineTransform,
+		HandleTag::PivotScaling,
+		HandleTag::FlipSkew,
+		HandleTag::AlphaFilter,
+		HandleTag::TintFilter,
+		HandleTag::GrayscaleFilter,
+		HandleTag::HueRotateFilter,
+		HandleTag::BrightnessFilter,
+		HandleTag::ContrastFilter,
+		HandleTag::SaturationFilter,
+		HandleTag::LerpColorFilter,
+		HandleTag::FlashFilter,
+		HandleTag::RenderGraph,
+		HandleTag::GuiCache,
+		HandleTag::TextureAtlasBuilder,
+		HandleTag::PickBuffer,
+		HandleTag::TextureLoader,
+		HandleTag::HotReloader,
+		HandleTag::ChatFilter,
+		HandleTag::Scheduler,
+		HandleTag::TileMapProgram,
+		HandleTag::ComputeProgram,
+		HandleTag::StorageBuffer,
+		HandleTag::StringTable,
+		HandleTag::TaskPump,
+		HandleTag::CursorHandle,
+		HandleTag::ForceFeedbackDevice,
+		HandleTag::ModelTransformRef,
+		HandleTag::ColorFilterRef,
+	];
+
+	fn from_raw(raw: u16) -> Option<Self> {
+		Self::ALL.iter().find(|t| **t as u16 == raw).copied()
+	}
+}
+
+/// Implemented by all the types that may be stored in [HandleRegistry].
+pub trait Tagged {
+	const TAG: HandleTag;
+}
+
+/// An index into [HandleRegistry] with a type discriminant and a generation.
+///
+/// The raw form is packed as `id << 32 | generation << 16 | tag`,
+/// which fits in a Java `long`.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct TaggedId {
+	id: u32,
+	generation: u16,
+	tag: HandleTag,
+}
+
+impl TaggedId {
+	pub fn from_raw(raw: i64) -> Result<Self, HandleError> {
+		let raw = raw as u64;
+		Ok(Self {
+			id: (raw >> 32) as u32,
+			generation: (raw >> 16) as u16,
+			tag: HandleTag::from_raw(raw as u16).ok_or(HandleError::Invalid(raw))?,
+		})
+	}
+
+	pub fn tag(&self) -> HandleTag {
+		self.tag
+	}
+
+	pub fn into_raw(self) -> i64 {
+		(((self.id as u64) << 32) | ((self.generation as u64) << 16) | self.tag as u64) as i64
+	}
+}
+
+#[derive(Debug)]
+pub enum HandleError {
+	Invalid(u64),
+	Mismatched { expected: HandleTag, actual: HandleTag },
+	Stale(HandleTag),
+	/// Used by a call that panicked, so its state may be inconsistent
+	Poisoned(HandleTag),
+}
+
+impl Display for HandleError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			HandleError::Invalid(raw) => write!(f, "invalid handle {raw:#x}"),
+			HandleError::Mismatched { expected, actual } => write!(f, "expected {expected:?}, got {actual:?}"),
+			HandleError::Stale(tag) => write!(f, "{tag:?} has already been dropped"),
+			HandleError::Poisoned(tag) => write!(f, "{tag:?} is poisoned by a previous panic"),
+		}
+	}
+}
+
+impl From<HandleError> for FerriciaError {
+	fn from(value: HandleError) -> Self {
+		value.to_string().into()
+	}
+}
+
+/// With the drop glue of the type, for removal without knowing the type.
+type Entry = (HandleTag, *mut (), unsafe fn(*mut ()));
+
+struct Slot {
+	generation: u16,
+	entry: Option<Entry>,
+	/// Poisoned values may only be dropped.
+	poisoned: bool,
+	/// Handles removed along with this, such as those referencing into the value
+	dependents: Vec<TaggedId>,
+}
+
+/// Owns all the boxed values referenced by [TaggedId]s.
+///
+/// Freed slots are reused with a bumped generation, so any dropped handle is detected
+/// instead of accessing a reused or freed value.
+pub struct HandleRegistry {
+	slots: Vec<Slot>,
+	free: Vec<u32>,
+}
+
+/// Only raw pointers of the boxed values are stored, which are never dereferenced here.
+unsafe impl Send for HandleRegistry {}
+
+static HANDLES: Mutex<HandleRegistry> = Mutex::new(HandleRegistry::new());
+
+impl HandleRegistry {
+	const fn new() -> Self {
+		Self {
+			slots: Vec::new(),
+			free: Vec::new(),
+		}
+	}
+
+	pub fn lock() -> MutexGuard<'static, HandleRegistry> {
+		HANDLES.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	pub fn insert<T: Tagged>(&mut self, val: T) -> TaggedId {
+		let entry = Some((T::TAG, Box::into_raw(Box::new(val)) as *mut (), drop_erased::<T> as unsafe fn(*mut ())));
+		match self.free.pop() {
+			Some(id) => {
+				let slot = &mut self.slots[id as usize];
+				slot.entry = entry;
+				slot.poisoned = false;
+				TaggedId { id, generation: slot.generation, tag: T::TAG }
+			}
+			None => {
+				self.slots.push(Slot { generation: 0, entry, poisoned: false, dependents: Vec::new() });
+				TaggedId { id: (self.slots.len() - 1) as u32, generation: 0, tag: T::TAG }
+			}
+		}
+	}
+
+	/// Inserts a value referencing into the value of `owner`, which is removed along with the owner,
+	/// so that it never outlives the referenced value.
+	pub fn insert_dependent<T: Tagged>(&mut self, owner: TaggedId, val: T) -> Result<TaggedId, HandleError> {
+		if !self.slots.get(owner.id as usize).is_some_and(|v| v.generation == owner.generation && v.entry.is_some()) {
+			return Err(HandleError::Stale(owner.tag));
+		}
+		let id = self.insert(val);
+		self.slots[owner.id as usize].dependents.push(id);
+		Ok(id)
+	}
+
+	fn check<T: Tagged>(&self, id: TaggedId) -> Result<*mut (), HandleError> {
+		if id.tag != T::TAG {
+			return Err(HandleError::Mismatched { expected: T::TAG, actual: id.tag });
+		}
+		match self.slots.get(id.id as usize) {
+			Some(Slot { generation, entry: Some((tag, ptr, _)), .. }) if *generation == id.generation => {
+				// Raw handles may be forged with another tag on the Java side.
+				if *tag != id.tag {
+					return Err(HandleError::Mismatched { expected: id.tag, actual: *tag });
+				}
+				Ok(*ptr)
+			}
+			_ => Err(HandleError::Stale(id.tag)),
+		}
+	}
+
+	pub fn get<T: Tagged>(&self, id: TaggedId) -> Result<*mut T, HandleError> {
+		let ptr = self.check::<T>(id)?;
+		if self.slots[id.id as usize].poisoned {
+			return Err(HandleError::Poisoned(id.tag));
+		}
+		Ok(ptr as *mut T)
+	}
+
+	/// Returns the handles of the values of the tag that are alive and not poisoned.
+	pub fn live(&self, tag: HandleTag) -> Vec<TaggedId> {
+		self.slots.iter().enumerate()
+			.filter(|(_, v)| !v.poisoned && matches!(v.entry, Some((t, ..)) if t == tag))
+			.map(|(i, v)| TaggedId { id: i as u32, generation: v.generation, tag })
+			.collect()
+	}
+
+	/// Marks the value as poisoned if it is still alive, so that it is only accessible by dropping.
+	pub fn poison(&mut self, id: TaggedId) {
+		let slot = self.slots.get_mut(id.id as usize)
+			.filter(|v| v.generation == id.generation && v.entry.is_some());
+		if let Some(slot) = slot {
+			slot.poisoned = true;
+		}
+	}
+
+	pub fn remove<T: Tagged>(&mut self, id: TaggedId) -> Result<Box<T>, HandleError> {
+		let ptr = self.check::<T>(id)?;
+		self.slots[id.id as usize].entry = None;
+		self.free_slot(id.id);
+		Ok(unsafe { Box::from_raw(ptr as *mut T) })
+	}
+
+	/// Removes the value regardless of its type, such as for drops deferred by [TaggedId] only.
+	/// The tag of the handle must still match the stored value, so a mistagged handle never drops
+	/// a value of another type.
+	///
+	/// The value is dropped along with the returned box, which should be after releasing the lock.
+	pub fn remove_any(&mut self, id: TaggedId) -> Result<ErasedBox, HandleError> {
+		let slot = self.slots.get_mut(id.id as usize)
+			.filter(|v| v.generation == id.generation)
+			.ok_or(HandleError::Stale(id.tag))?;
+		match slot.entry {
+			Some((tag, ..)) if tag != id.tag => return Err(HandleError::Mismatched { expected: id.tag, actual: tag }),
+			Some(_) => {}
+			None => return Err(HandleError::Stale(id.tag)),
+		}
+		let (_, ptr, drop_fn) = slot.entry.take().expect("should be checked");
+		self.free_slot(id.id);
+		Ok(ErasedBox { ptr, drop_fn })
+	}
+
+	/// Frees the slot of which the entry is taken, removing its dependents.
+	///
+	/// Dependents are dropped while the registry is locked, so they must not lock it on drop.
+	fn free_slot(&mut self, index: u32) {
+		let slot = &mut self.slots[index as usize];
+		slot.generation = slot.generation.wrapping_add(1);
+		let dependents = std::mem::take(&mut slot.dependents);
+		self.free.push(index);
+		// Those already removed are stale.
+		dependents.into_iter().for_each(|v| drop(self.remove_any(v)));
+	}
+}
+
+unsafe fn drop_erased<T>(ptr: *mut ()) {
+	drop(unsafe { Box::from_raw(ptr as *mut T) });
+}
+
+/// A value removed from [HandleRegistry] without its type, which is dropped along with this.
+pub struct ErasedBox {
+	ptr: *mut (),
+	drop_fn: unsafe fn(*mut ()),
+}
+
+impl Drop for ErasedBox {
+	fn drop(&mut self) {
+		unsafe { (self.drop_fn)(self.ptr) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	impl Tagged for u32 {
+		const TAG: HandleTag = HandleTag::Scheduler;
+	}
+
+	impl Tagged for String {
+		const TAG: HandleTag = HandleTag::StringTable;
+	}
+
+	fn retagged(id: TaggedId, tag: HandleTag) -> TaggedId {
+		TaggedId { tag, ..id }
+	}
+
+	#[test]
+	fn raw_round_trip() {
+		let id = TaggedId { id: 7, generation: 3, tag: HandleTag::TaskPump };
+		assert_eq!(TaggedId::from_raw(id.into_raw()).unwrap(), id);
+		assert!(matches!(TaggedId::from_raw(0), Err(HandleError::Invalid(0))));
+	}
+
+	#[test]
+	fn removed_handles_are_stale() {
+		let mut registry = HandleRegistry::new();
+		let id = registry.insert(1u32);
+		assert_eq!(*registry.remove::<u32>(id).unwrap(), 1);
+		assert!(matches!(registry.get::<u32>(id), Err(HandleError::Stale(_))));
+		// The slot is reused with another generation.
+		let reused = registry.insert(2u32);
+		assert_eq!(reused.id, id.id);
+		assert!(matches!(registry.get::<u32>(id), Err(HandleError::Stale(_))));
+		assert!(registry.get::<u32>(reused).is_ok());
+	}
+
+	#[test]
+	fn mismatched_tags_are_rejected() {
+		let mut registry = HandleRegistry::new();
+		let id = registry.insert(1u32);
+		assert!(matches!(registry.get::<String>(id), Err(HandleError::Mismatched { .. })));
+		// Forged with the tag of the expected type
+		assert!(matches!(registry.get::<String>(retagged(id, HandleTag::StringTable)), Err(HandleError::Mismatched { .. })));
+	}
+
+	#[test]
+	fn remove_any_checks_tags() {
+		let mut registry = HandleRegistry::new();
+		let id = registry.insert(String::from("kept"));
+		let mistagged = retagged(id, HandleTag::Scheduler);
+		assert!(matches!(registry.remove_any(mistagged), Err(HandleError::Mismatched { .. })));
+		assert_eq!(unsafe { &*registry.get::<String>(id).unwrap() }, "kept");
+		drop(registry.remove_any(id).unwrap());
+		assert!(matches!(registry.remove_any(id), Err(HandleError::Stale(_))));
+	}
+
+	#[test]
+	fn dependents_are_removed_with_owner() {
+		let mut registry = HandleRegistry::new();
+		let owner = registry.insert(String::from("owner"));
+		let dependent = registry.insert_dependent(owner, 1u32).unwrap();
+		let removed = registry.insert_dependent(owner, 2u32).unwrap();
+		registry.remove::<u32>(removed).unwrap();
+		// The slot of the removed dependent is reused by an unrelated value.
+		let unrelated = registry.insert(3u32);
+		drop(registry.remove::<String>(owner).unwrap());
+		assert!(matches!(registry.get::<u32>(dependent), Err(HandleError::Stale(_))));
+		assert!(registry.get::<u32>(unrelated).is_ok());
+		assert!(matches!(registry.insert_dependent(owner, 4u32), Err(HandleError::Stale(_))));
+	}
 
-impl OpaqueId {
-	pub fn new(counter: &'static AtomicUsize) -> Self {
-		Self(counter.fetch_add(1, Ordering::Relaxed))
+	#[test]
+	fn poisoned_handles_are_only_removable() {
+		let mut registry = HandleRegistry::new();
+		let id = registry.insert(1u32);
+		registry.poison(id);
+		assert!(matches!(registry.get::<u32>(id), Err(HandleError::Poisoned(_))));
+		assert!(registry.remove::<u32>(id).is_ok());
 	}
 
-	pub fn id(&self) -> usize {
-		self.0
+	#[test]
+	fn live_handles_of_tag() {
+		let mut registry = HandleRegistry::new();
+		let live = registry.insert(1u32);
+		let removed = registry.insert(2u32);
+		let poisoned = registry.insert(3u32);
+		registry.insert(String::from("other"));
+		registry.remove::<u32>(removed).unwrap();
+		registry.poison(poisoned);
+		assert_eq!(registry.live(HandleTag::Scheduler), [live]);
 	}
 }